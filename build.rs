@@ -5,9 +5,39 @@ fn main() {
         println!("cargo:rustc-link-lib=framework=AppKit");
     }
 
+    #[cfg(feature = "qt")]
+    if target.contains("linux") {
+        build_qt_shim();
+    }
+
     // if std::env::var("TARGET").unwrap().contains("-ios") {
     //     println!("cargo:rustc-link-lib=framework=UIKit");
     // } else {
     //     println!("cargo:rustc-link-lib=framework=AppKit");
     // }
 }
+
+/// Compiles the C++ side of the Qt backend, against Qt 6 or Qt 5
+#[cfg(feature = "qt")]
+fn build_qt_shim() {
+    let shim = "src/backend/qt/shim.cpp";
+    println!("cargo:rerun-if-changed={}", shim);
+
+    let (qt, std) = match pkg_config::probe_library("Qt6Widgets") {
+        Ok(qt) => (qt, "c++17"),
+        Err(_) => (
+            pkg_config::probe_library("Qt5Widgets")
+                .expect("The `qt` feature needs the Qt 6 or Qt 5 Widgets development files"),
+            "c++11",
+        ),
+    };
+
+    let mut build = cc::Build::new();
+    build.cpp(true).file(shim).flag(format!("-std={}", std));
+    // Qt requires position independent code
+    build.flag("-fPIC");
+    for include in &qt.include_paths {
+        build.include(include);
+    }
+    build.compile("rfd_qt_shim");
+}
@@ -0,0 +1,39 @@
+//! Light or dark look of the dialogs, see [`set_color_scheme`]
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Colors dialogs are drawn with, see [`set_color_scheme`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorScheme {
+    /// Default look of each backend, the system theme for native dialogs
+    #[default]
+    Auto,
+    /// Dark background with light text
+    Dark,
+    /// Light background with dark text
+    Light,
+}
+
+static COLOR_SCHEME: AtomicU8 = AtomicU8::new(0);
+
+/// Draw dialogs in the theme the app forces instead of the default of the OS
+///
+/// Honored by GTK, for the dialogs created after the call, by the WASM file dialog and by the
+/// software backend. Native dialogs of Windows, macOS and the portal follow the system theme.
+pub fn set_color_scheme(scheme: ColorScheme) {
+    let value = match scheme {
+        ColorScheme::Auto => 0,
+        ColorScheme::Dark => 1,
+        ColorScheme::Light => 2,
+    };
+    COLOR_SCHEME.store(value, Ordering::Relaxed);
+}
+
+/// Scheme set by [`set_color_scheme`]
+pub fn color_scheme() -> ColorScheme {
+    match COLOR_SCHEME.load(Ordering::Relaxed) {
+        1 => ColorScheme::Dark,
+        2 => ColorScheme::Light,
+        _ => ColorScheme::Auto,
+    }
+}
@@ -1,67 +1,638 @@
-use crate::FileHandle;
+use crate::dialog::{Filter, MessageButtons};
+use crate::{
+    CredentialDialog, Credentials, Error, FileDialog, FileHandle, InputDialog, MessageDialog,
+    Notification, PickListDialog, ProgressDialog, ProgressState,
+};
 use std::future::Future;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+#[cfg(not(target_arch = "wasm32"))]
+use url::Url;
 
-#[cfg(target_os = "linux")]
+#[cfg(target_os = "android")]
+pub(crate) mod android;
+#[cfg(all(target_os = "linux", feature = "gtk3"))]
 mod gtk3;
+#[cfg(not(target_arch = "wasm32"))]
+mod headless;
+#[cfg(target_os = "ios")]
+pub(crate) mod ios;
 #[cfg(target_os = "macos")]
 mod macos;
+#[cfg(all(target_os = "linux", feature = "qt"))]
+mod qt;
+#[cfg(all(target_os = "linux", feature = "software"))]
+mod software;
+#[cfg(feature = "tracing")]
+mod traced;
 #[cfg(target_arch = "wasm32")]
 mod wasm;
 #[cfg(target_os = "windows")]
 mod win_cid;
+#[cfg(all(target_os = "linux", feature = "portal"))]
+mod xdg_portal;
+#[cfg(target_os = "linux")]
+mod zenity;
+
+// Return type of async dialogs:
+#[cfg(not(target_arch = "wasm32"))]
+pub type DialogFutureType<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+#[cfg(target_arch = "wasm32")]
+pub type DialogFutureType<T> = Pin<Box<dyn Future<Output = T>>>;
+
+/// Implementation of every dialog rfd can show
+///
+/// File dialogs return `Ok(None)` when the user cancels and `Err` when the dialog could not be shown.
+///
+/// All built-in platform backends implement this trait,
+/// applications can provide their own implementation (eg. an in-game UI) and register it with [`set_backend`]
+pub trait DialogBackend: Send + Sync {
+    //
+    // Sync
+    //
+
+    /// Pick one file
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error>;
+
+    /// Pick multiple files
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error>;
+
+    /// Pick one folder
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error>;
+
+    /// Pick multiple folders
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error>;
+
+    /// Pick one file or one folder
+    ///
+    /// Falls back to [`pick_file`](Self::pick_file) on platforms that can't mix both
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_path(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        self.pick_file(dialog)
+    }
+
+    /// Pick one file as a URL, items that have no local path included
+    ///
+    /// Falls back to [`pick_file`](Self::pick_file) and converts the path to a `file://` URL
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_file_url(&self, dialog: FileDialog) -> Result<Option<Url>, Error> {
+        let path = self.pick_file(dialog)?;
+        Ok(path.and_then(|path| Url::from_file_path(path).ok()))
+    }
+
+    /// Opens save file dialog
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error>;
+
+    /// Opens save file dialog, also returns the index of the selected filter
+    ///
+    /// Falls back to [`save_file`](Self::save_file) and guesses the index from the extension of the chosen file
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_file_with_filter(&self, dialog: FileDialog) -> Result<Option<(PathBuf, usize)>, Error> {
+        let (filters, skip) = (dialog.filters.clone(), dialog.all_supported_filter as usize);
+        let path = self.save_file(dialog)?;
+        Ok(path.map(|path| {
+            let index = filter_index(&filters, skip, &path);
+            (path, index)
+        }))
+    }
+
+    /// Shows a message dialog, returns `true` when `Ok`/`Yes` was pressed
+    fn show_message(&self, dialog: MessageDialog) -> bool;
+
+    /// Shows a message dialog, returns the label of the pressed button, see [`MessageDialog::button_labels`]
+    ///
+    /// Falls back to [`show_message`](Self::show_message), `Ok` and `Cancel` stand in for the first and the last label
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        let (dialog, labels) = custom_fallback(dialog);
+        let pressed = self.show_message(dialog);
+        custom_label(&labels, pressed)
+    }
+
+    /// Shows a message dialog, returns the index of the pressed button in [`MessageDialog::button_labels`]
+    ///
+    /// Falls back to [`show_message_custom`](Self::show_message_custom) and looks up the label it returns
+    fn show_message_index(&self, dialog: MessageDialog) -> Option<usize> {
+        let labels = dialog.button_labels();
+        let label = self.show_message_custom(dialog)?;
+        labels.iter().position(|shown| *shown == label)
+    }
+
+    /// Shows an input dialog, returns the entered text or `None` when it was cancelled
+    ///
+    /// Backends without one return `None`
+    fn show_input(&self, _dialog: InputDialog) -> Option<String> {
+        None
+    }
+
+    /// Shows a pick list dialog, returns the index of the chosen item or `None` when it was cancelled
+    ///
+    /// Backends without one return `None`
+    fn show_pick_list(&self, _dialog: PickListDialog) -> Option<usize> {
+        None
+    }
+
+    /// Shows a credential dialog, returns the entered credentials or `None` when it was cancelled
+    ///
+    /// Backends without one return `None`
+    fn show_credentials(&self, _dialog: CredentialDialog) -> Option<Credentials> {
+        None
+    }
+
+    /// Shows a message dialog like [`show_message_custom`](Self::show_message_custom),
+    /// returns `Err` when it could not be shown
+    ///
+    /// Falls back to [`show_message_custom`](Self::show_message_custom), which reports no errors
+    fn try_show_message(&self, dialog: MessageDialog) -> Result<Option<String>, Error> {
+        Ok(self.show_message_custom(dialog))
+    }
+
+    /// Shows an input dialog like [`show_input`](Self::show_input), returns `Err` when it could not be shown
+    ///
+    /// Falls back to [`show_input`](Self::show_input), which reports no errors
+    fn try_show_input(&self, dialog: InputDialog) -> Result<Option<String>, Error> {
+        Ok(self.show_input(dialog))
+    }
+
+    /// Shows a pick list dialog like [`show_pick_list`](Self::show_pick_list), returns `Err` when it could not be shown
+    ///
+    /// Falls back to [`show_pick_list`](Self::show_pick_list), which reports no errors
+    fn try_show_pick_list(&self, dialog: PickListDialog) -> Result<Option<usize>, Error> {
+        Ok(self.show_pick_list(dialog))
+    }
+
+    /// Shows a credential dialog like [`show_credentials`](Self::show_credentials), returns `Err` when it could not be shown
+    ///
+    /// Falls back to [`show_credentials`](Self::show_credentials), which reports no errors
+    fn try_show_credentials(&self, dialog: CredentialDialog) -> Result<Option<Credentials>, Error> {
+        Ok(self.show_credentials(dialog))
+    }
+
+    /// Shows a progress dialog without blocking, it follows the changes of `state`
+    /// until [`ProgressState::is_closed`], and calls [`ProgressState::cancel`] when Cancel is pressed
+    ///
+    /// Backends without one show nothing
+    fn show_progress(&self, _dialog: ProgressDialog, _state: ProgressState) {}
+
+    /// Shows a desktop notification without blocking
+    ///
+    /// Backends without one return [`Error::Unsupported`]
+    fn show_notification(&self, _notification: Notification) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    //
+    // Async
+    //
+
+    /// Pick one file
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>>;
+
+    /// Pick multiple files
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>>;
+
+    /// Pick one folder
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>>;
+
+    /// Pick multiple folders
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>>;
+
+    /// Pick one file or one folder
+    ///
+    /// Falls back to [`pick_file_async`](Self::pick_file_async) on platforms that can't mix both
+    fn pick_path_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        self.pick_file_async(dialog)
+    }
+
+    /// Pick one file as a URL, items that have no local path included
+    ///
+    /// Falls back to [`pick_file_async`](Self::pick_file_async) like [`pick_file_url`](Self::pick_file_url)
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_file_url_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Url>, Error>> {
+        let future = self.pick_file_async(dialog);
+        Box::pin(async move {
+            let file = future.await?;
+            Ok(file.and_then(|file| Url::from_file_path(file.path()).ok()))
+        })
+    }
+
+    /// Opens save file dialog
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>>;
+
+    /// Opens save file dialog, also returns the index of the selected filter
+    ///
+    /// Falls back to [`save_file_async`](Self::save_file_async) and guesses the index from the extension of the chosen file
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_file_with_filter_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<(FileHandle, usize)>, Error>> {
+        let (filters, skip) = (dialog.filters.clone(), dialog.all_supported_filter as usize);
+        let future = self.save_file_async(dialog);
+        Box::pin(async move {
+            let file = future.await?;
+            Ok(file.map(|file| {
+                let index = filter_index(&filters, skip, file.path());
+                (file, index)
+            }))
+        })
+    }
+
+    /// Shows a message dialog, resolves to `true` when `Ok`/`Yes` was pressed
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool>;
+
+    /// Shows a message dialog, resolves to the label of the pressed button
+    ///
+    /// Falls back to [`show_message_async`](Self::show_message_async) like [`show_message_custom`](Self::show_message_custom)
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        let (dialog, labels) = custom_fallback(dialog);
+        let pressed = self.show_message_async(dialog);
+        Box::pin(async move { custom_label(&labels, pressed.await) })
+    }
 
-//
-// Sync
-//
+    /// Shows a message dialog, resolves to the index of the pressed button
+    ///
+    /// Falls back to [`show_message_custom_async`](Self::show_message_custom_async) like [`show_message_index`](Self::show_message_index)
+    fn show_message_index_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<usize>> {
+        let labels = dialog.button_labels();
+        let label = self.show_message_custom_async(dialog);
+        Box::pin(async move {
+            let label = label.await?;
+            labels.iter().position(|shown| *shown == label)
+        })
+    }
 
-/// Dialog used to pick file/files
-pub trait FilePickerDialogImpl {
-    fn pick_file(self) -> Option<PathBuf>;
-    fn pick_files(self) -> Option<Vec<PathBuf>>;
+    /// Shows an input dialog, resolves to the entered text or `None` when it was cancelled
+    fn show_input_async(&self, dialog: InputDialog) -> DialogFutureType<Option<String>> {
+        Box::pin(std::future::ready(self.show_input(dialog)))
+    }
+
+    /// Shows a pick list dialog, resolves to the index of the chosen item or `None` when it was cancelled
+    fn show_pick_list_async(&self, dialog: PickListDialog) -> DialogFutureType<Option<usize>> {
+        Box::pin(std::future::ready(self.show_pick_list(dialog)))
+    }
+
+    /// Shows a credential dialog, resolves to the entered credentials or `None` when it was cancelled
+    fn show_credentials_async(
+        &self,
+        dialog: CredentialDialog,
+    ) -> DialogFutureType<Option<Credentials>> {
+        Box::pin(std::future::ready(self.show_credentials(dialog)))
+    }
+
+    /// Shows a message dialog like [`show_message_custom_async`](Self::show_message_custom_async),
+    /// resolves to `Err` when it could not be shown
+    fn try_show_message_async(
+        &self,
+        dialog: MessageDialog,
+    ) -> DialogFutureType<Result<Option<String>, Error>> {
+        let future = self.show_message_custom_async(dialog);
+        Box::pin(async move { Ok(future.await) })
+    }
+
+    /// Shows an input dialog like [`show_input_async`](Self::show_input_async), resolves to `Err` when it could not be shown
+    fn try_show_input_async(
+        &self,
+        dialog: InputDialog,
+    ) -> DialogFutureType<Result<Option<String>, Error>> {
+        let future = self.show_input_async(dialog);
+        Box::pin(async move { Ok(future.await) })
+    }
+
+    /// Shows a pick list dialog like [`show_pick_list_async`](Self::show_pick_list_async), resolves to `Err` when it could not be shown
+    fn try_show_pick_list_async(
+        &self,
+        dialog: PickListDialog,
+    ) -> DialogFutureType<Result<Option<usize>, Error>> {
+        let future = self.show_pick_list_async(dialog);
+        Box::pin(async move { Ok(future.await) })
+    }
+
+    /// Shows a credential dialog like [`show_credentials_async`](Self::show_credentials_async), resolves to `Err` when it could not be shown
+    fn try_show_credentials_async(
+        &self,
+        dialog: CredentialDialog,
+    ) -> DialogFutureType<Result<Option<Credentials>, Error>> {
+        let future = self.show_credentials_async(dialog);
+        Box::pin(async move { Ok(future.await) })
+    }
+
+    /// Checks if the backend can show dialogs in the current environment
+    ///
+    /// Unavailable backends are skipped by the fallback chain, see [`Backend`]
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Checks if a user answers the dialogs, so showing one again can give another answer
+    ///
+    /// Backends answering from a script, like the headless one, return `false`
+    fn is_interactive(&self) -> bool {
+        true
+    }
+
+    /// Checks if the save dialog asks the user before the chosen file replaces an existing one
+    ///
+    /// `None` when the backend can't tell, eg. because another process shows the dialog
+    fn confirms_overwrite(&self, dialog: &FileDialog) -> Option<bool> {
+        let _ = dialog;
+        None
+    }
+
+    /// Does the one-time setup that would otherwise slow down the first dialog, see [`init`]
+    ///
+    /// Does nothing by default
+    fn init(&self) {}
 }
 
-/// Dialog used to save file
-pub trait FileSaveDialogImpl {
-    fn save_file(self) -> Option<PathBuf>;
+/// Standard buttons standing in for the custom ones, with the labels they stand for
+fn custom_fallback(mut dialog: MessageDialog) -> (MessageDialog, Vec<String>) {
+    let labels = dialog.button_labels();
+    dialog.custom_buttons.clear();
+    // Any button but the first stands for `Cancel`
+    dialog.default_button = dialog.default_button.min(1);
+    dialog.buttons = if labels.len() > 1 {
+        MessageButtons::OkCancel
+    } else {
+        MessageButtons::Ok
+    };
+    (dialog, labels)
 }
 
-/// Dialog used to pick folder
-pub trait FolderPickerDialogImpl {
-    fn pick_folder(self) -> Option<PathBuf>;
+fn custom_label(labels: &[String], pressed: bool) -> Option<String> {
+    if pressed {
+        labels.first().cloned()
+    } else if labels.len() > 1 {
+        labels.last().cloned()
+    } else {
+        None
+    }
 }
 
-pub trait MessageDialogImpl {
-    fn show(self) -> bool;
+/// Index of the filter after the first `skip` ones that contains the extension of `path`, `0` if none does
+///
+/// The longest extension wins, so `a.tar.gz` picks a `tar.gz` filter over an earlier `gz` one
+#[cfg(not(target_arch = "wasm32"))]
+fn filter_index(filters: &[Filter], skip: usize, path: &Path) -> usize {
+    let name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) => name,
+        None => return 0,
+    };
+
+    let mut best = (0, 0);
+    for (index, filter) in filters.iter().enumerate().skip(skip) {
+        match filter.matching_extension(name) {
+            Some(ext) if ext.len() > best.1 => best = (index, ext.len()),
+            _ => {}
+        }
+    }
+    best.0
 }
 
-//
-// Async
-//
+/// Built-in backend used to show a dialog
+///
+/// When the chosen backend is not compiled in, or is not available at runtime,
+/// the next one from the fallback chain is used: `Portal` → `Qt` → `Gtk3` → `Zenity` → `Software`
+///
+/// Platforms other than Linux have only one backend, so the choice is ignored there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Backend {
+    /// Backend registered with [`set_backend`], or the first available built-in one
+    #[default]
+    Default,
+    /// XDG Desktop Portal, requires the `portal` feature
+    Portal,
+    /// Qt `QFileDialog` and `QMessageBox`, requires the `qt` feature
+    Qt,
+    /// GTK3, requires the `gtk3` feature
+    Gtk3,
+    /// `zenity`, `kdialog` or `yad` subprocess, always compiled in on Linux
+    Zenity,
+    /// File browser drawn by rfd itself with `winit`, requires the `software` feature
+    Software,
+}
 
-// Return type of async dialogs:
-#[cfg(not(target_arch = "wasm32"))]
-pub type DialogFutureType<T> = Pin<Box<dyn Future<Output = T> + Send>>;
-#[cfg(target_arch = "wasm32")]
-pub type DialogFutureType<T> = Pin<Box<dyn Future<Output = T>>>;
+impl Backend {
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Default => "default",
+            Backend::Portal => "portal",
+            Backend::Qt => "qt",
+            Backend::Gtk3 => "gtk3",
+            Backend::Zenity => "zenity",
+            Backend::Software => "software",
+        }
+    }
+}
+
+/// Order in which built-in backends are tried
+const FALLBACK_CHAIN: &[Backend] = &[
+    Backend::Portal,
+    Backend::Qt,
+    Backend::Gtk3,
+    Backend::Zenity,
+    Backend::Software,
+];
+
+static BACKEND: RwLock<Option<Arc<dyn DialogBackend>>> = RwLock::new(None);
 
-/// Dialog used to pick file/files
-pub trait AsyncFilePickerDialogImpl {
-    fn pick_file_async(self) -> DialogFutureType<Option<FileHandle>>;
-    fn pick_files_async(self) -> DialogFutureType<Option<Vec<FileHandle>>>;
+/// Replace the built-in platform backend with a custom one.
+///
+/// Every dialog shown after this call goes through `backend`,
+/// unless a built-in one was explicitly requested with `with_backend`.
+/// With `RFD_HEADLESS=1` dialogs are answered from a response file instead, see the README.
+pub fn set_backend(backend: Box<dyn DialogBackend>) {
+    *BACKEND.write().unwrap() = Some(backend.into());
 }
 
-/// Dialog used to pick folder
-pub trait AsyncFolderPickerDialogImpl {
-    fn pick_folder_async(self) -> DialogFutureType<Option<FileHandle>>;
+/// Does the one-time setup of the backend upfront, so the first dialog opens without delay
+///
+/// - Windows: loads the shell libraries behind the dialogs on the COM apartment of the rfd UI thread
+/// - Linux: picks the backend, which initializes GTK or connects to the XDG Desktop Portal
+///
+/// Calling it is optional, dialogs do the same setup on first use. It blocks until the setup is done,
+/// call it from a thread when even that should not hold up the application.
+pub fn init() {
+    backend(Backend::Default).init();
 }
 
-/// Dialog used to pick folder
-pub trait AsyncFileSaveDialogImpl {
-    fn save_file_async(self) -> DialogFutureType<Option<FileHandle>>;
+/// Backend that should show a dialog which asked for `preferred`
+pub(crate) fn backend(preferred: Backend) -> Arc<dyn DialogBackend> {
+    let (backend, name) = choose_backend(preferred);
+    trace_debug!(backend = name, ?preferred, "backend chosen");
+    traced(backend, name)
 }
 
-pub trait AsyncMessageDialogImpl {
-    fn show_async(self) -> DialogFutureType<bool>;
+/// Name of the backend that would show a dialog which asked for `preferred`
+pub(crate) fn backend_name(preferred: Backend) -> &'static str {
+    choose_backend(preferred).1
+}
+
+/// Backend for `preferred` with its name for diagnostics
+fn choose_backend(preferred: Backend) -> (Arc<dyn DialogBackend>, &'static str) {
+    #[cfg(not(target_arch = "wasm32"))]
+    if headless::enabled() {
+        return (Arc::new(headless::HeadlessBackend), "headless");
+    }
+
+    if preferred == Backend::Default {
+        if let Some(backend) = BACKEND.read().unwrap().as_ref() {
+            return (backend.clone(), "custom");
+        }
+    }
+
+    let chain = std::iter::once(preferred)
+        .chain(FALLBACK_CHAIN.iter().copied())
+        .filter_map(|choice| Some((builtin_backend(choice)?, choice.name())));
+
+    let mut last = None;
+    for (backend, name) in chain {
+        if backend.is_available() {
+            return (backend, name);
+        }
+        trace_debug!(backend = name, "backend not available");
+        last = Some((backend, name));
+    }
+
+    // Nothing is available, let the last one fail the way it usually does
+    last.unwrap_or_else(|| (platform_backend(), "platform"))
+}
+
+#[cfg(feature = "tracing")]
+fn traced(backend: Arc<dyn DialogBackend>, name: &'static str) -> Arc<dyn DialogBackend> {
+    Arc::new(traced::Traced { backend, name })
+}
+
+#[cfg(not(feature = "tracing"))]
+fn traced(backend: Arc<dyn DialogBackend>, _name: &'static str) -> Arc<dyn DialogBackend> {
+    backend
+}
+
+#[allow(unreachable_patterns)]
+fn builtin_backend(backend: Backend) -> Option<Arc<dyn DialogBackend>> {
+    match backend {
+        #[cfg(all(target_os = "linux", feature = "portal"))]
+        Backend::Portal => Some(Arc::new(xdg_portal::PortalBackend)),
+        #[cfg(all(target_os = "linux", feature = "qt"))]
+        Backend::Qt => Some(Arc::new(qt::QtBackend)),
+        #[cfg(all(target_os = "linux", feature = "gtk3"))]
+        Backend::Gtk3 => Some(Arc::new(gtk3::Gtk3Backend)),
+        #[cfg(target_os = "linux")]
+        Backend::Zenity => Some(Arc::new(zenity::ZenityBackend)),
+        #[cfg(all(target_os = "linux", feature = "software"))]
+        Backend::Software => Some(Arc::new(software::SoftwareBackend)),
+        _ => None,
+    }
+}
+
+fn platform_backend() -> Arc<dyn DialogBackend> {
+    #[cfg(all(target_os = "linux", feature = "portal"))]
+    let backend = Arc::new(xdg_portal::PortalBackend);
+    #[cfg(all(target_os = "linux", feature = "qt", not(feature = "portal")))]
+    let backend = Arc::new(qt::QtBackend);
+    #[cfg(all(
+        target_os = "linux",
+        feature = "gtk3",
+        not(any(feature = "portal", feature = "qt"))
+    ))]
+    let backend = Arc::new(gtk3::Gtk3Backend);
+    #[cfg(all(
+        target_os = "linux",
+        not(any(feature = "portal", feature = "qt", feature = "gtk3"))
+    ))]
+    let backend = Arc::new(zenity::ZenityBackend);
+    #[cfg(target_os = "macos")]
+    let backend = Arc::new(macos::MacOSBackend);
+    #[cfg(target_arch = "wasm32")]
+    let backend = Arc::new(wasm::WasmBackend);
+    #[cfg(target_os = "windows")]
+    let backend = Arc::new(win_cid::WinBackend);
+    #[cfg(target_os = "android")]
+    let backend = Arc::new(android::AndroidBackend);
+    #[cfg(target_os = "ios")]
+    let backend = Arc::new(ios::IOSBackend);
+
+    backend
+}
+
+#[cfg(test)]
+mod tests {
+    use super::filter_index;
+    use crate::dialog::Filter;
+    use std::path::Path;
+
+    #[test]
+    fn filter_index_from_extension() {
+        let filters = vec![
+            Filter {
+                name: "Text".into(),
+                extensions: vec!["txt".into()],
+                mime_types: Vec::new(),
+                patterns: Vec::new(),
+                file_names: Vec::new(),
+            },
+            Filter {
+                name: "Image".into(),
+                extensions: vec!["png".into(), "jpg".into()],
+                mime_types: Vec::new(),
+                patterns: Vec::new(),
+                file_names: Vec::new(),
+            },
+        ];
+
+        assert_eq!(filter_index(&filters, 0, Path::new("/tmp/a.JPG")), 1);
+        assert_eq!(filter_index(&filters, 0, Path::new("/tmp/a.txt")), 0);
+        assert_eq!(filter_index(&filters, 0, Path::new("/tmp/a.rs")), 0);
+        assert_eq!(filter_index(&filters, 0, Path::new("/tmp/a")), 0);
+        assert_eq!(filter_index(&filters, 1, Path::new("/tmp/a.txt")), 0);
+    }
+
+    #[test]
+    fn filter_index_prefers_compound_extensions() {
+        let filter = |name: &str, extensions: &[&str]| Filter {
+            name: name.into(),
+            extensions: extensions.iter().map(|ext| ext.to_string()).collect(),
+            mime_types: Vec::new(),
+            patterns: Vec::new(),
+            file_names: Vec::new(),
+        };
+        let filters = vec![
+            filter("Gzip", &["gz"]),
+            filter("Tarball", &["tar.gz", "tgz"]),
+            filter("TypeScript", &["ts", "d.ts"]),
+        ];
+
+        assert_eq!(filter_index(&filters, 0, Path::new("/tmp/a.tar.gz")), 1);
+        assert_eq!(filter_index(&filters, 0, Path::new("/tmp/a.gz")), 0);
+        assert_eq!(filter_index(&filters, 0, Path::new("/tmp/a.TGZ")), 1);
+        assert_eq!(filter_index(&filters, 0, Path::new("/tmp/index.d.ts")), 2);
+    }
 }
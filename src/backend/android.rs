@@ -0,0 +1,413 @@
+//! Android Storage Access Framework
+//!
+//! Dialogs are `ACTION_OPEN_DOCUMENT`, `ACTION_CREATE_DOCUMENT` and `ACTION_OPEN_DOCUMENT_TREE`
+//! intents. Native code can't receive their result, so they are started by a small activity
+//! shipped with rfd, `RfdFileDialogActivity.java` next to this file. It has to be compiled into
+//! the app and declared in its manifest:
+//!
+//! ```xml
+//! <activity android:name="rs.rfd.RfdFileDialogActivity"
+//!     android:theme="@android:style/Theme.Translucent.NoTitleBar" />
+//! ```
+//!
+//! The activity and the Java VM are found through `ndk-context`, which is set up by
+//! `ndk-glue` and `android-activity`. The chosen files are `content://` URIs,
+//! [`FileHandle`] opens them through the `ContentResolver`.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::future::Future;
+use std::io;
+use std::os::unix::io::FromRawFd;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use jni::objects::{JClass, JObject, JObjectArray, JString, JValue};
+use jni::sys::jlong;
+use jni::{JNIEnv, JavaVM};
+
+use crate::backend::{DialogBackend, DialogFutureType};
+use crate::{Error, FileDialog, FileHandle, MessageDialog};
+
+const ACTIVITY: &str = "rs.rfd.RfdFileDialogActivity";
+
+const OPEN_DOCUMENT: &str = "android.intent.action.OPEN_DOCUMENT";
+const CREATE_DOCUMENT: &str = "android.intent.action.CREATE_DOCUMENT";
+const OPEN_DOCUMENT_TREE: &str = "android.intent.action.OPEN_DOCUMENT_TREE";
+
+/// Runs `run` with the JNI environment of this thread and the activity of the app
+fn with_activity<T>(
+    run: impl FnOnce(&mut JNIEnv, &JObject) -> jni::errors::Result<T>,
+) -> Result<T, Error> {
+    let context = ndk_context::android_context();
+    let vm = unsafe { JavaVM::from_raw(context.vm().cast()) }
+        .map_err(|err| Error::Jni(err.to_string()))?;
+    let mut env = vm
+        .attach_current_thread()
+        .map_err(|err| Error::Jni(err.to_string()))?;
+    let activity = unsafe { JObject::from_raw(context.context().cast()) };
+
+    let res = run(&mut *env, &activity);
+    if res.is_err() {
+        // A thrown Java exception stays pending until it is cleared
+        let _ = env.exception_clear();
+    }
+    res.map_err(|err| Error::Jni(err.to_string()))
+}
+
+/// Result of a dialog, filled in by `RfdFileDialogActivity.onResult`
+#[derive(Default)]
+struct Pending {
+    state: Mutex<PendingState>,
+    finished: Condvar,
+}
+
+#[derive(Default)]
+struct PendingState {
+    uris: Option<Vec<String>>,
+    waker: Option<Waker>,
+}
+
+impl Pending {
+    fn finish(&self, uris: Vec<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.uris = Some(uris);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.finished.notify_all();
+    }
+
+    /// Blocks until the dialog is closed
+    fn wait(&self) -> Vec<String> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(uris) = state.uris.take() {
+                return uris;
+            }
+            state = self.finished.wait(state).unwrap();
+        }
+    }
+}
+
+/// Resolves once the dialog is closed
+struct PendingFuture(Arc<Pending>);
+
+impl Future for PendingFuture {
+    type Output = Vec<String>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.state.lock().unwrap();
+        match state.uris.take() {
+            Some(uris) => Poll::Ready(uris),
+            None => {
+                state.waker.replace(ctx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Dialogs waiting for their result, by the id passed to the activity
+static REQUESTS: Mutex<BTreeMap<jlong, Arc<Pending>>> = Mutex::new(BTreeMap::new());
+static NEXT_REQUEST: AtomicI64 = AtomicI64::new(0);
+
+/// Called by `RfdFileDialogActivity` with the chosen URIs, none when the dialog was cancelled
+#[no_mangle]
+pub extern "system" fn Java_rs_rfd_RfdFileDialogActivity_onResult(
+    mut env: JNIEnv,
+    _class: JClass,
+    request: jlong,
+    uris: JObjectArray,
+) {
+    let mut read = || -> jni::errors::Result<Vec<String>> {
+        let len = env.get_array_length(&uris)?;
+        let mut strings = Vec::new();
+        for index in 0..len {
+            let uri = JString::from(env.get_object_array_element(&uris, index)?);
+            strings.push(env.get_string(&uri)?.into());
+        }
+        Ok(strings)
+    };
+    let uris = read().unwrap_or_default();
+
+    if let Some(pending) = REQUESTS.lock().unwrap().remove(&request) {
+        pending.finish(uris);
+    }
+}
+
+/// Starts `RfdFileDialogActivity`, which shows the dialog of `action`
+fn launch(opt: &FileDialog, action: &str, multiple: bool) -> Result<Arc<Pending>, Error> {
+    let request = NEXT_REQUEST.fetch_add(1, Ordering::Relaxed);
+    let pending = Arc::new(Pending::default());
+    REQUESTS.lock().unwrap().insert(request, pending.clone());
+
+    // The intent filters by MIME type only, extension filters can't be applied
+    let mime_types: Vec<&str> = opt
+        .filters
+        .iter()
+        .flat_map(|filter| filter.mime_types.iter().map(String::as_str))
+        .collect();
+
+    let res = with_activity(|env, activity| {
+        // Classes of the app are not visible to `FindClass` on native threads
+        let name = env.new_string(ACTIVITY)?;
+        let loader = env
+            .call_method(activity, "getClassLoader", "()Ljava/lang/ClassLoader;", &[])?
+            .l()?;
+        let class = env
+            .call_method(
+                &loader,
+                "loadClass",
+                "(Ljava/lang/String;)Ljava/lang/Class;",
+                &[JValue::Object(&name)],
+            )?
+            .l()?;
+
+        let intent = env.new_object(
+            "android/content/Intent",
+            "(Landroid/content/Context;Ljava/lang/Class;)V",
+            &[JValue::Object(activity), JValue::Object(&class)],
+        )?;
+        put_string(env, &intent, "action", action)?;
+        if let Some(file_name) = &opt.file_name {
+            put_string(env, &intent, "file_name", file_name)?;
+        }
+
+        let key = env.new_string("request")?;
+        env.call_method(
+            &intent,
+            "putExtra",
+            "(Ljava/lang/String;J)Landroid/content/Intent;",
+            &[JValue::Object(&key), JValue::Long(request)],
+        )?;
+        let key = env.new_string("multiple")?;
+        env.call_method(
+            &intent,
+            "putExtra",
+            "(Ljava/lang/String;Z)Landroid/content/Intent;",
+            &[JValue::Object(&key), JValue::Bool(multiple as u8)],
+        )?;
+
+        if !mime_types.is_empty() {
+            let array =
+                env.new_object_array(mime_types.len() as i32, "java/lang/String", JObject::null())?;
+            for (index, mime_type) in mime_types.iter().enumerate() {
+                let mime_type = env.new_string(mime_type)?;
+                env.set_object_array_element(&array, index as i32, mime_type)?;
+            }
+            let key = env.new_string("mime_types")?;
+            env.call_method(
+                &intent,
+                "putExtra",
+                "(Ljava/lang/String;[Ljava/lang/String;)Landroid/content/Intent;",
+                &[JValue::Object(&key), JValue::Object(&array)],
+            )?;
+        }
+
+        env.call_method(
+            activity,
+            "startActivity",
+            "(Landroid/content/Intent;)V",
+            &[JValue::Object(&intent)],
+        )?;
+        Ok(())
+    });
+
+    if let Err(err) = res {
+        REQUESTS.lock().unwrap().remove(&request);
+        return Err(err);
+    }
+    Ok(pending)
+}
+
+fn put_string(
+    env: &mut JNIEnv,
+    intent: &JObject,
+    key: &str,
+    value: &str,
+) -> jni::errors::Result<()> {
+    let key = env.new_string(key)?;
+    let value = env.new_string(value)?;
+    env.call_method(
+        intent,
+        "putExtra",
+        "(Ljava/lang/String;Ljava/lang/String;)Landroid/content/Intent;",
+        &[JValue::Object(&key), JValue::Object(&value)],
+    )?;
+    Ok(())
+}
+
+fn paths(uris: Vec<String>) -> Option<Vec<PathBuf>> {
+    if uris.is_empty() {
+        None
+    } else {
+        Some(uris.into_iter().map(PathBuf::from).collect())
+    }
+}
+
+fn pick(opt: FileDialog, action: &str, multiple: bool) -> Result<Option<Vec<PathBuf>>, Error> {
+    let pending = launch(&opt, action, multiple)?;
+    Ok(paths(pending.wait()))
+}
+
+fn pick_async(
+    opt: FileDialog,
+    action: &str,
+    multiple: bool,
+) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+    let pending = launch(&opt, action, multiple);
+    Box::pin(async move {
+        let uris = PendingFuture(pending?).await;
+        Ok(paths(uris).map(|paths| paths.into_iter().map(FileHandle::wrap).collect()))
+    })
+}
+
+fn first<T>(items: Option<Vec<T>>) -> Option<T> {
+    items.and_then(|items| items.into_iter().next())
+}
+
+/// Opens a `content://` URI with `ContentResolver.openFileDescriptor`, `mode` is eg. `"r"` or `"wt"`
+pub(crate) fn open_content(uri: &str, mode: &str) -> io::Result<File> {
+    let fd = with_activity(|env, activity| {
+        let resolver = env
+            .call_method(
+                activity,
+                "getContentResolver",
+                "()Landroid/content/ContentResolver;",
+                &[],
+            )?
+            .l()?;
+        let uri = env.new_string(uri)?;
+        let uri = env
+            .call_static_method(
+                "android/net/Uri",
+                "parse",
+                "(Ljava/lang/String;)Landroid/net/Uri;",
+                &[JValue::Object(&uri)],
+            )?
+            .l()?;
+        let mode = env.new_string(mode)?;
+        let descriptor = env
+            .call_method(
+                &resolver,
+                "openFileDescriptor",
+                "(Landroid/net/Uri;Ljava/lang/String;)Landroid/os/ParcelFileDescriptor;",
+                &[JValue::Object(&uri), JValue::Object(&mode)],
+            )?
+            .l()?;
+        // The descriptor is owned by the `File` from now on
+        env.call_method(&descriptor, "detachFd", "()I", &[])?.i()
+    })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
+
+/// Name of the document of a `content://` URI, the last part of its percent-decoded id
+pub(crate) fn content_file_name(uri: &str) -> String {
+    let id = uri.rsplit('/').next().unwrap_or_default();
+
+    let mut bytes = Vec::with_capacity(id.len());
+    let mut rest = id.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let hex = tail.get(..2).and_then(|hex| std::str::from_utf8(hex).ok());
+        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(decoded) if byte == b'%' => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    // Ids look like `primary:Documents/notes.txt`
+    let id = String::from_utf8_lossy(&bytes);
+    id.rsplit(|c: char| c == '/' || c == ':')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Android backend, Storage Access Framework intents
+pub struct AndroidBackend;
+
+impl DialogBackend for AndroidBackend {
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        pick(dialog, OPEN_DOCUMENT, false).map(first)
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        pick(dialog, OPEN_DOCUMENT, true)
+    }
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        pick(dialog, OPEN_DOCUMENT_TREE, false).map(first)
+    }
+
+    // The tree picker chooses one folder
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        pick(dialog, OPEN_DOCUMENT_TREE, false)
+    }
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        pick(dialog, CREATE_DOCUMENT, false).map(first)
+    }
+
+    // There is no message dialog without a Java UI of the app
+    fn show_message(&self, _dialog: MessageDialog) -> bool {
+        trace_warn!(
+            title = %_dialog.title,
+            "MessageDialog is not supported on Android, it was not shown"
+        );
+        false
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(dialog, OPEN_DOCUMENT, false);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        pick_async(dialog, OPEN_DOCUMENT, true)
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(dialog, OPEN_DOCUMENT_TREE, false);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        pick_async(dialog, OPEN_DOCUMENT_TREE, false)
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(dialog, CREATE_DOCUMENT, false);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        Box::pin(std::future::ready(self.show_message(dialog)))
+    }
+}
@@ -0,0 +1,64 @@
+package rs.rfd;
+
+import android.app.Activity;
+import android.content.ClipData;
+import android.content.Intent;
+import android.os.Bundle;
+
+/**
+ * Shows a Storage Access Framework dialog for rfd and hands the chosen URIs back to native code.
+ *
+ * Native code can't receive activity results, this translucent activity starts the dialog
+ * with the extras set by rfd and finishes once it got the result.
+ */
+public class RfdFileDialogActivity extends Activity {
+    private static native void onResult(long request, String[] uris);
+
+    @Override
+    protected void onCreate(Bundle savedInstanceState) {
+        super.onCreate(savedInstanceState);
+        // Recreated after a configuration change, the dialog is still shown
+        if (savedInstanceState != null) {
+            return;
+        }
+
+        Intent extras = getIntent();
+        String action = extras.getStringExtra("action");
+        Intent dialog = new Intent(action);
+
+        if (!Intent.ACTION_OPEN_DOCUMENT_TREE.equals(action)) {
+            dialog.addCategory(Intent.CATEGORY_OPENABLE);
+            dialog.setType("*/*");
+            String[] mimeTypes = extras.getStringArrayExtra("mime_types");
+            if (mimeTypes != null) {
+                dialog.putExtra(Intent.EXTRA_MIME_TYPES, mimeTypes);
+            }
+        }
+        dialog.putExtra(Intent.EXTRA_ALLOW_MULTIPLE, extras.getBooleanExtra("multiple", false));
+        String fileName = extras.getStringExtra("file_name");
+        if (fileName != null) {
+            dialog.putExtra(Intent.EXTRA_TITLE, fileName);
+        }
+
+        startActivityForResult(dialog, 0);
+    }
+
+    @Override
+    protected void onActivityResult(int requestCode, int resultCode, Intent data) {
+        String[] uris = new String[0];
+        if (resultCode == RESULT_OK && data != null) {
+            ClipData clip = data.getClipData();
+            if (clip != null) {
+                uris = new String[clip.getItemCount()];
+                for (int i = 0; i < uris.length; i++) {
+                    uris[i] = clip.getItemAt(i).getUri().toString();
+                }
+            } else if (data.getData() != null) {
+                uris = new String[] { data.getData().toString() };
+            }
+        }
+
+        onResult(getIntent().getLongExtra("request", 0), uris);
+        finish();
+    }
+}
@@ -1,11 +1,162 @@
+mod credential_dialog;
 mod file_dialog;
+mod input_dialog;
 mod message_dialog;
+mod pick_list_dialog;
+mod progress_dialog;
 
 mod gtk_future;
 
 mod utils;
 
+use std::path::PathBuf;
+use url::Url;
+
+use crate::backend::{DialogBackend, DialogFutureType};
+use crate::{
+    CredentialDialog, Credentials, Error, FileDialog, FileHandle, InputDialog, MessageDialog,
+    PickListDialog, ProgressDialog, ProgressState,
+};
+use utils::{gtk_init_check, GTK_MUTEX};
+
 pub(self) trait AsGtkDialog {
     fn gtk_dialog_ptr(&self) -> *mut gtk_sys::GtkDialog;
     unsafe fn show(&self);
 }
+
+/// GTK3 backend, `GtkFileChooserNative` and `GtkMessageDialog`
+pub struct Gtk3Backend;
+
+impl DialogBackend for Gtk3Backend {
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        file_dialog::pick_file(dialog)
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        file_dialog::pick_files(dialog)
+    }
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        file_dialog::pick_folder(dialog)
+    }
+
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        file_dialog::pick_folders(dialog)
+    }
+
+    fn pick_file_url(&self, dialog: FileDialog) -> Result<Option<Url>, Error> {
+        file_dialog::pick_file_url(dialog)
+    }
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        file_dialog::save_file(dialog)
+    }
+
+    fn save_file_with_filter(&self, dialog: FileDialog) -> Result<Option<(PathBuf, usize)>, Error> {
+        file_dialog::save_file_with_filter(dialog)
+    }
+
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        message_dialog::show(dialog)
+    }
+
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        message_dialog::show_custom(dialog)
+    }
+
+    fn show_input(&self, dialog: InputDialog) -> Option<String> {
+        input_dialog::show(dialog)
+    }
+
+    fn show_pick_list(&self, dialog: PickListDialog) -> Option<usize> {
+        pick_list_dialog::show(dialog)
+    }
+
+    fn show_credentials(&self, dialog: CredentialDialog) -> Option<Credentials> {
+        credential_dialog::show(dialog)
+    }
+
+    fn show_progress(&self, dialog: ProgressDialog, state: ProgressState) {
+        progress_dialog::show(dialog, state)
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        file_dialog::pick_file_async(dialog)
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        file_dialog::pick_files_async(dialog)
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        file_dialog::pick_folder_async(dialog)
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        file_dialog::pick_folders_async(dialog)
+    }
+
+    fn pick_file_url_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Url>, Error>> {
+        file_dialog::pick_file_url_async(dialog)
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        file_dialog::save_file_async(dialog)
+    }
+
+    fn save_file_with_filter_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<(FileHandle, usize)>, Error>> {
+        file_dialog::save_file_with_filter_async(dialog)
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        message_dialog::show_async(dialog)
+    }
+
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        message_dialog::show_custom_async(dialog)
+    }
+
+    fn show_input_async(&self, dialog: InputDialog) -> DialogFutureType<Option<String>> {
+        input_dialog::show_async(dialog)
+    }
+
+    fn show_pick_list_async(&self, dialog: PickListDialog) -> DialogFutureType<Option<usize>> {
+        pick_list_dialog::show_async(dialog)
+    }
+
+    fn show_credentials_async(
+        &self,
+        dialog: CredentialDialog,
+    ) -> DialogFutureType<Option<Credentials>> {
+        credential_dialog::show_async(dialog)
+    }
+
+    fn confirms_overwrite(&self, dialog: &FileDialog) -> Option<bool> {
+        Some(dialog.confirm_overwrite != Some(false))
+    }
+
+    fn is_available(&self) -> bool {
+        GTK_MUTEX.run_locked(gtk_init_check)
+    }
+}
@@ -0,0 +1,158 @@
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use super::gtk_future::GtkDialogFuture;
+use super::utils::wait_for_cleanup;
+use super::AsGtkDialog;
+
+use crate::backend::DialogFutureType;
+use crate::localizer::{localize, mnemonic};
+use crate::{BuiltinString, CredentialDialog, Credentials, DialogHandle};
+
+pub struct GtkCredentialDialog {
+    ptr: *mut gtk_sys::GtkDialog,
+    user: *mut gtk_sys::GtkWidget,
+    password: *mut gtk_sys::GtkWidget,
+}
+
+impl GtkCredentialDialog {
+    pub fn new(opt: CredentialDialog) -> Self {
+        super::utils::gtk_init_check();
+
+        let title = CString::new(opt.title).unwrap();
+        let message = CString::new(opt.message).unwrap();
+        let user = CString::new(opt.user.as_str()).unwrap();
+
+        let ptr = unsafe {
+            gtk_sys::gtk_message_dialog_new(
+                ptr::null_mut(),
+                gtk_sys::GTK_DIALOG_MODAL,
+                gtk_sys::GTK_MESSAGE_QUESTION,
+                gtk_sys::GTK_BUTTONS_OK_CANCEL,
+                b"%s\0".as_ptr() as *mut _,
+                title.as_ptr(),
+            ) as *mut gtk_sys::GtkDialog
+        };
+
+        #[cfg(feature = "parent")]
+        unsafe {
+            super::utils::set_parent(ptr as _, opt.parent());
+        }
+
+        let (user, password) = unsafe {
+            gtk_sys::gtk_message_dialog_format_secondary_text(
+                ptr as *mut _,
+                b"%s\0".as_ptr() as *const _,
+                message.as_ptr(),
+            );
+            gtk_sys::gtk_dialog_set_default_response(ptr, gtk_sys::GTK_RESPONSE_OK);
+
+            let grid = gtk_sys::gtk_grid_new();
+            gtk_sys::gtk_grid_set_row_spacing(grid as *mut _, 6);
+            gtk_sys::gtk_grid_set_column_spacing(grid as *mut _, 12);
+
+            let user_entry = add_row(grid, 0, BuiltinString::UserName, &user);
+            let password = add_row(grid, 1, BuiltinString::Password, &CString::default());
+            gtk_sys::gtk_entry_set_visibility(password as *mut _, glib_sys::GFALSE);
+            gtk_sys::gtk_entry_set_input_purpose(
+                password as *mut _,
+                gtk_sys::GTK_INPUT_PURPOSE_PASSWORD,
+            );
+
+            let area = gtk_sys::gtk_message_dialog_get_message_area(ptr as *mut _);
+            gtk_sys::gtk_container_add(area as *mut gtk_sys::GtkContainer, grid);
+            gtk_sys::gtk_widget_show_all(grid);
+
+            // A known user only has to type the password
+            if user.as_bytes().is_empty() {
+                gtk_sys::gtk_widget_grab_focus(user_entry);
+            } else {
+                gtk_sys::gtk_widget_grab_focus(password);
+            }
+            (user_entry, password)
+        };
+
+        Self {
+            ptr,
+            user,
+            password,
+        }
+    }
+
+    pub fn run(self) -> Option<Credentials> {
+        let res = unsafe { gtk_sys::gtk_dialog_run(self.ptr) };
+
+        self.result(res)
+    }
+
+    /// Content of the fields when OK was pressed
+    fn result(&self, res: i32) -> Option<Credentials> {
+        if res != gtk_sys::GTK_RESPONSE_OK {
+            return None;
+        }
+
+        let text = |entry: *mut gtk_sys::GtkWidget| unsafe {
+            let text = CStr::from_ptr(gtk_sys::gtk_entry_get_text(entry as *mut _));
+            text.to_string_lossy().into_owned()
+        };
+        Some(Credentials {
+            user: text(self.user),
+            password: text(self.password).into(),
+        })
+    }
+}
+
+/// Labeled entry in row `top` of `grid`, Enter in it presses OK
+unsafe fn add_row(
+    grid: *mut gtk_sys::GtkWidget,
+    top: i32,
+    label: BuiltinString,
+    text: &CStr,
+) -> *mut gtk_sys::GtkWidget {
+    let label = CString::new(mnemonic(&format!("{}:", localize(label)), '_')).unwrap_or_default();
+    let label = gtk_sys::gtk_label_new_with_mnemonic(label.as_ptr());
+    gtk_sys::gtk_widget_set_halign(label, gtk_sys::GTK_ALIGN_END);
+
+    let entry = gtk_sys::gtk_entry_new();
+    gtk_sys::gtk_entry_set_text(entry as *mut _, text.as_ptr());
+    gtk_sys::gtk_entry_set_activates_default(entry as *mut _, glib_sys::GTRUE);
+    gtk_sys::gtk_widget_set_hexpand(entry, glib_sys::GTRUE);
+    gtk_sys::gtk_label_set_mnemonic_widget(label as *mut _, entry);
+
+    gtk_sys::gtk_grid_attach(grid as *mut _, label, 0, top, 1, 1);
+    gtk_sys::gtk_grid_attach(grid as *mut _, entry, 1, top, 1, 1);
+    entry
+}
+
+impl Drop for GtkCredentialDialog {
+    fn drop(&mut self) {
+        unsafe {
+            wait_for_cleanup();
+            gtk_sys::gtk_widget_destroy(self.ptr as *mut _);
+            wait_for_cleanup();
+        }
+    }
+}
+
+impl AsGtkDialog for GtkCredentialDialog {
+    fn gtk_dialog_ptr(&self) -> *mut gtk_sys::GtkDialog {
+        self.ptr as *mut _
+    }
+    unsafe fn show(&self) {
+        gtk_sys::gtk_widget_show_all(self.ptr as *mut _);
+    }
+}
+
+pub fn show(opt: CredentialDialog) -> Option<Credentials> {
+    let dialog = GtkCredentialDialog::new(opt);
+    dialog.run()
+}
+
+pub fn show_async(opt: CredentialDialog) -> DialogFutureType<Option<Credentials>> {
+    let builder = move || GtkCredentialDialog::new(opt);
+
+    let future = GtkDialogFuture::new(DialogHandle::default(), builder, |dialog, res| {
+        dialog.result(res)
+    });
+    Box::pin(async move { future.await.ok().flatten() })
+}
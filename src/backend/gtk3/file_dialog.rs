@@ -1,167 +1,305 @@
+mod dialog_events;
 pub mod dialog_ffi;
 
 use dialog_ffi::GtkFileDialog;
 
 use std::path::PathBuf;
+use url::Url;
 
-use super::utils::{gtk_init_check, GTK_MUTEX};
+use super::utils::{emit_response, gtk_init_check, init_error, GTK_MUTEX};
+use super::AsGtkDialog;
 use crate::backend::DialogFutureType;
-use crate::{FileDialog, FileHandle};
+use crate::{DialogHandle, Error, FileDialog, FileHandle};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use super::gtk_future::GtkDialogFuture;
 
+/// `gtk_native_dialog_run`, closing `handle` makes the dialog respond with cancel
+///
+/// The GTK_MUTEX is locked for the whole run, so the response is emitted from an idle callback of the dialog's main loop
+fn run(dialog: &GtkFileDialog, handle: &DialogHandle) -> i32 {
+    struct Close {
+        ptr: usize,
+        done: Arc<AtomicBool>,
+    }
+
+    unsafe extern "C" fn close_idle(data: glib_sys::gpointer) -> glib_sys::gboolean {
+        let close = Box::from_raw(data as *mut Close);
+        if !close.done.load(Ordering::Acquire) {
+            emit_response(close.ptr as *mut _, gtk_sys::GTK_RESPONSE_CANCEL);
+        }
+        glib_sys::GFALSE
+    }
+
+    let done = Arc::new(AtomicBool::new(false));
+    {
+        let close = Close {
+            ptr: dialog.gtk_dialog_ptr() as usize,
+            done: done.clone(),
+        };
+        handle.on_close(move || unsafe {
+            glib_sys::g_idle_add(Some(close_idle), Box::into_raw(Box::new(close)) as _);
+        });
+    }
+
+    let res = dialog.run();
+    done.store(true, Ordering::Release);
+    res
+}
+
 //
 // File Picker
 //
 
-use crate::backend::FilePickerDialogImpl;
-impl FilePickerDialogImpl for FileDialog {
-    fn pick_file(self) -> Option<PathBuf> {
-        GTK_MUTEX.run_locked(|| {
-            if !gtk_init_check() {
-                return None;
-            };
-
-            let dialog = GtkFileDialog::build_pick_file(&self);
-
-            if dialog.run() == gtk_sys::GTK_RESPONSE_ACCEPT {
-                dialog.get_result()
-            } else {
-                None
-            }
-        })
-    }
+pub fn pick_file(opt: FileDialog) -> Result<Option<PathBuf>, Error> {
+    GTK_MUTEX.run_locked(|| {
+        if !gtk_init_check() {
+            return Err(init_error());
+        };
 
-    fn pick_files(self) -> Option<Vec<PathBuf>> {
-        GTK_MUTEX.run_locked(|| {
-            if !gtk_init_check() {
-                return None;
-            };
+        let dialog = GtkFileDialog::build_pick_file(&opt);
 
-            let dialog = GtkFileDialog::build_pick_files(&self);
+        if run(&dialog, &opt.handle) == gtk_sys::GTK_RESPONSE_ACCEPT {
+            Ok(dialog.get_result())
+        } else {
+            Ok(None)
+        }
+    })
+}
 
-            if dialog.run() == gtk_sys::GTK_RESPONSE_ACCEPT {
-                Some(dialog.get_results())
-            } else {
-                None
-            }
-        })
-    }
+pub fn pick_file_url(opt: FileDialog) -> Result<Option<Url>, Error> {
+    GTK_MUTEX.run_locked(|| {
+        if !gtk_init_check() {
+            return Err(init_error());
+        };
+
+        let dialog = GtkFileDialog::build_pick_file(&opt);
+
+        if run(&dialog, &opt.handle) == gtk_sys::GTK_RESPONSE_ACCEPT {
+            Ok(dialog
+                .get_result_uri()
+                .and_then(|uri| Url::parse(&uri).ok()))
+        } else {
+            Ok(None)
+        }
+    })
 }
 
-use crate::backend::AsyncFilePickerDialogImpl;
-impl AsyncFilePickerDialogImpl for FileDialog {
-    fn pick_file_async(self) -> DialogFutureType<Option<FileHandle>> {
-        let builder = move || GtkFileDialog::build_pick_file(&self);
-
-        let future = GtkDialogFuture::new(builder, |dialog, res_id| {
-            if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
-                dialog.get_result().map(FileHandle::wrap)
-            } else {
-                None
-            }
-        });
+pub fn pick_files(opt: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+    GTK_MUTEX.run_locked(|| {
+        if !gtk_init_check() {
+            return Err(init_error());
+        };
 
-        Box::pin(future)
-    }
+        let dialog = GtkFileDialog::build_pick_files(&opt);
 
-    fn pick_files_async(self) -> DialogFutureType<Option<Vec<FileHandle>>> {
-        let builder = move || GtkFileDialog::build_pick_files(&self);
-
-        let future = GtkDialogFuture::new(builder, |dialog, res_id| {
-            if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
-                Some(
-                    dialog
-                        .get_results()
-                        .into_iter()
-                        .map(FileHandle::wrap)
-                        .collect(),
-                )
-            } else {
-                None
-            }
-        });
+        if run(&dialog, &opt.handle) == gtk_sys::GTK_RESPONSE_ACCEPT {
+            Ok(Some(dialog.get_results()))
+        } else {
+            Ok(None)
+        }
+    })
+}
 
-        Box::pin(future)
-    }
+pub fn pick_file_async(opt: FileDialog) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+    let handle = opt.handle.clone();
+    let builder = move || GtkFileDialog::build_pick_file(&opt);
+
+    let future = GtkDialogFuture::new(handle, builder, |dialog, res_id| {
+        if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
+            dialog.get_result().map(FileHandle::wrap)
+        } else {
+            None
+        }
+    });
+
+    Box::pin(future)
+}
+
+pub fn pick_file_url_async(opt: FileDialog) -> DialogFutureType<Result<Option<Url>, Error>> {
+    let handle = opt.handle.clone();
+    let builder = move || GtkFileDialog::build_pick_file(&opt);
+
+    let future = GtkDialogFuture::new(handle, builder, |dialog, res_id| {
+        if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
+            dialog
+                .get_result_uri()
+                .and_then(|uri| Url::parse(&uri).ok())
+        } else {
+            None
+        }
+    });
+
+    Box::pin(future)
+}
+
+pub fn pick_files_async(
+    opt: FileDialog,
+) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+    let handle = opt.handle.clone();
+    let builder = move || GtkFileDialog::build_pick_files(&opt);
+
+    let future = GtkDialogFuture::new(handle, builder, |dialog, res_id| {
+        if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
+            Some(
+                dialog
+                    .get_results()
+                    .into_iter()
+                    .map(FileHandle::wrap)
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    });
+
+    Box::pin(future)
 }
 
 //
 // Folder Picker
 //
 
-use crate::backend::FolderPickerDialogImpl;
-impl FolderPickerDialogImpl for FileDialog {
-    fn pick_folder(self) -> Option<PathBuf> {
-        GTK_MUTEX.run_locked(|| {
-            if !gtk_init_check() {
-                return None;
-            };
-
-            let dialog = GtkFileDialog::build_pick_folder(&self);
-
-            if dialog.run() == gtk_sys::GTK_RESPONSE_ACCEPT {
-                dialog.get_result()
-            } else {
-                None
-            }
-        })
-    }
+pub fn pick_folder(opt: FileDialog) -> Result<Option<PathBuf>, Error> {
+    GTK_MUTEX.run_locked(|| {
+        if !gtk_init_check() {
+            return Err(init_error());
+        };
+
+        let dialog = GtkFileDialog::build_pick_folder(&opt);
+
+        if run(&dialog, &opt.handle) == gtk_sys::GTK_RESPONSE_ACCEPT {
+            Ok(dialog.get_result())
+        } else {
+            Ok(None)
+        }
+    })
 }
 
-use crate::backend::AsyncFolderPickerDialogImpl;
-impl AsyncFolderPickerDialogImpl for FileDialog {
-    fn pick_folder_async(self) -> DialogFutureType<Option<FileHandle>> {
-        let builder = move || GtkFileDialog::build_pick_folder(&self);
-
-        let future = GtkDialogFuture::new(builder, |dialog, res_id| {
-            if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
-                dialog.get_result().map(FileHandle::wrap)
-            } else {
-                None
-            }
-        });
+pub fn pick_folder_async(opt: FileDialog) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+    let handle = opt.handle.clone();
+    let builder = move || GtkFileDialog::build_pick_folder(&opt);
 
-        Box::pin(future)
-    }
+    let future = GtkDialogFuture::new(handle, builder, |dialog, res_id| {
+        if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
+            dialog.get_result().map(FileHandle::wrap)
+        } else {
+            None
+        }
+    });
+
+    Box::pin(future)
+}
+
+pub fn pick_folders(opt: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+    GTK_MUTEX.run_locked(|| {
+        if !gtk_init_check() {
+            return Err(init_error());
+        };
+
+        let dialog = GtkFileDialog::build_pick_folders(&opt);
+
+        if run(&dialog, &opt.handle) == gtk_sys::GTK_RESPONSE_ACCEPT {
+            Ok(Some(dialog.get_results()))
+        } else {
+            Ok(None)
+        }
+    })
+}
+
+pub fn pick_folders_async(
+    opt: FileDialog,
+) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+    let handle = opt.handle.clone();
+    let builder = move || GtkFileDialog::build_pick_folders(&opt);
+
+    let future = GtkDialogFuture::new(handle, builder, |dialog, res_id| {
+        if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
+            Some(
+                dialog
+                    .get_results()
+                    .into_iter()
+                    .map(FileHandle::wrap)
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    });
+
+    Box::pin(future)
 }
 
 //
 // File Save
 //
 
-use crate::backend::FileSaveDialogImpl;
-impl FileSaveDialogImpl for FileDialog {
-    fn save_file(self) -> Option<PathBuf> {
-        GTK_MUTEX.run_locked(|| {
-            if !gtk_init_check() {
-                return None;
-            };
-
-            let dialog = GtkFileDialog::build_save_file(&self);
-
-            if dialog.run() == gtk_sys::GTK_RESPONSE_ACCEPT {
-                dialog.get_result()
-            } else {
-                None
-            }
-        })
-    }
+pub fn save_file(opt: FileDialog) -> Result<Option<PathBuf>, Error> {
+    GTK_MUTEX.run_locked(|| {
+        if !gtk_init_check() {
+            return Err(init_error());
+        };
+
+        let dialog = GtkFileDialog::build_save_file(&opt);
+
+        if run(&dialog, &opt.handle) == gtk_sys::GTK_RESPONSE_ACCEPT {
+            Ok(dialog.get_result())
+        } else {
+            Ok(None)
+        }
+    })
 }
 
-use crate::backend::AsyncFileSaveDialogImpl;
-impl AsyncFileSaveDialogImpl for FileDialog {
-    fn save_file_async(self) -> DialogFutureType<Option<FileHandle>> {
-        let builder = move || GtkFileDialog::build_save_file(&self);
-
-        let future = GtkDialogFuture::new(builder, |dialog, res_id| {
-            if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
-                dialog.get_result().map(FileHandle::wrap)
-            } else {
-                None
-            }
-        });
+pub fn save_file_async(opt: FileDialog) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+    let handle = opt.handle.clone();
+    let builder = move || GtkFileDialog::build_save_file(&opt);
 
-        Box::pin(future)
-    }
+    let future = GtkDialogFuture::new(handle, builder, |dialog, res_id| {
+        if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
+            dialog.get_result().map(FileHandle::wrap)
+        } else {
+            None
+        }
+    });
+
+    Box::pin(future)
+}
+
+pub fn save_file_with_filter(opt: FileDialog) -> Result<Option<(PathBuf, usize)>, Error> {
+    GTK_MUTEX.run_locked(|| {
+        if !gtk_init_check() {
+            return Err(init_error());
+        };
+
+        let dialog = GtkFileDialog::build_save_file(&opt);
+
+        if run(&dialog, &opt.handle) == gtk_sys::GTK_RESPONSE_ACCEPT {
+            Ok(dialog
+                .get_result()
+                .map(|path| (path, dialog.get_filter_index())))
+        } else {
+            Ok(None)
+        }
+    })
+}
+
+pub fn save_file_with_filter_async(
+    opt: FileDialog,
+) -> DialogFutureType<Result<Option<(FileHandle, usize)>, Error>> {
+    let handle = opt.handle.clone();
+    let builder = move || GtkFileDialog::build_save_file(&opt);
+
+    let future = GtkDialogFuture::new(handle, builder, |dialog, res_id| {
+        if res_id == gtk_sys::GTK_RESPONSE_ACCEPT {
+            dialog
+                .get_result()
+                .map(|path| (FileHandle::wrap(path), dialog.get_filter_index()))
+        } else {
+            None
+        }
+    });
+
+    Box::pin(future)
 }
@@ -0,0 +1,461 @@
+//! Forwards GtkFileChooser signals to [`DialogEvents`], the validator, the custom controls and the preview
+
+use super::super::gtk_future::connect_raw;
+use crate::localizer::{localize_with, BuiltinString};
+use crate::{DialogChoices, DialogEvents, FileDialog, Filter, Preview, PreviewContent, Validate};
+
+use gtk_sys::GtkFileChooser;
+
+use std::ffi::{CStr, CString};
+use std::mem::transmute;
+use std::os::raw::c_int;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::Arc;
+
+type Events = Arc<dyn DialogEvents>;
+
+unsafe fn path(filename: *const std::os::raw::c_char) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(
+        CStr::from_ptr(filename).to_bytes(),
+    ))
+}
+
+/// Position of the selected filter in the list of added filters
+pub unsafe fn filter_index(chooser: *mut GtkFileChooser) -> usize {
+    let current = gtk_sys::gtk_file_chooser_get_filter(chooser);
+    if current.is_null() {
+        return 0;
+    }
+
+    let filters = gtk_sys::gtk_file_chooser_list_filters(chooser);
+    let index = glib_sys::g_slist_index(filters, current as _);
+    glib_sys::g_slist_free(filters);
+
+    index.max(0) as usize
+}
+
+unsafe extern "C" fn folder_changed(chooser: *mut GtkFileChooser, events: glib_sys::gpointer) {
+    let events = &*(events as *const Events);
+
+    let folder = gtk_sys::gtk_file_chooser_get_current_folder(chooser);
+    if !folder.is_null() {
+        let folder_path = path(folder);
+        glib_sys::g_free(folder as _);
+        events.on_folder_change(&folder_path);
+    }
+}
+
+unsafe fn filenames(chooser: *mut GtkFileChooser) -> Vec<PathBuf> {
+    let list = gtk_sys::gtk_file_chooser_get_filenames(chooser);
+    let mut paths = Vec::new();
+    let mut item = list;
+    while !item.is_null() {
+        paths.push(path((*item).data as _));
+        item = (*item).next;
+    }
+    glib_sys::g_slist_free_full(list, Some(glib_sys::g_free));
+    paths
+}
+
+unsafe extern "C" fn selection_changed(chooser: *mut GtkFileChooser, events: glib_sys::gpointer) {
+    let events = &*(events as *const Events);
+    events.on_selection_change(&filenames(chooser));
+}
+
+unsafe extern "C" fn filter_changed(
+    chooser: *mut GtkFileChooser,
+    _: *mut gobject_sys::GParamSpec,
+    events: glib_sys::gpointer,
+) {
+    let events = &*(events as *const Events);
+    events.on_type_change(filter_index(chooser));
+}
+
+/// Connects the signals of `chooser`, the events are dropped together with it
+pub unsafe fn connect(chooser: *mut GtkFileChooser, events: &Events) {
+    connect_raw(
+        chooser as _,
+        b"current-folder-changed\0".as_ptr() as _,
+        Some(transmute::<_, unsafe extern "C" fn()>(
+            folder_changed as *const (),
+        )),
+        Box::into_raw(Box::new(events.clone())),
+    );
+    connect_raw(
+        chooser as _,
+        b"selection-changed\0".as_ptr() as _,
+        Some(transmute::<_, unsafe extern "C" fn()>(
+            selection_changed as *const (),
+        )),
+        Box::into_raw(Box::new(events.clone())),
+    );
+    connect_raw(
+        chooser as _,
+        b"notify::filter\0".as_ptr() as _,
+        Some(transmute::<_, unsafe extern "C" fn()>(
+            filter_changed as *const (),
+        )),
+        Box::into_raw(Box::new(events.clone())),
+    );
+}
+
+/// `name` with the extension of the filter at `index`, instead of the one of another filter
+fn with_filter_extension(name: &str, filters: &[Filter], index: usize) -> Option<String> {
+    let ext = filters.get(index)?.extensions.first()?;
+    if name.is_empty() {
+        return None;
+    }
+    let stem = match filters
+        .iter()
+        .find_map(|filter| filter.matching_extension(name))
+    {
+        Some(known) => &name[..name.len() - known.len() - 1],
+        // A name with an extension no filter has is kept as it is
+        None if Path::new(name).extension().is_some() => return None,
+        None => name,
+    };
+    Some(format!("{}.{}", stem, ext))
+}
+
+unsafe extern "C" fn extension_filter_changed(
+    chooser: *mut GtkFileChooser,
+    _: *mut gobject_sys::GParamSpec,
+    filters: glib_sys::gpointer,
+) {
+    let filters = &*(filters as *const Vec<Filter>);
+    let current = gtk_sys::gtk_file_chooser_get_current_name(chooser);
+    if current.is_null() {
+        return;
+    }
+    let name = CStr::from_ptr(current).to_string_lossy().into_owned();
+    glib_sys::g_free(current as _);
+
+    if let Some(name) = with_filter_extension(&name, filters, filter_index(chooser)) {
+        let name = label(&name);
+        gtk_sys::gtk_file_chooser_set_current_name(chooser, name.as_ptr());
+    }
+}
+
+/// Puts the extension of the selected filter on the typed name of a save dialog with
+/// `set_append_extension`, so the overwrite confirmation asks about the path that is returned
+pub unsafe fn connect_extension(chooser: *mut GtkFileChooser, opt: &FileDialog) {
+    let saves =
+        gtk_sys::gtk_file_chooser_get_action(chooser) == gtk_sys::GTK_FILE_CHOOSER_ACTION_SAVE;
+    if !saves || opt.append_extension != Some(true) || opt.filters.is_empty() {
+        return;
+    }
+
+    connect_raw(
+        chooser as _,
+        b"notify::filter\0".as_ptr() as _,
+        Some(transmute::<_, unsafe extern "C" fn()>(
+            extension_filter_changed as *const (),
+        )),
+        Box::into_raw(Box::new(opt.filters.clone())),
+    );
+}
+
+unsafe fn show_error(parent: *mut gtk_sys::GtkDialog, message: &str) {
+    let message = CString::new(message.replace('\0', "")).unwrap();
+    let dialog = gtk_sys::gtk_message_dialog_new(
+        parent as _,
+        gtk_sys::GTK_DIALOG_MODAL | gtk_sys::GTK_DIALOG_DESTROY_WITH_PARENT,
+        gtk_sys::GTK_MESSAGE_ERROR,
+        gtk_sys::GTK_BUTTONS_OK,
+        b"%s\0".as_ptr() as *mut _,
+        message.as_ptr(),
+    );
+    gtk_sys::gtk_dialog_run(dialog as _);
+    gtk_sys::gtk_widget_destroy(dialog);
+}
+
+/// Checkboxes, combo boxes and text fields of the extra widget, owned by the dialog
+#[derive(Default)]
+struct Controls {
+    checkboxes: Vec<(u32, *mut gtk_sys::GtkToggleButton)>,
+    combos: Vec<(u32, *mut gtk_sys::GtkComboBox)>,
+    text_fields: Vec<(u32, *mut gtk_sys::GtkEntry)>,
+}
+
+impl Controls {
+    fn is_empty(&self) -> bool {
+        self.checkboxes.is_empty() && self.combos.is_empty() && self.text_fields.is_empty()
+    }
+}
+
+struct Accept {
+    validator: Option<Arc<Validate>>,
+    /// Filters of a save dialog with `set_strict_file_types`
+    file_types: Option<Vec<Filter>>,
+    // They live as long as the signal handler
+    controls: Controls,
+    choices: DialogChoices,
+}
+
+unsafe extern "C" fn response(
+    dialog: *mut gtk_sys::GtkDialog,
+    response_id: c_int,
+    accept: glib_sys::gpointer,
+) {
+    if response_id != gtk_sys::GTK_RESPONSE_ACCEPT {
+        return;
+    }
+
+    let accept = &*(accept as *const Accept);
+    if let Some(name) = unknown_file_type(dialog as _, accept) {
+        // Appending an extension to `notes.md` would replace a file nobody asked about
+        gobject_sys::g_signal_stop_emission_by_name(dialog as _, b"response\0".as_ptr() as _);
+        show_error(
+            dialog,
+            &localize_with(BuiltinString::UnknownFileType, &name),
+        );
+        return;
+    }
+    if let Some(validator) = &accept.validator {
+        if let Some(Err(message)) = filenames(dialog as _)
+            .iter()
+            .map(|path| validator(path))
+            .find(Result::is_err)
+        {
+            // Keeps the dialog open, the chooser's own handler already ran so it is the last check
+            gobject_sys::g_signal_stop_emission_by_name(dialog as _, b"response\0".as_ptr() as _);
+            show_error(dialog, &message);
+            return;
+        }
+    }
+
+    for (id, button) in &accept.controls.checkboxes {
+        let checked = gtk_sys::gtk_toggle_button_get_active(*button) != glib_sys::GFALSE;
+        accept.choices.set_checked(*id, checked);
+    }
+    for (id, combo) in &accept.controls.combos {
+        let index = gtk_sys::gtk_combo_box_get_active(*combo);
+        if index >= 0 {
+            accept.choices.set_selected(*id, index as usize);
+        }
+    }
+    for (id, entry) in &accept.controls.text_fields {
+        let text = CStr::from_ptr(gtk_sys::gtk_entry_get_text(*entry));
+        accept
+            .choices
+            .set_text(*id, text.to_string_lossy().into_owned());
+    }
+}
+
+/// Name of the file chosen in a strict save dialog when it has an extension none of the filters has,
+/// names without any get the extension of the selected filter appended
+unsafe fn unknown_file_type(chooser: *mut GtkFileChooser, accept: &Accept) -> Option<String> {
+    let filters = accept.file_types.as_ref()?;
+    let path = filenames(chooser).into_iter().next()?;
+    path.extension()?;
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let known = filters
+        .iter()
+        .any(|filter| filter.matching_extension(&name).is_some());
+    (!known).then_some(name)
+}
+
+fn label(label: &str) -> CString {
+    CString::new(label.replace('\0', "")).unwrap()
+}
+
+/// Row of the extra widget with `label` in front of `widget`
+unsafe fn labeled_row(
+    label_text: &str,
+    widget: *mut gtk_sys::GtkWidget,
+) -> *mut gtk_sys::GtkWidget {
+    let row = gtk_sys::gtk_box_new(gtk_sys::GTK_ORIENTATION_HORIZONTAL, 6);
+    let text = label(label_text);
+    gtk_sys::gtk_box_pack_start(
+        row as _,
+        gtk_sys::gtk_label_new(text.as_ptr()),
+        glib_sys::GFALSE,
+        glib_sys::GFALSE,
+        0,
+    );
+    gtk_sys::gtk_box_pack_start(row as _, widget, glib_sys::GFALSE, glib_sys::GFALSE, 0);
+    row
+}
+
+/// Packs the description, checkboxes, combo boxes and text fields into the extra widget of the chooser
+unsafe fn add_controls(chooser: *mut GtkFileChooser, opt: &FileDialog) -> Controls {
+    if opt.description.is_none()
+        && opt.checkboxes.is_empty()
+        && opt.combos.is_empty()
+        && opt.text_fields.is_empty()
+    {
+        return Controls::default();
+    }
+
+    let column = gtk_sys::gtk_box_new(gtk_sys::GTK_ORIENTATION_VERTICAL, 6);
+    let mut controls = Controls::default();
+
+    if let Some(description) = &opt.description {
+        let text = label(description);
+        let header = gtk_sys::gtk_label_new(text.as_ptr());
+        gtk_sys::gtk_label_set_xalign(header as _, 0.0);
+        gtk_sys::gtk_box_pack_start(column as _, header, glib_sys::GFALSE, glib_sys::GFALSE, 0);
+    }
+
+    for combo in &opt.combos {
+        let combo_box = gtk_sys::gtk_combo_box_text_new();
+        for option in &combo.options {
+            let option = label(option);
+            gtk_sys::gtk_combo_box_text_append_text(combo_box as _, option.as_ptr());
+        }
+        gtk_sys::gtk_combo_box_set_active(combo_box as _, combo.default as _);
+
+        let row = labeled_row(&combo.label, combo_box);
+        gtk_sys::gtk_box_pack_start(column as _, row, glib_sys::GFALSE, glib_sys::GFALSE, 0);
+        controls.combos.push((combo.id, combo_box as _));
+    }
+
+    for text_field in &opt.text_fields {
+        let entry = gtk_sys::gtk_entry_new();
+        let text = label(&text_field.default);
+        gtk_sys::gtk_entry_set_text(entry as _, text.as_ptr());
+
+        let row = labeled_row(&text_field.label, entry);
+        gtk_sys::gtk_box_pack_start(column as _, row, glib_sys::GFALSE, glib_sys::GFALSE, 0);
+        controls.text_fields.push((text_field.id, entry as _));
+    }
+
+    for checkbox in &opt.checkboxes {
+        let text = label(&checkbox.label);
+        let button = gtk_sys::gtk_check_button_new_with_label(text.as_ptr());
+        gtk_sys::gtk_toggle_button_set_active(button as _, checkbox.default as _);
+        gtk_sys::gtk_box_pack_start(column as _, button, glib_sys::GFALSE, glib_sys::GFALSE, 0);
+        controls.checkboxes.push((checkbox.id, button as _));
+    }
+
+    gtk_sys::gtk_widget_show_all(column);
+    gtk_sys::gtk_file_chooser_set_extra_widget(chooser, column);
+
+    controls
+}
+
+/// Checks the file type, runs the validator and records the custom controls when the dialog gets accepted,
+/// it has to be connected before the dialog is run
+pub unsafe fn connect_accept(dialog: *mut gtk_sys::GtkDialog, opt: &FileDialog) {
+    let controls = add_controls(dialog as _, opt);
+    let saves =
+        gtk_sys::gtk_file_chooser_get_action(dialog as _) == gtk_sys::GTK_FILE_CHOOSER_ACTION_SAVE;
+    let file_types = if saves && opt.strict_file_types == Some(true) && !opt.filters.is_empty() {
+        Some(opt.filters.clone())
+    } else {
+        None
+    };
+    if opt.validator.is_none() && file_types.is_none() && controls.is_empty() {
+        return;
+    }
+
+    let accept = Accept {
+        validator: opt.validator.as_ref().map(|validator| validator.0.clone()),
+        file_types,
+        controls,
+        choices: opt.choices.clone(),
+    };
+    connect_raw(
+        dialog as _,
+        b"response\0".as_ptr() as _,
+        Some(transmute::<_, unsafe extern "C" fn()>(
+            response as *const (),
+        )),
+        Box::into_raw(Box::new(accept)),
+    );
+}
+
+/// Longest side of preview images
+const PREVIEW_SIZE: i32 = 256;
+
+struct PreviewPane {
+    renderer: Arc<Preview>,
+    // Owned by the dialog, they live as long as the signal handler
+    image: *mut gtk_sys::GtkImage,
+    text: *mut gtk_sys::GtkLabel,
+}
+
+unsafe fn pixbuf(width: u32, height: u32, rgba: &[u8]) -> Option<*mut gdk_pixbuf_sys::GdkPixbuf> {
+    let full = super::super::utils::pixbuf_from_rgba(width, height, rgba)?;
+
+    let scale = PREVIEW_SIZE as f64 / width.max(height) as f64;
+    if scale >= 1.0 {
+        return Some(full);
+    }
+    let scaled = gdk_pixbuf_sys::gdk_pixbuf_scale_simple(
+        full,
+        ((width as f64 * scale) as i32).max(1),
+        ((height as f64 * scale) as i32).max(1),
+        gdk_pixbuf_sys::GDK_INTERP_BILINEAR,
+    );
+    gobject_sys::g_object_unref(full as _);
+    Some(scaled)
+}
+
+unsafe extern "C" fn update_preview(chooser: *mut GtkFileChooser, pane: glib_sys::gpointer) {
+    let pane = &*(pane as *const PreviewPane);
+
+    let filename = gtk_sys::gtk_file_chooser_get_preview_filename(chooser);
+    let content = if filename.is_null() {
+        PreviewContent::None
+    } else {
+        let preview_path = path(filename);
+        glib_sys::g_free(filename as _);
+        (pane.renderer)(&preview_path)
+    };
+
+    let active = match content {
+        PreviewContent::Text(text) => {
+            let text = CString::new(text.replace('\0', "")).unwrap();
+            gtk_sys::gtk_label_set_text(pane.text, text.as_ptr());
+            gtk_sys::gtk_image_clear(pane.image);
+            true
+        }
+        PreviewContent::Image {
+            width,
+            height,
+            rgba,
+        } => match pixbuf(width, height, &rgba) {
+            Some(pixbuf) => {
+                gtk_sys::gtk_image_set_from_pixbuf(pane.image, pixbuf);
+                gobject_sys::g_object_unref(pixbuf as _);
+                gtk_sys::gtk_label_set_text(pane.text, b"\0".as_ptr() as _);
+                true
+            }
+            None => false,
+        },
+        PreviewContent::None => false,
+    };
+    gtk_sys::gtk_file_chooser_set_preview_widget_active(chooser, active as _);
+}
+
+/// Adds a preview widget that `renderer` fills whenever another file gets highlighted
+pub unsafe fn connect_preview(chooser: *mut GtkFileChooser, renderer: &Arc<Preview>) {
+    let column = gtk_sys::gtk_box_new(gtk_sys::GTK_ORIENTATION_VERTICAL, 6);
+    let image = gtk_sys::gtk_image_new();
+    let text = gtk_sys::gtk_label_new(ptr::null());
+    gtk_sys::gtk_label_set_line_wrap(text as _, glib_sys::GTRUE);
+    gtk_sys::gtk_label_set_max_width_chars(text as _, 32);
+    gtk_sys::gtk_box_pack_start(column as _, image, glib_sys::GFALSE, glib_sys::GFALSE, 0);
+    gtk_sys::gtk_box_pack_start(column as _, text, glib_sys::GFALSE, glib_sys::GFALSE, 0);
+    gtk_sys::gtk_widget_show_all(column);
+
+    gtk_sys::gtk_file_chooser_set_preview_widget(chooser, column);
+    gtk_sys::gtk_file_chooser_set_use_preview_label(chooser, glib_sys::GFALSE);
+
+    let pane = PreviewPane {
+        renderer: renderer.clone(),
+        image: image as _,
+        text: text as _,
+    };
+    connect_raw(
+        chooser as _,
+        b"update-preview\0".as_ptr() as _,
+        Some(transmute::<_, unsafe extern "C" fn()>(
+            update_preview as *const (),
+        )),
+        Box::into_raw(Box::new(pane)),
+    );
+}
@@ -1,11 +1,17 @@
 use super::super::AsGtkDialog;
-use crate::FileDialog;
+use crate::dialog::branded_title;
+use crate::localizer::or_localized;
+use crate::{BuiltinString, FileDialog, Modality, MonitorSelection, Position, WindowIcon};
 use gtk_sys::GtkFileChooserNative;
 
 use std::{
+    cell::{Cell, RefCell},
     ffi::{CStr, CString},
+    os::raw::c_char,
+    os::unix::ffi::OsStrExt,
     path::{Path, PathBuf},
     ptr,
+    sync::Mutex,
 };
 
 #[repr(i32)]
@@ -16,8 +22,61 @@ pub enum GtkFileChooserAction {
     // CreateFolder = 3,
 }
 
+/// `path` in the GLib filename encoding, which are the bytes of the path on Unix, UTF-8 or not
+fn filename(path: &Path) -> Option<CString> {
+    CString::new(path.as_os_str().as_bytes()).ok()
+}
+
 pub struct GtkFileDialog {
     pub ptr: *mut GtkFileChooserNative,
+    /// Whether the dialog holds `gtk-recent-files-enabled` off
+    recent_files_off: Cell<bool>,
+    /// X11 window the dialog is transient for
+    #[cfg(feature = "parent")]
+    parent: Cell<Option<std::os::raw::c_ulong>>,
+    /// Where the dialog is moved once it is shown
+    placement: Cell<Option<(Position, Option<MonitorSelection>)>>,
+    /// Whether the dialog is kept above other windows once it is shown
+    keep_above: Cell<Option<bool>>,
+    /// Icon of the dialog window once it is shown
+    icon: RefCell<Option<WindowIcon>>,
+}
+
+/// Sets `gtk-recent-files-enabled` of the default settings and returns the previous value,
+/// GtkFileChooser adds the chosen files to the recently used list while it is enabled
+unsafe fn set_recent_files_enabled(enabled: glib_sys::gboolean) -> Option<glib_sys::gboolean> {
+    let settings = gtk_sys::gtk_settings_get_default();
+    if settings.is_null() {
+        return None;
+    }
+
+    let name = b"gtk-recent-files-enabled\0".as_ptr() as *const c_char;
+    let mut previous: glib_sys::gboolean = glib_sys::GTRUE;
+    gobject_sys::g_object_get(settings as _, name, &mut previous, ptr::null::<c_char>());
+    gobject_sys::g_object_set(settings as _, name, enabled, ptr::null::<c_char>());
+    Some(previous)
+}
+
+/// Dialogs holding `gtk-recent-files-enabled` off, and its value before the first of them did.
+/// Overlapping dialogs only change the setting when the first one starts and the last one ends.
+static RECENT_FILES_OFF: Mutex<(usize, Option<glib_sys::gboolean>)> = Mutex::new((0, None));
+
+unsafe fn hold_recent_files_off() {
+    let mut off = RECENT_FILES_OFF.lock().unwrap();
+    if off.0 == 0 {
+        off.1 = set_recent_files_enabled(glib_sys::GFALSE);
+    }
+    off.0 += 1;
+}
+
+unsafe fn release_recent_files_off() {
+    let mut off = RECENT_FILES_OFF.lock().unwrap();
+    off.0 -= 1;
+    if off.0 == 0 {
+        if let Some(enabled) = off.1.take() {
+            set_recent_files_enabled(enabled);
+        }
+    }
 }
 
 impl GtkFileDialog {
@@ -37,7 +96,15 @@ impl GtkFileDialog {
             dialog as _
         };
 
-        Self { ptr }
+        Self {
+            ptr,
+            recent_files_off: Cell::new(false),
+            #[cfg(feature = "parent")]
+            parent: Cell::new(None),
+            placement: Cell::new(None),
+            keep_above: Cell::new(None),
+            icon: RefCell::new(None),
+        }
     }
 
     fn add_filters(&mut self, filters: &[crate::dialog::Filter]) {
@@ -47,9 +114,8 @@ impl GtkFileDialog {
                     let filter = gtk_sys::gtk_file_filter_new();
 
                     let paterns: Vec<_> = f
-                        .extensions
-                        .iter()
-                        .filter_map(|e| CString::new(format!("*.{}", e)).ok())
+                        .caseless_globs()
+                        .filter_map(|p| CString::new(p).ok())
                         .collect();
 
                     gtk_sys::gtk_file_filter_set_name(filter, name.as_ptr());
@@ -58,12 +124,34 @@ impl GtkFileDialog {
                         gtk_sys::gtk_file_filter_add_pattern(filter, p.as_ptr());
                     }
 
+                    for m in f
+                        .mime_types
+                        .iter()
+                        .filter_map(|m| CString::new(m.as_str()).ok())
+                    {
+                        gtk_sys::gtk_file_filter_add_mime_type(filter, m.as_ptr());
+                    }
+
                     gtk_sys::gtk_file_chooser_add_filter(self.ptr as _, filter);
                 }
             }
         }
     }
 
+    fn set_filter_index(&self, index: Option<usize>) {
+        if let Some(index) = index {
+            unsafe {
+                let filters = gtk_sys::gtk_file_chooser_list_filters(self.ptr as _);
+                let filter = glib_sys::g_slist_nth_data(filters, index as _);
+                glib_sys::g_slist_free(filters);
+
+                if !filter.is_null() {
+                    gtk_sys::gtk_file_chooser_set_filter(self.ptr as _, filter as _);
+                }
+            }
+        }
+    }
+
     fn set_file_name(&self, name: Option<&str>) {
         if let Some(name) = name {
             if let Ok(name) = CString::new(name) {
@@ -85,20 +173,101 @@ impl GtkFileDialog {
     }
 
     fn set_path(&self, path: Option<&Path>) {
-        if let Some(path) = path {
-            if let Some(path) = path.to_str() {
-                if let Ok(path) = CString::new(path) {
-                    unsafe {
-                        gtk_sys::gtk_file_chooser_set_current_folder(self.ptr as _, path.as_ptr());
+        if let Some(path) = path.and_then(filename) {
+            unsafe {
+                gtk_sys::gtk_file_chooser_set_current_folder(self.ptr as _, path.as_ptr());
+            }
+        }
+    }
+
+    fn add_places(&self, places: &[crate::dialog::Place]) {
+        for place in places {
+            if let Some(path) = filename(&place.path) {
+                unsafe {
+                    let mut error = ptr::null_mut();
+                    gtk_sys::gtk_file_chooser_add_shortcut_folder(
+                        self.ptr as _,
+                        path.as_ptr(),
+                        &mut error,
+                    );
+                    if !error.is_null() {
+                        glib_sys::g_error_free(error);
                     }
                 }
             }
         }
     }
 
+    /// Optional settings, `None` keeps the GTK default
+    fn set_options(&self, opt: &FileDialog) {
+        self.add_places(&opt.places);
+        #[cfg(feature = "parent")]
+        self.parent
+            .set(super::super::utils::parent_xid(opt.parent()));
+        #[cfg(feature = "parent")]
+        let parented = self.parent.get().is_some();
+        #[cfg(not(feature = "parent"))]
+        let parented = false;
+        self.placement
+            .set(opt.placement().map(|(position, monitor)| match position {
+                // A dialog without parent is centered on the chosen monitor instead
+                Position::CenterOnParent if !parented && monitor.is_some() => {
+                    (Position::CenterOnScreen, monitor)
+                }
+                _ => (position, monitor),
+            }));
+        self.keep_above.set(opt.always_on_top);
+        self.icon.replace(opt.icon.clone());
+        if opt.app_name.is_some() {
+            unsafe {
+                let title = gtk_sys::gtk_native_dialog_get_title(self.ptr as _);
+                let title = if title.is_null() {
+                    String::new()
+                } else {
+                    CStr::from_ptr(title).to_string_lossy().into_owned()
+                };
+                let title = CString::new(branded_title(&title, opt.app_name.as_deref())).unwrap();
+                gtk_sys::gtk_native_dialog_set_title(self.ptr as _, title.as_ptr());
+            }
+        }
+        unsafe {
+            gtk_sys::gtk_native_dialog_set_modal(
+                self.ptr as _,
+                (opt.modality != Modality::None) as _,
+            );
+            if let Some(events) = &opt.events {
+                super::dialog_events::connect(self.ptr as _, &events.0);
+            }
+            super::dialog_events::connect_accept(self.ptr as _, opt);
+            super::dialog_events::connect_extension(self.ptr as _, opt);
+            if let Some(preview) = &opt.preview {
+                super::dialog_events::connect_preview(self.ptr as _, &preview.0);
+            }
+            if let Some(extra_widget) = &opt.extra_widget {
+                (extra_widget.0)(self.ptr as _);
+            }
+            if let Some(local_only) = opt.local_only {
+                gtk_sys::gtk_file_chooser_set_local_only(self.ptr as _, local_only as _);
+            }
+            if let Some(show_hidden) = opt.show_hidden {
+                gtk_sys::gtk_file_chooser_set_show_hidden(self.ptr as _, show_hidden as _);
+            }
+            if let Some(can) = opt.can_create_directories {
+                gtk_sys::gtk_file_chooser_set_create_folders(self.ptr as _, can as _);
+            }
+            if opt.add_to_recent == Some(false) && !self.recent_files_off.replace(true) {
+                hold_recent_files_off();
+            }
+        }
+    }
+
     pub fn get_result(&self) -> Option<PathBuf> {
         let cstr = unsafe {
             let chosen_filename = gtk_sys::gtk_file_chooser_get_filename(self.ptr as _);
+            // Remote files have no local filename
+            if chosen_filename.is_null() {
+                return None;
+            }
             CStr::from_ptr(chosen_filename).to_str()
         };
 
@@ -109,6 +278,19 @@ impl GtkFileDialog {
         }
     }
 
+    /// URI of the chosen file, remote files that have no local filename included
+    pub fn get_result_uri(&self) -> Option<String> {
+        unsafe {
+            let uri = gtk_sys::gtk_file_chooser_get_uri(self.ptr as _);
+            if uri.is_null() {
+                return None;
+            }
+            let res = CStr::from_ptr(uri).to_str().ok().map(str::to_owned);
+            glib_sys::g_free(uri as _);
+            res
+        }
+    }
+
     pub fn get_results(&self) -> Vec<PathBuf> {
         #[derive(Debug)]
         struct FileList(*mut glib_sys::GSList);
@@ -148,29 +330,92 @@ impl GtkFileDialog {
         paths
     }
 
+    /// Position of the selected filter in the list of added filters
+    pub fn get_filter_index(&self) -> usize {
+        unsafe { super::dialog_events::filter_index(self.ptr as _) }
+    }
+
     pub fn run(&self) -> i32 {
+        // The dialog only exists once the run shows it
+        #[cfg(feature = "parent")]
+        if let Some(xid) = self.parent.get() {
+            unsafe extern "C" fn attach_idle(data: glib_sys::gpointer) -> glib_sys::gboolean {
+                attach_to_parent(data as _);
+                glib_sys::GFALSE
+            }
+            unsafe {
+                glib_sys::g_idle_add(Some(attach_idle), xid as _);
+            }
+        }
+
+        if let Some(placement) = self.placement.get() {
+            unsafe extern "C" fn place_idle(data: glib_sys::gpointer) -> glib_sys::gboolean {
+                let (position, monitor) =
+                    *Box::from_raw(data as *mut (Position, Option<MonitorSelection>));
+                place_chooser(position, monitor);
+                glib_sys::GFALSE
+            }
+            unsafe {
+                glib_sys::g_idle_add(Some(place_idle), Box::into_raw(Box::new(placement)) as _);
+            }
+        }
+
+        if let Some(icon) = self.icon.borrow().clone() {
+            unsafe extern "C" fn icon_idle(data: glib_sys::gpointer) -> glib_sys::gboolean {
+                set_chooser_icon(&Box::from_raw(data as *mut WindowIcon));
+                glib_sys::GFALSE
+            }
+            unsafe {
+                glib_sys::g_idle_add(Some(icon_idle), Box::into_raw(Box::new(icon)) as _);
+            }
+        }
+
+        if let Some(above) = self.keep_above.get() {
+            unsafe extern "C" fn keep_above_idle(data: glib_sys::gpointer) -> glib_sys::gboolean {
+                keep_chooser_above(!data.is_null());
+                glib_sys::GFALSE
+            }
+            unsafe {
+                glib_sys::g_idle_add(Some(keep_above_idle), above as usize as _);
+            }
+        }
+
         unsafe { gtk_sys::gtk_native_dialog_run(self.ptr as *mut _) }
     }
 }
 
 impl GtkFileDialog {
     pub fn build_pick_file(opt: &FileDialog) -> Self {
-        let mut dialog =
-            GtkFileDialog::new(opt.title.as_deref().unwrap_or("Open File"), GtkFileChooserAction::Open, "Cancel", "Open");
+        let mut dialog = GtkFileDialog::new(
+            &or_localized(opt.title.as_deref(), BuiltinString::OpenFile),
+            GtkFileChooserAction::Open,
+            &or_localized(opt.cancel_label.as_deref(), BuiltinString::Cancel),
+            &or_localized(opt.accept_label.as_deref(), BuiltinString::Open),
+        );
 
         dialog.add_filters(&opt.filters);
+        dialog.set_filter_index(opt.default_filter);
         dialog.set_path(opt.starting_directory.as_deref());
         dialog.set_file_name(opt.file_name.as_deref());
+        dialog.set_options(opt);
         dialog
     }
 
     pub fn build_save_file(opt: &FileDialog) -> Self {
-        let mut dialog =
-            GtkFileDialog::new(opt.title.as_deref().unwrap_or("Save File"), GtkFileChooserAction::Save, "Cancel", "Save");
+        let mut dialog = GtkFileDialog::new(
+            &or_localized(opt.title.as_deref(), BuiltinString::SaveFile),
+            GtkFileChooserAction::Save,
+            &or_localized(opt.cancel_label.as_deref(), BuiltinString::Cancel),
+            &or_localized(opt.accept_label.as_deref(), BuiltinString::Save),
+        );
 
-        unsafe { gtk_sys::gtk_file_chooser_set_do_overwrite_confirmation(dialog.ptr as _, 1) };
+        let confirm = opt.confirm_overwrite.unwrap_or(true);
+        unsafe {
+            gtk_sys::gtk_file_chooser_set_do_overwrite_confirmation(dialog.ptr as _, confirm as _)
+        };
 
         dialog.add_filters(&opt.filters);
+        dialog.set_filter_index(opt.default_filter);
         dialog.set_path(opt.starting_directory.as_deref());
 
         if let (Some(mut path), Some(file_name)) =
@@ -189,33 +434,130 @@ impl GtkFileDialog {
             dialog.set_current_name(opt.file_name.as_deref());
         }
 
+        dialog.set_options(opt);
         dialog
     }
 
     pub fn build_pick_folder(opt: &FileDialog) -> Self {
         let dialog = GtkFileDialog::new(
-            opt.title.as_deref().unwrap_or("Select Folder"),
+            &or_localized(opt.title.as_deref(), BuiltinString::SelectFolder),
             GtkFileChooserAction::SelectFolder,
-            "Cancel",
-            "Select",
+            &or_localized(opt.cancel_label.as_deref(), BuiltinString::Cancel),
+            &or_localized(opt.accept_label.as_deref(), BuiltinString::Select),
         );
         dialog.set_path(opt.starting_directory.as_deref());
         dialog.set_file_name(opt.file_name.as_deref());
+        dialog.set_options(opt);
+        dialog
+    }
+
+    pub fn build_pick_folders(opt: &FileDialog) -> Self {
+        let dialog = GtkFileDialog::new(
+            &or_localized(opt.title.as_deref(), BuiltinString::SelectFolders),
+            GtkFileChooserAction::SelectFolder,
+            &or_localized(opt.cancel_label.as_deref(), BuiltinString::Cancel),
+            &or_localized(opt.accept_label.as_deref(), BuiltinString::Select),
+        );
+        unsafe { gtk_sys::gtk_file_chooser_set_select_multiple(dialog.ptr as _, 1) };
+        dialog.set_path(opt.starting_directory.as_deref());
+        dialog.set_options(opt);
         dialog
     }
 
     pub fn build_pick_files(opt: &FileDialog) -> Self {
-        let mut dialog =
-            GtkFileDialog::new(opt.title.as_deref().unwrap_or("Open File"), GtkFileChooserAction::Open, "Cancel", "Open");
+        let mut dialog = GtkFileDialog::new(
+            &or_localized(opt.title.as_deref(), BuiltinString::OpenFile),
+            GtkFileChooserAction::Open,
+            &or_localized(opt.cancel_label.as_deref(), BuiltinString::Cancel),
+            &or_localized(opt.accept_label.as_deref(), BuiltinString::Open),
+        );
 
         unsafe { gtk_sys::gtk_file_chooser_set_select_multiple(dialog.ptr as _, 1) };
         dialog.add_filters(&opt.filters);
+        dialog.set_filter_index(opt.default_filter);
         dialog.set_path(opt.starting_directory.as_deref());
         dialog.set_file_name(opt.file_name.as_deref());
+        dialog.set_options(opt);
         dialog
     }
 }
 
+/// Visible file chooser dialogs that are not marked with `key` yet, they get marked
+///
+/// `GtkFileChooserNative` keeps its dialog private, so it is looked up among the toplevels.
+unsafe fn unmarked_choosers(key: &[u8]) -> Vec<*mut gtk_sys::GtkWidget> {
+    let key = key.as_ptr() as *const c_char;
+    let mut choosers = Vec::new();
+    let toplevels = gtk_sys::gtk_window_list_toplevels();
+    let mut item = toplevels;
+    while !item.is_null() {
+        let window = (*item).data as *mut gtk_sys::GtkWidget;
+        let chooser = gobject_sys::g_type_check_instance_is_a(
+            window as _,
+            gtk_sys::gtk_file_chooser_dialog_get_type(),
+        ) != 0;
+        if chooser
+            && gtk_sys::gtk_widget_get_visible(window) != 0
+            && gobject_sys::g_object_get_data(window as _, key).is_null()
+        {
+            gobject_sys::g_object_set_data(window as _, key, 1 as _);
+            choosers.push(window);
+        }
+        item = (*item).next;
+    }
+    glib_sys::g_list_free(toplevels);
+    choosers
+}
+
+/// Makes the shown file chooser transient for `xid`
+///
+/// `GtkFileChooserNative` only accepts a `GtkWindow` parent, so it is the visible file chooser that has no parent yet.
+#[cfg(feature = "parent")]
+unsafe fn attach_to_parent(xid: std::os::raw::c_ulong) {
+    for window in unmarked_choosers(b"rfd-parent\0") {
+        super::super::utils::set_transient_for_xid(window, xid);
+    }
+}
+
+/// Moves the shown file chooser to `position`, centered ones on the monitor of `monitor`
+///
+/// Centering on the parent is left to the window manager, which does it for transient dialogs.
+unsafe fn place_chooser(position: Position, monitor: Option<MonitorSelection>) {
+    for window in unmarked_choosers(b"rfd-position\0") {
+        let workarea = monitor.and_then(|monitor| super::super::utils::monitor_workarea(monitor));
+        match (position, workarea) {
+            (Position::At(x, y), _) => gtk_sys::gtk_window_move(window as _, x, y),
+            (Position::CenterOnScreen, Some(area)) => {
+                let (mut width, mut height) = (0, 0);
+                gtk_sys::gtk_window_get_size(window as _, &mut width, &mut height);
+                gtk_sys::gtk_window_move(
+                    window as _,
+                    area.x + (area.width - width) / 2,
+                    area.y + (area.height - height) / 2,
+                );
+            }
+            (Position::CenterOnScreen, None) => {
+                gtk_sys::gtk_window_set_position(window as _, gtk_sys::GTK_WIN_POS_CENTER_ALWAYS)
+            }
+            (Position::CenterOnParent, _) => {}
+        }
+    }
+}
+
+/// Keeps the shown file chooser above other windows, or lets them cover it
+unsafe fn keep_chooser_above(above: bool) {
+    for window in unmarked_choosers(b"rfd-keep-above\0") {
+        gtk_sys::gtk_window_set_keep_above(window as _, above as _);
+    }
+}
+
+/// Sets the icon of the shown file chooser
+unsafe fn set_chooser_icon(icon: &WindowIcon) {
+    for window in unmarked_choosers(b"rfd-icon\0") {
+        super::super::utils::brand_window(window as _, Some(icon), None);
+    }
+}
+
 impl AsGtkDialog for GtkFileDialog {
     fn gtk_dialog_ptr(&self) -> *mut gtk_sys::GtkDialog {
         self.ptr as *mut _
@@ -223,6 +565,19 @@ impl AsGtkDialog for GtkFileDialog {
 
     unsafe fn show(&self) {
         gtk_sys::gtk_native_dialog_show(self.ptr as *mut _);
+        #[cfg(feature = "parent")]
+        if let Some(xid) = self.parent.get() {
+            attach_to_parent(xid);
+        }
+        if let Some((position, monitor)) = self.placement.get() {
+            place_chooser(position, monitor);
+        }
+        if let Some(above) = self.keep_above.get() {
+            keep_chooser_above(above);
+        }
+        if let Some(icon) = &*self.icon.borrow() {
+            set_chooser_icon(icon);
+        }
     }
 }
 
@@ -232,6 +587,10 @@ impl Drop for GtkFileDialog {
             super::super::utils::wait_for_cleanup();
             gtk_sys::gtk_native_dialog_destroy(self.ptr as _);
             super::super::utils::wait_for_cleanup();
+
+            if self.recent_files_off.get() {
+                release_recent_files_off();
+            }
         }
     }
 }
@@ -8,12 +8,13 @@ use std::sync::{Arc, Mutex};
 
 use std::task::{Context, Poll, Waker};
 
-use super::utils::gtk_init_check;
+use super::utils::{emit_response, gtk_init_check, init_error};
 use super::AsGtkDialog;
+use crate::{DialogHandle, Error};
 
 struct FutureState<R, D> {
     waker: Option<Waker>,
-    data: Option<R>,
+    data: Option<Result<R, Error>>,
     dialog: Option<D>,
 }
 
@@ -25,8 +26,8 @@ pub(super) struct GtkDialogFuture<R, D> {
 
 unsafe impl<R, D> Send for GtkDialogFuture<R, D> {}
 
-impl<R: Default + 'static, D: AsGtkDialog + 'static> GtkDialogFuture<R, D> {
-    pub fn new<B, F>(build: B, cb: F) -> Self
+impl<R: 'static, D: AsGtkDialog + 'static> GtkDialogFuture<R, D> {
+    pub fn new<B, F>(handle: DialogHandle, build: B, cb: F) -> Self
     where
         B: FnOnce() -> D + Send + 'static,
         F: Fn(&mut D, i32) -> R + Send + 'static,
@@ -51,7 +52,7 @@ impl<R: Default + 'static, D: AsGtkDialog + 'static> GtkDialogFuture<R, D> {
                         let mut state = state.lock().unwrap();
 
                         if let Some(mut dialog) = state.dialog.take() {
-                            state.data = Some(cb(&mut dialog, res_id));
+                            state.data = Some(Ok(cb(&mut dialog, res_id)));
                         }
 
                         // Drop the request
@@ -77,11 +78,21 @@ impl<R: Default + 'static, D: AsGtkDialog + 'static> GtkDialogFuture<R, D> {
                             connect_response(ptr as *mut _, callback);
                         }
                     } else {
-                        state.data = Some(Default::default());
+                        state.data = Some(Err(init_error()));
                     }
                 });
 
                 request.replace(Some(GTK_EVENT_HANDLER.request_iteration_start()));
+
+                // Closing acts like a press of the cancel button, so the response callback cleans up as usual
+                handle.on_close(move || {
+                    GTK_MUTEX.run_locked(|| {
+                        let ptr = state.lock().unwrap().dialog.as_ref().map(D::gtk_dialog_ptr);
+                        if let Some(ptr) = ptr {
+                            unsafe { emit_response(ptr, gtk_sys::GTK_RESPONSE_CANCEL) };
+                        }
+                    })
+                });
             });
         }
 
@@ -90,7 +101,7 @@ impl<R: Default + 'static, D: AsGtkDialog + 'static> GtkDialogFuture<R, D> {
 }
 
 impl<R, D> std::future::Future for GtkDialogFuture<R, D> {
-    type Output = R;
+    type Output = Result<R, Error>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let mut state = self.state.lock().unwrap();
@@ -109,7 +120,7 @@ use gtk_sys::{GtkDialog, GtkResponseType};
 use std::ffi::c_void;
 use std::os::raw::c_char;
 
-unsafe fn connect_raw<F>(
+pub(super) unsafe fn connect_raw<F>(
     receiver: *mut gobject_sys::GObject,
     signal_name: *const c_char,
     trampoline: GCallback,
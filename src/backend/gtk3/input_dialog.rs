@@ -0,0 +1,205 @@
+use std::ffi::{CStr, CString};
+use std::mem::transmute;
+use std::ptr;
+
+use super::gtk_future::{connect_raw, GtkDialogFuture};
+use super::utils::wait_for_cleanup;
+use super::AsGtkDialog;
+
+use crate::backend::DialogFutureType;
+use crate::localizer::{localize, mnemonic};
+use crate::{BuiltinString, DialogHandle, InputDialog};
+
+pub struct GtkInputDialog {
+    ptr: *mut gtk_sys::GtkDialog,
+    /// `GtkEntry`, or the `GtkTextView` of a multi-line dialog
+    entry: *mut gtk_sys::GtkWidget,
+    multiline: bool,
+}
+
+impl GtkInputDialog {
+    pub fn new(opt: InputDialog) -> Self {
+        super::utils::gtk_init_check();
+
+        let (width, height) = opt.size_hint();
+        let title = CString::new(opt.title).unwrap();
+        let prompt = CString::new(opt.prompt).unwrap();
+        let default = CString::new(opt.default).unwrap();
+
+        let ptr = unsafe {
+            gtk_sys::gtk_message_dialog_new(
+                ptr::null_mut(),
+                gtk_sys::GTK_DIALOG_MODAL,
+                gtk_sys::GTK_MESSAGE_QUESTION,
+                gtk_sys::GTK_BUTTONS_OK_CANCEL,
+                b"%s\0".as_ptr() as *mut _,
+                title.as_ptr(),
+            ) as *mut gtk_sys::GtkDialog
+        };
+
+        #[cfg(feature = "parent")]
+        unsafe {
+            super::utils::set_parent(ptr as _, opt.parent());
+        }
+
+        unsafe {
+            gtk_sys::gtk_message_dialog_format_secondary_text(
+                ptr as *mut _,
+                b"%s\0".as_ptr() as *const _,
+                prompt.as_ptr(),
+            );
+        }
+
+        if opt.multiline {
+            let entry = unsafe { add_text_view(ptr, &default, width as i32, height as i32) };
+            return Self {
+                ptr,
+                entry,
+                multiline: true,
+            };
+        }
+
+        let entry = unsafe {
+            let entry = gtk_sys::gtk_entry_new();
+            gtk_sys::gtk_entry_set_text(entry as *mut _, default.as_ptr());
+            // Enter in the entry presses OK
+            gtk_sys::gtk_entry_set_activates_default(entry as *mut _, glib_sys::GTRUE);
+            gtk_sys::gtk_dialog_set_default_response(ptr, gtk_sys::GTK_RESPONSE_OK);
+
+            let area = gtk_sys::gtk_message_dialog_get_message_area(ptr as *mut _);
+            gtk_sys::gtk_container_add(area as *mut gtk_sys::GtkContainer, entry);
+            gtk_sys::gtk_widget_show(entry);
+            gtk_sys::gtk_widget_grab_focus(entry);
+
+            if opt.password {
+                gtk_sys::gtk_entry_set_visibility(entry as *mut _, glib_sys::GFALSE);
+                gtk_sys::gtk_entry_set_input_purpose(
+                    entry as *mut _,
+                    gtk_sys::GTK_INPUT_PURPOSE_PASSWORD,
+                );
+            }
+
+            if opt.password && opt.reveal_toggle {
+                add_reveal_toggle(area, entry);
+            }
+            entry
+        };
+
+        Self {
+            ptr,
+            entry,
+            multiline: false,
+        }
+    }
+
+    pub fn run(self) -> Option<String> {
+        let res = unsafe { gtk_sys::gtk_dialog_run(self.ptr) };
+
+        self.result(res)
+    }
+
+    /// Text of the entry when OK was pressed
+    fn result(&self, res: i32) -> Option<String> {
+        if res != gtk_sys::GTK_RESPONSE_OK {
+            return None;
+        }
+
+        if self.multiline {
+            return Some(unsafe { buffer_text(self.entry) });
+        }
+
+        let text = unsafe { CStr::from_ptr(gtk_sys::gtk_entry_get_text(self.entry as *mut _)) };
+        Some(text.to_string_lossy().into_owned())
+    }
+}
+
+/// Scrolling `GtkTextView` with `text` in the message area, Enter inserts a line break
+unsafe fn add_text_view(
+    dialog: *mut gtk_sys::GtkDialog,
+    text: &CStr,
+    width: i32,
+    height: i32,
+) -> *mut gtk_sys::GtkWidget {
+    let view = gtk_sys::gtk_text_view_new();
+    gtk_sys::gtk_text_view_set_wrap_mode(view as *mut _, gtk_sys::GTK_WRAP_WORD_CHAR);
+    let buffer = gtk_sys::gtk_text_view_get_buffer(view as *mut _);
+    gtk_sys::gtk_text_buffer_set_text(buffer, text.as_ptr(), -1);
+
+    let scrolled = gtk_sys::gtk_scrolled_window_new(ptr::null_mut(), ptr::null_mut());
+    gtk_sys::gtk_scrolled_window_set_shadow_type(scrolled as *mut _, gtk_sys::GTK_SHADOW_IN);
+    gtk_sys::gtk_widget_set_size_request(scrolled, width, height);
+    gtk_sys::gtk_container_add(scrolled as *mut gtk_sys::GtkContainer, view);
+
+    let area = gtk_sys::gtk_message_dialog_get_message_area(dialog as *mut _);
+    gtk_sys::gtk_container_add(area as *mut gtk_sys::GtkContainer, scrolled);
+    gtk_sys::gtk_widget_show_all(scrolled);
+    gtk_sys::gtk_widget_grab_focus(view);
+    view
+}
+
+unsafe fn buffer_text(view: *mut gtk_sys::GtkWidget) -> String {
+    let buffer = gtk_sys::gtk_text_view_get_buffer(view as *mut _);
+    let mut start = std::mem::zeroed();
+    let mut end = std::mem::zeroed();
+    gtk_sys::gtk_text_buffer_get_bounds(buffer, &mut start, &mut end);
+
+    let text = gtk_sys::gtk_text_buffer_get_text(buffer, &start, &end, glib_sys::GFALSE);
+    let string = CStr::from_ptr(text).to_string_lossy().into_owned();
+    glib_sys::g_free(text as *mut _);
+    string
+}
+
+/// "Show password" checkbox below `entry` that switches its visibility
+unsafe fn add_reveal_toggle(area: *mut gtk_sys::GtkWidget, entry: *mut gtk_sys::GtkWidget) {
+    unsafe extern "C" fn toggled(check: *mut gtk_sys::GtkToggleButton, entry: glib_sys::gpointer) {
+        let entry = *(entry as *const *mut gtk_sys::GtkWidget);
+        let active = gtk_sys::gtk_toggle_button_get_active(check);
+        gtk_sys::gtk_entry_set_visibility(entry as *mut _, active);
+    }
+
+    let label = mnemonic(&localize(BuiltinString::ShowPassword), '_');
+    let label = CString::new(label).unwrap_or_default();
+    let check = gtk_sys::gtk_check_button_new_with_mnemonic(label.as_ptr());
+    gtk_sys::gtk_container_add(area as *mut gtk_sys::GtkContainer, check);
+    gtk_sys::gtk_widget_show(check);
+
+    connect_raw(
+        check as *mut _,
+        b"toggled\0".as_ptr() as *const _,
+        Some(transmute::<_, unsafe extern "C" fn()>(toggled as *const ())),
+        Box::into_raw(Box::new(entry)),
+    );
+}
+
+impl Drop for GtkInputDialog {
+    fn drop(&mut self) {
+        unsafe {
+            wait_for_cleanup();
+            gtk_sys::gtk_widget_destroy(self.ptr as *mut _);
+            wait_for_cleanup();
+        }
+    }
+}
+
+impl AsGtkDialog for GtkInputDialog {
+    fn gtk_dialog_ptr(&self) -> *mut gtk_sys::GtkDialog {
+        self.ptr as *mut _
+    }
+    unsafe fn show(&self) {
+        gtk_sys::gtk_widget_show_all(self.ptr as *mut _);
+    }
+}
+
+pub fn show(opt: InputDialog) -> Option<String> {
+    let dialog = GtkInputDialog::new(opt);
+    dialog.run()
+}
+
+pub fn show_async(opt: InputDialog) -> DialogFutureType<Option<String>> {
+    let builder = move || GtkInputDialog::new(opt);
+
+    let future = GtkDialogFuture::new(DialogHandle::default(), builder, |dialog, res| {
+        dialog.result(res)
+    });
+    Box::pin(async move { future.await.ok().flatten() })
+}
@@ -1,14 +1,24 @@
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::mem::transmute;
+use std::os::raw::c_char;
 use std::ptr;
 
-use super::gtk_future::GtkDialogFuture;
+use super::gtk_future::{connect_raw, GtkDialogFuture};
 use super::utils::wait_for_cleanup;
 use super::AsGtkDialog;
 
+use crate::backend::DialogFutureType;
 use crate::dialog::{MessageButtons, MessageDialog, MessageLevel};
+use crate::dialog_links::{text_parts, LinkHandler};
+use crate::localizer::{localize, mnemonic};
+use crate::{BuiltinString, DialogChoices, DialogHandle, Modality};
 
 pub struct GtkMessageDialog {
     ptr: *mut gtk_sys::GtkDialog,
+    labels: Vec<String>,
+    /// Suppression checkbox, null when there is none
+    suppression: *mut gtk_sys::GtkWidget,
+    choices: DialogChoices,
 }
 
 impl GtkMessageDialog {
@@ -19,12 +29,16 @@ impl GtkMessageDialog {
             MessageLevel::Info => gtk_sys::GTK_MESSAGE_INFO,
             MessageLevel::Warning => gtk_sys::GTK_MESSAGE_WARNING,
             MessageLevel::Error => gtk_sys::GTK_MESSAGE_ERROR,
+            MessageLevel::Question => gtk_sys::GTK_MESSAGE_QUESTION,
         };
 
         let buttons = match opt.buttons {
+            _ if !opt.custom_buttons.is_empty() => gtk_sys::GTK_BUTTONS_NONE,
             MessageButtons::Ok => gtk_sys::GTK_BUTTONS_OK,
             MessageButtons::OkCancel => gtk_sys::GTK_BUTTONS_OK_CANCEL,
             MessageButtons::YesNo => gtk_sys::GTK_BUTTONS_YES_NO,
+            // There is no GtkButtonsType with three buttons, they are added below
+            MessageButtons::YesNoCancel => gtk_sys::GTK_BUTTONS_NONE,
         };
 
         let s: &str = &opt.title;
@@ -43,17 +57,201 @@ impl GtkMessageDialog {
             ) as *mut gtk_sys::GtkDialog
         };
 
+        #[cfg(feature = "parent")]
+        unsafe {
+            super::utils::set_parent(ptr as _, opt.parent());
+        }
+
+        // The title is the primary text of message dialogs, the title bar shows the app name
         unsafe {
-            gtk_sys::gtk_message_dialog_format_secondary_text(ptr as *mut _, description.as_ptr());
+            super::utils::brand_window(ptr as _, opt.icon.as_ref(), opt.app_name.as_deref());
+        }
+
+        if opt.modality == Modality::None {
+            unsafe { gtk_sys::gtk_window_set_modal(ptr as _, glib_sys::GFALSE) };
+        }
+
+        if opt.hyperlinks {
+            let markup = CString::new(markup(&opt.description)).unwrap();
+            unsafe {
+                gtk_sys::gtk_message_dialog_format_secondary_markup(
+                    ptr as *mut _,
+                    b"%s\0".as_ptr() as *const _,
+                    markup.as_ptr(),
+                );
+                connect_links(ptr, &opt.link_handler);
+            }
+        } else {
+            unsafe {
+                gtk_sys::gtk_message_dialog_format_secondary_text(
+                    ptr as *mut _,
+                    description.as_ptr(),
+                );
+            }
+        }
+
+        if opt.custom_buttons.is_empty() && matches!(opt.buttons, MessageButtons::YesNoCancel) {
+            let buttons = [
+                (BuiltinString::Yes, gtk_sys::GTK_RESPONSE_YES),
+                (BuiltinString::No, gtk_sys::GTK_RESPONSE_NO),
+                (BuiltinString::Cancel, gtk_sys::GTK_RESPONSE_CANCEL),
+            ];
+            for (label, response) in buttons {
+                let label = CString::new(mnemonic(&localize(label), '_')).unwrap_or_default();
+                unsafe { gtk_sys::gtk_dialog_add_button(ptr, label.as_ptr(), response) };
+            }
+        }
+
+        // Custom buttons respond with their index
+        for (id, label) in opt.custom_buttons.iter().enumerate() {
+            let label = CString::new(label.as_str()).unwrap();
+            unsafe {
+                gtk_sys::gtk_dialog_add_button(ptr, label.as_ptr(), id as i32);
+            }
+        }
+
+        if let Some(&response) = responses(&opt).get(opt.default_button) {
+            unsafe {
+                gtk_sys::gtk_dialog_set_default_response(ptr, response);
+                // The focused button is the one Enter presses
+                let button = gtk_sys::gtk_dialog_get_widget_for_response(ptr, response);
+                if !button.is_null() {
+                    gtk_sys::gtk_widget_grab_focus(button);
+                }
+            }
         }
 
-        Self { ptr }
+        let suppression = match &opt.suppression {
+            Some(label) => unsafe {
+                let label = CString::new(label.as_str()).unwrap();
+                let check = gtk_sys::gtk_check_button_new_with_label(label.as_ptr());
+                let area = gtk_sys::gtk_message_dialog_get_message_area(ptr as *mut _);
+                gtk_sys::gtk_container_add(area as *mut gtk_sys::GtkContainer, check);
+                gtk_sys::gtk_widget_show(check);
+                check
+            },
+            None => ptr::null_mut(),
+        };
+
+        Self {
+            ptr,
+            labels: opt.custom_buttons,
+            suppression,
+            choices: opt.choices,
+        }
     }
 
     pub fn run(self) -> bool {
         let res = unsafe { gtk_sys::gtk_dialog_run(self.ptr) };
+        self.store_choices();
 
+        pressed(&self.labels, res)
+    }
+
+    pub fn run_custom(self) -> Option<String> {
+        let res = unsafe { gtk_sys::gtk_dialog_run(self.ptr) };
+        self.store_choices();
+
+        label(&self.labels, res)
+    }
+
+    /// Records the state of the suppression checkbox
+    fn store_choices(&self) {
+        if !self.suppression.is_null() {
+            let active =
+                unsafe { gtk_sys::gtk_toggle_button_get_active(self.suppression as *mut _) };
+            self.choices.set_suppressed(active != 0);
+        }
+    }
+}
+
+/// Pango markup of a description with `<a href="...">` links, the rest of the text is escaped
+fn markup(description: &str) -> String {
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    text_parts(description)
+        .iter()
+        .map(|part| match part.href {
+            Some(href) => format!("<a href=\"{}\">{}</a>", escape(href), escape(part.text)),
+            None => escape(part.text),
+        })
+        .collect()
+}
+
+/// Hands links clicked in the secondary label to `handler`, without one GTK opens them itself
+unsafe fn connect_links(dialog: *mut gtk_sys::GtkDialog, handler: &Option<LinkHandler>) {
+    unsafe extern "C" fn activate_link(
+        _: *mut gtk_sys::GtkLabel,
+        uri: *const c_char,
+        handler: glib_sys::gpointer,
+    ) -> glib_sys::gboolean {
+        match &*(handler as *const Option<LinkHandler>) {
+            Some(handler) => {
+                (handler.0)(&CStr::from_ptr(uri).to_string_lossy());
+                glib_sys::GTRUE
+            }
+            None => glib_sys::GFALSE,
+        }
+    }
+
+    // The message area holds the primary and the secondary label
+    let area = gtk_sys::gtk_message_dialog_get_message_area(dialog as *mut _);
+    let children = gtk_sys::gtk_container_get_children(area as *mut gtk_sys::GtkContainer);
+    let label = glib_sys::g_list_nth_data(children, 1);
+    glib_sys::g_list_free(children);
+    if label.is_null() {
+        return;
+    }
+
+    connect_raw(
+        label as *mut _,
+        b"activate-link\0".as_ptr() as *const _,
+        Some(transmute::<_, unsafe extern "C" fn()>(
+            activate_link as *const (),
+        )),
+        Box::into_raw(Box::new(handler.clone())),
+    );
+}
+
+/// Responses of the buttons in the order of [`MessageDialog::button_labels`]
+fn responses(opt: &MessageDialog) -> Vec<i32> {
+    if !opt.custom_buttons.is_empty() {
+        return (0..opt.custom_buttons.len() as i32).collect();
+    }
+    match opt.buttons {
+        MessageButtons::Ok => vec![gtk_sys::GTK_RESPONSE_OK],
+        MessageButtons::OkCancel => vec![gtk_sys::GTK_RESPONSE_OK, gtk_sys::GTK_RESPONSE_CANCEL],
+        MessageButtons::YesNo => vec![gtk_sys::GTK_RESPONSE_YES, gtk_sys::GTK_RESPONSE_NO],
+        MessageButtons::YesNoCancel => vec![
+            gtk_sys::GTK_RESPONSE_YES,
+            gtk_sys::GTK_RESPONSE_NO,
+            gtk_sys::GTK_RESPONSE_CANCEL,
+        ],
+    }
+}
+
+fn pressed(labels: &[String], res: i32) -> bool {
+    if labels.is_empty() {
         res == gtk_sys::GTK_RESPONSE_OK || res == gtk_sys::GTK_RESPONSE_YES
+    } else {
+        res == 0
+    }
+}
+
+/// Label of the button with response `res`, standard buttons are named by [`MessageDialog::button_labels`]
+fn label(labels: &[String], res: i32) -> Option<String> {
+    match res {
+        gtk_sys::GTK_RESPONSE_OK => Some("OK".into()),
+        gtk_sys::GTK_RESPONSE_CANCEL => Some("Cancel".into()),
+        gtk_sys::GTK_RESPONSE_YES => Some("Yes".into()),
+        gtk_sys::GTK_RESPONSE_NO => Some("No".into()),
+        id if id >= 0 => labels.get(id as usize).cloned(),
+        _ => None,
     }
 }
 
@@ -76,25 +274,34 @@ impl AsGtkDialog for GtkMessageDialog {
     }
 }
 
-use crate::backend::MessageDialogImpl;
+pub fn show(opt: MessageDialog) -> bool {
+    let dialog = GtkMessageDialog::new(opt);
+    dialog.run()
+}
 
-impl MessageDialogImpl for MessageDialog {
-    fn show(self) -> bool {
-        let dialog = GtkMessageDialog::new(self);
-        dialog.run()
-    }
+pub fn show_async(opt: MessageDialog) -> DialogFutureType<bool> {
+    let labels = opt.custom_buttons.clone();
+    let builder = move || GtkMessageDialog::new(opt);
+
+    let future = GtkDialogFuture::new(DialogHandle::default(), builder, move |dialog, res| {
+        dialog.store_choices();
+        pressed(&labels, res)
+    });
+    Box::pin(async move { future.await.unwrap_or(false) })
 }
 
-use crate::backend::AsyncMessageDialogImpl;
-use crate::backend::DialogFutureType;
+pub fn show_custom(opt: MessageDialog) -> Option<String> {
+    let dialog = GtkMessageDialog::new(opt);
+    dialog.run_custom()
+}
 
-impl AsyncMessageDialogImpl for MessageDialog {
-    fn show_async(self) -> DialogFutureType<bool> {
-        let builder = move || GtkMessageDialog::new(self);
+pub fn show_custom_async(opt: MessageDialog) -> DialogFutureType<Option<String>> {
+    let labels = opt.custom_buttons.clone();
+    let builder = move || GtkMessageDialog::new(opt);
 
-        let future = GtkDialogFuture::new(builder, |_, res| {
-            res == gtk_sys::GTK_RESPONSE_OK || res == gtk_sys::GTK_RESPONSE_YES
-        });
-        Box::pin(future)
-    }
+    let future = GtkDialogFuture::new(DialogHandle::default(), builder, move |dialog, res| {
+        dialog.store_choices();
+        label(&labels, res)
+    });
+    Box::pin(async move { future.await.ok().flatten() })
 }
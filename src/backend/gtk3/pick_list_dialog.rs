@@ -0,0 +1,158 @@
+use std::ffi::CString;
+use std::mem::transmute;
+use std::ptr;
+
+use super::gtk_future::{connect_raw, GtkDialogFuture};
+use super::utils::wait_for_cleanup;
+use super::AsGtkDialog;
+
+use crate::backend::DialogFutureType;
+use crate::{DialogHandle, PickListDialog};
+
+pub struct GtkPickListDialog {
+    ptr: *mut gtk_sys::GtkDialog,
+    list: *mut gtk_sys::GtkListBox,
+}
+
+impl GtkPickListDialog {
+    pub fn new(opt: PickListDialog) -> Self {
+        super::utils::gtk_init_check();
+
+        let title = CString::new(opt.title).unwrap();
+        let prompt = CString::new(opt.prompt).unwrap();
+
+        let ptr = unsafe {
+            gtk_sys::gtk_message_dialog_new(
+                ptr::null_mut(),
+                gtk_sys::GTK_DIALOG_MODAL,
+                gtk_sys::GTK_MESSAGE_QUESTION,
+                gtk_sys::GTK_BUTTONS_OK_CANCEL,
+                b"%s\0".as_ptr() as *mut _,
+                title.as_ptr(),
+            ) as *mut gtk_sys::GtkDialog
+        };
+
+        #[cfg(feature = "parent")]
+        unsafe {
+            super::utils::set_parent(ptr as _, opt.parent());
+        }
+
+        let list = unsafe {
+            gtk_sys::gtk_message_dialog_format_secondary_text(
+                ptr as *mut _,
+                b"%s\0".as_ptr() as *const _,
+                prompt.as_ptr(),
+            );
+            gtk_sys::gtk_dialog_set_default_response(ptr, gtk_sys::GTK_RESPONSE_OK);
+
+            let list = gtk_sys::gtk_list_box_new() as *mut gtk_sys::GtkListBox;
+            for item in &opt.items {
+                let item = CString::new(item.as_str()).unwrap();
+                let label = gtk_sys::gtk_label_new(item.as_ptr());
+                gtk_sys::gtk_widget_set_halign(label, gtk_sys::GTK_ALIGN_START);
+                gtk_sys::gtk_list_box_insert(list, label, -1);
+            }
+            gtk_sys::gtk_list_box_select_row(list, gtk_sys::gtk_list_box_get_row_at_index(list, 0));
+            connect_row_activated(ptr, list);
+
+            let scrolled = gtk_sys::gtk_scrolled_window_new(ptr::null_mut(), ptr::null_mut());
+            gtk_sys::gtk_scrolled_window_set_shadow_type(
+                scrolled as *mut _,
+                gtk_sys::GTK_SHADOW_IN,
+            );
+            gtk_sys::gtk_scrolled_window_set_policy(
+                scrolled as *mut _,
+                gtk_sys::GTK_POLICY_NEVER,
+                gtk_sys::GTK_POLICY_AUTOMATIC,
+            );
+            // Up to 8 rows are shown without scrolling
+            let rows = opt.items.len().clamp(1, 8) as i32;
+            gtk_sys::gtk_scrolled_window_set_min_content_height(scrolled as *mut _, rows * 32);
+            gtk_sys::gtk_container_add(scrolled as *mut gtk_sys::GtkContainer, list as *mut _);
+
+            let area = gtk_sys::gtk_message_dialog_get_message_area(ptr as *mut _);
+            gtk_sys::gtk_container_add(area as *mut gtk_sys::GtkContainer, scrolled);
+            gtk_sys::gtk_widget_show_all(scrolled);
+            gtk_sys::gtk_widget_grab_focus(list as *mut _);
+            list
+        };
+
+        Self { ptr, list }
+    }
+
+    pub fn run(self) -> Option<usize> {
+        let res = unsafe { gtk_sys::gtk_dialog_run(self.ptr) };
+
+        self.result(res)
+    }
+
+    /// Index of the selected row when OK was pressed
+    fn result(&self, res: i32) -> Option<usize> {
+        if res != gtk_sys::GTK_RESPONSE_OK {
+            return None;
+        }
+
+        let row = unsafe { gtk_sys::gtk_list_box_get_selected_row(self.list) };
+        if row.is_null() {
+            return None;
+        }
+        match unsafe { gtk_sys::gtk_list_box_row_get_index(row) } {
+            index if index >= 0 => Some(index as usize),
+            _ => None,
+        }
+    }
+}
+
+/// Double click or Enter on a row presses OK
+unsafe fn connect_row_activated(dialog: *mut gtk_sys::GtkDialog, list: *mut gtk_sys::GtkListBox) {
+    unsafe extern "C" fn row_activated(
+        _: *mut gtk_sys::GtkListBox,
+        _: *mut gtk_sys::GtkListBoxRow,
+        dialog: glib_sys::gpointer,
+    ) {
+        let dialog = *(dialog as *const *mut gtk_sys::GtkDialog);
+        gtk_sys::gtk_dialog_response(dialog, gtk_sys::GTK_RESPONSE_OK);
+    }
+
+    connect_raw(
+        list as *mut _,
+        b"row-activated\0".as_ptr() as *const _,
+        Some(transmute::<_, unsafe extern "C" fn()>(
+            row_activated as *const (),
+        )),
+        Box::into_raw(Box::new(dialog)),
+    );
+}
+
+impl Drop for GtkPickListDialog {
+    fn drop(&mut self) {
+        unsafe {
+            wait_for_cleanup();
+            gtk_sys::gtk_widget_destroy(self.ptr as *mut _);
+            wait_for_cleanup();
+        }
+    }
+}
+
+impl AsGtkDialog for GtkPickListDialog {
+    fn gtk_dialog_ptr(&self) -> *mut gtk_sys::GtkDialog {
+        self.ptr as *mut _
+    }
+    unsafe fn show(&self) {
+        gtk_sys::gtk_widget_show_all(self.ptr as *mut _);
+    }
+}
+
+pub fn show(opt: PickListDialog) -> Option<usize> {
+    let dialog = GtkPickListDialog::new(opt);
+    dialog.run()
+}
+
+pub fn show_async(opt: PickListDialog) -> DialogFutureType<Option<usize>> {
+    let builder = move || GtkPickListDialog::new(opt);
+
+    let future = GtkDialogFuture::new(DialogHandle::default(), builder, |dialog, res| {
+        dialog.result(res)
+    });
+    Box::pin(async move { future.await.ok().flatten() })
+}
@@ -0,0 +1,150 @@
+use std::ffi::CString;
+use std::mem::transmute;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+use super::gtk_future::connect_raw;
+use super::utils::{gtk_init_check, IterationRequest, GTK_EVENT_HANDLER, GTK_MUTEX};
+
+use crate::{ProgressDialog, ProgressState};
+
+/// Shown dialog, destroyed when it is dropped
+struct Panel {
+    dialog: *mut gtk_sys::GtkWidget,
+    bar: *mut gtk_sys::GtkProgressBar,
+    pulse: u32,
+    // Keeps GTK iterating while the dialog is shown, it is dropped last
+    _request: IterationRequest,
+}
+
+/// The panel is only touched while the `GTK_MUTEX` is locked
+type SharedPanel = Arc<Mutex<Option<Panel>>>;
+
+unsafe impl Send for Panel {}
+
+impl Panel {
+    unsafe fn new(opt: &ProgressDialog, state: &ProgressState) -> Self {
+        let title = CString::new(opt.title.as_str()).unwrap();
+
+        let dialog = gtk_sys::gtk_message_dialog_new(
+            ptr::null_mut(),
+            0,
+            gtk_sys::GTK_MESSAGE_OTHER,
+            gtk_sys::GTK_BUTTONS_CANCEL,
+            b"%s\0".as_ptr() as *mut _,
+            title.as_ptr(),
+        );
+        #[cfg(feature = "parent")]
+        super::utils::set_parent(dialog, opt.parent());
+
+        let bar = gtk_sys::gtk_progress_bar_new();
+        let area = gtk_sys::gtk_message_dialog_get_message_area(dialog as *mut _);
+        gtk_sys::gtk_container_add(area as *mut gtk_sys::GtkContainer, bar);
+
+        // The bar of a marquee dialog moves a little every 100ms
+        unsafe extern "C" fn pulse(data: glib_sys::gpointer) -> glib_sys::gboolean {
+            let (bar, state) = &*(data as *const (*mut gtk_sys::GtkProgressBar, ProgressState));
+            if state.marquee() {
+                gtk_sys::gtk_progress_bar_pulse(*bar);
+            }
+            glib_sys::GTRUE
+        }
+        unsafe extern "C" fn free(data: glib_sys::gpointer) {
+            drop(Box::from_raw(
+                data as *mut (*mut gtk_sys::GtkProgressBar, ProgressState),
+            ));
+        }
+        let data = Box::new((bar as *mut gtk_sys::GtkProgressBar, state.clone()));
+        let pulse = glib_sys::g_timeout_add_full(
+            glib_sys::G_PRIORITY_DEFAULT,
+            100,
+            Some(pulse),
+            Box::into_raw(data) as *mut _,
+            Some(free),
+        );
+
+        let panel = Self {
+            dialog,
+            bar: bar as *mut _,
+            pulse,
+            _request: GTK_EVENT_HANDLER.request_iteration_start(),
+        };
+        panel.update(state);
+        gtk_sys::gtk_widget_show_all(dialog);
+        panel
+    }
+
+    unsafe fn update(&self, state: &ProgressState) {
+        let message = CString::new(state.message()).unwrap();
+        gtk_sys::gtk_message_dialog_format_secondary_text(
+            self.dialog as *mut _,
+            b"%s\0".as_ptr() as *const _,
+            message.as_ptr(),
+        );
+
+        if !state.marquee() {
+            gtk_sys::gtk_progress_bar_set_fraction(self.bar, state.progress() as f64);
+        }
+    }
+}
+
+impl Drop for Panel {
+    fn drop(&mut self) {
+        unsafe {
+            glib_sys::g_source_remove(self.pulse);
+            gtk_sys::gtk_widget_destroy(self.dialog);
+        }
+    }
+}
+
+/// Any response, Cancel or closing the window, cancels the operation
+unsafe fn connect_cancel(
+    dialog: *mut gtk_sys::GtkWidget,
+    panel: SharedPanel,
+    state: ProgressState,
+) {
+    unsafe extern "C" fn response(
+        _: *mut gtk_sys::GtkDialog,
+        _: gtk_sys::GtkResponseType,
+        data: glib_sys::gpointer,
+    ) {
+        let (panel, state) = &*(data as *const (SharedPanel, ProgressState));
+        state.cancel();
+        // Called while GTK iterates, so the `GTK_MUTEX` is locked already
+        panel.lock().unwrap().take();
+    }
+
+    connect_raw(
+        dialog as *mut _,
+        b"response\0".as_ptr() as *const _,
+        Some(transmute::<_, unsafe extern "C" fn()>(
+            response as *const (),
+        )),
+        Box::into_raw(Box::new((panel, state))),
+    );
+}
+
+pub fn show(opt: ProgressDialog, state: ProgressState) {
+    let panel: SharedPanel = Default::default();
+
+    GTK_MUTEX.run_locked(|| {
+        if !gtk_init_check() {
+            return;
+        }
+
+        let shown = unsafe { Panel::new(&opt, &state) };
+        unsafe { connect_cancel(shown.dialog, panel.clone(), state.clone()) };
+        panel.lock().unwrap().replace(shown);
+    });
+
+    state.on_update(move |state| {
+        GTK_MUTEX.run_locked(|| {
+            let mut panel = panel.lock().unwrap();
+            if state.is_closed() {
+                panel.take();
+            } else if let Some(panel) = &*panel {
+                unsafe { panel.update(state) };
+            }
+        })
+    });
+}
@@ -1,10 +1,21 @@
 use lazy_static::lazy_static;
 
+use crate::{
+    color_scheme, text_direction, ColorScheme, Error, MonitorSelection, TextDirection, WindowIcon,
+};
+
+use std::ffi::{CStr, CString};
+#[cfg(feature = "parent")]
+use std::os::raw::c_ulong;
+use std::os::raw::{c_char, c_int, c_void};
 use std::ptr;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
 
+#[cfg(feature = "parent")]
+use raw_window_handle::RawWindowHandle;
+
 /// Ensures that gtk is allways called from one thread at the time
 pub struct GtkGlobalMutex {
     locker: Mutex<()>,
@@ -131,7 +142,107 @@ impl Drop for GtkThread {
 }
 
 pub fn gtk_init_check() -> bool {
-    unsafe { gtk_sys::gtk_init_check(ptr::null_mut(), ptr::null_mut()) == 1 }
+    let initialized = unsafe { gtk_sys::gtk_init_check(ptr::null_mut(), ptr::null_mut()) == 1 };
+    trace_debug!(initialized, "gtk_init_check");
+    if initialized {
+        apply_text_direction();
+        apply_color_scheme();
+    }
+    initialized
+}
+
+/// Lays out the widgets created from now on in the direction set by `set_text_direction`
+fn apply_text_direction() {
+    let direction = match text_direction() {
+        TextDirection::Platform => return,
+        TextDirection::Ltr => gtk_sys::GTK_TEXT_DIR_LTR,
+        TextDirection::Rtl => gtk_sys::GTK_TEXT_DIR_RTL,
+    };
+    unsafe { gtk_sys::gtk_widget_set_default_direction(direction) };
+}
+
+/// Switches the default settings to the theme variant set by `set_color_scheme`
+///
+/// `Light` also leaves the dark variant of a theme named like `Adwaita-dark`, which ignores
+/// `gtk-application-prefer-dark-theme`.
+fn apply_color_scheme() {
+    let dark = match color_scheme() {
+        ColorScheme::Auto => return,
+        ColorScheme::Dark => glib_sys::GTRUE,
+        ColorScheme::Light => glib_sys::GFALSE,
+    };
+
+    unsafe {
+        let settings = gtk_sys::gtk_settings_get_default();
+        if settings.is_null() {
+            return;
+        }
+
+        let prefer_dark = b"gtk-application-prefer-dark-theme\0".as_ptr() as *const c_char;
+        gobject_sys::g_object_set(settings as _, prefer_dark, dark, ptr::null::<c_char>());
+
+        if dark == glib_sys::GTRUE {
+            return;
+        }
+        let theme_name = b"gtk-theme-name\0".as_ptr() as *const c_char;
+        let mut theme: *mut c_char = ptr::null_mut();
+        gobject_sys::g_object_get(settings as _, theme_name, &mut theme, ptr::null::<c_char>());
+        if theme.is_null() {
+            return;
+        }
+        let name = CStr::from_ptr(theme).to_string_lossy().into_owned();
+        glib_sys::g_free(theme as _);
+        if let Some(light) = name
+            .strip_suffix("-dark")
+            .and_then(|n| CString::new(n).ok())
+        {
+            gobject_sys::g_object_set(
+                settings as _,
+                theme_name,
+                light.as_ptr(),
+                ptr::null::<c_char>(),
+            );
+        }
+    }
+}
+
+/// Error returned when `gtk_init_check` fails, usually because there is no display to connect to
+///
+/// Initializes once more with `gtk_init_with_args`, which reports why as a `GError` when it can
+pub fn init_error() -> Error {
+    let mut error = ptr::null_mut();
+    unsafe {
+        gtk_sys::gtk_init_with_args(
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            &mut error,
+        );
+        if !error.is_null() {
+            return take_error(error);
+        }
+    }
+    Error::Gtk("failed to initialize GTK, is a display available?".into())
+}
+
+/// [`Error::Glib`] of `error`, which gets freed
+pub unsafe fn take_error(error: *mut glib_sys::GError) -> Error {
+    let domain = CStr::from_ptr(glib_sys::g_quark_to_string((*error).domain));
+    let message = CStr::from_ptr((*error).message);
+    let err = Error::Glib {
+        domain: domain.to_string_lossy().into_owned(),
+        code: (*error).code,
+        message: message.to_string_lossy().into_owned(),
+    };
+    glib_sys::g_error_free(error);
+    err
+}
+
+/// Emits the `response` signal, as if the user pressed a button of the dialog
+pub unsafe fn emit_response(dialog: *mut gtk_sys::GtkDialog, res: gtk_sys::GtkResponseType) {
+    gobject_sys::g_signal_emit_by_name(dialog as *mut _, b"response\0".as_ptr() as *const _, res);
 }
 
 /// gtk_main_iteration()
@@ -140,3 +251,155 @@ pub unsafe fn wait_for_cleanup() {
         gtk_sys::gtk_main_iteration();
     }
 }
+
+extern "C" {
+    // gdk-sys is not a dependency, GDK itself is linked by gtk-sys
+    fn gdk_display_get_default() -> *mut c_void;
+    fn gdk_display_get_primary_monitor(display: *mut c_void) -> *mut c_void;
+    fn gdk_display_get_monitor(display: *mut c_void, monitor_num: c_int) -> *mut c_void;
+    fn gdk_display_get_monitor_at_point(display: *mut c_void, x: c_int, y: c_int) -> *mut c_void;
+    fn gdk_display_get_default_seat(display: *mut c_void) -> *mut c_void;
+    fn gdk_seat_get_pointer(seat: *mut c_void) -> *mut c_void;
+    fn gdk_device_get_position(
+        device: *mut c_void,
+        screen: *mut *mut c_void,
+        x: *mut c_int,
+        y: *mut c_int,
+    );
+    fn gdk_monitor_get_workarea(monitor: *mut c_void, workarea: *mut GdkRectangle);
+}
+
+#[cfg(feature = "parent")]
+extern "C" {
+    fn gdk_x11_display_get_type() -> glib_sys::GType;
+    fn gdk_x11_window_foreign_new_for_display(display: *mut c_void, window: c_ulong)
+        -> *mut c_void;
+    fn gdk_window_set_transient_for(window: *mut c_void, parent: *mut c_void);
+}
+
+/// Pixbuf of `width * height` RGBA pixels, `None` when `rgba` does not hold that many
+pub unsafe fn pixbuf_from_rgba(
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+) -> Option<*mut gdk_pixbuf_sys::GdkPixbuf> {
+    if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+        return None;
+    }
+
+    let bytes = glib_sys::g_bytes_new(rgba.as_ptr() as _, rgba.len());
+    let pixbuf = gdk_pixbuf_sys::gdk_pixbuf_new_from_bytes(
+        bytes,
+        gdk_pixbuf_sys::GDK_COLORSPACE_RGB,
+        glib_sys::GTRUE,
+        8,
+        width as _,
+        height as _,
+        width as i32 * 4,
+    );
+    glib_sys::g_bytes_unref(bytes);
+    Some(pixbuf)
+}
+
+/// Sets the icon and the title with the app name of the toplevel `window`
+pub unsafe fn brand_window(
+    window: *mut gtk_sys::GtkWindow,
+    icon: Option<&WindowIcon>,
+    title: Option<&str>,
+) {
+    if let Some(pixbuf) =
+        icon.and_then(|icon| pixbuf_from_rgba(icon.width, icon.height, &icon.rgba))
+    {
+        gtk_sys::gtk_window_set_icon(window, pixbuf);
+        gobject_sys::g_object_unref(pixbuf as _);
+    }
+    if let Some(title) = title.and_then(|title| CString::new(title).ok()) {
+        gtk_sys::gtk_window_set_title(window, title.as_ptr());
+    }
+}
+
+/// `GdkRectangle`
+#[repr(C)]
+#[derive(Default)]
+pub struct GdkRectangle {
+    pub x: c_int,
+    pub y: c_int,
+    pub width: c_int,
+    pub height: c_int,
+}
+
+/// Work area of the monitor `selection` points to, `None` when there is no such monitor
+pub unsafe fn monitor_workarea(selection: MonitorSelection) -> Option<GdkRectangle> {
+    let display = gdk_display_get_default();
+    if display.is_null() {
+        return None;
+    }
+
+    let monitor = match selection {
+        // Wayland has no primary monitor, the first one stands in for it
+        MonitorSelection::Primary => match gdk_display_get_primary_monitor(display) {
+            monitor if monitor.is_null() => gdk_display_get_monitor(display, 0),
+            monitor => monitor,
+        },
+        MonitorSelection::Index(index) => gdk_display_get_monitor(display, index as _),
+        MonitorSelection::ContainingCursor => {
+            let pointer = gdk_seat_get_pointer(gdk_display_get_default_seat(display));
+            if pointer.is_null() {
+                return None;
+            }
+            let (mut x, mut y) = (0, 0);
+            gdk_device_get_position(pointer, ptr::null_mut(), &mut x, &mut y);
+            gdk_display_get_monitor_at_point(display, x, y)
+        }
+    };
+    if monitor.is_null() {
+        return None;
+    }
+
+    let mut workarea = GdkRectangle::default();
+    gdk_monitor_get_workarea(monitor, &mut workarea);
+    Some(workarea)
+}
+
+/// X11 id of the parent window, GTK 3 can't wrap other kinds of foreign windows
+#[cfg(feature = "parent")]
+pub fn parent_xid(parent: Option<RawWindowHandle>) -> Option<c_ulong> {
+    let xid = match parent? {
+        RawWindowHandle::Xlib(handle) => handle.window,
+        RawWindowHandle::Xcb(handle) => handle.window as _,
+        _ => return None,
+    };
+    Some(xid).filter(|xid| *xid != 0)
+}
+
+/// Makes the toplevel `widget` transient for the X11 window `xid` of the application,
+/// so the window manager keeps it above that window and centers it there
+#[cfg(feature = "parent")]
+pub unsafe fn set_transient_for_xid(widget: *mut gtk_sys::GtkWidget, xid: c_ulong) {
+    let display = gdk_display_get_default();
+    if display.is_null()
+        || gobject_sys::g_type_check_instance_is_a(display as _, gdk_x11_display_get_type()) == 0
+    {
+        return;
+    }
+
+    let parent = gdk_x11_window_foreign_new_for_display(display, xid);
+    if parent.is_null() {
+        return;
+    }
+
+    gtk_sys::gtk_widget_realize(widget);
+    let window = gtk_sys::gtk_widget_get_window(widget);
+    if !window.is_null() {
+        gdk_window_set_transient_for(window as _, parent);
+    }
+    gobject_sys::g_object_unref(parent as _);
+}
+
+/// [`set_transient_for_xid`] for dialogs whose `parent` is set
+#[cfg(feature = "parent")]
+pub unsafe fn set_parent(widget: *mut gtk_sys::GtkWidget, parent: Option<RawWindowHandle>) {
+    if let Some(xid) = parent_xid(parent) {
+        set_transient_for_xid(widget, xid);
+    }
+}
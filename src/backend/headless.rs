@@ -0,0 +1,260 @@
+//! Headless mode for CI runners without a display, turned on with `RFD_HEADLESS=1`
+//!
+//! No dialog is shown, each one is answered from the response file named by `RFD_RESPONSE_FILE`,
+//! where `key = value` lines map the persistence id or the title of a dialog to its response:
+//!
+//! ```text
+//! # Paths picked by file dialogs, one line per path for the dialogs picking several
+//! export = /tmp/out/report.pdf
+//! Open Images = /tmp/in/a.png
+//! Open Images = /tmp/in/b.png
+//! # Label of the pressed button
+//! Unsaved changes = No
+//! # Entered text, chosen item or its index, and `user:password`
+//! Layer name = Background
+//! Sign in = ci:hunter2
+//! ```
+//!
+//! Dialogs without a line take the value of `RFD_RESPONSE`, empty values and dialogs without any
+//! response count as cancelled.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::backend::{DialogBackend, DialogFutureType};
+use crate::{
+    CredentialDialog, Credentials, Error, FileDialog, FileHandle, InputDialog, MessageDialog,
+    Notification, PickListDialog,
+};
+
+const HEADLESS: &str = "RFD_HEADLESS";
+const RESPONSE_FILE: &str = "RFD_RESPONSE_FILE";
+const RESPONSE: &str = "RFD_RESPONSE";
+
+/// Whether `RFD_HEADLESS=1` asks to answer dialogs without showing them
+pub fn enabled() -> bool {
+    env::var_os(HEADLESS).is_some_and(|value| value == "1")
+}
+
+/// Values of every key in the response file, in the order of their lines
+fn parse(text: &str) -> HashMap<String, Vec<String>> {
+    let mut responses: HashMap<String, Vec<String>> = HashMap::new();
+    let lines = text.lines().map(str::trim);
+    for line in lines.filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        if let Some((key, value)) = line.split_once('=') {
+            let values = responses.entry(key.trim().to_string()).or_default();
+            values.push(value.trim().to_string());
+        }
+    }
+    responses
+}
+
+/// Values answering the dialog with the first of `keys` found in the response file,
+/// read again for every dialog so tests can rewrite it between them
+fn response(keys: &[Option<&str>]) -> Result<Vec<String>, Error> {
+    let responses = match env::var_os(RESPONSE_FILE) {
+        Some(path) => {
+            let text = fs::read_to_string(&path).map_err(|err| {
+                let path = PathBuf::from(path);
+                Error::Headless(format!("can't read {}: {}", path.display(), err))
+            })?;
+            parse(&text)
+        }
+        None => HashMap::new(),
+    };
+
+    let values = keys
+        .iter()
+        .flatten()
+        .find_map(|key| responses.get(*key).cloned())
+        .or_else(|| env::var(RESPONSE).ok().map(|value| vec![value]))
+        .unwrap_or_default();
+    Ok(values
+        .into_iter()
+        .filter(|value| !value.is_empty())
+        .collect())
+}
+
+/// Response of a dialog that reports no errors, an unreadable response file cancels it
+fn first_value(key: &str) -> Option<String> {
+    response(&[Some(key)]).ok()?.into_iter().next()
+}
+
+/// Index of the item named by `value`, or of the item at the index it holds
+fn pick_index(items: &[String], value: &str) -> Option<usize> {
+    items
+        .iter()
+        .position(|item| item == value)
+        .or_else(|| value.parse().ok().filter(|index| *index < items.len()))
+}
+
+/// Button label matching `value` regardless of case
+fn button(labels: &[String], value: &str) -> Option<String> {
+    labels
+        .iter()
+        .find(|label| label.eq_ignore_ascii_case(value))
+        .cloned()
+}
+
+fn paths(dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+    let values = response(&[dialog.persistence_id(), dialog.title()])?;
+    if values.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(values.into_iter().map(PathBuf::from).collect()))
+}
+
+fn path(dialog: &FileDialog) -> Result<Option<PathBuf>, Error> {
+    Ok(paths(dialog)?.and_then(|paths| paths.into_iter().next()))
+}
+
+fn file(path: Result<Option<PathBuf>, Error>) -> Result<Option<FileHandle>, Error> {
+    path.map(|path| path.map(FileHandle::wrap))
+}
+
+fn files(paths: Result<Option<Vec<PathBuf>>, Error>) -> Result<Option<Vec<FileHandle>>, Error> {
+    paths.map(|paths| paths.map(|paths| paths.into_iter().map(FileHandle::wrap).collect()))
+}
+
+/// Answers dialogs from the response file instead of showing them
+pub struct HeadlessBackend;
+
+impl DialogBackend for HeadlessBackend {
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        path(&dialog)
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        paths(&dialog)
+    }
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        path(&dialog)
+    }
+
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        paths(&dialog)
+    }
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        path(&dialog)
+    }
+
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        let labels = dialog.button_labels();
+        self.show_message_custom(dialog) == labels.first().cloned()
+    }
+
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        let value = first_value(dialog.title())?;
+        button(&dialog.button_labels(), &value)
+    }
+
+    fn show_input(&self, dialog: InputDialog) -> Option<String> {
+        first_value(dialog.title())
+    }
+
+    fn show_pick_list(&self, dialog: PickListDialog) -> Option<usize> {
+        let value = first_value(dialog.title())?;
+        pick_index(dialog.items(), &value)
+    }
+
+    fn show_credentials(&self, dialog: CredentialDialog) -> Option<Credentials> {
+        let value = first_value(dialog.title())?;
+        let (user, password) = value.split_once(':')?;
+        Some(Credentials {
+            user: user.to_string(),
+            password: password.to_string().into(),
+        })
+    }
+
+    fn show_notification(&self, _notification: Notification) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        Box::pin(std::future::ready(file(self.pick_file(dialog))))
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        Box::pin(std::future::ready(files(self.pick_files(dialog))))
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        Box::pin(std::future::ready(file(self.pick_folder(dialog))))
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        Box::pin(std::future::ready(files(self.pick_folders(dialog))))
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        Box::pin(std::future::ready(file(self.save_file(dialog))))
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        Box::pin(std::future::ready(self.show_message(dialog)))
+    }
+
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        Box::pin(std::future::ready(self.show_message_custom(dialog)))
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+
+    fn confirms_overwrite(&self, _dialog: &FileDialog) -> Option<bool> {
+        Some(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response_file_lines() {
+        let responses = parse(
+            "# comment\n\
+             export = /tmp/report.pdf\n\
+             Open Images = /tmp/a.png\n\
+             Open Images=/tmp/b.png\n\
+             \n\
+             Cancelled =\n",
+        );
+        assert_eq!(responses["export"], ["/tmp/report.pdf"]);
+        assert_eq!(responses["Open Images"], ["/tmp/a.png", "/tmp/b.png"]);
+        assert_eq!(responses["Cancelled"], [""]);
+        assert_eq!(responses.len(), 3);
+    }
+
+    #[test]
+    fn items_and_buttons_from_values() {
+        let items = vec!["Red".to_string(), "Green".to_string()];
+        assert_eq!(pick_index(&items, "Green"), Some(1));
+        assert_eq!(pick_index(&items, "0"), Some(0));
+        assert_eq!(pick_index(&items, "2"), None);
+
+        let labels = vec!["Yes".to_string(), "No".to_string()];
+        assert_eq!(button(&labels, "no"), Some("No".to_string()));
+        assert_eq!(button(&labels, "Maybe"), None);
+    }
+}
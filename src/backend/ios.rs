@@ -0,0 +1,476 @@
+//! iOS `UIDocumentPickerViewController`
+//!
+//! The picker is presented by the top view controller of the key window, which is set up by
+//! winit. Without a key window dialogs fail with [`Error::Unsupported`].
+//!
+//! Files of other apps are security-scoped URLs, their access has to be started before they are
+//! read. The picked URLs are kept, [`FileHandle`] starts and stops their access around each
+//! operation with [`with_security_scope`].
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, Once};
+use std::task::{Context, Poll, Waker};
+
+use objc::declare::ClassDecl;
+use objc::runtime::{Class, Object, Sel, BOOL, NO, YES};
+use objc::{class, msg_send, sel, sel_impl};
+use objc_foundation::{INSString, NSString};
+use objc_id::Id;
+
+use crate::backend::{DialogBackend, DialogFutureType};
+use crate::{Error, FileDialog, FileHandle, MessageDialog};
+
+#[link(name = "UIKit", kind = "framework")]
+extern "C" {}
+
+#[link(name = "MobileCoreServices", kind = "framework")]
+extern "C" {
+    fn UTTypeCreatePreferredIdentifierForTag(
+        tag_class: *mut Object,
+        tag: *mut Object,
+        conforming_to: *mut Object,
+    ) -> *mut Object;
+}
+
+#[allow(non_upper_case_globals)]
+const nil: *mut Object = 0 as *mut _;
+
+/// `UIDocumentPickerModeOpen`, files stay where they are
+const MODE_OPEN: usize = 1;
+/// `UIDocumentPickerModeMoveToService`, the new empty file is moved to the chosen place
+const MODE_MOVE_TO_SERVICE: usize = 3;
+
+#[derive(Clone, Copy)]
+enum Kind {
+    File,
+    Folder,
+    Save,
+}
+
+/// Result of a dialog, filled in by the picker delegate
+#[derive(Default)]
+struct Pending {
+    state: Mutex<PendingState>,
+    finished: Condvar,
+}
+
+#[derive(Default)]
+struct PendingState {
+    res: Option<Result<Vec<PathBuf>, Error>>,
+    waker: Option<Waker>,
+}
+
+impl Pending {
+    fn finish(&self, res: Result<Vec<PathBuf>, Error>) {
+        let mut state = self.state.lock().unwrap();
+        state.res = Some(res);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.finished.notify_all();
+    }
+
+    fn is_finished(&self) -> bool {
+        self.state.lock().unwrap().res.is_some()
+    }
+
+    /// Blocks until the dialog is closed
+    fn wait(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(res) = state.res.take() {
+                return res;
+            }
+            state = self.finished.wait(state).unwrap();
+        }
+    }
+}
+
+/// Resolves once the dialog is closed
+struct PendingFuture(Arc<Pending>);
+
+impl Future for PendingFuture {
+    type Output = Result<Vec<PathBuf>, Error>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.state.lock().unwrap();
+        match state.res.take() {
+            Some(res) => Poll::Ready(res),
+            None => {
+                state.waker.replace(ctx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Picked security-scoped URLs, by their path
+static SCOPED_URLS: Mutex<Option<HashMap<PathBuf, ScopedUrl>>> = Mutex::new(None);
+
+struct ScopedUrl(*mut Object);
+
+// Only retained, `NSURL` is immutable
+unsafe impl Send for ScopedUrl {}
+
+impl Drop for ScopedUrl {
+    fn drop(&mut self) {
+        let _: () = unsafe { msg_send![self.0, release] };
+    }
+}
+
+/// Runs `run` with access to the security-scoped URL `path` was picked as, or is inside of
+pub(crate) fn with_security_scope<T>(path: &Path, run: impl FnOnce() -> T) -> T {
+    let url = SCOPED_URLS.lock().unwrap().as_ref().and_then(|urls| {
+        urls.iter()
+            .find(|(picked, _)| path.starts_with(picked))
+            .map(|(_, url)| url.0)
+    });
+    let url = match url {
+        Some(url) => url,
+        None => return run(),
+    };
+
+    let started: BOOL = unsafe { msg_send![url, startAccessingSecurityScopedResource] };
+    let res = run();
+    if started == YES {
+        let _: () = unsafe { msg_send![url, stopAccessingSecurityScopedResource] };
+    }
+    res
+}
+
+fn is_main_thread() -> bool {
+    unsafe { msg_send![class!(NSThread), isMainThread] }
+}
+
+fn to_path(url: *mut Object) -> PathBuf {
+    let path: *mut Object = unsafe { msg_send![url, path] };
+    let path: Id<NSString> = unsafe { Id::from_ptr(path as *mut NSString) };
+    path.as_str().into()
+}
+
+/// Delegate of the picker, its `rfd_pending` ivar is the `Arc<Pending>` of the dialog
+fn delegate_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        let mut decl = ClassDecl::new("RFDDocumentPickerDelegate", class!(NSObject)).unwrap();
+        decl.add_ivar::<usize>("rfd_pending");
+        unsafe {
+            decl.add_method(
+                sel!(documentPicker:didPickDocumentsAtURLs:),
+                did_pick as extern "C" fn(&mut Object, Sel, *mut Object, *mut Object),
+            );
+            decl.add_method(
+                sel!(documentPickerWasCancelled:),
+                was_cancelled as extern "C" fn(&mut Object, Sel, *mut Object),
+            );
+        }
+        decl.register();
+    });
+    class!(RFDDocumentPickerDelegate)
+}
+
+/// Takes the `Pending` out of the delegate, which is released once the picker is done with it
+fn take_pending(delegate: &mut Object) -> Option<Arc<Pending>> {
+    let ptr = unsafe {
+        let ptr = *delegate.get_ivar::<usize>("rfd_pending");
+        delegate.set_ivar::<usize>("rfd_pending", 0);
+        ptr
+    };
+    if ptr == 0 {
+        return None;
+    }
+
+    let _: () = unsafe { msg_send![delegate, autorelease] };
+    Some(unsafe { Arc::from_raw(ptr as *const Pending) })
+}
+
+extern "C" fn did_pick(this: &mut Object, _: Sel, _picker: *mut Object, urls: *mut Object) {
+    let pending = match take_pending(this) {
+        Some(pending) => pending,
+        None => return,
+    };
+
+    let count: usize = unsafe { msg_send![urls, count] };
+    let mut paths = Vec::with_capacity(count);
+    let mut scoped = SCOPED_URLS.lock().unwrap();
+    for index in 0..count {
+        let url: *mut Object = unsafe { msg_send![urls, objectAtIndex: index] };
+        let _: () = unsafe { msg_send![url, retain] };
+        let path = to_path(url);
+        scoped
+            .get_or_insert_with(HashMap::new)
+            .insert(path.clone(), ScopedUrl(url));
+        paths.push(path);
+    }
+    drop(scoped);
+
+    pending.finish(Ok(paths));
+}
+
+extern "C" fn was_cancelled(this: &mut Object, _: Sel, _picker: *mut Object) {
+    if let Some(pending) = take_pending(this) {
+        pending.finish(Ok(Vec::new()));
+    }
+}
+
+/// Uniform type identifiers of the filters, anything when there are none
+fn content_types(opt: &FileDialog) -> Vec<Id<NSString>> {
+    let extension_class = NSString::from_str("public.filename-extension");
+    let mime_class = NSString::from_str("public.mime-type");
+
+    let mut types = Vec::new();
+    let mut push = |class: &NSString, tag: &str| {
+        let tag = NSString::from_str(tag);
+        let uti =
+            unsafe { UTTypeCreatePreferredIdentifierForTag(ns_ptr(class), ns_ptr(&tag), nil) };
+        if !uti.is_null() {
+            // `Create` returns a retained string
+            types.push(unsafe { Id::from_retained_ptr(uti as *mut NSString) });
+        }
+    };
+    for filter in &opt.filters {
+        for ext in filter.plain_extensions() {
+            push(&extension_class, &ext);
+        }
+        for mime_type in &filter.mime_types {
+            push(&mime_class, mime_type);
+        }
+    }
+
+    if types.is_empty() {
+        types.push(NSString::from_str("public.item"));
+    }
+    types
+}
+
+fn ns_ptr(string: &NSString) -> *mut Object {
+    string as *const NSString as *mut Object
+}
+
+fn ns_array(items: &[Id<NSString>]) -> *mut Object {
+    let ptrs: Vec<*mut Object> = items.iter().map(|item| ns_ptr(item)).collect();
+    unsafe { msg_send![class!(NSArray), arrayWithObjects: ptrs.as_ptr() count: ptrs.len()] }
+}
+
+/// Top view controller of the key window, which presents the picker
+fn presenter() -> Option<*mut Object> {
+    unsafe {
+        let app: *mut Object = msg_send![class!(UIApplication), sharedApplication];
+        let window: *mut Object = msg_send![app, keyWindow];
+        if window.is_null() {
+            return None;
+        }
+        let mut controller: *mut Object = msg_send![window, rootViewController];
+        if controller.is_null() {
+            return None;
+        }
+        loop {
+            let presented: *mut Object = msg_send![controller, presentedViewController];
+            if presented.is_null() {
+                return Some(controller);
+            }
+            controller = presented;
+        }
+    }
+}
+
+/// Builds the picker, a save dialog moves a new empty file named `file_name` to the chosen place
+fn build_picker(opt: &FileDialog, kind: Kind) -> Result<*mut Object, Error> {
+    let picker: *mut Object = unsafe { msg_send![class!(UIDocumentPickerViewController), alloc] };
+    let picker: *mut Object = match kind {
+        Kind::File => {
+            let types = ns_array(&content_types(opt));
+            unsafe { msg_send![picker, initWithDocumentTypes: types inMode: MODE_OPEN] }
+        }
+        Kind::Folder => {
+            let types = ns_array(&[NSString::from_str("public.folder")]);
+            unsafe { msg_send![picker, initWithDocumentTypes: types inMode: MODE_OPEN] }
+        }
+        Kind::Save => {
+            let name = opt.file_name.as_deref().unwrap_or("Untitled");
+            let file = std::env::temp_dir().join(name);
+            if std::fs::write(&file, []).is_err() {
+                let _: () = unsafe { msg_send![picker, release] };
+                return Err(Error::Unsupported);
+            }
+            let file = NSString::from_str(&file.to_string_lossy());
+            let url: *mut Object =
+                unsafe { msg_send![class!(NSURL), fileURLWithPath: ns_ptr(&file)] };
+            unsafe { msg_send![picker, initWithURL: url inMode: MODE_MOVE_TO_SERVICE] }
+        }
+    };
+
+    if let Some(dir) = &opt.starting_directory {
+        let dir = NSString::from_str(&dir.to_string_lossy());
+        let url: *mut Object =
+            unsafe { msg_send![class!(NSURL), fileURLWithPath: ns_ptr(&dir) isDirectory: YES] };
+        let _: () = unsafe { msg_send![picker, setDirectoryURL: url] };
+    }
+    Ok(picker)
+}
+
+/// Presents the picker, must run on the main thread
+fn present(opt: &FileDialog, kind: Kind, multiple: bool, pending: Arc<Pending>) {
+    let presenter = match presenter() {
+        Some(presenter) => presenter,
+        None => return pending.finish(Err(Error::Unsupported)),
+    };
+    let picker = match build_picker(opt, kind) {
+        Ok(picker) => picker,
+        Err(err) => return pending.finish(Err(err)),
+    };
+
+    unsafe {
+        let delegate: *mut Object = msg_send![delegate_class(), new];
+        (*delegate).set_ivar::<usize>("rfd_pending", Arc::into_raw(pending) as usize);
+
+        let multiple = if multiple { YES } else { NO };
+        let _: () = msg_send![picker, setAllowsMultipleSelection: multiple];
+        // The picker holds a weak reference, the delegate releases itself once it got the result
+        let _: () = msg_send![picker, setDelegate: delegate];
+        let _: () =
+            msg_send![presenter, presentViewController: picker animated: YES completion: nil];
+        let _: () = msg_send![picker, release];
+    }
+}
+
+/// Shows the picker from the main thread
+fn launch(opt: FileDialog, kind: Kind, multiple: bool) -> Arc<Pending> {
+    let pending = Arc::new(Pending::default());
+    if is_main_thread() {
+        present(&opt, kind, multiple, pending.clone());
+    } else {
+        let pending = pending.clone();
+        let opt = SendDialog(opt);
+        dispatch::Queue::main().exec_async(move || present(&opt.0, kind, multiple, pending));
+    }
+    pending
+}
+
+/// `FileDialog` moved to the main queue, it is only touched there
+struct SendDialog(FileDialog);
+
+unsafe impl Send for SendDialog {}
+
+/// Waits for the picker, on the main thread by running its run loop
+fn wait(pending: &Pending) -> Result<Vec<PathBuf>, Error> {
+    if is_main_thread() {
+        let mode = NSString::from_str("kCFRunLoopDefaultMode");
+        while !pending.is_finished() {
+            unsafe {
+                let run_loop: *mut Object = msg_send![class!(NSRunLoop), currentRunLoop];
+                let date: *mut Object = msg_send![class!(NSDate), distantFuture];
+                let _: BOOL = msg_send![run_loop, runMode: ns_ptr(&mode) beforeDate: date];
+            }
+        }
+    }
+    pending.wait()
+}
+
+fn paths(paths: Vec<PathBuf>) -> Option<Vec<PathBuf>> {
+    if paths.is_empty() {
+        None
+    } else {
+        Some(paths)
+    }
+}
+
+fn pick(opt: FileDialog, kind: Kind, multiple: bool) -> Result<Option<Vec<PathBuf>>, Error> {
+    let pending = launch(opt, kind, multiple);
+    wait(&pending).map(paths)
+}
+
+fn pick_async(
+    opt: FileDialog,
+    kind: Kind,
+    multiple: bool,
+) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+    let pending = launch(opt, kind, multiple);
+    Box::pin(async move {
+        let picked = PendingFuture(pending).await?;
+        Ok(paths(picked).map(|paths| paths.into_iter().map(FileHandle::wrap).collect()))
+    })
+}
+
+fn first<T>(items: Option<Vec<T>>) -> Option<T> {
+    items.and_then(|items| items.into_iter().next())
+}
+
+/// iOS backend, `UIDocumentPickerViewController`
+pub struct IOSBackend;
+
+impl DialogBackend for IOSBackend {
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        pick(dialog, Kind::File, false).map(first)
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        pick(dialog, Kind::File, true)
+    }
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        pick(dialog, Kind::Folder, false).map(first)
+    }
+
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        pick(dialog, Kind::Folder, true)
+    }
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        pick(dialog, Kind::Save, false).map(first)
+    }
+
+    // `UIAlertController` needs a UI the app presents it in, which rfd can't assume
+    fn show_message(&self, _dialog: MessageDialog) -> bool {
+        trace_warn!(
+            title = %_dialog.title,
+            "MessageDialog is not supported on iOS, it was not shown"
+        );
+        false
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(dialog, Kind::File, false);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        pick_async(dialog, Kind::File, true)
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(dialog, Kind::Folder, false);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        pick_async(dialog, Kind::Folder, true)
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(dialog, Kind::Save, false);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        Box::pin(std::future::ready(self.show_message(dialog)))
+    }
+}
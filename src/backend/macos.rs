@@ -1,11 +1,195 @@
+mod credential_dialog;
 mod file_dialog;
+mod input_dialog;
 mod message_dialog;
+mod notification;
+mod pick_list_dialog;
+mod progress_dialog;
 
 mod modal_future;
 
 mod utils;
 
-use objc::runtime::Object;
+use std::path::PathBuf;
+
+use crate::backend::{DialogBackend, DialogFutureType};
+use crate::{
+    CredentialDialog, Credentials, Error, FileDialog, FileHandle, InputDialog, MessageDialog,
+    Notification, PickListDialog, ProgressDialog, ProgressState,
+};
+
+use objc::runtime::{Object, BOOL, NO};
+use objc::{class, msg_send, sel, sel_impl};
+
 trait AsModal {
     fn modal_ptr(&mut self) -> *mut Object;
+
+    /// Window that runs the modal session, alerts are not windows themselves
+    fn modal_window(&mut self) -> *mut Object {
+        let modal = self.modal_ptr();
+        unsafe {
+            let is_window: BOOL = msg_send![modal, isKindOfClass: class!(NSWindow)];
+            if is_window != NO {
+                modal
+            } else {
+                msg_send![modal, window]
+            }
+        }
+    }
+}
+
+/// The future of a file dialog, or `Error::NotMainThread` right away when it can't be shown
+fn file_future<T: Send + 'static>(
+    start: impl FnOnce() -> DialogFutureType<T>,
+) -> DialogFutureType<Result<T, Error>> {
+    match utils::main_thread_available() {
+        Ok(()) => {
+            let future = start();
+            Box::pin(async move { Ok(future.await) })
+        }
+        Err(err) => Box::pin(async move { Err(err) }),
+    }
+}
+
+/// MacOS backend, `NSOpenPanel`/`NSSavePanel` and `NSAlert`
+pub struct MacOSBackend;
+
+impl DialogBackend for MacOSBackend {
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        file_dialog::pick_file(dialog)
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        file_dialog::pick_files(dialog)
+    }
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        file_dialog::pick_folder(dialog)
+    }
+
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        file_dialog::pick_folders(dialog)
+    }
+
+    fn pick_path(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        file_dialog::pick_path(dialog)
+    }
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        file_dialog::save_file(dialog)
+    }
+
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        message_dialog::show(dialog)
+    }
+
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        message_dialog::show_custom(dialog)
+    }
+
+    fn show_input(&self, dialog: InputDialog) -> Option<String> {
+        input_dialog::show(dialog)
+    }
+
+    fn show_pick_list(&self, dialog: PickListDialog) -> Option<usize> {
+        pick_list_dialog::show(dialog)
+    }
+
+    fn show_credentials(&self, dialog: CredentialDialog) -> Option<Credentials> {
+        credential_dialog::show(dialog)
+    }
+
+    fn try_show_message(&self, dialog: MessageDialog) -> Result<Option<String>, Error> {
+        message_dialog::try_show_custom(dialog)
+    }
+
+    fn try_show_input(&self, dialog: InputDialog) -> Result<Option<String>, Error> {
+        input_dialog::try_show(dialog)
+    }
+
+    fn try_show_pick_list(&self, dialog: PickListDialog) -> Result<Option<usize>, Error> {
+        pick_list_dialog::try_show(dialog)
+    }
+
+    fn try_show_credentials(&self, dialog: CredentialDialog) -> Result<Option<Credentials>, Error> {
+        credential_dialog::try_show(dialog)
+    }
+
+    fn show_progress(&self, dialog: ProgressDialog, state: ProgressState) {
+        progress_dialog::show(dialog, state)
+    }
+
+    fn show_notification(&self, notification: Notification) -> Result<(), Error> {
+        notification::show(notification)
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        file_future(|| file_dialog::pick_file_async(dialog))
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        file_future(|| file_dialog::pick_files_async(dialog))
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        file_future(|| file_dialog::pick_folder_async(dialog))
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        file_future(|| file_dialog::pick_folders_async(dialog))
+    }
+
+    fn pick_path_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        file_future(|| file_dialog::pick_path_async(dialog))
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        file_future(|| file_dialog::save_file_async(dialog))
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        message_dialog::show_async(dialog)
+    }
+
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        message_dialog::show_custom_async(dialog)
+    }
+
+    fn show_input_async(&self, dialog: InputDialog) -> DialogFutureType<Option<String>> {
+        input_dialog::show_async(dialog)
+    }
+
+    fn show_pick_list_async(&self, dialog: PickListDialog) -> DialogFutureType<Option<usize>> {
+        pick_list_dialog::show_async(dialog)
+    }
+
+    fn show_credentials_async(
+        &self,
+        dialog: CredentialDialog,
+    ) -> DialogFutureType<Option<Credentials>> {
+        credential_dialog::show_async(dialog)
+    }
+
+    // `NSSavePanel` always asks
+    fn confirms_overwrite(&self, _dialog: &FileDialog) -> Option<bool> {
+        Some(true)
+    }
 }
@@ -0,0 +1,160 @@
+use std::ffi::CStr;
+use std::ops::DerefMut;
+use std::os::raw::c_char;
+
+use crate::backend::DialogFutureType;
+use crate::localizer::localize;
+use crate::{BuiltinString, CredentialDialog, Credentials, DialogHandle, Error};
+
+use super::{
+    modal_future::ModalFuture,
+    utils::{or_cancelled, run_on_main, FocusManager, PolicyManager},
+    AsModal,
+};
+
+use super::utils::{INSWindow, NSRect, NSWindow};
+use objc::runtime::Object;
+use objc::{class, msg_send, sel, sel_impl};
+use objc_foundation::{INSString, NSString};
+
+use objc_id::Id;
+
+// NSAlertFirstButtonReturn
+const FIRST_BUTTON: i64 = 1000;
+
+/// `NSAlert` with a user name and a password field as its accessory view
+pub struct NSCredentialAlert {
+    alert: Id<Object>,
+    user: Id<Object>,
+    password: Id<Object>,
+    _focus_manager: FocusManager,
+    _policy_manager: PolicyManager,
+}
+
+impl NSCredentialAlert {
+    pub fn new(opt: CredentialDialog) -> Self {
+        let _policy_manager = PolicyManager::new();
+
+        let alert: *mut Object = unsafe { msg_send![class!(NSAlert), new] };
+
+        unsafe {
+            for label in [BuiltinString::Ok, BuiltinString::Cancel] {
+                let label = NSString::from_str(&localize(label));
+                let _: () = msg_send![alert, addButtonWithTitle: label];
+            }
+
+            let text = NSString::from_str(&opt.title);
+            let _: () = msg_send![alert, setMessageText: text];
+            let text = NSString::from_str(&opt.message);
+            let _: () = msg_send![alert, setInformativeText: text];
+        }
+
+        let (user, password) = unsafe {
+            let view: *mut Object = msg_send![class!(NSView), alloc];
+            let view: *mut Object =
+                msg_send![view, initWithFrame: NSRect::new(0.0, 0.0, 240.0, 56.0)];
+
+            let user = add_field(
+                view,
+                class!(NSTextField),
+                32.0,
+                &localize(BuiltinString::UserName),
+            );
+            let text = NSString::from_str(&opt.user);
+            let _: () = msg_send![user, setStringValue: text];
+            // Secure fields mask the text and keep it off the pasteboard
+            let password = add_field(
+                view,
+                class!(NSSecureTextField),
+                0.0,
+                &localize(BuiltinString::Password),
+            );
+            let _: () = msg_send![user, setNextKeyView: password];
+
+            let _: () = msg_send![alert, setAccessoryView: view];
+            let _: () = msg_send![view, release];
+
+            // A known user only has to type the password
+            let first = if opt.user.is_empty() { user } else { password };
+            let window: *mut Object = msg_send![alert, window];
+            let _: () = msg_send![window, setInitialFirstResponder: first];
+            (user, password)
+        };
+
+        let _focus_manager = FocusManager::new();
+
+        Self {
+            alert: unsafe { Id::from_retained_ptr(alert) },
+            user: unsafe { Id::from_retained_ptr(user) },
+            password: unsafe { Id::from_retained_ptr(password) },
+            _focus_manager,
+            _policy_manager,
+        }
+    }
+
+    pub fn run(self) -> Option<Credentials> {
+        let ret: i64 = unsafe { msg_send![self.alert, runModal] };
+        self.credentials(ret)
+    }
+
+    /// Content of the fields, when the alert was closed with OK
+    fn credentials(&self, res_id: i64) -> Option<Credentials> {
+        if res_id != FIRST_BUTTON {
+            return None;
+        }
+
+        let text = |field: &Object| unsafe {
+            let value: *mut Object = msg_send![field, stringValue];
+            let value: *const c_char = msg_send![value, UTF8String];
+            CStr::from_ptr(value).to_string_lossy().into_owned()
+        };
+        Some(Credentials {
+            user: text(&self.user),
+            password: text(&self.password).into(),
+        })
+    }
+}
+
+/// Text field of `class` at height `y` of `view`, its retain count is handed to the caller
+unsafe fn add_field(
+    view: *mut Object,
+    class: &objc::runtime::Class,
+    y: f64,
+    placeholder: &str,
+) -> *mut Object {
+    let field: *mut Object = msg_send![class, alloc];
+    let field: *mut Object = msg_send![field, initWithFrame: NSRect::new(0.0, y, 240.0, 24.0)];
+    let placeholder = NSString::from_str(placeholder);
+    let _: () = msg_send![field, setPlaceholderString: placeholder];
+    let _: () = msg_send![view, addSubview: field];
+    field
+}
+
+impl AsModal for NSCredentialAlert {
+    fn modal_ptr(&mut self) -> *mut Object {
+        self.alert.deref_mut()
+    }
+}
+
+pub fn show(opt: CredentialDialog) -> Option<Credentials> {
+    or_cancelled(try_show(opt))
+}
+
+pub fn try_show(opt: CredentialDialog) -> Result<Option<Credentials>, Error> {
+    objc::rc::autoreleasepool(move || run_on_main(move || NSCredentialAlert::new(opt).run()))
+}
+
+pub fn show_async(opt: CredentialDialog) -> DialogFutureType<Option<Credentials>> {
+    #[cfg(feature = "parent")]
+    let win = opt.parent().as_ref().map(NSWindow::from_raw_window_handle);
+    #[cfg(not(feature = "parent"))]
+    let win = None;
+
+    let future = ModalFuture::new(
+        DialogHandle::default(),
+        win,
+        move || NSCredentialAlert::new(opt),
+        |alert, res_id| alert.credentials(res_id),
+    );
+    Box::pin(future)
+}
@@ -1,186 +1,284 @@
 mod panel_ffi;
+mod preview;
 use panel_ffi::Panel;
 
 use crate::backend::DialogFutureType;
-use crate::{FileDialog, FileHandle};
+use crate::{DialogHandle, Error, FileDialog, FileHandle, Modality};
 
 use std::path::PathBuf;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use objc::runtime::Object;
+use objc::{msg_send, sel, sel_impl};
 
 pub use objc::runtime::{BOOL, NO};
 
-use super::modal_future::ModalFuture;
+use objc_id::Id;
+
+use super::modal_future::{self, ModalFuture};
 use super::utils::{run_on_main, INSWindow, NSWindow};
 
+/// Window the panel is attached to as a sheet, only window modal panels have one
+#[cfg_attr(not(feature = "parent"), allow(unused_variables))]
+fn parent(opt: &FileDialog) -> Option<Id<NSWindow>> {
+    #[cfg(feature = "parent")]
+    let win = opt.parent().as_ref().map(NSWindow::from_raw_window_handle);
+    #[cfg(not(feature = "parent"))]
+    let win = None;
+
+    win.filter(|_| opt.modality == Modality::WindowModal)
+}
+
+/// Shows the panel modally, as a sheet when it has a parent,
+/// closing the `DialogHandle` presses the cancel button of the panel
+fn run_modal(panel: &mut Panel, opt: &FileDialog) -> i64 {
+    let handle = &opt.handle;
+    let done = Arc::new(AtomicBool::new(false));
+    {
+        let done = done.clone();
+        let panel_ptr = &*panel.panel as *const Object as usize;
+        handle.on_close(move || {
+            // The modal loop still serves the main queue, and the panel can't finish while this runs
+            dispatch::Queue::main().exec_async(move || {
+                if !done.load(Ordering::Acquire) {
+                    let nil: *mut Object = ptr::null_mut();
+                    let _: () = unsafe { msg_send![panel_ptr as *mut Object, cancel: nil] };
+                }
+            });
+        });
+    }
+
+    let res = modal_future::run_modal(panel, parent(opt));
+    done.store(true, Ordering::Release);
+    res
+}
+
 //
 // File Picker
 //
 
-use crate::backend::FilePickerDialogImpl;
-impl FilePickerDialogImpl for FileDialog {
-    fn pick_file(self) -> Option<PathBuf> {
-        objc::rc::autoreleasepool(move || {
-            run_on_main(move || {
-                let panel = Panel::build_pick_file(&self);
-
-                if panel.run_modal() == 1 {
-                    Some(panel.get_result())
-                } else {
-                    None
-                }
-            })
+pub fn pick_file(opt: FileDialog) -> Result<Option<PathBuf>, Error> {
+    objc::rc::autoreleasepool(move || {
+        run_on_main(move || {
+            let mut panel = Panel::build_pick_file(&opt);
+
+            if run_modal(&mut panel, &opt) == 1 {
+                Some(panel.get_result())
+            } else {
+                None
+            }
         })
-    }
+    })
+}
 
-    fn pick_files(self) -> Option<Vec<PathBuf>> {
-        objc::rc::autoreleasepool(move || {
-            run_on_main(move || {
-                let panel = Panel::build_pick_files(&self);
+pub fn pick_files(opt: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+    objc::rc::autoreleasepool(move || {
+        run_on_main(move || {
+            let mut panel = Panel::build_pick_files(&opt);
 
-                if panel.run_modal() == 1 {
-                    Some(panel.get_results())
-                } else {
-                    None
-                }
-            })
+            if run_modal(&mut panel, &opt) == 1 {
+                Some(panel.get_results())
+            } else {
+                None
+            }
         })
-    }
+    })
 }
 
-use crate::backend::AsyncFilePickerDialogImpl;
-impl AsyncFilePickerDialogImpl for FileDialog {
-    fn pick_file_async(self) -> DialogFutureType<Option<FileHandle>> {
-        #[cfg(feature = "parent")]
-        let win = self.parent.as_ref().map(NSWindow::from_raw_window_handle);
-        #[cfg(not(feature = "parent"))]
-        let win = None;
-
-        let future = ModalFuture::new(
-            win,
-            move || Panel::build_pick_file(&self),
-            |panel, res_id| {
-                if res_id == 1 {
-                    Some(panel.get_result().into())
-                } else {
-                    None
-                }
-            },
-        );
+pub fn pick_file_async(opt: FileDialog) -> DialogFutureType<Option<FileHandle>> {
+    let win = parent(&opt);
 
-        Box::pin(future)
-    }
+    let future = ModalFuture::with_modality(
+        opt.handle.clone(),
+        win,
+        opt.modality,
+        move || Panel::build_pick_file(&opt),
+        |panel, res_id| {
+            if res_id == 1 {
+                Some(panel.get_result().into())
+            } else {
+                None
+            }
+        },
+    );
 
-    fn pick_files_async(self) -> DialogFutureType<Option<Vec<FileHandle>>> {
-        #[cfg(feature = "parent")]
-        let win = self.parent.as_ref().map(NSWindow::from_raw_window_handle);
-        #[cfg(not(feature = "parent"))]
-        let win = None;
-
-        let future = ModalFuture::new(
-            win,
-            move || Panel::build_pick_files(&self),
-            |panel, res_id| {
-                if res_id == 1 {
-                    Some(
-                        panel
-                            .get_results()
-                            .into_iter()
-                            .map(FileHandle::wrap)
-                            .collect(),
-                    )
-                } else {
-                    None
-                }
-            },
-        );
+    Box::pin(future)
+}
 
-        Box::pin(future)
-    }
+pub fn pick_files_async(opt: FileDialog) -> DialogFutureType<Option<Vec<FileHandle>>> {
+    let win = parent(&opt);
+
+    let future = ModalFuture::with_modality(
+        opt.handle.clone(),
+        win,
+        opt.modality,
+        move || Panel::build_pick_files(&opt),
+        |panel, res_id| {
+            if res_id == 1 {
+                Some(
+                    panel
+                        .get_results()
+                        .into_iter()
+                        .map(FileHandle::wrap)
+                        .collect(),
+                )
+            } else {
+                None
+            }
+        },
+    );
+
+    Box::pin(future)
 }
 
 //
 // Folder Picker
 //
 
-use crate::backend::FolderPickerDialogImpl;
-impl FolderPickerDialogImpl for FileDialog {
-    fn pick_folder(self) -> Option<PathBuf> {
-        objc::rc::autoreleasepool(move || {
-            run_on_main(move || {
-                let panel = Panel::build_pick_folder(&self);
-                if panel.run_modal() == 1 {
-                    Some(panel.get_result())
-                } else {
-                    None
-                }
-            })
+pub fn pick_folder(opt: FileDialog) -> Result<Option<PathBuf>, Error> {
+    objc::rc::autoreleasepool(move || {
+        run_on_main(move || {
+            let mut panel = Panel::build_pick_folder(&opt);
+            if run_modal(&mut panel, &opt) == 1 {
+                Some(panel.get_result())
+            } else {
+                None
+            }
         })
-    }
+    })
 }
 
-use crate::backend::AsyncFolderPickerDialogImpl;
-impl AsyncFolderPickerDialogImpl for FileDialog {
-    fn pick_folder_async(self) -> DialogFutureType<Option<FileHandle>> {
-        #[cfg(feature = "parent")]
-        let win = self.parent.as_ref().map(NSWindow::from_raw_window_handle);
-        #[cfg(not(feature = "parent"))]
-        let win = None;
-
-        let future = ModalFuture::new(
-            win,
-            move || Panel::build_pick_folder(&self),
-            |panel, res_id| {
-                if res_id == 1 {
-                    Some(panel.get_result().into())
-                } else {
-                    None
-                }
-            },
-        );
+pub fn pick_folder_async(opt: FileDialog) -> DialogFutureType<Option<FileHandle>> {
+    let win = parent(&opt);
 
-        Box::pin(future)
-    }
+    let future = ModalFuture::with_modality(
+        opt.handle.clone(),
+        win,
+        opt.modality,
+        move || Panel::build_pick_folder(&opt),
+        |panel, res_id| {
+            if res_id == 1 {
+                Some(panel.get_result().into())
+            } else {
+                None
+            }
+        },
+    );
+
+    Box::pin(future)
+}
+
+pub fn pick_folders(opt: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+    objc::rc::autoreleasepool(move || {
+        run_on_main(move || {
+            let mut panel = Panel::build_pick_folders(&opt);
+            if run_modal(&mut panel, &opt) == 1 {
+                Some(panel.get_results())
+            } else {
+                None
+            }
+        })
+    })
+}
+
+pub fn pick_folders_async(opt: FileDialog) -> DialogFutureType<Option<Vec<FileHandle>>> {
+    let win = parent(&opt);
+
+    let future = ModalFuture::with_modality(
+        opt.handle.clone(),
+        win,
+        opt.modality,
+        move || Panel::build_pick_folders(&opt),
+        |panel, res_id| {
+            if res_id == 1 {
+                Some(
+                    panel
+                        .get_results()
+                        .into_iter()
+                        .map(FileHandle::wrap)
+                        .collect(),
+                )
+            } else {
+                None
+            }
+        },
+    );
+
+    Box::pin(future)
+}
+
+//
+// File Or Folder Picker
+//
+
+pub fn pick_path(opt: FileDialog) -> Result<Option<PathBuf>, Error> {
+    objc::rc::autoreleasepool(move || {
+        run_on_main(move || {
+            let mut panel = Panel::build_pick_path(&opt);
+            if run_modal(&mut panel, &opt) == 1 {
+                Some(panel.get_result())
+            } else {
+                None
+            }
+        })
+    })
+}
+
+pub fn pick_path_async(opt: FileDialog) -> DialogFutureType<Option<FileHandle>> {
+    let win = parent(&opt);
+
+    let future = ModalFuture::with_modality(
+        opt.handle.clone(),
+        win,
+        opt.modality,
+        move || Panel::build_pick_path(&opt),
+        |panel, res_id| {
+            if res_id == 1 {
+                Some(panel.get_result().into())
+            } else {
+                None
+            }
+        },
+    );
+
+    Box::pin(future)
 }
 
 //
 // File Save
 //
 
-use crate::backend::FileSaveDialogImpl;
-impl FileSaveDialogImpl for FileDialog {
-    fn save_file(self) -> Option<PathBuf> {
-        objc::rc::autoreleasepool(move || {
-            run_on_main(move || {
-                let panel = Panel::build_save_file(&self);
-                if panel.run_modal() == 1 {
-                    Some(panel.get_result())
-                } else {
-                    None
-                }
-            })
+pub fn save_file(opt: FileDialog) -> Result<Option<PathBuf>, Error> {
+    objc::rc::autoreleasepool(move || {
+        run_on_main(move || {
+            let mut panel = Panel::build_save_file(&opt);
+            if run_modal(&mut panel, &opt) == 1 {
+                Some(panel.get_result())
+            } else {
+                None
+            }
         })
-    }
+    })
 }
 
-use crate::backend::AsyncFileSaveDialogImpl;
-impl AsyncFileSaveDialogImpl for FileDialog {
-    fn save_file_async(self) -> DialogFutureType<Option<FileHandle>> {
-        #[cfg(feature = "parent")]
-        let win = self.parent.as_ref().map(NSWindow::from_raw_window_handle);
-        #[cfg(not(feature = "parent"))]
-        let win = None;
-
-        let future = ModalFuture::new(
-            win,
-            move || Panel::build_save_file(&self),
-            |panel, res_id| {
-                if res_id == 1 {
-                    Some(panel.get_result().into())
-                } else {
-                    None
-                }
-            },
-        );
+pub fn save_file_async(opt: FileDialog) -> DialogFutureType<Option<FileHandle>> {
+    let win = parent(&opt);
 
-        Box::pin(future)
-    }
+    let future = ModalFuture::with_modality(
+        opt.handle.clone(),
+        win,
+        opt.modality,
+        move || Panel::build_save_file(&opt),
+        |panel, res_id| {
+            if res_id == 1 {
+                Some(panel.get_result().into())
+            } else {
+                None
+            }
+        },
+    );
+
+    Box::pin(future)
 }
@@ -1,14 +1,15 @@
-use crate::FileDialog;
+use crate::{FileDialog, MonitorSelection, Position};
 
 use std::path::Path;
+use std::ptr;
 use std::{ops::DerefMut, path::PathBuf};
 
 use objc::{class, msg_send, sel, sel_impl};
 use objc_id::Id;
 
-use super::super::utils::{INSURL, NSURL};
+use super::super::utils::{NSPoint, NSRect, INSURL, NSURL};
 
-use objc::runtime::{Object, YES};
+use objc::runtime::{Class, Object, YES};
 use objc::runtime::{BOOL, NO};
 use objc_foundation::{INSArray, INSString, NSArray, NSString};
 
@@ -16,16 +17,85 @@ use super::super::{
     utils::{FocusManager, PolicyManager},
     AsModal,
 };
+use super::preview::{column, PreviewPane};
 
 extern "C" {
     pub fn CGShieldingWindowLevel() -> i32;
 }
 
+const NS_MODAL_PANEL_WINDOW_LEVEL: i64 = 8;
+const NS_WINDOW_COLLECTION_BEHAVIOR_FULL_SCREEN_AUXILIARY: usize = 1 << 8;
+
+/// `NSScreen` `selection` points to, null when there is no such screen
+unsafe fn screen(selection: MonitorSelection) -> *mut Object {
+    let screens: *mut Object = msg_send![class!(NSScreen), screens];
+    let count: usize = msg_send![screens, count];
+    let index = match selection {
+        // The first screen is the one with the menu bar
+        MonitorSelection::Primary => 0,
+        MonitorSelection::Index(index) => index as usize,
+        MonitorSelection::ContainingCursor => {
+            let cursor: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+            (0..count)
+                .find(|&index| {
+                    let screen: *mut Object = msg_send![screens, objectAtIndex: index];
+                    let area: NSRect = msg_send![screen, frame];
+                    cursor.x >= area.x
+                        && cursor.x < area.x + area.width
+                        && cursor.y >= area.y
+                        && cursor.y < area.y + area.height
+                })
+                .unwrap_or(0)
+        }
+    };
+    if index >= count {
+        return ptr::null_mut();
+    }
+    msg_send![screens, objectAtIndex: index]
+}
+
+/// `NSWindow` of the parent set with `set_parent`, null without one
+#[cfg_attr(not(feature = "parent"), allow(unused_variables))]
+fn parent_window(opt: &FileDialog) -> *mut Object {
+    #[cfg(feature = "parent")]
+    if let Some(raw_window_handle::RawWindowHandle::MacOS(handle)) = opt.parent() {
+        return handle.ns_window as _;
+    }
+    std::ptr::null_mut()
+}
+
+#[link(name = "CoreServices", kind = "framework")]
+extern "C" {
+    static kUTTagClassMIMEType: *const NSString;
+
+    fn UTTypeCreatePreferredIdentifierForTag(
+        tag_class: *const NSString,
+        tag: *const NSString,
+        conforming_to: *const NSString,
+    ) -> *mut NSString;
+}
+
 fn make_nsstring(s: &str) -> Id<NSString> {
     NSString::from_str(s)
 }
 
+/// Uniform Type Identifier of a MIME type, `setAllowedFileTypes:` accepts those next to extensions
+fn mime_to_uti(mime: &str) -> Option<Id<NSString>> {
+    let mime = make_nsstring(mime);
+    unsafe {
+        let uti =
+            UTTypeCreatePreferredIdentifierForTag(kUTTagClassMIMEType, &*mime, std::ptr::null());
+        if uti.is_null() {
+            None
+        } else {
+            Some(Id::from_retained_ptr(uti))
+        }
+    }
+}
+
 pub struct Panel {
+    // Dropped first, it takes its delegate off the panel
+    preview: Option<PreviewPane>,
     pub(crate) panel: Id<Object>,
     _focus_manager: FocusManager,
     _policy_manager: PolicyManager,
@@ -45,6 +115,7 @@ impl Panel {
 
         let _: () = unsafe { msg_send![panel, setLevel: CGShieldingWindowLevel()] };
         Self {
+            preview: None,
             panel: unsafe { Id::from_ptr(panel) },
             _focus_manager,
             _policy_manager,
@@ -59,10 +130,6 @@ impl Panel {
         Self::new(unsafe { msg_send![class!(NSSavePanel), savePanel] })
     }
 
-    pub fn run_modal(&self) -> i32 {
-        unsafe { msg_send![self.panel, runModal] }
-    }
-
     pub fn set_can_choose_directories(&self, v: BOOL) {
         let _: () = unsafe { msg_send![self.panel, setCanChooseDirectories: v] };
     }
@@ -76,20 +143,65 @@ impl Panel {
     }
 
     pub fn add_filters(&self, params: &FileDialog) {
+        if self.add_content_types(params) {
+            return;
+        }
+
         let mut exts: Vec<String> = Vec::new();
 
         for filter in params.filters.iter() {
-            exts.append(&mut filter.extensions.to_vec());
+            exts.extend(filter.plain_extensions());
         }
 
         unsafe {
-            let f_raw: Vec<_> = exts.iter().map(|ext| make_nsstring(&ext)).collect();
+            let mut f_raw: Vec<_> = exts.iter().map(|ext| make_nsstring(&ext)).collect();
+            for filter in params.filters.iter() {
+                f_raw.extend(filter.mime_types.iter().filter_map(|m| mime_to_uti(m)));
+            }
             let array = NSArray::from_vec(f_raw);
 
             let _: () = msg_send![self.panel, setAllowedFileTypes: array];
         }
     }
 
+    /// Filters through `UTType`s with `setAllowedContentTypes:`, which replaced `setAllowedFileTypes:` in macOS 11.
+    /// Returns `false` on older versions, they have neither.
+    ///
+    /// `UTType` also covers types without an extension, through their MIME type.
+    fn add_content_types(&self, params: &FileDialog) -> bool {
+        let ut_type = match Class::get("UTType") {
+            Some(ut_type) => ut_type,
+            None => return false,
+        };
+
+        unsafe {
+            let supported: BOOL =
+                msg_send![self.panel, respondsToSelector: sel!(setAllowedContentTypes:)];
+            if supported == NO {
+                return false;
+            }
+
+            let mut types: Vec<*mut Object> = Vec::new();
+            for filter in params.filters.iter() {
+                for ext in filter.plain_extensions() {
+                    let ext = make_nsstring(&ext);
+                    types.push(msg_send![ut_type, typeWithFilenameExtension: &*ext]);
+                }
+                for mime in filter.mime_types.iter() {
+                    let mime = make_nsstring(mime);
+                    types.push(msg_send![ut_type, typeWithMIMEType: &*mime]);
+                }
+            }
+            // Unknown extensions and MIME types have no `UTType`
+            types.retain(|ty| !ty.is_null());
+
+            let array: *mut Object =
+                msg_send![class!(NSArray), arrayWithObjects: types.as_ptr() count: types.len()];
+            let _: () = msg_send![self.panel, setAllowedContentTypes: array];
+        }
+        true
+    }
+
     pub fn set_path(&self, path: &Path, file_name: Option<&str>) {
         // if file_name is some, and path is a dir
         let path = if let (Some(name), true) = (file_name, path.is_dir()) {
@@ -123,6 +235,138 @@ impl Panel {
         }
     }
 
+    pub fn set_message(&self, message: &str) {
+        unsafe {
+            let message = make_nsstring(message);
+            let () = msg_send![self.panel, setMessage: message];
+        }
+    }
+
+    pub fn set_prompt(&self, prompt: &str) {
+        unsafe {
+            let prompt = make_nsstring(prompt);
+            let () = msg_send![self.panel, setPrompt: prompt];
+        }
+    }
+
+    pub fn set_name_field_label(&self, label: &str) {
+        unsafe {
+            let label = make_nsstring(label);
+            let () = msg_send![self.panel, setNameFieldLabel: label];
+        }
+    }
+
+    pub fn set_shows_hidden_files(&self, v: BOOL) {
+        let _: () = unsafe { msg_send![self.panel, setShowsHiddenFiles: v] };
+    }
+
+    pub fn set_can_create_directories(&self, v: BOOL) {
+        let _: () = unsafe { msg_send![self.panel, setCanCreateDirectories: v] };
+    }
+
+    pub fn set_treats_file_packages_as_directories(&self, v: BOOL) {
+        let _: () = unsafe { msg_send![self.panel, setTreatsFilePackagesAsDirectories: v] };
+    }
+
+    /// Keeps the panel above other windows, including fullscreen ones, or at the level of regular panels
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        unsafe {
+            if always_on_top {
+                let () = msg_send![self.panel, setLevel: CGShieldingWindowLevel()];
+                let behavior: usize = msg_send![self.panel, collectionBehavior];
+                let () = msg_send![self.panel, setCollectionBehavior: behavior
+                    | NS_WINDOW_COLLECTION_BEHAVIOR_FULL_SCREEN_AUXILIARY];
+            } else {
+                let () = msg_send![self.panel, setLevel: NS_MODAL_PANEL_WINDOW_LEVEL];
+            }
+        }
+    }
+
+    /// Moves the panel to `position`, `parent` is the `NSWindow` to center on or null,
+    /// centered panels without parent go to the screen `monitor` points to
+    pub fn set_position(
+        &self,
+        position: Position,
+        monitor: Option<MonitorSelection>,
+        parent: *mut Object,
+    ) {
+        unsafe {
+            let frame: NSRect = msg_send![self.panel, frame];
+            let origin = match position {
+                Position::At(x, y) => {
+                    // Cocoa counts from the bottom left of the primary screen
+                    let screens: *mut Object = msg_send![class!(NSScreen), screens];
+                    let primary: *mut Object = msg_send![screens, firstObject];
+                    if primary.is_null() {
+                        return;
+                    }
+                    let screen: NSRect = msg_send![primary, frame];
+                    NSPoint {
+                        x: x as f64,
+                        y: screen.height - y as f64 - frame.height,
+                    }
+                }
+                Position::CenterOnParent if !parent.is_null() => {
+                    let area: NSRect = msg_send![parent, frame];
+                    NSPoint {
+                        x: area.x + (area.width - frame.width) / 2.0,
+                        y: area.y + (area.height - frame.height) / 2.0,
+                    }
+                }
+                Position::CenterOnParent | Position::CenterOnScreen => {
+                    let screen = monitor.map_or(ptr::null_mut(), |monitor| screen(monitor));
+                    if screen.is_null() {
+                        let () = msg_send![self.panel, center];
+                        return;
+                    }
+                    let area: NSRect = msg_send![screen, visibleFrame];
+                    NSPoint {
+                        x: area.x + (area.width - frame.width) / 2.0,
+                        y: area.y + (area.height - frame.height) / 2.0,
+                    }
+                }
+            };
+            let () = msg_send![self.panel, setFrameOrigin: origin];
+        }
+    }
+
+    pub fn set_accessory_view(&self, view: *mut Object) {
+        unsafe {
+            let () = msg_send![self.panel, setAccessoryView: view];
+            // Open panels hide it behind a disclosure button otherwise
+            let is_open_panel: BOOL = msg_send![self.panel, isKindOfClass: class!(NSOpenPanel)];
+            if is_open_panel == YES {
+                let () = msg_send![self.panel, setAccessoryViewDisclosed: YES];
+            }
+        }
+    }
+
+    /// Applies the options every kind of panel shares
+    pub fn set_options(&mut self, opt: &FileDialog) {
+        if let Some(show_hidden) = opt.show_hidden {
+            self.set_shows_hidden_files(if show_hidden { YES } else { NO });
+        }
+
+        if let Some(can) = opt.can_create_directories {
+            self.set_can_create_directories(if can { YES } else { NO });
+        }
+
+        if let Some(preview) = &opt.preview {
+            self.preview = Some(PreviewPane::new(&*self.panel as *const _ as _, &preview.0));
+        }
+
+        let accessory = opt.accessory_view.as_ref().map(|view| view.as_ptr());
+        match (accessory, &self.preview) {
+            (Some(view), None) => self.set_accessory_view(view),
+            (None, Some(preview)) => self.set_accessory_view(preview.view()),
+            // Preview below the application's view
+            (Some(view), Some(preview)) => unsafe {
+                self.set_accessory_view(column(&[view, preview.view()]))
+            },
+            (None, None) => {}
+        }
+    }
+
     pub fn get_result(&self) -> PathBuf {
         unsafe {
             let url = msg_send![self.panel, URL];
@@ -148,7 +392,7 @@ impl Panel {
 
 impl Panel {
     pub fn build_pick_file(opt: &FileDialog) -> Self {
-        let panel = Panel::open_panel();
+        let mut panel = Panel::open_panel();
 
         if !opt.filters.is_empty() {
             panel.add_filters(&opt);
@@ -166,6 +410,28 @@ impl Panel {
             panel.set_title(title);
         }
 
+        if let Some(message) = &opt.description {
+            panel.set_message(message);
+        }
+
+        if let Some(prompt) = &opt.accept_label {
+            panel.set_prompt(prompt);
+        }
+
+        panel.set_options(opt);
+
+        if let Some(treat) = opt.treat_packages_as_directories {
+            panel.set_treats_file_packages_as_directories(if treat { YES } else { NO });
+        }
+
+        if let Some((position, monitor)) = opt.placement() {
+            panel.set_position(position, monitor, parent_window(opt));
+        }
+
+        if let Some(always_on_top) = opt.always_on_top {
+            panel.set_always_on_top(always_on_top);
+        }
+
         panel.set_can_choose_directories(NO);
         panel.set_can_choose_files(YES);
 
@@ -173,7 +439,7 @@ impl Panel {
     }
 
     pub fn build_save_file(opt: &FileDialog) -> Self {
-        let panel = Panel::save_panel();
+        let mut panel = Panel::save_panel();
 
         if !opt.filters.is_empty() {
             panel.add_filters(&opt);
@@ -187,15 +453,41 @@ impl Panel {
             panel.set_file_name(file_name);
         }
 
+        if let Some(label) = &opt.file_name_label {
+            panel.set_name_field_label(label);
+        }
+
         if let Some(title) = &opt.title {
             panel.set_title(title);
         }
 
+        if let Some(message) = &opt.description {
+            panel.set_message(message);
+        }
+
+        if let Some(prompt) = &opt.accept_label {
+            panel.set_prompt(prompt);
+        }
+
+        panel.set_options(opt);
+
+        if let Some(treat) = opt.treat_packages_as_directories {
+            panel.set_treats_file_packages_as_directories(if treat { YES } else { NO });
+        }
+
+        if let Some((position, monitor)) = opt.placement() {
+            panel.set_position(position, monitor, parent_window(opt));
+        }
+
+        if let Some(always_on_top) = opt.always_on_top {
+            panel.set_always_on_top(always_on_top);
+        }
+
         panel
     }
 
     pub fn build_pick_folder(opt: &FileDialog) -> Self {
-        let panel = Panel::open_panel();
+        let mut panel = Panel::open_panel();
 
         if let Some(path) = &opt.starting_directory {
             panel.set_path(path, opt.file_name.as_deref());
@@ -205,14 +497,119 @@ impl Panel {
             panel.set_title(title);
         }
 
+        if let Some(message) = &opt.description {
+            panel.set_message(message);
+        }
+
+        if let Some(prompt) = &opt.accept_label {
+            panel.set_prompt(prompt);
+        }
+
+        panel.set_options(opt);
+
+        if let Some(treat) = opt.treat_packages_as_directories {
+            panel.set_treats_file_packages_as_directories(if treat { YES } else { NO });
+        }
+
+        if let Some((position, monitor)) = opt.placement() {
+            panel.set_position(position, monitor, parent_window(opt));
+        }
+
+        if let Some(always_on_top) = opt.always_on_top {
+            panel.set_always_on_top(always_on_top);
+        }
+
         panel.set_can_choose_directories(YES);
         panel.set_can_choose_files(NO);
 
         panel
     }
 
+    pub fn build_pick_folders(opt: &FileDialog) -> Self {
+        let mut panel = Panel::open_panel();
+
+        if let Some(path) = &opt.starting_directory {
+            panel.set_path(path, opt.file_name.as_deref());
+        }
+
+        if let Some(title) = &opt.title {
+            panel.set_title(title);
+        }
+
+        if let Some(message) = &opt.description {
+            panel.set_message(message);
+        }
+
+        if let Some(prompt) = &opt.accept_label {
+            panel.set_prompt(prompt);
+        }
+
+        panel.set_options(opt);
+
+        if let Some(treat) = opt.treat_packages_as_directories {
+            panel.set_treats_file_packages_as_directories(if treat { YES } else { NO });
+        }
+
+        if let Some((position, monitor)) = opt.placement() {
+            panel.set_position(position, monitor, parent_window(opt));
+        }
+
+        if let Some(always_on_top) = opt.always_on_top {
+            panel.set_always_on_top(always_on_top);
+        }
+
+        panel.set_can_choose_directories(YES);
+        panel.set_can_choose_files(NO);
+        panel.set_allows_multiple_selection(YES);
+
+        panel
+    }
+
+    pub fn build_pick_path(opt: &FileDialog) -> Self {
+        let mut panel = Panel::open_panel();
+
+        if !opt.filters.is_empty() {
+            panel.add_filters(&opt);
+        }
+
+        if let Some(path) = &opt.starting_directory {
+            panel.set_path(path, opt.file_name.as_deref());
+        }
+
+        if let Some(title) = &opt.title {
+            panel.set_title(title);
+        }
+
+        if let Some(message) = &opt.description {
+            panel.set_message(message);
+        }
+
+        if let Some(prompt) = &opt.accept_label {
+            panel.set_prompt(prompt);
+        }
+
+        panel.set_options(opt);
+
+        if let Some(treat) = opt.treat_packages_as_directories {
+            panel.set_treats_file_packages_as_directories(if treat { YES } else { NO });
+        }
+
+        if let Some((position, monitor)) = opt.placement() {
+            panel.set_position(position, monitor, parent_window(opt));
+        }
+
+        if let Some(always_on_top) = opt.always_on_top {
+            panel.set_always_on_top(always_on_top);
+        }
+
+        panel.set_can_choose_directories(YES);
+        panel.set_can_choose_files(YES);
+
+        panel
+    }
+
     pub fn build_pick_files(opt: &FileDialog) -> Self {
-        let panel = Panel::open_panel();
+        let mut panel = Panel::open_panel();
 
         if !opt.filters.is_empty() {
             panel.add_filters(&opt);
@@ -226,6 +623,28 @@ impl Panel {
             panel.set_title(title);
         }
 
+        if let Some(message) = &opt.description {
+            panel.set_message(message);
+        }
+
+        if let Some(prompt) = &opt.accept_label {
+            panel.set_prompt(prompt);
+        }
+
+        panel.set_options(opt);
+
+        if let Some(treat) = opt.treat_packages_as_directories {
+            panel.set_treats_file_packages_as_directories(if treat { YES } else { NO });
+        }
+
+        if let Some((position, monitor)) = opt.placement() {
+            panel.set_position(position, monitor, parent_window(opt));
+        }
+
+        if let Some(always_on_top) = opt.always_on_top {
+            panel.set_always_on_top(always_on_top);
+        }
+
         panel.set_can_choose_directories(NO);
         panel.set_can_choose_files(YES);
         panel.set_allows_multiple_selection(YES);
@@ -0,0 +1,194 @@
+//! Preview pane in the accessory view, refilled by a panel delegate whenever the selection changes
+
+use crate::{Preview, PreviewContent};
+
+use std::ffi::c_void;
+use std::ptr;
+use std::sync::{Arc, Once};
+
+use objc::declare::ClassDecl;
+use objc::runtime::{Class, Object, Sel, NO, YES};
+use objc::{class, msg_send, sel, sel_impl};
+use objc_foundation::{INSString, NSString};
+
+use super::super::utils::{NSSize, INSURL, NSURL};
+
+/// Longest side of preview images
+const PREVIEW_SIZE: f64 = 256.0;
+
+const NS_USER_INTERFACE_LAYOUT_ORIENTATION_VERTICAL: i64 = 1;
+const NS_IMAGE_SCALE_PROPORTIONALLY_DOWN: u64 = 0;
+
+struct Pane {
+    renderer: Arc<Preview>,
+    // Subviews of `PreviewPane::view`, they live as long as it does
+    image: *mut Object,
+    text: *mut Object,
+}
+
+/// Preview view with the delegate that fills it, the panel only holds a weak reference to the delegate
+pub struct PreviewPane {
+    panel: *mut Object,
+    view: *mut Object,
+    delegate: *mut Object,
+    pane: *mut Pane,
+}
+
+fn delegate_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        let mut decl = ClassDecl::new("RFDPreviewDelegate", class!(NSObject)).unwrap();
+        decl.add_ivar::<*mut c_void>("rfdPane");
+        unsafe {
+            decl.add_method(
+                sel!(panelSelectionDidChange:),
+                selection_changed as extern "C" fn(&Object, Sel, *mut Object),
+            );
+        }
+        decl.register();
+    });
+    class!(RFDPreviewDelegate)
+}
+
+extern "C" fn selection_changed(this: &Object, _: Sel, panel: *mut Object) {
+    unsafe {
+        let pane = *this.get_ivar::<*mut c_void>("rfdPane") as *const Pane;
+        if pane.is_null() {
+            return;
+        }
+        let pane = &*pane;
+
+        let url: *mut NSURL = msg_send![panel, URL];
+        let content = if url.is_null() {
+            PreviewContent::None
+        } else {
+            (pane.renderer)(&(*url).to_path_buf())
+        };
+        pane.show(content);
+    }
+}
+
+/// `NSImage` of `rgba`, scaled down to fit the pane, `None` when the pixels don't match the size
+unsafe fn ns_image(width: u32, height: u32, rgba: &[u8]) -> Option<*mut Object> {
+    if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+        return None;
+    }
+
+    let color_space = NSString::from_str("NSDeviceRGBColorSpace");
+    let planes: *mut *mut u8 = ptr::null_mut();
+    let rep: *mut Object = msg_send![class!(NSBitmapImageRep), alloc];
+    let rep: *mut Object = msg_send![rep,
+        initWithBitmapDataPlanes: planes
+        pixelsWide: width as i64
+        pixelsHigh: height as i64
+        bitsPerSample: 8i64
+        samplesPerPixel: 4i64
+        hasAlpha: YES
+        isPlanar: NO
+        colorSpaceName: &*color_space
+        bytesPerRow: width as i64 * 4
+        bitsPerPixel: 32i64];
+    if rep.is_null() {
+        return None;
+    }
+    let data: *mut u8 = msg_send![rep, bitmapData];
+    ptr::copy_nonoverlapping(rgba.as_ptr(), data, rgba.len());
+
+    let scale = (PREVIEW_SIZE / width.max(height) as f64).min(1.0);
+    let size = NSSize {
+        width: width as f64 * scale,
+        height: height as f64 * scale,
+    };
+    let image: *mut Object = msg_send![class!(NSImage), alloc];
+    let image: *mut Object = msg_send![image, initWithSize: size];
+    let () = msg_send![image, addRepresentation: rep];
+    let () = msg_send![rep, release];
+    Some(image)
+}
+
+/// Vertical `NSStackView` of `views`, autoreleased
+pub unsafe fn column(views: &[*mut Object]) -> *mut Object {
+    let views: *mut Object =
+        msg_send![class!(NSArray), arrayWithObjects: views.as_ptr() count: views.len()];
+    let stack: *mut Object = msg_send![class!(NSStackView), stackViewWithViews: views];
+    let () = msg_send![stack, setOrientation: NS_USER_INTERFACE_LAYOUT_ORIENTATION_VERTICAL];
+    stack
+}
+
+impl Pane {
+    unsafe fn show(&self, content: PreviewContent) {
+        let nil: *mut Object = ptr::null_mut();
+        let (text, image) = match content {
+            PreviewContent::Text(text) => (Some(text), None),
+            PreviewContent::Image {
+                width,
+                height,
+                rgba,
+            } => (None, ns_image(width, height, &rgba)),
+            PreviewContent::None => (None, None),
+        };
+
+        let string = NSString::from_str(text.as_deref().unwrap_or(""));
+        let () = msg_send![self.text, setStringValue: &*string];
+        let () = msg_send![self.text, setHidden: if text.is_some() { NO } else { YES }];
+
+        let () = msg_send![self.image, setImage: image.unwrap_or(nil)];
+        let () = msg_send![self.image, setHidden: if image.is_some() { NO } else { YES }];
+        if let Some(image) = image {
+            let () = msg_send![image, release];
+        }
+    }
+}
+
+impl PreviewPane {
+    /// Builds the pane, it stays empty until `panel` reports a selection
+    pub fn new(panel: *mut Object, renderer: &Arc<Preview>) -> Self {
+        unsafe {
+            let image: *mut Object = msg_send![class!(NSImageView), new];
+            let () = msg_send![image, setImageScaling: NS_IMAGE_SCALE_PROPORTIONALLY_DOWN];
+            let empty = NSString::from_str("");
+            let text: *mut Object =
+                msg_send![class!(NSTextField), wrappingLabelWithString: &*empty];
+            let () = msg_send![text, setPreferredMaxLayoutWidth: PREVIEW_SIZE];
+
+            let view: *mut Object = msg_send![column(&[image, text]), retain];
+            let () = msg_send![image, release];
+
+            let pane = Box::into_raw(Box::new(Pane {
+                renderer: renderer.clone(),
+                image,
+                text,
+            }));
+            (*pane).show(PreviewContent::None);
+
+            let delegate: *mut Object = msg_send![delegate_class(), new];
+            (*delegate).set_ivar::<*mut c_void>("rfdPane", pane as _);
+            let () = msg_send![panel, setDelegate: delegate];
+
+            Self {
+                panel,
+                view,
+                delegate,
+                pane,
+            }
+        }
+    }
+
+    pub fn view(&self) -> *mut Object {
+        self.view
+    }
+}
+
+impl Drop for PreviewPane {
+    fn drop(&mut self) {
+        unsafe {
+            let nil: *mut Object = ptr::null_mut();
+            let () = msg_send![self.panel, setDelegate: nil];
+            // The delegate may outlive this in an autorelease pool, it must not reach the freed pane
+            (*self.delegate).set_ivar::<*mut c_void>("rfdPane", ptr::null_mut());
+            let () = msg_send![self.delegate, release];
+            let () = msg_send![self.view, release];
+            drop(Box::from_raw(self.pane));
+        }
+    }
+}
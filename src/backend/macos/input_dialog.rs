@@ -0,0 +1,170 @@
+use std::ffi::CStr;
+use std::ops::DerefMut;
+use std::os::raw::c_char;
+
+use crate::backend::DialogFutureType;
+use crate::localizer::localize;
+use crate::{BuiltinString, DialogHandle, Error, InputDialog};
+
+use super::{
+    modal_future::ModalFuture,
+    utils::{or_cancelled, run_on_main, FocusManager, PolicyManager},
+    AsModal,
+};
+
+use super::utils::{INSWindow, NSRect, NSWindow};
+use objc::runtime::{Object, NO, YES};
+use objc::{class, msg_send, sel, sel_impl};
+use objc_foundation::{INSString, NSString};
+
+use objc_id::Id;
+
+// NSAlertFirstButtonReturn
+const FIRST_BUTTON: i64 = 1000;
+
+/// `NSAlert` with a text field as its accessory view
+pub struct NSInputAlert {
+    alert: Id<Object>,
+    /// `NSTextField`, or the `NSTextView` of a multi-line alert
+    field: Id<Object>,
+    multiline: bool,
+    _focus_manager: FocusManager,
+    _policy_manager: PolicyManager,
+}
+
+impl NSInputAlert {
+    pub fn new(opt: InputDialog) -> Self {
+        let _policy_manager = PolicyManager::new();
+
+        let alert: *mut Object = unsafe { msg_send![class!(NSAlert), new] };
+
+        unsafe {
+            for label in [BuiltinString::Ok, BuiltinString::Cancel] {
+                let label = NSString::from_str(&localize(label));
+                let _: () = msg_send![alert, addButtonWithTitle: label];
+            }
+
+            let text = NSString::from_str(&opt.title);
+            let _: () = msg_send![alert, setMessageText: text];
+            let text = NSString::from_str(&opt.prompt);
+            let _: () = msg_send![alert, setInformativeText: text];
+        }
+
+        let field: *mut Object = if opt.multiline {
+            let (width, height) = opt.size_hint();
+            unsafe { add_text_view(alert, &opt.default, width as f64, height as f64) }
+        } else {
+            unsafe { add_text_field(alert, &opt) }
+        };
+
+        let _focus_manager = FocusManager::new();
+
+        Self {
+            alert: unsafe { Id::from_retained_ptr(alert) },
+            field: unsafe { Id::from_retained_ptr(field) },
+            multiline: opt.multiline,
+            _focus_manager,
+            _policy_manager,
+        }
+    }
+
+    pub fn run(self) -> Option<String> {
+        let ret: i64 = unsafe { msg_send![self.alert, runModal] };
+        self.text(ret)
+    }
+
+    /// Content of the text field, when the alert was closed with OK
+    fn text(&self, res_id: i64) -> Option<String> {
+        if res_id != FIRST_BUTTON {
+            return None;
+        }
+
+        unsafe {
+            let value: *mut Object = if self.multiline {
+                msg_send![self.field, string]
+            } else {
+                msg_send![self.field, stringValue]
+            };
+            let value: *const c_char = msg_send![value, UTF8String];
+            Some(CStr::from_ptr(value).to_string_lossy().into_owned())
+        }
+    }
+}
+
+/// Single line text field as the accessory view of `alert`
+unsafe fn add_text_field(alert: *mut Object, opt: &InputDialog) -> *mut Object {
+    let frame = NSRect::new(0.0, 0.0, 240.0, 24.0);
+    // Secure fields mask the text and keep it off the pasteboard
+    let field: *mut Object = if opt.password {
+        msg_send![class!(NSSecureTextField), alloc]
+    } else {
+        msg_send![class!(NSTextField), alloc]
+    };
+    let field: *mut Object = msg_send![field, initWithFrame: frame];
+
+    let text = NSString::from_str(&opt.default);
+    let _: () = msg_send![field, setStringValue: text];
+
+    let _: () = msg_send![alert, setAccessoryView: field];
+    let window: *mut Object = msg_send![alert, window];
+    let _: () = msg_send![window, setInitialFirstResponder: field];
+    field
+}
+
+/// Scrolling `NSTextView` as the accessory view of `alert`, Return inserts a line break
+unsafe fn add_text_view(alert: *mut Object, text: &str, width: f64, height: f64) -> *mut Object {
+    let frame = NSRect::new(0.0, 0.0, width, height);
+
+    let scroll: *mut Object = msg_send![class!(NSScrollView), alloc];
+    let scroll: *mut Object = msg_send![scroll, initWithFrame: frame];
+    let _: () = msg_send![scroll, setHasVerticalScroller: YES];
+    // NSBezelBorder
+    let _: () = msg_send![scroll, setBorderType: 2u64];
+
+    let view: *mut Object = msg_send![class!(NSTextView), alloc];
+    let view: *mut Object = msg_send![view, initWithFrame: frame];
+    let _: () = msg_send![view, setRichText: NO];
+    // Grows downwards with the text and follows the width of the scroll view
+    let _: () = msg_send![view, setVerticallyResizable: YES];
+    // NSViewWidthSizable
+    let _: () = msg_send![view, setAutoresizingMask: 2u64];
+    let text = NSString::from_str(text);
+    let _: () = msg_send![view, setString: text];
+
+    let _: () = msg_send![scroll, setDocumentView: view];
+    let _: () = msg_send![alert, setAccessoryView: scroll];
+    let _: () = msg_send![scroll, release];
+
+    let window: *mut Object = msg_send![alert, window];
+    let _: () = msg_send![window, setInitialFirstResponder: view];
+    view
+}
+
+impl AsModal for NSInputAlert {
+    fn modal_ptr(&mut self) -> *mut Object {
+        self.alert.deref_mut()
+    }
+}
+
+pub fn show(opt: InputDialog) -> Option<String> {
+    or_cancelled(try_show(opt))
+}
+
+pub fn try_show(opt: InputDialog) -> Result<Option<String>, Error> {
+    objc::rc::autoreleasepool(move || run_on_main(move || NSInputAlert::new(opt).run()))
+}
+
+pub fn show_async(opt: InputDialog) -> DialogFutureType<Option<String>> {
+    #[cfg(feature = "parent")]
+    let win = opt.parent().as_ref().map(NSWindow::from_raw_window_handle);
+    #[cfg(not(feature = "parent"))]
+    let win = None;
+
+    let future = ModalFuture::new(
+        DialogHandle::default(),
+        win,
+        move || NSInputAlert::new(opt),
+        |alert, res_id| alert.text(res_id),
+    );
+    Box::pin(future)
+}
@@ -1,17 +1,19 @@
 use std::ops::DerefMut;
 
 use crate::backend::DialogFutureType;
-use crate::dialog::{MessageButtons, MessageDialog, MessageLevel};
+use crate::dialog::{MessageDialog, MessageLevel};
+use crate::dialog_links::text_parts;
+use crate::{DialogChoices, DialogHandle, Error, Modality};
 
 use super::{
-    modal_future::ModalFuture,
-    utils::{run_on_main, FocusManager, PolicyManager},
+    modal_future::{self, ModalFuture},
+    utils::{or_cancelled, run_on_main, FocusManager, PolicyManager},
     AsModal,
 };
 
 use super::utils::{INSWindow, NSWindow};
-use objc::runtime::Object;
-use objc::{class, msg_send, sel, sel_impl};
+use objc::runtime::{Object, YES};
+use objc::{class, msg_send, sel, sel_impl, Encode, Encoding};
 use objc_foundation::{INSString, NSString};
 
 use objc_id::Id;
@@ -32,8 +34,35 @@ enum NSAlertReturn {
     // ThirdButton = 1002,
 }
 
+#[repr(C)]
+struct NSRange {
+    location: usize,
+    length: usize,
+}
+
+unsafe impl Encode for NSRange {
+    fn encode() -> Encoding {
+        unsafe { Encoding::from_str("{_NSRange=QQ}") }
+    }
+}
+
+#[repr(C)]
+struct NSSize {
+    width: f64,
+    height: f64,
+}
+
+unsafe impl Encode for NSSize {
+    fn encode() -> Encoding {
+        unsafe { Encoding::from_str("{CGSize=dd}") }
+    }
+}
+
 pub struct NSAlert {
     alert: Id<Object>,
+    labels: Vec<String>,
+    suppression: bool,
+    choices: DialogChoices,
     _focus_manager: FocusManager,
     _policy_manager: PolicyManager,
 }
@@ -48,51 +77,143 @@ impl NSAlert {
             MessageLevel::Info => NSAlertStyle::Informational,
             MessageLevel::Warning => NSAlertStyle::Warning,
             MessageLevel::Error => NSAlertStyle::Critical,
+            // There is no question style, the alert shows the application icon
+            MessageLevel::Question => NSAlertStyle::Informational,
         };
 
         unsafe {
             let _: () = msg_send![alert, setAlertStyle: level as i64];
         }
 
-        match opt.buttons {
-            MessageButtons::Ok => unsafe {
-                let label = NSString::from_str("OK");
-                let _: () = msg_send![alert, addButtonWithTitle: label];
-            },
-            MessageButtons::OkCancel => unsafe {
-                let label = NSString::from_str("OK");
-                let _: () = msg_send![alert, addButtonWithTitle: label];
-                let label = NSString::from_str("Cancel");
+        // The custom labels, or the translated labels of the `MessageButtons`
+        let labels = opt.button_labels();
+        for label in &opt.shown_button_labels() {
+            unsafe {
+                let label = NSString::from_str(label);
                 let _: () = msg_send![alert, addButtonWithTitle: label];
-            },
-            MessageButtons::YesNo => unsafe {
-                let label = NSString::from_str("Yes");
-                let _: () = msg_send![alert, addButtonWithTitle: label];
-                let label = NSString::from_str("No");
-                let _: () = msg_send![alert, addButtonWithTitle: label];
-            },
+            }
+        }
+
+        // The first button gets the Return key, it is moved over to the default one
+        if opt.default_button > 0 && opt.default_button < labels.len() {
+            unsafe {
+                let buttons: *mut Object = msg_send![alert, buttons];
+                let first: *mut Object = msg_send![buttons, objectAtIndex: 0usize];
+                let default: *mut Object = msg_send![buttons, objectAtIndex: opt.default_button];
+                let key = NSString::from_str("");
+                let _: () = msg_send![first, setKeyEquivalent: key];
+                let key = NSString::from_str("\r");
+                let _: () = msg_send![default, setKeyEquivalent: key];
+            }
         }
 
         unsafe {
             let text = NSString::from_str(&opt.title);
             let _: () = msg_send![alert, setMessageText: text];
-            let text = NSString::from_str(&opt.description);
-            let _: () = msg_send![alert, setInformativeText: text];
+            if opt.hyperlinks {
+                set_link_text(alert, &opt.description);
+            } else {
+                let text = NSString::from_str(&opt.description);
+                let _: () = msg_send![alert, setInformativeText: text];
+            }
+        }
+
+        if let Some(label) = &opt.suppression {
+            unsafe {
+                let _: () = msg_send![alert, setShowsSuppressionButton: YES];
+                let button: *mut Object = msg_send![alert, suppressionButton];
+                let label = NSString::from_str(label);
+                let _: () = msg_send![button, setTitle: label];
+            }
         }
 
         let _focus_manager = FocusManager::new();
 
         Self {
             alert: unsafe { Id::from_retained_ptr(alert) },
+            labels,
+            suppression: opt.suppression.is_some(),
+            choices: opt.choices,
             _focus_manager,
             _policy_manager,
         }
     }
 
-    pub fn run(self) -> bool {
-        let ret: i64 = unsafe { msg_send![self.alert, runModal] };
+    /// Shows the alert, as a sheet of `win` when there is one
+    pub fn run(mut self, win: Option<Id<NSWindow>>) -> bool {
+        let ret = modal_future::run_modal(&mut self, win);
+        self.store_choices();
         ret == NSAlertReturn::FirstButton as i64
     }
+
+    pub fn run_custom(mut self, win: Option<Id<NSWindow>>) -> Option<String> {
+        let ret = modal_future::run_modal(&mut self, win);
+        self.store_choices();
+        label(&self.labels, ret)
+    }
+
+    /// Records the state of the suppression button
+    fn store_choices(&self) {
+        if self.suppression {
+            let state: i64 = unsafe {
+                let button: *mut Object = msg_send![self.alert, suppressionButton];
+                msg_send![button, state]
+            };
+            // NSControlStateValueOn
+            self.choices.set_suppressed(state == 1);
+        }
+    }
+}
+
+/// Shows `description` with clickable links in a label below the message,
+/// the informative text of an alert can't hold links
+unsafe fn set_link_text(alert: *mut Object, description: &str) {
+    let parts = text_parts(description);
+    let plain: String = parts.iter().map(|part| part.text).collect();
+
+    let string = NSString::from_str(&plain);
+    let text: *mut Object = msg_send![class!(NSMutableAttributedString), alloc];
+    let text: *mut Object = msg_send![text, initWithString: string];
+
+    // Ranges count UTF-16 code units
+    let mut location = 0;
+    for part in &parts {
+        let length = part.text.encode_utf16().count();
+        if let Some(href) = part.href {
+            let href = NSString::from_str(href);
+            let url: *mut Object = msg_send![class!(NSURL), URLWithString: href];
+            if !url.is_null() {
+                // NSLinkAttributeName
+                let name = NSString::from_str("NSLink");
+                let range = NSRange { location, length };
+                let _: () = msg_send![text, addAttribute: name value: url range: range];
+            }
+        }
+        location += length;
+    }
+
+    let label: *mut Object = msg_send![class!(NSTextField), labelWithAttributedString: text];
+    let _: () = msg_send![text, release];
+
+    // Links can only be clicked in selectable fields that keep their attributes
+    let _: () = msg_send![label, setSelectable: YES];
+    let _: () = msg_send![label, setAllowsEditingTextAttributes: YES];
+    // NSLineBreakByWordWrapping
+    let _: () = msg_send![label, setLineBreakMode: 0u64];
+    let _: () = msg_send![label, setPreferredMaxLayoutWidth: 300.0f64];
+    let size: NSSize = msg_send![label, fittingSize];
+    let _: () = msg_send![label, setFrameSize: size];
+
+    let _: () = msg_send![alert, setAccessoryView: label];
+}
+
+/// Buttons return `FirstButton` counting up in the order they were added
+fn label(labels: &[String], res_id: i64) -> Option<String> {
+    let index = res_id - NSAlertReturn::FirstButton as i64;
+    if index < 0 {
+        return None;
+    }
+    labels.get(index as usize).cloned()
 }
 
 impl AsModal for NSAlert {
@@ -101,27 +222,68 @@ impl AsModal for NSAlert {
     }
 }
 
-use crate::backend::MessageDialogImpl;
-impl MessageDialogImpl for MessageDialog {
-    fn show(self) -> bool {
-        objc::rc::autoreleasepool(move || run_on_main(move || NSAlert::new(self).run()))
-    }
+/// Window the alert is attached to as a sheet, only window modal alerts have one
+#[cfg_attr(not(feature = "parent"), allow(unused_variables))]
+fn parent(opt: &MessageDialog) -> Option<Id<NSWindow>> {
+    #[cfg(feature = "parent")]
+    let win = opt.parent().as_ref().map(NSWindow::from_raw_window_handle);
+    #[cfg(not(feature = "parent"))]
+    let win = None;
+
+    win.filter(|_| opt.modality == Modality::WindowModal)
+}
+
+pub fn show(opt: MessageDialog) -> bool {
+    objc::rc::autoreleasepool(move || {
+        or_cancelled(run_on_main(move || {
+            let win = parent(&opt);
+            NSAlert::new(opt).run(win)
+        }))
+    })
 }
 
-use crate::backend::AsyncMessageDialogImpl;
+pub fn show_async(opt: MessageDialog) -> DialogFutureType<bool> {
+    let win = parent(&opt);
 
-impl AsyncMessageDialogImpl for MessageDialog {
-    fn show_async(self) -> DialogFutureType<bool> {
-        #[cfg(feature = "parent")]
-        let win = self.parent.as_ref().map(NSWindow::from_raw_window_handle);
-        #[cfg(not(feature = "parent"))]
-        let win = None;
+    let future = ModalFuture::with_modality(
+        DialogHandle::default(),
+        win,
+        opt.modality,
+        move || NSAlert::new(opt),
+        |alert, res_id| {
+            alert.store_choices();
+            res_id == NSAlertReturn::FirstButton as i64
+        },
+    );
+    Box::pin(future)
+}
 
-        let future = ModalFuture::new(
-            win,
-            move || NSAlert::new(self),
-            |_, res_id| res_id == NSAlertReturn::FirstButton as i64,
-        );
-        Box::pin(future)
-    }
+pub fn show_custom(opt: MessageDialog) -> Option<String> {
+    or_cancelled(try_show_custom(opt))
+}
+
+pub fn try_show_custom(opt: MessageDialog) -> Result<Option<String>, Error> {
+    objc::rc::autoreleasepool(move || {
+        run_on_main(move || {
+            let win = parent(&opt);
+            NSAlert::new(opt).run_custom(win)
+        })
+    })
+}
+
+pub fn show_custom_async(opt: MessageDialog) -> DialogFutureType<Option<String>> {
+    let win = parent(&opt);
+
+    let labels = opt.button_labels();
+    let future = ModalFuture::with_modality(
+        DialogHandle::default(),
+        win,
+        opt.modality,
+        move || NSAlert::new(opt),
+        move |alert, res_id| {
+            alert.store_choices();
+            label(&labels, res_id)
+        },
+    );
+    Box::pin(future)
 }
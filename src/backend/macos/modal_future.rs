@@ -1,16 +1,19 @@
-use objc::{msg_send, sel, sel_impl};
+use objc::runtime::{Object, BOOL, NO};
+use objc::{class, msg_send, sel, sel_impl};
 use objc_id::Id;
 
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
-use std::{mem, pin::Pin};
 
 use std::task::{Context, Poll, Waker};
 
 use super::AsModal;
 
 use super::utils::{
-    activate_cocoa_multithreading, is_main_thread, INSApplication, NSApplication, NSWindow,
+    activate_cocoa_multithreading, is_main_thread, run_on_main, INSApplication, NSApplication,
+    NSWindow,
 };
+use crate::{DialogHandle, Modality};
 
 struct FutureState<R, D> {
     waker: Option<Waker>,
@@ -27,11 +30,28 @@ pub(super) struct ModalFuture<R, D> {
 unsafe impl<R, D> Send for ModalFuture<R, D> {}
 
 impl<R: 'static + Default, D: AsModal + 'static> ModalFuture<R, D> {
-    pub fn new<F, DBULD: FnOnce() -> D + Send>(
+    pub fn new<F, DBULD: FnOnce() -> D + Send + 'static>(
+        handle: DialogHandle,
         win: Option<Id<NSWindow>>,
         build_modal: DBULD,
         cb: F,
     ) -> Self
+    where
+        F: Fn(&mut D, i64) -> R + Send + 'static,
+    {
+        Self::with_modality(handle, win, Modality::WindowModal, build_modal, cb)
+    }
+
+    /// Window modal dialogs become sheets of `win`, or of the key window of the app,
+    /// application modal ones run their own modal session and ones that block nothing
+    /// are shown with `beginWithCompletionHandler:` when the modal supports it
+    pub fn with_modality<F, DBULD: FnOnce() -> D + Send + 'static>(
+        handle: DialogHandle,
+        win: Option<Id<NSWindow>>,
+        modality: Modality,
+        build_modal: DBULD,
+        cb: F,
+    ) -> Self
     where
         F: Fn(&mut D, i64) -> R + Send + 'static,
     {
@@ -58,15 +78,16 @@ impl<R: 'static + Default, D: AsModal + 'static> ModalFuture<R, D> {
 
         let app = NSApplication::shared_application();
 
-        let win = if let Some(win) = win {
-            Some(win.share())
-        } else {
-            app.get_window()
+        let win = match (modality, win) {
+            (Modality::WindowModal, Some(win)) => Some(win.share()),
+            (Modality::WindowModal, None) => app.get_window(),
+            _ => None,
         };
 
-        // if async exec is possible start sheet modal
+        // if async exec is possible start sheet modal, or an app modal one without a window,
         // otherwise fallback to sync
-        if app.is_running() && win.is_some() {
+        let sheet = win.is_some();
+        if app.is_running() {
             let state = state.clone();
             let main_runner = move || {
                 let completion = {
@@ -75,30 +96,59 @@ impl<R: 'static + Default, D: AsModal + 'static> ModalFuture<R, D> {
                         dialog_callback(state.clone(), result);
                     })
                 };
-
-                let window = win.unwrap();
+                let completion = completion.copy();
 
                 let mut modal = build_modal();
                 let modal_ptr = modal.modal_ptr();
 
                 state.lock().unwrap().modal = Some(modal);
 
-                let _: () = unsafe {
-                    msg_send![
-                        modal_ptr,
-                        beginSheetModalForWindow: window completionHandler: &completion
-                    ]
+                if let Some(window) = win {
+                    let _: () = unsafe {
+                        msg_send![
+                            modal_ptr,
+                            beginSheetModalForWindow: window completionHandler: &*completion
+                        ]
+                    };
+                    return;
+                }
+
+                let modeless: BOOL = unsafe {
+                    msg_send![modal_ptr, respondsToSelector: sel!(beginWithCompletionHandler:)]
                 };
-
-                mem::forget(completion);
+                if modality == Modality::None && modeless != NO {
+                    let _: () =
+                        unsafe { msg_send![modal_ptr, beginWithCompletionHandler: &*completion] };
+                } else {
+                    // Alerts can only be sheets or run their own session
+                    let ret: i64 = unsafe { msg_send![modal_ptr, runModal] };
+                    unsafe { completion.call((ret,)) };
+                }
             };
 
-            if !is_main_thread() {
-                let main = dispatch::Queue::main();
-                main.exec_sync(main_runner);
+            if sheet {
+                if !is_main_thread() {
+                    let main = dispatch::Queue::main();
+                    main.exec_sync(main_runner);
+                } else {
+                    main_runner();
+                }
             } else {
-                main_runner();
+                // `runModal` blocks until the dialog is closed, the caller gets the future first
+                dispatch::Queue::main().exec_async(main_runner);
             }
+
+            // `cancel:` ends the sheet with a cancel response, which calls the completion handler as usual
+            let state = state.clone();
+            handle.on_close(move || {
+                let _ = run_on_main(move || {
+                    let modal_ptr = state.lock().unwrap().modal.as_mut().map(D::modal_ptr);
+                    if let Some(modal_ptr) = modal_ptr {
+                        let nil: *mut Object = std::ptr::null_mut();
+                        let _: () = unsafe { msg_send![modal_ptr, cancel: nil] };
+                    }
+                });
+            });
         } else {
             eprintln!("\n Hi! It looks like you are running async dialog in unsupported environment, I will fallback to sync dialog for you. \n");
 
@@ -112,7 +162,13 @@ impl<R: 'static + Default, D: AsModal + 'static> ModalFuture<R, D> {
 
                 dialog_callback(state.clone(), ret);
             } else {
-                panic!("Fallback Sync Dialog Must Be Spawned On Main Thread (Why? If async dialog is unsupported in this env, it also means that spawning dialogs outside of main thread is also inpossible");
+                // Without an event loop on the main thread there is no way to get there,
+                // file dialogs already failed with `Error::NotMainThread` before
+                trace_warn!(
+                    error = %crate::Error::NotMainThread,
+                    "dialog counted as cancelled"
+                );
+                dialog_callback(state.clone(), 0);
             }
         }
 
@@ -120,6 +176,32 @@ impl<R: 'static + Default, D: AsModal + 'static> ModalFuture<R, D> {
     }
 }
 
+/// Blocks until `modal` is closed, attached as a sheet to `win` when there is one
+///
+/// The sheet only gets events while `runModalForWindow:` runs,
+/// its completion handler stops that session with the response.
+pub(super) fn run_modal<D: AsModal>(modal: &mut D, win: Option<Id<NSWindow>>) -> i64 {
+    let modal_ptr = modal.modal_ptr();
+    let window = match win {
+        Some(window) => window,
+        None => return unsafe { msg_send![modal_ptr, runModal] },
+    };
+
+    let completion = block::ConcreteBlock::new(move |result: i64| {
+        let app: *mut Object = unsafe { msg_send![class!(NSApplication), sharedApplication] };
+        let _: () = unsafe { msg_send![app, stopModalWithCode: result] };
+    });
+
+    unsafe {
+        let _: () = msg_send![
+            modal_ptr,
+            beginSheetModalForWindow: window completionHandler: &completion
+        ];
+        let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+        msg_send![app, runModalForWindow: modal.modal_window()]
+    }
+}
+
 impl<R, D> std::future::Future for ModalFuture<R, D> {
     type Output = R;
 
@@ -0,0 +1,35 @@
+use crate::{Error, Notification};
+
+use super::utils::run_on_main;
+
+use objc::runtime::Object;
+use objc::{class, msg_send, sel, sel_impl};
+use objc_foundation::{INSString, NSString};
+
+pub fn show(opt: Notification) -> Result<(), Error> {
+    objc::rc::autoreleasepool(move || {
+        run_on_main(move || unsafe {
+            let center: *mut Object = msg_send![
+                class!(NSUserNotificationCenter),
+                defaultUserNotificationCenter
+            ];
+            // Apps run outside of a bundle have no identifier to post notifications for
+            if center.is_null() {
+                return Err(Error::Unsupported);
+            }
+
+            let notification: *mut Object = msg_send![class!(NSUserNotification), new];
+            let title = NSString::from_str(&opt.title);
+            let _: () = msg_send![notification, setTitle: title];
+            if !opt.body.is_empty() {
+                let body = NSString::from_str(&opt.body);
+                let _: () = msg_send![notification, setInformativeText: body];
+            }
+
+            let _: () = msg_send![center, deliverNotification: notification];
+            let _: () = msg_send![notification, release];
+            Ok(())
+        })
+        .and_then(|res| res)
+    })
+}
@@ -0,0 +1,128 @@
+use std::ops::DerefMut;
+use std::ptr;
+
+use crate::backend::DialogFutureType;
+use crate::localizer::localize;
+use crate::{BuiltinString, DialogHandle, Error, PickListDialog};
+
+use super::{
+    modal_future::ModalFuture,
+    utils::{or_cancelled, run_on_main, FocusManager, PolicyManager},
+    AsModal,
+};
+
+use super::utils::{INSWindow, NSRect, NSWindow};
+use objc::runtime::{Object, NO};
+use objc::{class, msg_send, sel, sel_impl};
+use objc_foundation::{INSString, NSString};
+
+use objc_id::Id;
+
+// NSAlertFirstButtonReturn
+const FIRST_BUTTON: i64 = 1000;
+
+/// `NSAlert` with a pop-up button of the items as its accessory view
+pub struct NSPickListAlert {
+    alert: Id<Object>,
+    popup: Id<Object>,
+    _focus_manager: FocusManager,
+    _policy_manager: PolicyManager,
+}
+
+impl NSPickListAlert {
+    pub fn new(opt: PickListDialog) -> Self {
+        let _policy_manager = PolicyManager::new();
+
+        let alert: *mut Object = unsafe { msg_send![class!(NSAlert), new] };
+
+        unsafe {
+            for label in [BuiltinString::Ok, BuiltinString::Cancel] {
+                let label = NSString::from_str(&localize(label));
+                let _: () = msg_send![alert, addButtonWithTitle: label];
+            }
+
+            let text = NSString::from_str(&opt.title);
+            let _: () = msg_send![alert, setMessageText: text];
+            let text = NSString::from_str(&opt.prompt);
+            let _: () = msg_send![alert, setInformativeText: text];
+        }
+
+        let popup: *mut Object = unsafe {
+            let frame = NSRect::new(0.0, 0.0, 240.0, 26.0);
+            let popup: *mut Object = msg_send![class!(NSPopUpButton), alloc];
+            let popup: *mut Object = msg_send![popup, initWithFrame: frame pullsDown: NO];
+
+            // `addItemWithTitle:` drops duplicates, menu items are kept as they are
+            let menu: *mut Object = msg_send![popup, menu];
+            let nil: *mut Object = ptr::null_mut();
+            for item in &opt.items {
+                let title = NSString::from_str(item);
+                let key = NSString::from_str("");
+                let _: *mut Object =
+                    msg_send![menu, addItemWithTitle: title action: nil keyEquivalent: key];
+            }
+
+            let _: () = msg_send![alert, setAccessoryView: popup];
+            let window: *mut Object = msg_send![alert, window];
+            let _: () = msg_send![window, setInitialFirstResponder: popup];
+            popup
+        };
+
+        let _focus_manager = FocusManager::new();
+
+        Self {
+            alert: unsafe { Id::from_retained_ptr(alert) },
+            popup: unsafe { Id::from_retained_ptr(popup) },
+            _focus_manager,
+            _policy_manager,
+        }
+    }
+
+    pub fn run(self) -> Option<usize> {
+        let ret: i64 = unsafe { msg_send![self.alert, runModal] };
+        self.selected(ret)
+    }
+
+    /// Index of the selected item, when the alert was closed with OK
+    fn selected(&self, res_id: i64) -> Option<usize> {
+        if res_id != FIRST_BUTTON {
+            return None;
+        }
+
+        let index: i64 = unsafe { msg_send![self.popup, indexOfSelectedItem] };
+        if index < 0 {
+            None
+        } else {
+            Some(index as usize)
+        }
+    }
+}
+
+impl AsModal for NSPickListAlert {
+    fn modal_ptr(&mut self) -> *mut Object {
+        self.alert.deref_mut()
+    }
+}
+
+pub fn show(opt: PickListDialog) -> Option<usize> {
+    or_cancelled(try_show(opt))
+}
+
+pub fn try_show(opt: PickListDialog) -> Result<Option<usize>, Error> {
+    objc::rc::autoreleasepool(move || run_on_main(move || NSPickListAlert::new(opt).run()))
+}
+
+pub fn show_async(opt: PickListDialog) -> DialogFutureType<Option<usize>> {
+    #[cfg(feature = "parent")]
+    let win = opt.parent().as_ref().map(NSWindow::from_raw_window_handle);
+    #[cfg(not(feature = "parent"))]
+    let win = None;
+
+    let future = ModalFuture::new(
+        DialogHandle::default(),
+        win,
+        move || NSPickListAlert::new(opt),
+        |alert, res_id| alert.selected(res_id),
+    );
+    Box::pin(future)
+}
@@ -0,0 +1,97 @@
+use std::cell::{Cell, RefCell};
+use std::mem;
+use std::ptr;
+
+use crate::localizer::localize;
+use crate::{BuiltinString, ProgressDialog, ProgressState};
+
+use super::utils::NSRect;
+
+use objc::runtime::{Object, Sel, NO, YES};
+use objc::{class, msg_send, sel, sel_impl};
+use objc_foundation::{INSString, NSString};
+
+/// Shows the progress in the window of an `NSAlert` that is not run modally,
+/// the changes of the state are picked up by a timer on the main run loop
+unsafe fn show_panel(title: &str, state: ProgressState) {
+    let alert: *mut Object = msg_send![class!(NSAlert), new];
+
+    let text = NSString::from_str(title);
+    let _: () = msg_send![alert, setMessageText: text];
+    let text = NSString::from_str(&state.message());
+    let _: () = msg_send![alert, setInformativeText: text];
+
+    // Without a modal session the button can't end the alert, a click only toggles its state,
+    // which the timer reads
+    let label = NSString::from_str(&localize(BuiltinString::Cancel));
+    let button: *mut Object = msg_send![alert, addButtonWithTitle: label];
+    // NSButtonTypePushOnPushOff
+    let _: () = msg_send![button, setButtonType: 1u64];
+    let nil: *mut Object = ptr::null_mut();
+    let _: () = msg_send![button, setTarget: nil];
+    let _: () = msg_send![button, setAction: Sel::from_ptr(ptr::null())];
+
+    let frame = NSRect::new(0.0, 0.0, 240.0, 20.0);
+    let bar: *mut Object = msg_send![class!(NSProgressIndicator), alloc];
+    let bar: *mut Object = msg_send![bar, initWithFrame: frame];
+    let _: () = msg_send![bar, setMinValue: 0.0f64];
+    let _: () = msg_send![bar, setMaxValue: 1.0f64];
+    let _: () = msg_send![alert, setAccessoryView: bar];
+    let _: () = msg_send![bar, release];
+
+    let _: () = msg_send![alert, layout];
+    let window: *mut Object = msg_send![alert, window];
+    let _: () = msg_send![window, makeKeyAndOrderFront: nil];
+
+    // What the alert shows, the block is called as `Fn`
+    let marquee = Cell::new(None);
+    let message = RefCell::new(state.message());
+    let tick = block::ConcreteBlock::new(move |timer: *mut Object| {
+        let pressed: i64 = msg_send![button, state];
+        if pressed == 1 {
+            state.cancel();
+        }
+
+        if pressed == 1 || state.is_closed() {
+            let _: () = msg_send![timer, invalidate];
+            let _: () = msg_send![window, orderOut: nil];
+            let _: () = msg_send![alert, release];
+            return;
+        }
+
+        let now = state.marquee();
+        if marquee.get() != Some(now) {
+            let _: () = msg_send![bar, setIndeterminate: if now { YES } else { NO }];
+            if now {
+                let _: () = msg_send![bar, startAnimation: nil];
+            } else {
+                let _: () = msg_send![bar, stopAnimation: nil];
+            }
+            marquee.set(Some(now));
+        }
+        if !now {
+            let _: () = msg_send![bar, setDoubleValue: state.progress() as f64];
+        }
+
+        let now = state.message();
+        if now != *message.borrow() {
+            let text = NSString::from_str(&now);
+            let _: () = msg_send![alert, setInformativeText: text];
+            let _: () = msg_send![alert, layout];
+            message.replace(now);
+        }
+    });
+
+    let _: *mut Object = msg_send![
+        class!(NSTimer),
+        scheduledTimerWithTimeInterval: 0.1f64
+        repeats: YES
+        block: &tick
+    ];
+    // The timer keeps a copy of the block
+    mem::forget(tick);
+}
+
+pub fn show(opt: ProgressDialog, state: ProgressState) {
+    dispatch::Queue::main().exec_async(move || unsafe { show_panel(&opt.title, state) });
+}
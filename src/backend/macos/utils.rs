@@ -1,5 +1,5 @@
 use objc::runtime::Object;
-use objc::{class, msg_send, sel, sel_impl};
+use objc::{class, msg_send, sel, sel_impl, Encode, Encoding};
 
 mod application;
 mod focus_manager;
@@ -13,9 +13,63 @@ pub use policy_manager::PolicyManager;
 pub use url::{INSURL, NSURL};
 pub use window::{INSWindow, NSWindow};
 
+use crate::Error;
+
 #[allow(non_upper_case_globals)]
 pub const nil: *mut Object = 0 as *mut _;
 
+/// Frame of a view or window, for `initWithFrame:` and `frame`
+#[repr(C)]
+pub struct NSRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl NSRect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+unsafe impl Encode for NSRect {
+    fn encode() -> Encoding {
+        unsafe { Encoding::from_str("{CGRect={CGPoint=dd}{CGSize=dd}}") }
+    }
+}
+
+/// Point on the screen, for `setFrameOrigin:`
+#[repr(C)]
+pub struct NSPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+unsafe impl Encode for NSPoint {
+    fn encode() -> Encoding {
+        unsafe { Encoding::from_str("{CGPoint=dd}") }
+    }
+}
+
+/// Width and height, for `setSize:`
+#[repr(C)]
+pub struct NSSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+unsafe impl Encode for NSSize {
+    fn encode() -> Encoding {
+        unsafe { Encoding::from_str("{CGSize=dd}") }
+    }
+}
+
 pub fn is_main_thread() -> bool {
     unsafe { msg_send![class!(NSThread), isMainThread] }
 }
@@ -27,16 +81,32 @@ pub fn activate_cocoa_multithreading() {
     }
 }
 
-pub fn run_on_main<R: Send, F: FnOnce() -> R + Send>(run: F) -> R {
+/// `Err(Error::NotMainThread)` when AppKit can't be reached from this thread,
+/// which is off the main thread while the app does not run its event loop
+pub fn main_thread_available() -> Result<(), Error> {
+    if is_main_thread() || NSApplication::shared_application().is_running() {
+        Ok(())
+    } else {
+        Err(Error::NotMainThread)
+    }
+}
+
+/// Runs `run` on the main thread, through the main dispatch queue when called from another one
+pub fn run_on_main<R: Send, F: FnOnce() -> R + Send>(run: F) -> Result<R, Error> {
     if is_main_thread() {
-        run()
+        Ok(run())
     } else {
-        let app = NSApplication::shared_application();
-        if app.is_running() {
-            let main = dispatch::Queue::main();
-            main.exec_sync(run)
-        } else {
-            panic!("You are running RFD in NonWindowed environment, it is impossible to spawn dialog from thread different than main in this env.");
-        }
+        main_thread_available()?;
+        let main = dispatch::Queue::main();
+        Ok(main.exec_sync(run))
     }
 }
+
+/// Dialogs without an error in their result report it and count as cancelled, the `try_show`
+/// methods of the dialogs return it instead
+pub fn or_cancelled<T: Default>(res: Result<T, Error>) -> T {
+    res.unwrap_or_else(|_err| {
+        trace_warn!(error = %_err, "dialog failed, counted as cancelled");
+        T::default()
+    })
+}
@@ -0,0 +1,464 @@
+//! Qt `QFileDialog` and `QMessageBox`
+//!
+//! Dialogs are shown by `qt/shim.cpp`, which build.rs compiles against the Qt found by `pkg-config`.
+//! Qt widgets belong to the thread that created the `QApplication`, so every dialog runs on one
+//! `rfd_qt` thread, which owns it.
+
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::future::Future;
+use std::os::raw::{c_char, c_int, c_ulong, c_void};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{mpsc, Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+
+use crate::backend::{DialogBackend, DialogFutureType};
+use crate::dialog::MessageLevel;
+use crate::{Error, FileDialog, FileHandle, MessageDialog};
+
+#[cfg(feature = "parent")]
+use raw_window_handle::RawWindowHandle;
+
+#[repr(C)]
+struct RfdQtFilter {
+    name: *const c_char,
+    patterns: *const c_char,
+}
+
+#[repr(C)]
+struct RfdQtFileDialog {
+    mode: c_int,
+    title: *const c_char,
+    directory: *const c_char,
+    file_name: *const c_char,
+    accept_label: *const c_char,
+    cancel_label: *const c_char,
+    filters: *const RfdQtFilter,
+    filter_count: usize,
+    default_filter: c_int,
+    show_hidden: c_int,
+    confirm_overwrite: c_int,
+    parent: c_ulong,
+}
+
+#[repr(C)]
+struct RfdQtMessageBox {
+    title: *const c_char,
+    text: *const c_char,
+    level: c_int,
+    labels: *const *const c_char,
+    label_count: usize,
+    default_button: usize,
+    parent: c_ulong,
+}
+
+type RfdQtPush = extern "C" fn(ctx: *mut c_void, path: *const c_char);
+
+extern "C" {
+    fn rfd_qt_init();
+    fn rfd_qt_file_dialog(opt: *const RfdQtFileDialog, push: RfdQtPush, ctx: *mut c_void) -> c_int;
+    fn rfd_qt_message_box(opt: *const RfdQtMessageBox) -> c_int;
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    PickFile = 0,
+    PickFiles = 1,
+    PickFolder = 2,
+    SaveFile = 3,
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Sends `job` to the `rfd_qt` thread, which is started by the first dialog
+fn run_on_qt_thread(job: Job) {
+    static QT_THREAD: OnceLock<Mutex<mpsc::Sender<Job>>> = OnceLock::new();
+    let sender = QT_THREAD.get_or_init(|| {
+        let (sender, jobs) = mpsc::channel::<Job>();
+        std::thread::Builder::new()
+            .name("rfd_qt".into())
+            .spawn(move || {
+                unsafe { rfd_qt_init() };
+                for job in jobs {
+                    job();
+                }
+            })
+            .unwrap();
+        Mutex::new(sender)
+    });
+    sender.lock().unwrap().send(job).unwrap();
+}
+
+struct ReplyState<T> {
+    res: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// Result of a dialog shown on the `rfd_qt` thread
+struct Reply<T> {
+    state: Arc<(Mutex<ReplyState<T>>, Condvar)>,
+}
+
+impl<T: Send + 'static> Reply<T> {
+    fn new(run: impl FnOnce() -> T + Send + 'static) -> Self {
+        let state = Arc::new((
+            Mutex::new(ReplyState {
+                res: None,
+                waker: None,
+            }),
+            Condvar::new(),
+        ));
+
+        {
+            let state = state.clone();
+            run_on_qt_thread(Box::new(move || {
+                let res = run();
+
+                let mut reply = state.0.lock().unwrap();
+                reply.res.replace(res);
+                if let Some(waker) = reply.waker.take() {
+                    waker.wake();
+                }
+                state.1.notify_all();
+            }));
+        }
+
+        Self { state }
+    }
+
+    /// Blocks until the dialog is closed
+    fn wait(self) -> T {
+        let mut reply = self.state.0.lock().unwrap();
+        loop {
+            if let Some(res) = reply.res.take() {
+                return res;
+            }
+            reply = self.state.1.wait(reply).unwrap();
+        }
+    }
+}
+
+impl<T> Future for Reply<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut reply = self.state.0.lock().unwrap();
+        match reply.res.take() {
+            Some(res) => Poll::Ready(res),
+            None => {
+                reply.waker.replace(ctx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(feature = "parent")]
+fn xid(parent: Option<RawWindowHandle>) -> c_ulong {
+    // Only X11 windows can be parents, like in the portal backend
+    match parent {
+        Some(RawWindowHandle::Xlib(handle)) => handle.window,
+        Some(RawWindowHandle::Xcb(handle)) => handle.window as _,
+        _ => 0,
+    }
+}
+
+fn c_string(s: &str) -> CString {
+    // Strings with a NUL can't be passed to C, they are cut off there
+    CString::new(s.split('\0').next().unwrap_or_default()).unwrap()
+}
+
+fn c_ptr(s: &Option<CString>) -> *const c_char {
+    s.as_ref().map_or(std::ptr::null(), |s| s.as_ptr())
+}
+
+/// Options of a file dialog, owned so they can be sent to the `rfd_qt` thread
+struct FileOptions {
+    mode: Mode,
+    title: Option<CString>,
+    directory: Option<CString>,
+    file_name: Option<CString>,
+    accept_label: Option<CString>,
+    cancel_label: Option<CString>,
+    filters: Vec<(CString, CString)>,
+    default_filter: Option<usize>,
+    show_hidden: bool,
+    confirm_overwrite: bool,
+    parent: c_ulong,
+}
+
+impl FileOptions {
+    fn new(opt: &FileDialog, mode: Mode) -> Self {
+        let filters = opt
+            .filters
+            .iter()
+            .map(|filter| {
+                let globs: Vec<String> = filter.globs().collect();
+                (c_string(&filter.name), c_string(&globs.join(" ")))
+            })
+            .collect();
+
+        Self {
+            mode,
+            title: opt.title.as_deref().map(c_string),
+            directory: opt
+                .starting_directory
+                .as_ref()
+                .map(|dir| c_string(&dir.to_string_lossy())),
+            file_name: opt.file_name.as_deref().map(c_string),
+            accept_label: opt.accept_label.as_deref().map(c_string),
+            cancel_label: opt.cancel_label.as_deref().map(c_string),
+            filters,
+            default_filter: opt.default_filter,
+            show_hidden: opt.show_hidden.unwrap_or(false),
+            confirm_overwrite: opt.confirm_overwrite.unwrap_or(true),
+            #[cfg(feature = "parent")]
+            parent: xid(opt.parent()),
+            #[cfg(not(feature = "parent"))]
+            parent: 0,
+        }
+    }
+
+    /// Shows the dialog, returns the chosen paths and the index of the selected filter
+    fn show(&self) -> Option<(Vec<PathBuf>, usize)> {
+        extern "C" fn push(ctx: *mut c_void, path: *const c_char) {
+            let paths = unsafe { &mut *(ctx as *mut Vec<PathBuf>) };
+            let path = unsafe { CStr::from_ptr(path) };
+            paths.push(path.to_string_lossy().into_owned().into());
+        }
+
+        let filters: Vec<RfdQtFilter> = self
+            .filters
+            .iter()
+            .map(|(name, patterns)| RfdQtFilter {
+                name: name.as_ptr(),
+                patterns: patterns.as_ptr(),
+            })
+            .collect();
+        let opt = RfdQtFileDialog {
+            mode: self.mode as c_int,
+            title: c_ptr(&self.title),
+            directory: c_ptr(&self.directory),
+            file_name: c_ptr(&self.file_name),
+            accept_label: c_ptr(&self.accept_label),
+            cancel_label: c_ptr(&self.cancel_label),
+            filters: filters.as_ptr(),
+            filter_count: filters.len(),
+            default_filter: self.default_filter.map_or(-1, |index| index as c_int),
+            show_hidden: self.show_hidden as c_int,
+            confirm_overwrite: self.confirm_overwrite as c_int,
+            parent: self.parent,
+        };
+
+        let mut paths: Vec<PathBuf> = Vec::new();
+        let index = unsafe {
+            rfd_qt_file_dialog(&opt, push, &mut paths as *mut Vec<PathBuf> as *mut c_void)
+        };
+        if index < 0 || paths.is_empty() {
+            None
+        } else {
+            Some((paths, index as usize))
+        }
+    }
+}
+
+fn file_dialog(opt: FileDialog, mode: Mode) -> Reply<Option<(Vec<PathBuf>, usize)>> {
+    let options = FileOptions::new(&opt, mode);
+    Reply::new(move || options.show())
+}
+
+fn first(res: Option<(Vec<PathBuf>, usize)>) -> Option<PathBuf> {
+    res.and_then(|(paths, _)| paths.into_iter().next())
+}
+
+fn all(res: Option<(Vec<PathBuf>, usize)>) -> Option<Vec<PathBuf>> {
+    res.map(|(paths, _)| paths)
+}
+
+/// Options of a message box, owned so they can be sent to the `rfd_qt` thread
+struct MessageOptions {
+    title: CString,
+    text: CString,
+    level: MessageLevel,
+    labels: Vec<CString>,
+    default_button: usize,
+    parent: c_ulong,
+}
+
+impl MessageOptions {
+    fn new(opt: &MessageDialog) -> Self {
+        Self {
+            title: c_string(&opt.title),
+            text: c_string(&opt.description),
+            level: opt.level,
+            labels: opt
+                .shown_button_labels()
+                .iter()
+                .map(|l| c_string(l))
+                .collect(),
+            default_button: opt.default_button,
+            #[cfg(feature = "parent")]
+            parent: xid(opt.parent()),
+            #[cfg(not(feature = "parent"))]
+            parent: 0,
+        }
+    }
+
+    /// Shows the message box, returns the index of the pressed button
+    fn show(&self) -> Option<usize> {
+        let labels: Vec<*const c_char> = self.labels.iter().map(|l| l.as_ptr()).collect();
+        let opt = RfdQtMessageBox {
+            title: self.title.as_ptr(),
+            text: self.text.as_ptr(),
+            level: match self.level {
+                MessageLevel::Info => 0,
+                MessageLevel::Warning => 1,
+                MessageLevel::Error => 2,
+                MessageLevel::Question => 3,
+            },
+            labels: labels.as_ptr(),
+            label_count: labels.len(),
+            default_button: self.default_button,
+            parent: self.parent,
+        };
+
+        let index = unsafe { rfd_qt_message_box(&opt) };
+        usize::try_from(index).ok()
+    }
+}
+
+fn message_box(opt: &MessageDialog) -> Reply<Option<usize>> {
+    let options = MessageOptions::new(opt);
+    Reply::new(move || options.show())
+}
+
+fn pressed_label(opt: &MessageDialog, index: Option<usize>) -> Option<String> {
+    index.and_then(|index| opt.button_labels().get(index).cloned())
+}
+
+/// Qt backend, `QFileDialog` and `QMessageBox`
+pub struct QtBackend;
+
+impl DialogBackend for QtBackend {
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        Ok(first(file_dialog(dialog, Mode::PickFile).wait()))
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        Ok(all(file_dialog(dialog, Mode::PickFiles).wait()))
+    }
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        Ok(first(file_dialog(dialog, Mode::PickFolder).wait()))
+    }
+
+    // `QFileDialog` picks one folder
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        Ok(all(file_dialog(dialog, Mode::PickFolder).wait()))
+    }
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        Ok(first(file_dialog(dialog, Mode::SaveFile).wait()))
+    }
+
+    fn save_file_with_filter(&self, dialog: FileDialog) -> Result<Option<(PathBuf, usize)>, Error> {
+        let res = file_dialog(dialog, Mode::SaveFile).wait();
+        Ok(res.and_then(|(paths, index)| Some((paths.into_iter().next()?, index))))
+    }
+
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        message_box(&dialog).wait() == Some(0)
+    }
+
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        let index = message_box(&dialog).wait();
+        pressed_label(&dialog, index)
+    }
+
+    fn show_message_index(&self, dialog: MessageDialog) -> Option<usize> {
+        message_box(&dialog).wait()
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let reply = file_dialog(dialog, Mode::PickFile);
+        Box::pin(async move { Ok(first(reply.await).map(FileHandle::wrap)) })
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        let reply = file_dialog(dialog, Mode::PickFiles);
+        Box::pin(async move {
+            Ok(all(reply.await).map(|paths| paths.into_iter().map(FileHandle::wrap).collect()))
+        })
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let reply = file_dialog(dialog, Mode::PickFolder);
+        Box::pin(async move { Ok(first(reply.await).map(FileHandle::wrap)) })
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        let reply = file_dialog(dialog, Mode::PickFolder);
+        Box::pin(async move {
+            Ok(all(reply.await).map(|paths| paths.into_iter().map(FileHandle::wrap).collect()))
+        })
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let reply = file_dialog(dialog, Mode::SaveFile);
+        Box::pin(async move { Ok(first(reply.await).map(FileHandle::wrap)) })
+    }
+
+    fn save_file_with_filter_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<(FileHandle, usize)>, Error>> {
+        let reply = file_dialog(dialog, Mode::SaveFile);
+        Box::pin(async move {
+            let res = reply.await;
+            Ok(res.and_then(|(paths, index)| {
+                Some((FileHandle::wrap(paths.into_iter().next()?), index))
+            }))
+        })
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        let reply = message_box(&dialog);
+        Box::pin(async move { reply.await == Some(0) })
+    }
+
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        let reply = message_box(&dialog);
+        Box::pin(async move { pressed_label(&dialog, reply.await) })
+    }
+
+    fn show_message_index_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<usize>> {
+        Box::pin(message_box(&dialog))
+    }
+
+    fn confirms_overwrite(&self, dialog: &FileDialog) -> Option<bool> {
+        Some(dialog.confirm_overwrite != Some(false))
+    }
+
+    // `QApplication` aborts the process when it can't connect to a display
+    fn is_available(&self) -> bool {
+        ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()))
+    }
+}
@@ -0,0 +1,195 @@
+// C interface of the Qt backend, see `qt.rs`
+//
+// Every function has to be called from the thread that called `rfd_qt_init`.
+
+#include <QApplication>
+#include <QDir>
+#include <QFileDialog>
+#include <QMessageBox>
+#include <QPushButton>
+#include <QString>
+#include <QStringList>
+#include <QWindow>
+
+#include <cstddef>
+
+extern "C" {
+
+struct RfdQtFilter {
+    const char *name;
+    // Space separated globs, eg. `*.png *.jpg`
+    const char *patterns;
+};
+
+enum RfdQtFileMode {
+    RFD_QT_PICK_FILE = 0,
+    RFD_QT_PICK_FILES = 1,
+    RFD_QT_PICK_FOLDER = 2,
+    RFD_QT_SAVE_FILE = 3,
+};
+
+struct RfdQtFileDialog {
+    int mode;
+    // Null when not set
+    const char *title;
+    const char *directory;
+    const char *file_name;
+    const char *accept_label;
+    const char *cancel_label;
+    const RfdQtFilter *filters;
+    size_t filter_count;
+    // -1 when not set
+    int default_filter;
+    int show_hidden;
+    int confirm_overwrite;
+    // X11 window of the parent, 0 when there is none
+    unsigned long parent;
+};
+
+struct RfdQtMessageBox {
+    const char *title;
+    const char *text;
+    // 0 info, 1 warning, 2 error, 3 question
+    int level;
+    const char *const *labels;
+    size_t label_count;
+    size_t default_button;
+    unsigned long parent;
+};
+
+typedef void (*RfdQtPush)(void *ctx, const char *path);
+
+static int argc = 1;
+static char arg0[] = "rfd";
+static char *argv[] = {arg0, nullptr};
+
+void rfd_qt_init() {
+    if (!QApplication::instance()) {
+        new QApplication(argc, argv);
+    }
+}
+
+static QString string(const char *utf8) {
+    return QString::fromUtf8(utf8);
+}
+
+static void set_parent(QWidget *widget, unsigned long parent) {
+    if (parent == 0) {
+        return;
+    }
+    // Creates the native window, so it can be made transient for a foreign one
+    widget->winId();
+    QWindow *foreign = QWindow::fromWinId(parent);
+    if (widget->windowHandle() && foreign) {
+        widget->windowHandle()->setTransientParent(foreign);
+    }
+}
+
+// Returns the index of the selected filter, -1 when the dialog was cancelled
+int rfd_qt_file_dialog(const RfdQtFileDialog *opt, RfdQtPush push, void *ctx) {
+    QFileDialog dialog;
+    set_parent(&dialog, opt->parent);
+
+    switch (opt->mode) {
+    case RFD_QT_PICK_FILE:
+        dialog.setFileMode(QFileDialog::ExistingFile);
+        break;
+    case RFD_QT_PICK_FILES:
+        dialog.setFileMode(QFileDialog::ExistingFiles);
+        break;
+    case RFD_QT_PICK_FOLDER:
+        dialog.setFileMode(QFileDialog::Directory);
+        dialog.setOption(QFileDialog::ShowDirsOnly);
+        break;
+    case RFD_QT_SAVE_FILE:
+        dialog.setFileMode(QFileDialog::AnyFile);
+        dialog.setAcceptMode(QFileDialog::AcceptSave);
+        dialog.setOption(QFileDialog::DontConfirmOverwrite, !opt->confirm_overwrite);
+        break;
+    }
+
+    if (opt->title) {
+        dialog.setWindowTitle(string(opt->title));
+    }
+    if (opt->directory) {
+        dialog.setDirectory(string(opt->directory));
+    }
+    if (opt->file_name) {
+        dialog.selectFile(string(opt->file_name));
+    }
+    if (opt->accept_label) {
+        dialog.setLabelText(QFileDialog::Accept, string(opt->accept_label));
+    }
+    if (opt->cancel_label) {
+        dialog.setLabelText(QFileDialog::Reject, string(opt->cancel_label));
+    }
+    if (opt->show_hidden) {
+        dialog.setFilter(dialog.filter() | QDir::Hidden);
+    }
+
+    QStringList filters;
+    for (size_t i = 0; i < opt->filter_count; i++) {
+        const RfdQtFilter &filter = opt->filters[i];
+        filters << QString("%1 (%2)").arg(string(filter.name), string(filter.patterns));
+    }
+    if (!filters.isEmpty()) {
+        dialog.setNameFilters(filters);
+        if (opt->default_filter >= 0 && opt->default_filter < filters.size()) {
+            dialog.selectNameFilter(filters[opt->default_filter]);
+        }
+    }
+
+    if (dialog.exec() != QDialog::Accepted) {
+        return -1;
+    }
+
+    for (const QString &path : dialog.selectedFiles()) {
+        push(ctx, path.toUtf8().constData());
+    }
+    int index = filters.indexOf(dialog.selectedNameFilter());
+    return index < 0 ? 0 : index;
+}
+
+// Returns the index of the pressed button, -1 when the dialog was closed without one
+int rfd_qt_message_box(const RfdQtMessageBox *opt) {
+    QMessageBox box;
+    set_parent(&box, opt->parent);
+
+    switch (opt->level) {
+    case 1:
+        box.setIcon(QMessageBox::Warning);
+        break;
+    case 2:
+        box.setIcon(QMessageBox::Critical);
+        break;
+    case 3:
+        box.setIcon(QMessageBox::Question);
+        break;
+    default:
+        box.setIcon(QMessageBox::Information);
+        break;
+    }
+    box.setWindowTitle(string(opt->title));
+    box.setText(string(opt->title));
+    box.setInformativeText(string(opt->text));
+
+    // The first button accepts, the last one of several rejects, like `MessageDialog::show`
+    QList<QPushButton *> buttons;
+    for (size_t i = 0; i < opt->label_count; i++) {
+        QMessageBox::ButtonRole role = QMessageBox::ActionRole;
+        if (i == 0) {
+            role = QMessageBox::AcceptRole;
+        } else if (i + 1 == opt->label_count) {
+            role = QMessageBox::RejectRole;
+        }
+        buttons << box.addButton(string(opt->labels[i]), role);
+    }
+    if (opt->default_button < (size_t)buttons.size()) {
+        box.setDefaultButton(buttons[opt->default_button]);
+    }
+
+    box.exec();
+    return buttons.indexOf(qobject_cast<QPushButton *>(box.clickedButton()));
+}
+
+} // extern "C"
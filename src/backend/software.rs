@@ -0,0 +1,704 @@
+//! Built-in file browser drawn with `winit` and `softbuffer`
+//!
+//! Last resort when neither a native toolkit, the XDG portal nor `zenity` is around,
+//! it only needs X11 or Wayland. Every dialog is a window of one event loop
+//! that runs on the `rfd_software` thread, `winit` can't create a second one.
+
+mod browser;
+mod view;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use winit::application::ApplicationHandler;
+use winit::dpi::{LogicalSize, PhysicalPosition};
+use winit::event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy, OwnedDisplayHandle};
+use winit::keyboard::{Key as LogicalKey, NamedKey};
+use winit::platform::wayland::{EventLoopBuilderExtWayland, WindowAttributesExtWayland};
+use winit::platform::x11::{EventLoopBuilderExtX11, WindowAttributesExtX11};
+use winit::window::{Icon, Window, WindowId};
+
+use crate::backend::{DialogBackend, DialogFutureType};
+use crate::dialog::branded_title;
+use crate::localizer::or_localized;
+use crate::{BuiltinString, Error, FileDialog, FileHandle, MessageDialog, WindowIcon};
+
+use browser::{Browser, Key, Mode, Outcome};
+use view::{Canvas, Layout};
+
+/// Two clicks on the same entry within this time open it
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+struct ReplyState<T> {
+    res: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// Result of a dialog shown on the `rfd_software` thread
+struct Reply<T> {
+    state: Arc<(Mutex<ReplyState<T>>, Condvar)>,
+}
+
+impl<T> Clone for Reply<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T> Reply<T> {
+    fn new() -> Self {
+        Self {
+            state: Arc::new((
+                Mutex::new(ReplyState {
+                    res: None,
+                    waker: None,
+                }),
+                Condvar::new(),
+            )),
+        }
+    }
+
+    fn send(&self, res: T) {
+        let mut reply = self.state.0.lock().unwrap();
+        reply.res.replace(res);
+        if let Some(waker) = reply.waker.take() {
+            waker.wake();
+        }
+        self.state.1.notify_all();
+    }
+
+    /// Blocks until the dialog is closed
+    fn wait(self) -> T {
+        let mut reply = self.state.0.lock().unwrap();
+        loop {
+            if let Some(res) = reply.res.take() {
+                return res;
+            }
+            reply = self.state.1.wait(reply).unwrap();
+        }
+    }
+}
+
+impl<T> Future for Reply<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut reply = self.state.0.lock().unwrap();
+        match reply.res.take() {
+            Some(res) => Poll::Ready(res),
+            None => {
+                reply.waker.replace(ctx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+type FileReply = Reply<Result<Option<Vec<PathBuf>>, Error>>;
+
+/// Title bar of a dialog window
+struct Branding {
+    title: String,
+    icon: Option<WindowIcon>,
+    /// Also the X11 class and Wayland app id, so the window is grouped with the app
+    app_name: Option<String>,
+}
+
+impl Branding {
+    fn new(title: &str, icon: &Option<WindowIcon>, app_name: &Option<String>) -> Self {
+        Self {
+            title: branded_title(title, app_name.as_deref()),
+            icon: icon.clone(),
+            app_name: app_name.clone(),
+        }
+    }
+}
+
+/// Sent to the event loop
+enum Request {
+    File {
+        id: u64,
+        branding: Branding,
+        browser: Browser,
+        reply: FileReply,
+    },
+    Message {
+        branding: Branding,
+        description: String,
+        labels: Vec<String>,
+        focus: usize,
+        reply: Reply<Option<usize>>,
+    },
+    /// Dismisses the file dialog with this id, see `DialogHandle`
+    Close(u64),
+}
+
+/// Proxy of the event loop, or why it could not be started
+fn event_loop() -> Result<&'static Mutex<EventLoopProxy<Request>>, Error> {
+    static PROXY: OnceLock<Result<Mutex<EventLoopProxy<Request>>, Error>> = OnceLock::new();
+    PROXY
+        .get_or_init(|| {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            std::thread::Builder::new()
+                .name("rfd_software".into())
+                .spawn(move || {
+                    let mut builder = EventLoop::with_user_event();
+                    EventLoopBuilderExtX11::with_any_thread(&mut builder, true);
+                    EventLoopBuilderExtWayland::with_any_thread(&mut builder, true);
+                    let event_loop = match builder.build() {
+                        Ok(event_loop) => event_loop,
+                        Err(err) => {
+                            let _ = sender.send(Err(Error::Software(err.to_string())));
+                            return;
+                        }
+                    };
+                    let context = softbuffer::Context::new(event_loop.owned_display_handle());
+                    let context = match context {
+                        Ok(context) => context,
+                        Err(err) => {
+                            let _ = sender.send(Err(Error::Software(err.to_string())));
+                            return;
+                        }
+                    };
+                    let _ = sender.send(Ok(event_loop.create_proxy()));
+
+                    let mut app = App {
+                        context,
+                        dialogs: HashMap::new(),
+                    };
+                    let _ = event_loop.run_app(&mut app);
+                })
+                .map_err(|err| Error::Software(err.to_string()))?;
+
+            match receiver.recv() {
+                Ok(res) => res.map(Mutex::new),
+                Err(_) => Err(Error::Software("event loop thread stopped".into())),
+            }
+        })
+        .as_ref()
+        .map_err(Clone::clone)
+}
+
+fn send(request: Request) -> Result<(), Error> {
+    event_loop()?
+        .lock()
+        .unwrap()
+        .send_event(request)
+        .map_err(|_| Error::Software("event loop has exited".into()))
+}
+
+enum Kind {
+    File {
+        id: u64,
+        browser: Browser,
+        reply: FileReply,
+    },
+    Message {
+        description: String,
+        labels: Vec<String>,
+        focus: usize,
+        reply: Reply<Option<usize>>,
+    },
+}
+
+struct Dialog {
+    window: Rc<Window>,
+    surface: softbuffer::Surface<OwnedDisplayHandle, Rc<Window>>,
+    kind: Kind,
+    cursor: PhysicalPosition<f64>,
+    ctrl: bool,
+    /// Entry and time of the last click, for double clicks
+    last_click: Option<(usize, Instant)>,
+}
+
+impl Dialog {
+    fn size(&self) -> (u32, u32) {
+        let size = self.window.inner_size();
+        (size.width.max(1), size.height.max(1))
+    }
+
+    fn layout(&self) -> Layout {
+        let (width, height) = self.size();
+        let header =
+            matches!(&self.kind, Kind::File { browser, .. } if browser.description.is_some());
+        Layout::new(width, height, header)
+    }
+
+    /// Sends the result, `None` cancels the dialog
+    fn finish(self, res: Option<Vec<PathBuf>>, pressed: Option<usize>) {
+        match self.kind {
+            Kind::File { reply, .. } => reply.send(Ok(res)),
+            Kind::Message { reply, .. } => reply.send(pressed),
+        }
+    }
+
+    fn key(&mut self, event: &KeyEvent) -> Option<(Option<Vec<PathBuf>>, Option<usize>)> {
+        let ctrl = self.ctrl;
+        match &mut self.kind {
+            Kind::File { browser, .. } => {
+                let key = match &event.logical_key {
+                    LogicalKey::Named(NamedKey::ArrowUp) => Key::Up,
+                    LogicalKey::Named(NamedKey::ArrowDown) => Key::Down,
+                    LogicalKey::Named(NamedKey::PageUp) => Key::PageUp,
+                    LogicalKey::Named(NamedKey::PageDown) => Key::PageDown,
+                    LogicalKey::Named(NamedKey::Home) => Key::Home,
+                    LogicalKey::Named(NamedKey::End) => Key::End,
+                    LogicalKey::Named(NamedKey::Enter) if ctrl => Key::Accept,
+                    LogicalKey::Named(NamedKey::Enter) => Key::Enter,
+                    LogicalKey::Named(NamedKey::Escape) => Key::Escape,
+                    LogicalKey::Named(NamedKey::Backspace) => Key::Backspace,
+                    LogicalKey::Named(NamedKey::Tab) => Key::Tab,
+                    LogicalKey::Named(NamedKey::Space) if browser.mode != Mode::SaveFile => {
+                        Key::Space
+                    }
+                    LogicalKey::Character(c) if ctrl && c.eq_ignore_ascii_case("h") => {
+                        Key::ToggleHidden
+                    }
+                    _ if ctrl => return None,
+                    _ => Key::Char(event.text.as_ref()?.chars().next()?),
+                };
+                match browser.key(key) {
+                    Outcome::Continue => None,
+                    Outcome::Done(res) => Some((res, None)),
+                }
+            }
+            Kind::Message { labels, focus, .. } => {
+                let last = labels.len().saturating_sub(1);
+                match &event.logical_key {
+                    LogicalKey::Named(NamedKey::Enter) | LogicalKey::Named(NamedKey::Space) => {
+                        return Some((None, Some(*focus).filter(|_| !labels.is_empty())))
+                    }
+                    LogicalKey::Named(NamedKey::Escape) => return Some((None, None)),
+                    LogicalKey::Named(NamedKey::ArrowLeft) => *focus = focus.saturating_sub(1),
+                    LogicalKey::Named(NamedKey::ArrowRight) => *focus = (*focus + 1).min(last),
+                    LogicalKey::Named(NamedKey::Tab) => *focus = (*focus + 1) % (last + 1),
+                    _ => {}
+                }
+                None
+            }
+        }
+    }
+
+    fn click(&mut self) -> Option<(Option<Vec<PathBuf>>, Option<usize>)> {
+        let (x, y) = (self.cursor.x, self.cursor.y);
+        let (width, height) = self.size();
+        let layout = self.layout();
+        match &mut self.kind {
+            Kind::File { browser, .. } => {
+                if view::contains(&layout.cancel, x, y) {
+                    return Some((None, None));
+                }
+                let outcome = if view::contains(&layout.accept, x, y) {
+                    browser.accept()
+                } else if let Some(index) = layout.row_at(browser, x, y) {
+                    let double = self
+                        .last_click
+                        .is_some_and(|(last, at)| last == index && at.elapsed() < DOUBLE_CLICK);
+                    browser.select(index);
+                    if double {
+                        self.last_click = None;
+                        browser.activate()
+                    } else {
+                        self.last_click = Some((index, Instant::now()));
+                        Outcome::Continue
+                    }
+                } else {
+                    Outcome::Continue
+                };
+                match outcome {
+                    Outcome::Continue => None,
+                    Outcome::Done(res) => Some((res, None)),
+                }
+            }
+            Kind::Message { labels, .. } => view::message_buttons(width, height, labels)
+                .iter()
+                .position(|rect| view::contains(rect, x, y))
+                .map(|index| (None, Some(index))),
+        }
+    }
+
+    fn draw(&mut self) {
+        let (width, height) = self.size();
+        let layout = self.layout();
+        if let Kind::File { browser, .. } = &mut self.kind {
+            browser.page = layout.rows();
+        }
+
+        let (w, h) = match (NonZeroU32::new(width), NonZeroU32::new(height)) {
+            (Some(w), Some(h)) => (w, h),
+            _ => return,
+        };
+        if self.surface.resize(w, h).is_err() {
+            return;
+        }
+        let mut buffer = match self.surface.buffer_mut() {
+            Ok(buffer) => buffer,
+            Err(_) => return,
+        };
+
+        let mut canvas = Canvas {
+            pixels: &mut buffer,
+            width,
+            height,
+        };
+        match &self.kind {
+            Kind::File { browser, .. } => {
+                let accept = view::accept_label(browser.mode);
+                view::draw_browser(&mut canvas, browser, &layout, &accept);
+            }
+            Kind::Message {
+                description,
+                labels,
+                focus,
+                ..
+            } => view::draw_message(&mut canvas, description, labels, *focus),
+        }
+        let _ = buffer.present();
+    }
+}
+
+struct App {
+    context: softbuffer::Context<OwnedDisplayHandle>,
+    dialogs: HashMap<WindowId, Dialog>,
+}
+
+impl App {
+    fn open(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        branding: Branding,
+        size: (f64, f64),
+        kind: Kind,
+    ) {
+        let icon = branding
+            .icon
+            .and_then(|icon| Icon::from_rgba(icon.rgba, icon.width, icon.height).ok());
+        let mut attributes = Window::default_attributes()
+            .with_title(branding.title)
+            .with_window_icon(icon)
+            .with_inner_size(LogicalSize::new(size.0, size.1))
+            .with_min_inner_size(LogicalSize::new(240.0, 160.0));
+        if let Some(name) = &branding.app_name {
+            attributes = WindowAttributesExtX11::with_name(attributes, name, name);
+            attributes = WindowAttributesExtWayland::with_name(attributes, name, name);
+        }
+
+        let opened = event_loop
+            .create_window(attributes)
+            .map_err(|err| err.to_string())
+            .and_then(|window| {
+                let window = Rc::new(window);
+                softbuffer::Surface::new(&self.context, window.clone())
+                    .map(|surface| (window, surface))
+                    .map_err(|err| err.to_string())
+            });
+
+        match opened {
+            Ok((window, surface)) => {
+                window.request_redraw();
+                let dialog = Dialog {
+                    window,
+                    surface,
+                    kind,
+                    cursor: PhysicalPosition::new(0.0, 0.0),
+                    ctrl: false,
+                    last_click: None,
+                };
+                self.dialogs.insert(dialog.window.id(), dialog);
+            }
+            Err(err) => match kind {
+                Kind::File { reply, .. } => reply.send(Err(Error::Software(err))),
+                Kind::Message { reply, .. } => reply.send(None),
+            },
+        }
+    }
+
+    fn close(&mut self, id: WindowId, res: Option<Vec<PathBuf>>, pressed: Option<usize>) {
+        if let Some(dialog) = self.dialogs.remove(&id) {
+            dialog.finish(res, pressed);
+        }
+    }
+}
+
+impl ApplicationHandler<Request> for App {
+    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, request: Request) {
+        match request {
+            Request::File {
+                id,
+                branding,
+                browser,
+                reply,
+            } => {
+                let kind = Kind::File { id, browser, reply };
+                self.open(event_loop, branding, (640.0, 440.0), kind);
+            }
+            Request::Message {
+                branding,
+                description,
+                labels,
+                focus,
+                reply,
+            } => {
+                let kind = Kind::Message {
+                    description,
+                    labels,
+                    focus,
+                    reply,
+                };
+                self.open(event_loop, branding, (420.0, 160.0), kind);
+            }
+            Request::Close(closed) => {
+                let window = self
+                    .dialogs
+                    .iter()
+                    .find_map(|(window, dialog)| match dialog.kind {
+                        Kind::File { id, .. } if id == closed => Some(*window),
+                        _ => None,
+                    });
+                if let Some(window) = window {
+                    self.close(window, None, None);
+                }
+            }
+        }
+    }
+
+    fn window_event(&mut self, _event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
+        let dialog = match self.dialogs.get_mut(&id) {
+            Some(dialog) => dialog,
+            None => return,
+        };
+
+        let done = match event {
+            WindowEvent::CloseRequested => Some((None, None)),
+            WindowEvent::ModifiersChanged(modifiers) => {
+                dialog.ctrl = modifiers.state().control_key();
+                None
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                let done = dialog.key(&event);
+                dialog.window.request_redraw();
+                done
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                dialog.cursor = position;
+                None
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                let done = dialog.click();
+                dialog.window.request_redraw();
+                done
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                if let Kind::File { browser, .. } = &mut dialog.kind {
+                    let rows = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => -(y * 3.0) as isize,
+                        MouseScrollDelta::PixelDelta(pos) => {
+                            -(pos.y / view::ROW_HEIGHT as f64) as isize
+                        }
+                    };
+                    browser.scroll_by(rows);
+                    dialog.window.request_redraw();
+                }
+                None
+            }
+            WindowEvent::Resized(_) => {
+                dialog.window.request_redraw();
+                None
+            }
+            WindowEvent::RedrawRequested => {
+                dialog.draw();
+                None
+            }
+            _ => None,
+        };
+
+        if let Some((res, pressed)) = done {
+            self.close(id, res, pressed);
+        }
+    }
+}
+
+/// Shows the file browser, it is dismissed through the `DialogHandle` of `opt`
+fn file_dialog(opt: &FileDialog, mode: Mode) -> FileReply {
+    static NEXT_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+    let reply = Reply::new();
+    let id = NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let title = or_localized(
+        opt.title.as_deref(),
+        match mode {
+            Mode::PickFile => BuiltinString::OpenFile,
+            Mode::PickFiles => BuiltinString::OpenFiles,
+            Mode::PickFolder => BuiltinString::SelectFolder,
+            Mode::SaveFile => BuiltinString::SaveFile,
+        },
+    );
+
+    let request = Request::File {
+        id,
+        branding: Branding::new(&title, &opt.icon, &opt.app_name),
+        browser: Browser::new(opt, mode),
+        reply: reply.clone(),
+    };
+    match send(request) {
+        Ok(()) => opt.handle.on_close(move || {
+            let _ = send(Request::Close(id));
+        }),
+        Err(err) => reply.send(Err(err)),
+    }
+    reply
+}
+
+fn first<T>(items: Option<Vec<T>>) -> Option<T> {
+    items.and_then(|items| items.into_iter().next())
+}
+
+fn pick_async(
+    opt: &FileDialog,
+    mode: Mode,
+) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+    let reply = file_dialog(opt, mode);
+    Box::pin(async move {
+        let paths = reply.await?;
+        Ok(paths.map(|paths| paths.into_iter().map(FileHandle::wrap).collect()))
+    })
+}
+
+/// Shows the message box, the reply is the index of the pressed button
+fn message(opt: &MessageDialog) -> (Reply<Option<usize>>, Vec<String>) {
+    let reply = Reply::new();
+    let labels = opt.button_labels();
+    let request = Request::Message {
+        branding: Branding::new(&opt.title, &opt.icon, &opt.app_name),
+        description: opt.description.clone(),
+        focus: opt.default_button.min(labels.len().saturating_sub(1)),
+        labels: opt.shown_button_labels(),
+        reply: reply.clone(),
+    };
+    if send(request).is_err() {
+        reply.send(None);
+    }
+    (reply, labels)
+}
+
+/// Built-in dialogs drawn by rfd itself, see the [module docs](self)
+pub struct SoftwareBackend;
+
+impl DialogBackend for SoftwareBackend {
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        file_dialog(&dialog, Mode::PickFile).wait().map(first)
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        file_dialog(&dialog, Mode::PickFiles).wait()
+    }
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        file_dialog(&dialog, Mode::PickFolder).wait().map(first)
+    }
+
+    // The browser selects one folder at a time
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        file_dialog(&dialog, Mode::PickFolder).wait()
+    }
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        file_dialog(&dialog, Mode::SaveFile).wait().map(first)
+    }
+
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        message(&dialog).0.wait() == Some(0)
+    }
+
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        let (reply, labels) = message(&dialog);
+        let index = reply.wait()?;
+        labels.get(index).cloned()
+    }
+
+    fn show_message_index(&self, dialog: MessageDialog) -> Option<usize> {
+        message(&dialog).0.wait()
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(&dialog, Mode::PickFile);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        pick_async(&dialog, Mode::PickFiles)
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(&dialog, Mode::PickFolder);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        pick_async(&dialog, Mode::PickFolder)
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(&dialog, Mode::SaveFile);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        let (reply, _) = message(&dialog);
+        Box::pin(async move { reply.await == Some(0) })
+    }
+
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        let (reply, labels) = message(&dialog);
+        Box::pin(async move {
+            let index = reply.await?;
+            labels.get(index).cloned()
+        })
+    }
+
+    fn show_message_index_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<usize>> {
+        let (reply, _) = message(&dialog);
+        Box::pin(reply)
+    }
+
+    fn confirms_overwrite(&self, dialog: &FileDialog) -> Option<bool> {
+        Some(dialog.confirm_overwrite != Some(false))
+    }
+
+    fn is_available(&self) -> bool {
+        ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()))
+    }
+}
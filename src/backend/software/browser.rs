@@ -0,0 +1,461 @@
+//! State of the file browser, it knows nothing about the window it is drawn in
+
+use std::path::{Path, PathBuf};
+
+use crate::dialog::Filter;
+use crate::FileDialog;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    PickFile,
+    PickFiles,
+    PickFolder,
+    SaveFile,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+/// Keys the browser reacts to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    /// Accepts the current folder, or the highlighted entry (Ctrl+Enter)
+    Accept,
+    Escape,
+    Backspace,
+    /// Next filter
+    Tab,
+    /// Marks the highlighted file when picking several
+    Space,
+    /// Shows or hides hidden files (Ctrl+H)
+    ToggleHidden,
+    Char(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Continue,
+    /// The dialog is done, `None` when it was cancelled
+    Done(Option<Vec<PathBuf>>),
+}
+
+pub struct Browser {
+    pub mode: Mode,
+    pub dir: PathBuf,
+    /// `..` first, unless `dir` is the root, then folders and files sorted by name
+    pub entries: Vec<Entry>,
+    pub cursor: usize,
+    /// First entry on screen
+    pub scroll: usize,
+    /// Entries on screen, set by the view
+    pub page: usize,
+    /// Files marked with Space when picking several
+    pub marked: Vec<PathBuf>,
+    /// File name typed in a save dialog
+    pub name: String,
+    pub filters: Vec<Filter>,
+    pub filter: usize,
+    pub show_hidden: bool,
+    confirm_overwrite: bool,
+    /// File that would be replaced, waiting for confirmation
+    pub confirming: Option<PathBuf>,
+    /// Why `dir` could not be listed
+    pub error: Option<String>,
+    /// Instruction shown above the location
+    pub description: Option<String>,
+}
+
+impl Browser {
+    pub fn new(opt: &FileDialog, mode: Mode) -> Self {
+        let dir = opt
+            .starting_directory
+            .clone()
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("/"));
+        let filter = opt
+            .default_filter
+            .filter(|index| *index < opt.filters.len())
+            .unwrap_or(0);
+
+        let mut browser = Self {
+            mode,
+            dir,
+            entries: Vec::new(),
+            cursor: 0,
+            scroll: 0,
+            page: 1,
+            marked: Vec::new(),
+            name: opt.file_name.clone().unwrap_or_default(),
+            filters: opt.filters.clone(),
+            filter,
+            show_hidden: opt.show_hidden.unwrap_or(false),
+            confirm_overwrite: opt.confirm_overwrite.unwrap_or(true),
+            confirming: None,
+            error: None,
+            description: opt.description.clone(),
+        };
+        browser.load();
+        browser
+    }
+
+    /// Lists `dir` again
+    pub fn load(&mut self) {
+        self.entries.clear();
+        if let Some(parent) = self.dir.parent() {
+            self.entries.push(Entry {
+                name: "..".into(),
+                path: parent.to_path_buf(),
+                is_dir: true,
+            });
+        }
+
+        let mut listed = Vec::new();
+        match std::fs::read_dir(&self.dir) {
+            Ok(read_dir) => {
+                self.error = None;
+                for entry in read_dir.flatten() {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    let path = entry.path();
+                    // Follows symlinks, a link to a folder is a folder
+                    let is_dir = path.is_dir();
+                    if self.is_shown(&name, is_dir) {
+                        listed.push(Entry { name, path, is_dir });
+                    }
+                }
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+
+        listed.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        self.entries.extend(listed);
+        self.cursor = self.cursor.min(self.entries.len().saturating_sub(1));
+        self.scroll_to_cursor();
+    }
+
+    fn is_shown(&self, name: &str, is_dir: bool) -> bool {
+        if name.starts_with('.') && !self.show_hidden {
+            return false;
+        }
+        if is_dir {
+            return true;
+        }
+        match self.mode {
+            Mode::PickFolder => false,
+            _ => self
+                .filters
+                .get(self.filter)
+                .is_none_or(|f| matches(f, name)),
+        }
+    }
+
+    pub fn highlighted(&self) -> Option<&Entry> {
+        self.entries.get(self.cursor)
+    }
+
+    /// Name of the selected filter
+    pub fn filter_name(&self) -> Option<&str> {
+        self.filters.get(self.filter).map(|f| f.name.as_str())
+    }
+
+    fn enter(&mut self, dir: PathBuf) {
+        self.dir = dir;
+        self.cursor = 0;
+        self.scroll = 0;
+        self.load();
+    }
+
+    fn move_cursor(&mut self, by: isize) {
+        let last = self.entries.len().saturating_sub(1) as isize;
+        self.cursor = (self.cursor as isize + by).clamp(0, last) as usize;
+        self.scroll_to_cursor();
+    }
+
+    pub fn scroll_to_cursor(&mut self) {
+        let page = self.page.max(1);
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + page {
+            self.scroll = self.cursor + 1 - page;
+        }
+    }
+
+    pub fn scroll_by(&mut self, rows: isize) {
+        let max = self.entries.len().saturating_sub(self.page.max(1)) as isize;
+        self.scroll = (self.scroll as isize + rows).clamp(0, max.max(0)) as usize;
+    }
+
+    /// Highlights the entry at `index`, a save dialog takes the name of a highlighted file
+    pub fn select(&mut self, index: usize) {
+        if index >= self.entries.len() {
+            return;
+        }
+        self.cursor = index;
+        self.scroll_to_cursor();
+        if self.mode == Mode::SaveFile {
+            if let Some(entry) = self.entries.get(index).filter(|entry| !entry.is_dir) {
+                self.name = entry.name.clone();
+            }
+        }
+    }
+
+    /// Enter or a double click on the highlighted entry
+    pub fn activate(&mut self) -> Outcome {
+        let entry = match self.highlighted() {
+            Some(entry) => entry.clone(),
+            None => return Outcome::Continue,
+        };
+
+        // A typed file name is saved in the current folder
+        let typed = self.mode == Mode::SaveFile && !self.name.is_empty();
+        if entry.is_dir && !typed {
+            self.enter(entry.path);
+            return Outcome::Continue;
+        }
+        self.accept()
+    }
+
+    /// Accept button
+    pub fn accept(&mut self) -> Outcome {
+        match self.mode {
+            Mode::PickFile => match self.highlighted() {
+                Some(entry) if !entry.is_dir => Outcome::Done(Some(vec![entry.path.clone()])),
+                _ => Outcome::Continue,
+            },
+            Mode::PickFiles => {
+                if !self.marked.is_empty() {
+                    return Outcome::Done(Some(self.marked.clone()));
+                }
+                match self.highlighted() {
+                    Some(entry) if !entry.is_dir => Outcome::Done(Some(vec![entry.path.clone()])),
+                    _ => Outcome::Continue,
+                }
+            }
+            Mode::PickFolder => {
+                let dir = match self.highlighted() {
+                    Some(entry) if entry.name != ".." => entry.path.clone(),
+                    _ => self.dir.clone(),
+                };
+                Outcome::Done(Some(vec![dir]))
+            }
+            Mode::SaveFile => {
+                if self.name.is_empty() {
+                    return Outcome::Continue;
+                }
+                let path = self.dir.join(&self.name);
+                if path.is_dir() {
+                    self.name.clear();
+                    self.enter(path);
+                    return Outcome::Continue;
+                }
+                if self.confirm_overwrite && path.exists() {
+                    self.confirming = Some(path);
+                    return Outcome::Continue;
+                }
+                Outcome::Done(Some(vec![path]))
+            }
+        }
+    }
+
+    pub fn key(&mut self, key: Key) -> Outcome {
+        if let Some(path) = &self.confirming {
+            return match key {
+                Key::Enter | Key::Accept | Key::Char('y') | Key::Char('Y') => {
+                    Outcome::Done(Some(vec![path.clone()]))
+                }
+                Key::Escape | Key::Char('n') | Key::Char('N') => {
+                    self.confirming = None;
+                    Outcome::Continue
+                }
+                _ => Outcome::Continue,
+            };
+        }
+
+        let page = self.page.max(1) as isize;
+        match key {
+            Key::Up => self.move_cursor(-1),
+            Key::Down => self.move_cursor(1),
+            Key::PageUp => self.move_cursor(-page),
+            Key::PageDown => self.move_cursor(page),
+            Key::Home => self.move_cursor(-(self.entries.len() as isize)),
+            Key::End => self.move_cursor(self.entries.len() as isize),
+            Key::Enter => return self.activate(),
+            Key::Accept => return self.accept(),
+            Key::Escape => return Outcome::Done(None),
+            Key::Backspace if self.mode == Mode::SaveFile && !self.name.is_empty() => {
+                self.name.pop();
+            }
+            Key::Backspace => {
+                if let Some(parent) = self.dir.parent() {
+                    let parent = parent.to_path_buf();
+                    self.enter(parent);
+                }
+            }
+            Key::Tab if !self.filters.is_empty() => {
+                self.filter = (self.filter + 1) % self.filters.len();
+                self.load();
+            }
+            Key::Space if self.mode == Mode::PickFiles => self.toggle_mark(),
+            Key::ToggleHidden => {
+                self.show_hidden = !self.show_hidden;
+                self.load();
+            }
+            Key::Char(c) if self.mode == Mode::SaveFile && !c.is_control() && c != '/' => {
+                self.name.push(c);
+            }
+            // Typing jumps to the next entry starting with that letter
+            Key::Char(c) => self.jump_to(c),
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let path = match self.highlighted() {
+            Some(entry) if !entry.is_dir => entry.path.clone(),
+            _ => return,
+        };
+        match self.marked.iter().position(|marked| *marked == path) {
+            Some(index) => {
+                self.marked.remove(index);
+            }
+            None => self.marked.push(path),
+        }
+    }
+
+    pub fn is_marked(&self, path: &Path) -> bool {
+        self.marked.iter().any(|marked| marked == path)
+    }
+
+    fn jump_to(&mut self, c: char) {
+        let c = c.to_lowercase().next().unwrap_or(c);
+        let len = self.entries.len();
+        let found = (1..=len)
+            .map(|offset| (self.cursor + offset) % len.max(1))
+            .find(|&index| {
+                let name = self.entries[index].name.to_lowercase();
+                name.starts_with(c)
+            });
+        if let Some(index) = found {
+            self.cursor = index;
+            self.scroll_to_cursor();
+        }
+    }
+}
+
+/// Whether `name` matches one of the globs of `filter`, a filter with none matches everything
+fn matches(filter: &Filter, name: &str) -> bool {
+    let name: Vec<char> = name.chars().collect();
+    let mut globs = filter.globs().peekable();
+    if globs.peek().is_none() {
+        return true;
+    }
+    globs.any(|glob| {
+        let glob: Vec<char> = glob.chars().collect();
+        glob_match(&glob, &name)
+    })
+}
+
+/// `*` and `?` wildcards, ignoring case
+fn glob_match(glob: &[char], name: &[char]) -> bool {
+    match (glob.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some(('*', rest)), _) => {
+            glob_match(rest, name) || (!name.is_empty() && glob_match(glob, &name[1..]))
+        }
+        (Some(('?', rest)), Some((_, name_rest))) => glob_match(rest, name_rest),
+        (Some((g, rest)), Some((n, name_rest))) => {
+            g.to_lowercase().eq(n.to_lowercase()) && glob_match(rest, name_rest)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_match, Browser, Key, Mode, Outcome};
+    use crate::FileDialog;
+
+    fn glob(glob: &str, name: &str) -> bool {
+        let glob: Vec<char> = glob.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+        glob_match(&glob, &name)
+    }
+
+    #[test]
+    fn globs_match_names() {
+        assert!(glob("*.txt", "notes.TXT"));
+        assert!(glob("img_??.png", "img_01.png"));
+        assert!(!glob("img_??.png", "img_1.png"));
+        assert!(!glob("*.txt", "notes.txt.bak"));
+    }
+
+    #[test]
+    fn lists_folders_first_and_filters_files() {
+        let dir = std::env::temp_dir().join(format!("rfd_browser_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("b_folder")).unwrap();
+        for name in ["a.txt", "c.txt", "d.png", ".hidden.txt"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        let opt = FileDialog::new()
+            .set_directory(&dir)
+            .add_filter("Text", &["txt"]);
+        let mut browser = Browser::new(&opt, Mode::PickFile);
+        let names: Vec<&str> = browser.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["..", "b_folder", "a.txt", "c.txt"]);
+
+        browser.select(2);
+        assert_eq!(
+            browser.key(Key::Enter),
+            Outcome::Done(Some(vec![dir.join("a.txt")]))
+        );
+
+        browser.select(1);
+        assert_eq!(browser.key(Key::Enter), Outcome::Continue);
+        assert_eq!(browser.dir, dir.join("b_folder"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_asks_before_replacing() {
+        let dir = std::env::temp_dir().join(format!("rfd_browser_save_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("old.txt"), "").unwrap();
+
+        let opt = FileDialog::new()
+            .set_directory(&dir)
+            .set_file_name("old.txt");
+        let mut browser = Browser::new(&opt, Mode::SaveFile);
+        assert_eq!(browser.accept(), Outcome::Continue);
+        assert_eq!(browser.confirming, Some(dir.join("old.txt")));
+        assert_eq!(browser.key(Key::Escape), Outcome::Continue);
+
+        browser.key(Key::Backspace);
+        browser.key(Key::Backspace);
+        browser.key(Key::Backspace);
+        browser.key(Key::Char('m'));
+        browser.key(Key::Char('d'));
+        assert_eq!(
+            browser.key(Key::Accept),
+            Outcome::Done(Some(vec![dir.join("old.md")]))
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,439 @@
+//! Drawing of the file browser and the message box into a `softbuffer` frame
+
+use std::convert::Infallible;
+
+use embedded_graphics::mono_font::iso_8859_1::FONT_8X13;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, PrimitiveStyleBuilder, Rectangle};
+use embedded_graphics::text::{Baseline, Text};
+
+use super::browser::{Browser, Mode};
+use crate::localizer::{localize, localize_with};
+use crate::{color_scheme, text_direction, BuiltinString, ColorScheme, TextDirection};
+
+const CHAR_WIDTH: u32 = 8;
+pub const ROW_HEIGHT: u32 = 18;
+const PADDING: u32 = 8;
+const BUTTON_WIDTH: u32 = 96;
+
+/// Colors the dialogs are drawn with
+struct Palette {
+    background: Rgb888,
+    text: Rgb888,
+    dimmed: Rgb888,
+    list: Rgb888,
+    highlight: Rgb888,
+    highlight_text: Rgb888,
+    button: Rgb888,
+    border: Rgb888,
+}
+
+const LIGHT: Palette = Palette {
+    background: Rgb888::new(0xf0, 0xf0, 0xf0),
+    text: Rgb888::new(0x20, 0x20, 0x20),
+    dimmed: Rgb888::new(0x70, 0x70, 0x70),
+    list: Rgb888::new(0xff, 0xff, 0xff),
+    highlight: Rgb888::new(0x30, 0x74, 0xc4),
+    highlight_text: Rgb888::new(0xff, 0xff, 0xff),
+    button: Rgb888::new(0xdd, 0xdd, 0xdd),
+    border: Rgb888::new(0x90, 0x90, 0x90),
+};
+
+const DARK: Palette = Palette {
+    background: Rgb888::new(0x2b, 0x2b, 0x2b),
+    text: Rgb888::new(0xe8, 0xe8, 0xe8),
+    dimmed: Rgb888::new(0x9a, 0x9a, 0x9a),
+    list: Rgb888::new(0x1e, 0x1e, 0x1e),
+    highlight: Rgb888::new(0x35, 0x84, 0xe4),
+    highlight_text: Rgb888::new(0xff, 0xff, 0xff),
+    button: Rgb888::new(0x45, 0x45, 0x45),
+    border: Rgb888::new(0x6a, 0x6a, 0x6a),
+};
+
+/// Dark colors when `set_color_scheme` asks for them, the light ones otherwise
+fn palette() -> &'static Palette {
+    match color_scheme() {
+        ColorScheme::Dark => &DARK,
+        ColorScheme::Auto | ColorScheme::Light => &LIGHT,
+    }
+}
+
+/// `0RGB` pixels of a frame
+pub struct Canvas<'a> {
+    pub pixels: &'a mut [u32],
+    pub width: u32,
+    pub height: u32,
+}
+
+impl OriginDimensions for Canvas<'_> {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+impl DrawTarget for Canvas<'_> {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (x, y) = (point.x as u32, point.y as u32);
+            if point.x >= 0 && point.y >= 0 && x < self.width && y < self.height {
+                let index = (y * self.width + x) as usize;
+                self.pixels[index] =
+                    (color.r() as u32) << 16 | (color.g() as u32) << 8 | color.b() as u32;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn fill(canvas: &mut Canvas, rect: Rectangle, color: Rgb888) {
+    let _ = rect
+        .into_styled(PrimitiveStyle::with_fill(color))
+        .draw(canvas);
+}
+
+fn outline(canvas: &mut Canvas, rect: Rectangle, fill: Rgb888, border: Rgb888) {
+    let style = PrimitiveStyleBuilder::new()
+        .fill_color(fill)
+        .stroke_color(border)
+        .stroke_width(1)
+        .build();
+    let _ = rect.into_styled(style).draw(canvas);
+}
+
+/// Whether `set_text_direction` asked for a mirrored layout
+fn rtl() -> bool {
+    text_direction() == TextDirection::Rtl
+}
+
+/// `rect` flipped to the other side of a window `width` pixels wide
+fn mirrored(rect: Rectangle, width: u32) -> Rectangle {
+    let x = width as i32 - rect.top_left.x - rect.size.width as i32;
+    Rectangle::new(Point::new(x, rect.top_left.y), rect.size)
+}
+
+/// Draws `text` from `(x, y)`, cut off at `max_width`, or against the right end in RTL layouts
+fn text(canvas: &mut Canvas, text: &str, x: u32, y: u32, max_width: u32, color: Rgb888) {
+    let max_chars = (max_width / CHAR_WIDTH) as usize;
+    let shown: String = if text.chars().count() > max_chars {
+        let mut cut: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+        cut.push_str("...");
+        cut
+    } else {
+        text.to_string()
+    };
+
+    let x = if rtl() {
+        x + max_width.saturating_sub(text_width(&shown))
+    } else {
+        x
+    };
+    let style = MonoTextStyle::new(&FONT_8X13, color);
+    // Rows are a bit taller than the font
+    let top = y as i32 + 2;
+    let _ =
+        Text::with_baseline(&shown, Point::new(x as i32, top), style, Baseline::Top).draw(canvas);
+}
+
+fn text_width(text: &str) -> u32 {
+    text.chars().count() as u32 * CHAR_WIDTH
+}
+
+fn button(canvas: &mut Canvas, rect: Rectangle, label: &str, focused: bool) {
+    let colors = palette();
+    let border = if focused {
+        colors.highlight
+    } else {
+        colors.border
+    };
+    outline(canvas, rect, colors.button, border);
+    let width = text_width(label).min(rect.size.width);
+    let x = rect.top_left.x as u32 + (rect.size.width - width) / 2;
+    let y = rect.top_left.y as u32 + (rect.size.height - ROW_HEIGHT) / 2;
+    text(canvas, label, x, y, width, colors.text);
+}
+
+/// Parts of the file browser, in window pixels
+pub struct Layout {
+    pub list: Rectangle,
+    pub cancel: Rectangle,
+    pub accept: Rectangle,
+}
+
+impl Layout {
+    /// `header` makes room for a description above the location
+    pub fn new(width: u32, height: u32, header: bool) -> Self {
+        let width = width.max(2 * BUTTON_WIDTH + 3 * PADDING);
+        let height = height.max((8 + header as u32) * ROW_HEIGHT);
+
+        let list_top = (1 + header as u32) * ROW_HEIGHT + 2 * PADDING;
+        // Two lines and the buttons below the list
+        let list_bottom = height - 3 * ROW_HEIGHT - 3 * PADDING;
+        let button_y = (height - ROW_HEIGHT - 2 * PADDING) as i32;
+        let button_size = Size::new(BUTTON_WIDTH, ROW_HEIGHT + PADDING);
+
+        let layout = Self {
+            list: Rectangle::new(
+                Point::new(PADDING as i32, list_top as i32),
+                Size::new(width - 2 * PADDING, list_bottom - list_top),
+            ),
+            accept: Rectangle::new(
+                Point::new((width - PADDING - BUTTON_WIDTH) as i32, button_y),
+                button_size,
+            ),
+            cancel: Rectangle::new(
+                Point::new((width - 2 * PADDING - 2 * BUTTON_WIDTH) as i32, button_y),
+                button_size,
+            ),
+        };
+        if rtl() {
+            Self {
+                cancel: mirrored(layout.cancel, width),
+                accept: mirrored(layout.accept, width),
+                ..layout
+            }
+        } else {
+            layout
+        }
+    }
+
+    /// Entries that fit in the list
+    pub fn rows(&self) -> usize {
+        (self.list.size.height / ROW_HEIGHT).max(1) as usize
+    }
+
+    /// Entry under the window position `(x, y)`
+    pub fn row_at(&self, browser: &Browser, x: f64, y: f64) -> Option<usize> {
+        if !contains(&self.list, x, y) {
+            return None;
+        }
+        let row = (y as i32 - self.list.top_left.y) as u32 / ROW_HEIGHT;
+        let index = browser.scroll + row as usize;
+        Some(index).filter(|index| *index < browser.entries.len())
+    }
+}
+
+pub fn contains(rect: &Rectangle, x: f64, y: f64) -> bool {
+    rect.contains(Point::new(x as i32, y as i32))
+}
+
+pub fn accept_label(mode: Mode) -> String {
+    localize(match mode {
+        Mode::PickFile | Mode::PickFiles => BuiltinString::Open,
+        Mode::PickFolder => BuiltinString::Select,
+        Mode::SaveFile => BuiltinString::Save,
+    })
+}
+
+pub fn draw_browser(canvas: &mut Canvas, browser: &Browser, layout: &Layout, accept: &str) {
+    let colors = palette();
+    let (width, height) = (canvas.width, canvas.height);
+    let max_width = width - 2 * PADDING;
+    fill(
+        canvas,
+        Rectangle::new(Point::zero(), Size::new(width, height)),
+        colors.background,
+    );
+
+    let mut top = PADDING;
+    if let Some(description) = &browser.description {
+        text(canvas, description, PADDING, top, max_width, colors.text);
+        top += ROW_HEIGHT;
+    }
+
+    let location = browser.dir.to_string_lossy();
+    text(canvas, &location, PADDING, top, max_width, colors.text);
+
+    let list = layout.list;
+    outline(canvas, list, colors.list, colors.border);
+    let (list_x, list_y) = (list.top_left.x as u32, list.top_left.y as u32);
+
+    if let Some(error) = &browser.error {
+        text(
+            canvas,
+            error,
+            list_x + PADDING,
+            list_y + ROW_HEIGHT,
+            list.size.width,
+            colors.dimmed,
+        );
+    }
+
+    let shown = browser
+        .entries
+        .iter()
+        .enumerate()
+        .skip(browser.scroll)
+        .take(layout.rows());
+    for (row, (index, entry)) in shown.enumerate() {
+        let y = list_y + row as u32 * ROW_HEIGHT;
+        let highlighted = index == browser.cursor;
+        let color = if highlighted {
+            let rect = Rectangle::new(
+                Point::new(list_x as i32 + 1, y as i32),
+                Size::new(list.size.width - 2, ROW_HEIGHT),
+            );
+            fill(canvas, rect, colors.highlight);
+            colors.highlight_text
+        } else {
+            colors.text
+        };
+
+        let mark = if browser.is_marked(&entry.path) {
+            "* "
+        } else {
+            "  "
+        };
+        let suffix = if entry.is_dir { "/" } else { "" };
+        let label = format!("{}{}{}", mark, entry.name, suffix);
+        text(canvas, &label, list_x + 4, y, list.size.width - 8, color);
+    }
+
+    // Name of the new file, or how many files are marked
+    let line = list_y + list.size.height + PADDING;
+    match browser.mode {
+        Mode::SaveFile => {
+            let name = localize_with(BuiltinString::NameField, &format!("{}_", browser.name));
+            text(canvas, &name, PADDING, line, max_width, colors.text);
+        }
+        Mode::PickFiles if !browser.marked.is_empty() => {
+            let count = browser.marked.len().to_string();
+            let marked = localize_with(BuiltinString::SelectedCount, &count);
+            text(canvas, &marked, PADDING, line, max_width, colors.text);
+        }
+        Mode::PickFiles => {
+            let hint = localize(BuiltinString::MultiSelectHint);
+            text(canvas, &hint, PADDING, line, max_width, colors.dimmed);
+        }
+        _ => {}
+    }
+    if let Some(filter) = browser.filter_name() {
+        let filter = localize_with(BuiltinString::FilterField, filter);
+        let line = line + ROW_HEIGHT;
+        text(canvas, &filter, PADDING, line, max_width, colors.text);
+    }
+
+    let cancel = localize(BuiltinString::Cancel);
+    button(canvas, layout.cancel, &cancel, false);
+    button(canvas, layout.accept, accept, true);
+
+    if let Some(path) = &browser.confirming {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let question = localize_with(BuiltinString::ReplaceQuestion, &name);
+        let band = Rectangle::new(
+            Point::new(0, (height / 2 - ROW_HEIGHT) as i32),
+            Size::new(width, 2 * ROW_HEIGHT),
+        );
+        outline(canvas, band, colors.button, colors.highlight);
+        let y = height / 2 - ROW_HEIGHT / 2;
+        text(canvas, &question, PADDING, y, max_width, colors.text);
+    }
+}
+
+/// Lines of `text` no wider than `max_chars`, broken at spaces where possible
+pub fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let len = line.chars().count();
+            if len > 0 && len + 1 + word.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            } else if len > 0 {
+                line.push(' ');
+            }
+            line.push_str(word);
+            // Words longer than a line are broken anywhere
+            while line.chars().count() > max_chars {
+                let rest: String = line.chars().skip(max_chars).collect();
+                line = line.chars().take(max_chars).collect();
+                lines.push(std::mem::replace(&mut line, rest));
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Buttons of a message box, right aligned in the last row, or left aligned in RTL layouts
+pub fn message_buttons(width: u32, height: u32, labels: &[String]) -> Vec<Rectangle> {
+    let y = (height.max(ROW_HEIGHT * 3) - ROW_HEIGHT - 2 * PADDING) as i32;
+    let mut x = width as i32 - PADDING as i32;
+    let mut rects: Vec<Rectangle> = labels
+        .iter()
+        .rev()
+        .map(|label| {
+            let button_width = (text_width(label) + 2 * PADDING).max(BUTTON_WIDTH);
+            x -= button_width as i32;
+            let rect = Rectangle::new(
+                Point::new(x, y),
+                Size::new(button_width, ROW_HEIGHT + PADDING),
+            );
+            x -= PADDING as i32;
+            rect
+        })
+        .collect();
+    rects.reverse();
+    if rtl() {
+        rects = rects
+            .into_iter()
+            .map(|rect| mirrored(rect, width))
+            .collect();
+    }
+    rects
+}
+
+pub fn draw_message(canvas: &mut Canvas, description: &str, labels: &[String], focus: usize) {
+    let colors = palette();
+    let (width, height) = (canvas.width, canvas.height);
+    fill(
+        canvas,
+        Rectangle::new(Point::zero(), Size::new(width, height)),
+        colors.background,
+    );
+
+    let max_chars = ((width.saturating_sub(2 * PADDING)) / CHAR_WIDTH) as usize;
+    for (row, line) in wrap(description, max_chars).iter().enumerate() {
+        let y = PADDING + row as u32 * ROW_HEIGHT;
+        text(canvas, line, PADDING, y, width - 2 * PADDING, colors.text);
+    }
+
+    for (index, (rect, label)) in message_buttons(width, height, labels)
+        .into_iter()
+        .zip(labels)
+        .enumerate()
+    {
+        button(canvas, rect, label, index == focus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mirrored, wrap};
+    use embedded_graphics::prelude::*;
+    use embedded_graphics::primitives::Rectangle;
+
+    #[test]
+    fn wraps_at_spaces() {
+        assert_eq!(wrap("one two three", 8), ["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("a\nb", 10), ["a", "b"]);
+    }
+
+    #[test]
+    fn mirrors_to_the_other_side() {
+        let rect = Rectangle::new(Point::new(8, 4), Size::new(96, 26));
+        let flipped = mirrored(rect, 400);
+        assert_eq!(flipped.top_left, Point::new(296, 4));
+        assert_eq!(flipped.size, rect.size);
+        assert_eq!(mirrored(flipped, 400), rect);
+    }
+}
@@ -0,0 +1,407 @@
+//! Wraps the backend of every dialog with the `tracing` feature, logging the options each dialog
+//! was shown with and how it ended
+
+use std::fmt::Debug;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tracing::{debug, debug_span, warn, Instrument, Span};
+
+use crate::backend::{DialogBackend, DialogFutureType};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::Url;
+use crate::{
+    CredentialDialog, Credentials, Error, FileDialog, FileHandle, InputDialog, MessageDialog,
+    Notification, PickListDialog, ProgressDialog, ProgressState,
+};
+
+pub(crate) struct Traced {
+    pub(crate) backend: Arc<dyn DialogBackend>,
+    /// Built-in backend, or `custom` for the one registered with `set_backend`
+    pub(crate) name: &'static str,
+}
+
+impl Traced {
+    fn span(&self, method: &'static str) -> Span {
+        debug_span!("rfd", method, backend = self.name)
+    }
+
+    /// Shows a dialog that can't fail
+    fn run<D: Debug, T: Debug>(
+        &self,
+        method: &'static str,
+        dialog: D,
+        show: impl FnOnce(D) -> T,
+    ) -> T {
+        let _span = self.span(method).entered();
+        debug!(?dialog, "showing dialog");
+        let res = show(dialog);
+        debug!(result = ?res, "dialog closed");
+        res
+    }
+
+    /// Shows a dialog that reports errors, they are logged as warnings
+    fn run_fallible<D: Debug, T: Debug>(
+        &self,
+        method: &'static str,
+        dialog: D,
+        show: impl FnOnce(D) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let _span = self.span(method).entered();
+        debug!(?dialog, "showing dialog");
+        let res = show(dialog);
+        finished(&res);
+        res
+    }
+
+    fn run_async<D: Debug, T: Debug + 'static>(
+        &self,
+        method: &'static str,
+        dialog: D,
+        show: impl FnOnce(D) -> DialogFutureType<T>,
+    ) -> DialogFutureType<T> {
+        let span = self.span(method);
+        let future = span.in_scope(|| {
+            debug!(?dialog, "showing dialog");
+            show(dialog)
+        });
+        Box::pin(
+            async move {
+                let res = future.await;
+                debug!(result = ?res, "dialog closed");
+                res
+            }
+            .instrument(span),
+        )
+    }
+
+    fn run_fallible_async<D: Debug, T: Debug + 'static>(
+        &self,
+        method: &'static str,
+        dialog: D,
+        show: impl FnOnce(D) -> DialogFutureType<Result<T, Error>>,
+    ) -> DialogFutureType<Result<T, Error>> {
+        let span = self.span(method);
+        let future = span.in_scope(|| {
+            debug!(?dialog, "showing dialog");
+            show(dialog)
+        });
+        Box::pin(
+            async move {
+                let res = future.await;
+                finished(&res);
+                res
+            }
+            .instrument(span),
+        )
+    }
+}
+
+fn finished<T: Debug>(res: &Result<T, Error>) {
+    match res {
+        Ok(res) => debug!(result = ?res, "dialog closed"),
+        Err(err) => warn!(error = %err, native_code = ?err.native_code(), "dialog failed"),
+    }
+}
+
+impl DialogBackend for Traced {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        self.run_fallible("pick_file", dialog, |d| self.backend.pick_file(d))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        self.run_fallible("pick_files", dialog, |d| self.backend.pick_files(d))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        self.run_fallible("pick_folder", dialog, |d| self.backend.pick_folder(d))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        self.run_fallible("pick_folders", dialog, |d| self.backend.pick_folders(d))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_path(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        self.run_fallible("pick_path", dialog, |d| self.backend.pick_path(d))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_file_url(&self, dialog: FileDialog) -> Result<Option<Url>, Error> {
+        self.run_fallible("pick_file_url", dialog, |d| self.backend.pick_file_url(d))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        self.run_fallible("save_file", dialog, |d| self.backend.save_file(d))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_file_with_filter(&self, dialog: FileDialog) -> Result<Option<(PathBuf, usize)>, Error> {
+        self.run_fallible("save_file_with_filter", dialog, |d| {
+            self.backend.save_file_with_filter(d)
+        })
+    }
+
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        self.run("show_message", dialog, |d| self.backend.show_message(d))
+    }
+
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        self.run("show_message_custom", dialog, |d| {
+            self.backend.show_message_custom(d)
+        })
+    }
+
+    fn show_message_index(&self, dialog: MessageDialog) -> Option<usize> {
+        self.run("show_message_index", dialog, |d| {
+            self.backend.show_message_index(d)
+        })
+    }
+
+    fn show_input(&self, dialog: InputDialog) -> Option<String> {
+        // The entered text may be a password, only whether there is one is logged
+        let text = self.run("show_input", dialog, |d| {
+            Entered(self.backend.show_input(d))
+        });
+        text.0
+    }
+
+    fn show_pick_list(&self, dialog: PickListDialog) -> Option<usize> {
+        self.run("show_pick_list", dialog, |d| self.backend.show_pick_list(d))
+    }
+
+    fn show_credentials(&self, dialog: CredentialDialog) -> Option<Credentials> {
+        self.run("show_credentials", dialog, |d| {
+            self.backend.show_credentials(d)
+        })
+    }
+
+    fn try_show_message(&self, dialog: MessageDialog) -> Result<Option<String>, Error> {
+        self.run_fallible("try_show_message", dialog, |d| {
+            self.backend.try_show_message(d)
+        })
+    }
+
+    fn try_show_input(&self, dialog: InputDialog) -> Result<Option<String>, Error> {
+        let text = self.run_fallible("try_show_input", dialog, |d| {
+            self.backend.try_show_input(d).map(Entered)
+        });
+        text.map(|text| text.0)
+    }
+
+    fn try_show_pick_list(&self, dialog: PickListDialog) -> Result<Option<usize>, Error> {
+        self.run_fallible("try_show_pick_list", dialog, |d| {
+            self.backend.try_show_pick_list(d)
+        })
+    }
+
+    fn try_show_credentials(&self, dialog: CredentialDialog) -> Result<Option<Credentials>, Error> {
+        self.run_fallible("try_show_credentials", dialog, |d| {
+            self.backend.try_show_credentials(d)
+        })
+    }
+
+    fn show_progress(&self, dialog: ProgressDialog, state: ProgressState) {
+        let _span = self.span("show_progress").entered();
+        debug!(?dialog, "showing dialog");
+        self.backend.show_progress(dialog, state)
+    }
+
+    fn show_notification(&self, notification: Notification) -> Result<(), Error> {
+        let _span = self.span("show_notification").entered();
+        debug!(?notification, "showing notification");
+        let res = self.backend.show_notification(notification);
+        finished(&res);
+        res
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        self.run_fallible_async("pick_file_async", dialog, |d| {
+            self.backend.pick_file_async(d)
+        })
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        self.run_fallible_async("pick_files_async", dialog, |d| {
+            self.backend.pick_files_async(d)
+        })
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        self.run_fallible_async("pick_folder_async", dialog, |d| {
+            self.backend.pick_folder_async(d)
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        self.run_fallible_async("pick_folders_async", dialog, |d| {
+            self.backend.pick_folders_async(d)
+        })
+    }
+
+    fn pick_path_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        self.run_fallible_async("pick_path_async", dialog, |d| {
+            self.backend.pick_path_async(d)
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_file_url_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Url>, Error>> {
+        self.run_fallible_async("pick_file_url_async", dialog, |d| {
+            self.backend.pick_file_url_async(d)
+        })
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        self.run_fallible_async("save_file_async", dialog, |d| {
+            self.backend.save_file_async(d)
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_file_with_filter_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<(FileHandle, usize)>, Error>> {
+        self.run_fallible_async("save_file_with_filter_async", dialog, |d| {
+            self.backend.save_file_with_filter_async(d)
+        })
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        self.run_async("show_message_async", dialog, |d| {
+            self.backend.show_message_async(d)
+        })
+    }
+
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        self.run_async("show_message_custom_async", dialog, |d| {
+            self.backend.show_message_custom_async(d)
+        })
+    }
+
+    fn show_message_index_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<usize>> {
+        self.run_async("show_message_index_async", dialog, |d| {
+            self.backend.show_message_index_async(d)
+        })
+    }
+
+    fn show_input_async(&self, dialog: InputDialog) -> DialogFutureType<Option<String>> {
+        let future = self.run_async("show_input_async", dialog, |d| {
+            let future = self.backend.show_input_async(d);
+            Box::pin(async move { Entered(future.await) })
+        });
+        Box::pin(async move { future.await.0 })
+    }
+
+    fn show_pick_list_async(&self, dialog: PickListDialog) -> DialogFutureType<Option<usize>> {
+        self.run_async("show_pick_list_async", dialog, |d| {
+            self.backend.show_pick_list_async(d)
+        })
+    }
+
+    fn show_credentials_async(
+        &self,
+        dialog: CredentialDialog,
+    ) -> DialogFutureType<Option<Credentials>> {
+        self.run_async("show_credentials_async", dialog, |d| {
+            self.backend.show_credentials_async(d)
+        })
+    }
+
+    fn try_show_message_async(
+        &self,
+        dialog: MessageDialog,
+    ) -> DialogFutureType<Result<Option<String>, Error>> {
+        self.run_fallible_async("try_show_message_async", dialog, |d| {
+            self.backend.try_show_message_async(d)
+        })
+    }
+
+    fn try_show_input_async(
+        &self,
+        dialog: InputDialog,
+    ) -> DialogFutureType<Result<Option<String>, Error>> {
+        let future = self.run_fallible_async("try_show_input_async", dialog, |d| {
+            let future = self.backend.try_show_input_async(d);
+            Box::pin(async move { future.await.map(Entered) })
+        });
+        Box::pin(async move { future.await.map(|text| text.0) })
+    }
+
+    fn try_show_pick_list_async(
+        &self,
+        dialog: PickListDialog,
+    ) -> DialogFutureType<Result<Option<usize>, Error>> {
+        self.run_fallible_async("try_show_pick_list_async", dialog, |d| {
+            self.backend.try_show_pick_list_async(d)
+        })
+    }
+
+    fn try_show_credentials_async(
+        &self,
+        dialog: CredentialDialog,
+    ) -> DialogFutureType<Result<Option<Credentials>, Error>> {
+        self.run_fallible_async("try_show_credentials_async", dialog, |d| {
+            self.backend.try_show_credentials_async(d)
+        })
+    }
+
+    fn is_available(&self) -> bool {
+        self.backend.is_available()
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.backend.is_interactive()
+    }
+
+    fn confirms_overwrite(&self, dialog: &FileDialog) -> Option<bool> {
+        self.backend.confirms_overwrite(dialog)
+    }
+
+    fn init(&self) {
+        let _span = self.span("init").entered();
+        self.backend.init();
+        debug!("backend initialized");
+    }
+}
+
+/// Text of an input dialog, logged without the text
+struct Entered(Option<String>);
+
+impl Debug for Entered {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("Some(..)"),
+            None => f.write_str("None"),
+        }
+    }
+}
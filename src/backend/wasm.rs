@@ -5,7 +5,8 @@ use web_sys::Element;
 use web_sys::{HtmlButtonElement, HtmlInputElement};
 
 use crate::dialog::FileDialog;
-use crate::FileHandle;
+use crate::localizer::localize;
+use crate::{color_scheme, BuiltinString, ColorScheme, FileHandle};
 
 pub struct WasmDialog {
     overlay: Element,
@@ -23,6 +24,15 @@ impl WasmDialog {
 
         let overlay = document.create_element("div").unwrap();
         overlay.set_id("rfd-overlay");
+        // Picked up by the stylesheet, without it the card keeps its light look
+        let scheme = match color_scheme() {
+            ColorScheme::Auto => None,
+            ColorScheme::Dark => Some("dark"),
+            ColorScheme::Light => Some("light"),
+        };
+        if let Some(scheme) = scheme {
+            overlay.set_attribute("data-color-scheme", scheme).unwrap();
+        }
 
         let card = {
             let card = document.create_element("div").unwrap();
@@ -42,11 +52,15 @@ impl WasmDialog {
             let mut accept: Vec<String> = Vec::new();
 
             for filter in opt.filters.iter() {
-                accept.append(&mut filter.extensions.to_vec());
+                accept.extend(filter.plain_extensions());
             }
 
             accept.iter_mut().for_each(|ext| ext.insert_str(0, "."));
 
+            for filter in opt.filters.iter() {
+                accept.append(&mut filter.mime_types.to_vec());
+            }
+
             input.set_accept(&accept.join(","));
 
             card.append_child(&input).unwrap();
@@ -58,7 +72,7 @@ impl WasmDialog {
             let btn: HtmlButtonElement = wasm_bindgen::JsCast::dyn_into(btn_el).unwrap();
 
             btn.set_id("rfd-button");
-            btn.set_inner_text("Ok");
+            btn.set_inner_text(&localize(BuiltinString::Ok));
 
             card.append_child(&btn).unwrap();
             btn
@@ -130,6 +144,20 @@ impl WasmDialog {
         self.get_results()
     }
 
+    async fn pick_folder(self) -> Option<FileHandle> {
+        self.input.set_attribute("webkitdirectory", "").unwrap();
+
+        self.show().await;
+
+        let files = self.input.files()?;
+        let files: Vec<web_sys::File> =
+            (0..files.length()).filter_map(|id| files.get(id)).collect();
+        if files.is_empty() {
+            return None;
+        }
+        FileHandle::folder(files).ok()
+    }
+
     async fn pick_file(self) -> Option<FileHandle> {
         self.input.set_multiple(false);
 
@@ -150,18 +178,98 @@ impl Drop for WasmDialog {
     }
 }
 
-use std::future::Future;
+use super::{DialogBackend, DialogFutureType};
+use crate::dialog::{InputDialog, MessageButtons, MessageDialog, PickListDialog};
+use crate::Error;
+
+/// Save dialog of the File System Access API, its handle can be written.
+/// Other browsers have no save dialog, the handle downloads what is written to it.
+async fn save_file(opt: FileDialog) -> Result<Option<FileHandle>, Error> {
+    let window = web_sys::window().expect("Window not found");
+    let picker = js_sys::Reflect::get(&window, &"showSaveFilePicker".into())
+        .ok()
+        .and_then(|picker| picker.dyn_into::<js_sys::Function>().ok());
+    let picker = match picker {
+        Some(picker) => picker,
+        None => {
+            let file_name = opt.file_name.as_deref().unwrap_or("download");
+            return FileHandle::download(file_name)
+                .map(Some)
+                .map_err(|_| Error::Unsupported);
+        }
+    };
 
-use super::{AsyncFilePickerDialogImpl, DialogFutureType};
+    let options = js_sys::Object::new();
+    if let Some(file_name) = &opt.file_name {
+        js_sys::Reflect::set(&options, &"suggestedName".into(), &file_name.into()).unwrap();
+    }
 
-impl AsyncFilePickerDialogImpl for FileDialog {
-    fn pick_file_async(self) -> DialogFutureType<Option<FileHandle>> {
-        let dialog = WasmDialog::new(&self);
-        Box::pin(dialog.pick_file())
+    // [{ description, accept: { "<mime type>": [".<extension>"] } }]
+    let types: js_sys::Array = opt
+        .filters
+        .iter()
+        .map(|filter| {
+            let extensions: js_sys::Array = filter
+                .extensions
+                .iter()
+                .map(|ext| JsValue::from(format!(".{}", ext)))
+                .collect();
+            let mime_type = filter
+                .mime_types
+                .first()
+                .map(String::as_str)
+                .unwrap_or("application/octet-stream");
+            let accept = js_sys::Object::new();
+            js_sys::Reflect::set(&accept, &mime_type.into(), &extensions).unwrap();
+
+            let file_type = js_sys::Object::new();
+            js_sys::Reflect::set(
+                &file_type,
+                &"description".into(),
+                &filter.name.as_str().into(),
+            )
+            .unwrap();
+            js_sys::Reflect::set(&file_type, &"accept".into(), &accept).unwrap();
+            file_type
+        })
+        .collect();
+    if types.length() > 0 {
+        js_sys::Reflect::set(&options, &"types".into(), &types).unwrap();
     }
-    fn pick_files_async(self) -> DialogFutureType<Option<Vec<FileHandle>>> {
-        let dialog = WasmDialog::new(&self);
-        Box::pin(dialog.pick_files())
+
+    let promise = picker
+        .call1(&window, &options)
+        .ok()
+        .and_then(|promise| promise.dyn_into::<js_sys::Promise>().ok())
+        .ok_or(Error::Unsupported)?;
+
+    // Rejected with an `AbortError` when the user cancels, and when the page
+    // is not allowed to show the picker, eg. outside of a click handler
+    let handle = match wasm_bindgen_futures::JsFuture::from(promise).await {
+        Ok(handle) => handle,
+        Err(_) => return Ok(None),
+    };
+    Ok(FileHandle::from_file_system_handle(handle).await.ok())
+}
+
+/// Folder picker of the File System Access API, with an `<input webkitdirectory>` in other browsers
+async fn pick_folder(opt: FileDialog) -> Result<Option<FileHandle>, Error> {
+    let window = web_sys::window().expect("Window not found");
+    let picker = js_sys::Reflect::get(&window, &"showDirectoryPicker".into())
+        .ok()
+        .and_then(|picker| picker.dyn_into::<js_sys::Function>().ok());
+    let promise = picker
+        .and_then(|picker| picker.call0(&window).ok())
+        .and_then(|promise| promise.dyn_into::<js_sys::Promise>().ok());
+    let promise = match promise {
+        Some(promise) => promise,
+        None => return Ok(WasmDialog::new(&opt).pick_folder().await),
+    };
+
+    // Rejected with an `AbortError` when the user cancels
+    match wasm_bindgen_futures::JsFuture::from(promise).await {
+        Ok(handle) => Ok(FileHandle::from_directory_handle(handle).ok()),
+        Err(_) => Ok(None),
     }
 }
 
@@ -169,29 +277,85 @@ impl AsyncFilePickerDialogImpl for FileDialog {
 extern "C" {
     fn alert(s: &str);
     fn confirm(s: &str) -> bool;
+    fn prompt(message: &str, default: &str) -> Option<String>;
 }
 
-use crate::backend::MessageDialogImpl;
-use crate::dialog::{MessageButtons, MessageDialog};
+/// WASM32 backend, file input overlay and browser `alert`/`confirm`/`prompt`
+pub struct WasmBackend;
+
+impl DialogBackend for WasmBackend {
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let dialog = WasmDialog::new(&dialog);
+        Box::pin(async move { Ok(dialog.pick_file().await) })
+    }
 
-impl MessageDialogImpl for MessageDialog {
-    fn show(self) -> bool {
-        let text = format!("{}\n{}", self.title, self.description);
-        match self.buttons {
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        let dialog = WasmDialog::new(&dialog);
+        Box::pin(async move { Ok(dialog.pick_files().await) })
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        Box::pin(pick_folder(dialog))
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        Box::pin(save_file(dialog))
+    }
+
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        let text = format!(
+            "{}\n{}",
+            dialog.title,
+            crate::dialog_links::plain_description(&dialog)
+        );
+        match dialog.buttons {
             MessageButtons::Ok => {
                 alert(&text);
                 true
             }
-            MessageButtons::OkCancel | MessageButtons::YesNo => confirm(&text),
+            MessageButtons::OkCancel | MessageButtons::YesNo | MessageButtons::YesNoCancel => {
+                confirm(&text)
+            }
         }
     }
-}
-
-use crate::backend::AsyncMessageDialogImpl;
 
-impl AsyncMessageDialogImpl for MessageDialog {
-    fn show_async(self) -> DialogFutureType<bool> {
-        let val = MessageDialogImpl::show(self);
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        let val = self.show_message(dialog);
         Box::pin(std::future::ready(val))
     }
+
+    fn show_input(&self, dialog: InputDialog) -> Option<String> {
+        // `prompt` would show the password in plain text
+        if dialog.password {
+            return None;
+        }
+
+        let text = format!("{}\n{}", dialog.title, dialog.prompt);
+        prompt(&text, &dialog.default)
+    }
+
+    fn show_pick_list(&self, dialog: PickListDialog) -> Option<usize> {
+        // The browser has no list prompt, the items are numbered and the user types a number
+        let mut text = format!("{}\n{}", dialog.title, dialog.prompt);
+        for (i, item) in dialog.items.iter().enumerate() {
+            text.push_str(&format!("\n{}. {}", i + 1, item));
+        }
+
+        let number: usize = prompt(&text, "1")?.trim().parse().ok()?;
+        number
+            .checked_sub(1)
+            .filter(|&index| index < dialog.items.len())
+    }
 }
@@ -0,0 +1,163 @@
+use super::message_dialog::wide;
+use super::thread_future::ThreadFuture;
+use crate::backend::DialogFutureType;
+use crate::{CredentialDialog, Credentials};
+
+use winapi::shared::minwindef::{DWORD, FALSE, ULONG};
+use winapi::shared::winerror::ERROR_SUCCESS;
+use winapi::um::combaseapi::CoTaskMemFree;
+use winapi::um::wincred::{
+    CredPackAuthenticationBufferW, CredUIPromptForWindowsCredentialsW,
+    CredUnPackAuthenticationBufferW, CREDUIWIN_GENERIC, CREDUI_INFOW,
+    CREDUI_MAX_DOMAIN_TARGET_LENGTH, CREDUI_MAX_PASSWORD_LENGTH, CREDUI_MAX_USERNAME_LENGTH,
+};
+
+#[cfg(feature = "parent")]
+use raw_window_handle::RawWindowHandle;
+
+use std::{ffi::c_void, mem, ptr};
+
+pub struct WinCredentialDialog {
+    parent: Option<*mut c_void>,
+    title: Vec<u16>,
+    message: Vec<u16>,
+    user: Vec<u16>,
+}
+
+// Oh god, I don't like sending RawWindowHandle between threads but here we go anyways...
+// fingers crossed
+unsafe impl Send for WinCredentialDialog {}
+
+impl WinCredentialDialog {
+    pub fn new(opt: CredentialDialog) -> Self {
+        #[cfg(feature = "parent")]
+        let parent = match opt.parent() {
+            Some(RawWindowHandle::Windows(handle)) => Some(handle.hwnd),
+            None => None,
+            _ => unreachable!("unsupported window handle, expected: Windows"),
+        };
+        #[cfg(not(feature = "parent"))]
+        let parent = None;
+
+        Self {
+            parent,
+            title: wide(&opt.title),
+            message: wide(&opt.message),
+            user: wide(&opt.user),
+        }
+    }
+
+    pub fn run(self) -> Option<Credentials> {
+        unsafe {
+            let mut info: CREDUI_INFOW = mem::zeroed();
+            info.cbSize = mem::size_of::<CREDUI_INFOW>() as DWORD;
+            info.hwndParent = self.parent.unwrap_or_else(|| ptr::null_mut()) as _;
+            info.pszCaptionText = self.title.as_ptr();
+            info.pszMessageText = self.message.as_ptr();
+
+            // The user name is passed in as a packed buffer with an empty password
+            let empty = [0u16];
+            let mut size: DWORD = 0;
+            CredPackAuthenticationBufferW(
+                0,
+                self.user.as_ptr() as *mut _,
+                empty.as_ptr() as *mut _,
+                ptr::null_mut(),
+                &mut size,
+            );
+            let mut packed = vec![0u8; size as usize];
+            let packed_ok = CredPackAuthenticationBufferW(
+                0,
+                self.user.as_ptr() as *mut _,
+                empty.as_ptr() as *mut _,
+                packed.as_mut_ptr(),
+                &mut size,
+            ) != FALSE;
+            let (in_buffer, in_size) = if packed_ok {
+                (packed.as_ptr() as *const c_void, size)
+            } else {
+                (ptr::null(), 0)
+            };
+
+            let mut package: ULONG = 0;
+            let mut out_buffer: *mut c_void = ptr::null_mut();
+            let mut out_size: ULONG = 0;
+            let mut save = FALSE;
+            let res = CredUIPromptForWindowsCredentialsW(
+                &mut info,
+                0,
+                &mut package,
+                in_buffer,
+                in_size,
+                &mut out_buffer,
+                &mut out_size,
+                &mut save,
+                CREDUIWIN_GENERIC,
+            );
+            // ERROR_CANCELLED when the user closed the prompt
+            if res != ERROR_SUCCESS {
+                return None;
+            }
+
+            let mut user = vec![0u16; CREDUI_MAX_USERNAME_LENGTH as usize + 1];
+            let mut user_len = user.len() as DWORD;
+            let mut domain = vec![0u16; CREDUI_MAX_DOMAIN_TARGET_LENGTH as usize + 1];
+            let mut domain_len = domain.len() as DWORD;
+            let mut password = vec![0u16; CREDUI_MAX_PASSWORD_LENGTH as usize + 1];
+            let mut password_len = password.len() as DWORD;
+            let unpacked = CredUnPackAuthenticationBufferW(
+                0,
+                out_buffer,
+                out_size,
+                user.as_mut_ptr(),
+                &mut user_len,
+                domain.as_mut_ptr(),
+                &mut domain_len,
+                password.as_mut_ptr(),
+                &mut password_len,
+            ) != FALSE;
+
+            // The buffer holds the password in plain text
+            ptr::write_bytes(out_buffer as *mut u8, 0, out_size as usize);
+            CoTaskMemFree(out_buffer);
+
+            let credentials = if unpacked {
+                let text = |text: &[u16], len: DWORD| {
+                    // The lengths count the terminating NUL
+                    String::from_utf16_lossy(&text[..(len as usize).saturating_sub(1)])
+                };
+
+                let mut name = text(&user, user_len);
+                let domain = text(&domain, domain_len);
+                if !domain.is_empty() {
+                    name = format!("{}\\{}", domain, name);
+                }
+                Some(Credentials {
+                    user: name,
+                    password: text(&password, password_len).into(),
+                })
+            } else {
+                None
+            };
+
+            for c in password.iter_mut() {
+                ptr::write_volatile(c, 0);
+            }
+            credentials
+        }
+    }
+
+    pub fn run_async(self) -> ThreadFuture<Option<Credentials>> {
+        ThreadFuture::new(move |data| *data = Some(self.run()))
+    }
+}
+
+pub fn show(opt: CredentialDialog) -> Option<Credentials> {
+    let dialog = WinCredentialDialog::new(opt);
+    dialog.run()
+}
+
+pub fn show_async(opt: CredentialDialog) -> DialogFutureType<Option<Credentials>> {
+    let dialog = WinCredentialDialog::new(opt);
+    Box::pin(dialog.run_async())
+}
@@ -0,0 +1,77 @@
+//! In-memory dialog templates, for the dialogs Win32 has no ready-made version of
+
+use winapi::um::winuser::{
+    DS_CENTER, DS_MODALFRAME, DS_SETFONT, WS_CAPTION, WS_CHILD, WS_EX_LAYOUTRTL, WS_POPUP,
+    WS_SYSMENU, WS_VISIBLE,
+};
+
+use super::utils::rtl;
+
+use std::{ffi::OsStr, iter::once, os::windows::ffi::OsStrExt};
+
+// Atoms of the predefined window classes
+pub const BUTTON: u16 = 0x0080;
+pub const EDIT: u16 = 0x0081;
+pub const STATIC: u16 = 0x0082;
+pub const LISTBOX: u16 = 0x0083;
+
+/// `DLGTEMPLATE` followed by its `DLGITEMTEMPLATE`s, sizes are in dialog units
+pub struct DialogTemplate(Vec<u16>);
+
+impl DialogTemplate {
+    /// Modal dialog with the Segoe UI font that holds `items` controls, mirrored under [`rtl`]
+    pub fn new(title: &str, items: u16, width: u16, height: u16) -> Self {
+        let mut template = Self(Vec::new());
+
+        template
+            .push_u32(DS_MODALFRAME | DS_SETFONT | DS_CENTER | WS_POPUP | WS_CAPTION | WS_SYSMENU);
+        // dwExtendedStyle
+        template.push_u32(if rtl() { WS_EX_LAYOUTRTL } else { 0 });
+        // cdit, x, y, cx, cy
+        template.0.extend_from_slice(&[items, 0, 0, width, height]);
+        // No menu and the default dialog class
+        template.0.extend_from_slice(&[0, 0]);
+        template.push_str(title);
+        // Font size in points, as set by DS_SETFONT
+        template.0.push(9);
+        template.push_str("Segoe UI");
+
+        template
+    }
+
+    /// Appends a control of one of the predefined classes
+    pub fn item(&mut self, style: u32, rect: [u16; 4], id: u16, class: u16, text: &str) {
+        // Every item starts DWORD aligned
+        if self.0.len() % 2 == 1 {
+            self.0.push(0);
+        }
+
+        self.push_u32(style | WS_CHILD | WS_VISIBLE);
+        // dwExtendedStyle
+        self.push_u32(0);
+        self.0.extend_from_slice(&rect);
+        self.0.push(id);
+        self.0.push(0xFFFF);
+        self.0.push(class);
+        self.push_str(text);
+        // No creation data
+        self.0.push(0);
+    }
+
+    /// The template for `DialogBoxIndirectParamW`, it has to be DWORD aligned
+    pub fn finish(self) -> Vec<u32> {
+        self.0
+            .chunks(2)
+            .map(|pair| pair[0] as u32 | (*pair.get(1).unwrap_or(&0) as u32) << 16)
+            .collect()
+    }
+
+    fn push_u32(&mut self, value: u32) {
+        self.0.push(value as u16);
+        self.0.push((value >> 16) as u16);
+    }
+
+    fn push_str(&mut self, text: &str) {
+        self.0.extend(OsStr::new(text).encode_wide().chain(once(0)));
+    }
+}
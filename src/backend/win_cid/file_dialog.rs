@@ -1,111 +1,162 @@
+mod dialog_events;
 pub mod dialog_ffi;
 mod dialog_future;
 
+use super::thread_future::ThreadFuture;
+use super::utils::{in_sta, init_com};
 use dialog_ffi::IDialog;
-use dialog_future::{multiple_return_future, single_return_future};
+use dialog_future::{multiple_return_future, run_dialog, single_return_future};
 
 use crate::backend::DialogFutureType;
+use crate::Error;
 use crate::FileDialog;
 use crate::FileHandle;
 
 use std::path::PathBuf;
-
-use winapi::shared::winerror::HRESULT;
-
-use super::utils::init_com;
+use url::Url;
+
+/// Loads the shell libraries behind the dialogs on the UI thread, whose apartment is never
+/// uninitialized, so they stay loaded for the first dialog
+pub fn warm_up() {
+    crate::ui_thread::spawn(|| {
+        let _ = init_com(IDialog::preload);
+    })
+    .wait();
+}
 
 //
 // File Picker
 //
 
-use crate::backend::FilePickerDialogImpl;
-impl FilePickerDialogImpl for FileDialog {
-    fn pick_file(self) -> Option<PathBuf> {
-        fn run(opt: FileDialog) -> Result<PathBuf, HRESULT> {
-            init_com(|| {
-                let dialog = IDialog::build_pick_file(&opt)?;
-                dialog.show()?;
-                dialog.get_result()
-            })?
-        }
-        run(self).ok()
-    }
-
-    fn pick_files(self) -> Option<Vec<PathBuf>> {
-        fn run(opt: FileDialog) -> Result<Vec<PathBuf>, HRESULT> {
-            init_com(|| {
-                let dialog = IDialog::build_pick_files(&opt)?;
-                dialog.show()?;
-                dialog.get_results()
-            })?
-        }
-        run(self).ok()
-    }
-}
-
-use crate::backend::AsyncFilePickerDialogImpl;
-impl AsyncFilePickerDialogImpl for FileDialog {
-    fn pick_file_async(self) -> DialogFutureType<Option<FileHandle>> {
-        let ret = single_return_future(move || IDialog::build_pick_file(&self));
-        Box::pin(ret)
-    }
-
-    fn pick_files_async(self) -> DialogFutureType<Option<Vec<FileHandle>>> {
-        let ret = multiple_return_future(move || IDialog::build_pick_files(&self));
-        Box::pin(ret)
-    }
+pub fn pick_file(opt: FileDialog) -> Result<Option<PathBuf>, Error> {
+    in_sta(move || {
+        run_dialog(
+            &opt.handle,
+            || IDialog::build_pick_file(&opt),
+            IDialog::get_result,
+        )
+    })
+}
+
+pub fn pick_files(opt: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+    in_sta(move || {
+        run_dialog(
+            &opt.handle,
+            || IDialog::build_pick_files(&opt),
+            IDialog::get_results,
+        )
+    })
+}
+
+fn run_pick_file_url(opt: &FileDialog) -> Result<Option<Url>, Error> {
+    let url = run_dialog(
+        &opt.handle,
+        || IDialog::build_pick_file(opt),
+        IDialog::get_result_url,
+    )?;
+    Ok(url.and_then(|url| Url::parse(&url).ok()))
+}
+
+pub fn pick_file_url(opt: FileDialog) -> Result<Option<Url>, Error> {
+    in_sta(move || run_pick_file_url(&opt))
+}
+
+pub fn pick_file_url_async(opt: FileDialog) -> DialogFutureType<Result<Option<Url>, Error>> {
+    let ret = ThreadFuture::new(move |data| {
+        // Already on a thread of its own, which `in_sta` would not leave alone
+        *data = Some(run_pick_file_url(&opt));
+    });
+    Box::pin(ret)
+}
+
+pub fn pick_file_async(opt: FileDialog) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+    let ret = single_return_future(opt.handle.clone(), move || IDialog::build_pick_file(&opt));
+    Box::pin(ret)
+}
+
+pub fn pick_files_async(
+    opt: FileDialog,
+) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+    let ret = multiple_return_future(opt.handle.clone(), move || IDialog::build_pick_files(&opt));
+    Box::pin(ret)
 }
 
 //
 // Folder Picker
 //
 
-use crate::backend::FolderPickerDialogImpl;
-impl FolderPickerDialogImpl for FileDialog {
-    fn pick_folder(self) -> Option<PathBuf> {
-        fn run(opt: FileDialog) -> Result<PathBuf, HRESULT> {
-            init_com(|| {
-                let dialog = IDialog::build_pick_folder(&opt)?;
-                dialog.show()?;
-                dialog.get_result()
-            })?
-        }
+pub fn pick_folder(opt: FileDialog) -> Result<Option<PathBuf>, Error> {
+    in_sta(move || {
+        run_dialog(
+            &opt.handle,
+            || IDialog::build_pick_folder(&opt),
+            IDialog::get_result,
+        )
+    })
+}
 
-        run(self).ok()
-    }
+pub fn pick_folder_async(opt: FileDialog) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+    let ret = single_return_future(opt.handle.clone(), move || IDialog::build_pick_folder(&opt));
+    Box::pin(ret)
 }
 
-use crate::backend::AsyncFolderPickerDialogImpl;
-impl AsyncFolderPickerDialogImpl for FileDialog {
-    fn pick_folder_async(self) -> DialogFutureType<Option<FileHandle>> {
-        let ret = single_return_future(move || IDialog::build_pick_folder(&self));
-        Box::pin(ret)
-    }
+pub fn pick_folders(opt: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+    in_sta(move || {
+        run_dialog(
+            &opt.handle,
+            || IDialog::build_pick_folders(&opt),
+            IDialog::get_results,
+        )
+    })
+}
+
+pub fn pick_folders_async(
+    opt: FileDialog,
+) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+    let ret = multiple_return_future(opt.handle.clone(), move || {
+        IDialog::build_pick_folders(&opt)
+    });
+    Box::pin(ret)
 }
 
 //
 // File Save
 //
 
-use crate::backend::FileSaveDialogImpl;
-impl FileSaveDialogImpl for FileDialog {
-    fn save_file(self) -> Option<PathBuf> {
-        fn run(opt: FileDialog) -> Result<PathBuf, HRESULT> {
-            init_com(|| {
-                let dialog = IDialog::build_save_file(&opt)?;
-                dialog.show()?;
-                dialog.get_result()
-            })?
-        }
-
-        run(self).ok()
-    }
-}
-
-use crate::backend::AsyncFileSaveDialogImpl;
-impl AsyncFileSaveDialogImpl for FileDialog {
-    fn save_file_async(self) -> DialogFutureType<Option<FileHandle>> {
-        let ret = single_return_future(move || IDialog::build_save_file(&self));
-        Box::pin(ret)
-    }
+pub fn save_file(opt: FileDialog) -> Result<Option<PathBuf>, Error> {
+    in_sta(move || {
+        run_dialog(
+            &opt.handle,
+            || IDialog::build_save_file(&opt),
+            IDialog::get_result,
+        )
+    })
+}
+
+pub fn save_file_async(opt: FileDialog) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+    let ret = single_return_future(opt.handle.clone(), move || IDialog::build_save_file(&opt));
+    Box::pin(ret)
+}
+
+fn run_save_with_filter(opt: &FileDialog) -> Result<Option<(PathBuf, usize)>, Error> {
+    run_dialog(
+        &opt.handle,
+        || IDialog::build_save_file(opt),
+        |dialog| Ok((dialog.get_result()?, dialog.get_file_type_index()?)),
+    )
+}
+
+pub fn save_file_with_filter(opt: FileDialog) -> Result<Option<(PathBuf, usize)>, Error> {
+    in_sta(move || run_save_with_filter(&opt))
+}
+
+pub fn save_file_with_filter_async(
+    opt: FileDialog,
+) -> DialogFutureType<Result<Option<(FileHandle, usize)>, Error>> {
+    let ret = ThreadFuture::new(move |data| {
+        // Already on a thread of its own, which `in_sta` would not leave alone
+        let res = run_save_with_filter(&opt);
+        *data = Some(res.map(|res| res.map(|(path, index)| (FileHandle::wrap(path), index))));
+    });
+    Box::pin(ret)
 }
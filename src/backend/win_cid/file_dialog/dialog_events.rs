@@ -0,0 +1,563 @@
+//! `IFileDialogEvents` implementation that forwards to [`DialogEvents`], the validator, the custom controls and the preview
+
+use super::dialog_ffi::{item_path, PREVIEW_ID};
+use crate::{
+    DialogChoices, DialogEvents, DialogHandle, FileDialog, MonitorSelection, Position, Preview,
+    PreviewContent, Validate,
+};
+
+use std::cell::Cell;
+use std::ffi::OsStr;
+use std::iter::once;
+use std::os::windows::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::ptr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use winapi::{
+    ctypes::c_void,
+    shared::{
+        guiddef::{IsEqualGUID, REFIID},
+        minwindef::{BOOL, DWORD, FALSE, LPARAM, TRUE, UINT, ULONG},
+        ntdef::LPWSTR,
+        windef::{HDC, HMONITOR, HWND, LPRECT, POINT, RECT},
+        winerror::{E_NOINTERFACE, E_NOTIMPL, HRESULT, S_FALSE, S_OK},
+    },
+    um::{
+        combaseapi::CoTaskMemFree,
+        oleidl::IOleWindow,
+        shobjidl::{
+            IFileDialog, IFileDialogCustomize, IFileDialogEvents, IFileDialogEventsVtbl,
+            IFileOpenDialog, CDCS_ENABLED, CDCS_INACTIVE, CDCS_VISIBLE, FDE_OVERWRITE_RESPONSE,
+            FDE_SHAREVIOLATION_RESPONSE,
+        },
+        shobjidl_core::{IShellItem, IShellItemArray},
+        unknwnbase::{IUnknown, IUnknownVtbl},
+        winuser::{
+            EnumDisplayMonitors, GetCursorPos, GetMonitorInfoW, GetWindow, GetWindowRect, IsWindow,
+            MessageBoxW, MonitorFromPoint, MonitorFromWindow, PostMessageW, SetWindowPos, GW_OWNER,
+            HWND_NOTOPMOST, HWND_TOPMOST, MB_ICONERROR, MB_OK, MONITORINFO,
+            MONITOR_DEFAULTTONEAREST, MONITOR_DEFAULTTOPRIMARY, SWP_NOACTIVATE, SWP_NOMOVE,
+            SWP_NOSIZE, SWP_NOZORDER, WM_CLOSE,
+        },
+    },
+    Interface,
+};
+
+use super::super::utils::ToResult;
+
+#[repr(C)]
+struct EventHandler {
+    // Has to be the first field, COM only sees this part
+    interface: IFileDialogEvents,
+    refs: AtomicU32,
+    events: Option<Arc<dyn DialogEvents>>,
+    validator: Option<Arc<Validate>>,
+    checkboxes: Vec<u32>,
+    combos: Vec<u32>,
+    text_fields: Vec<u32>,
+    preview: Option<Arc<Preview>>,
+    choices: DialogChoices,
+    /// Taken once the dialog window exists
+    placement: Cell<Option<(Position, Option<MonitorSelection>)>>,
+    /// Taken once the dialog window exists
+    topmost: Cell<Option<bool>>,
+    /// Taken once the dialog window exists, which is the one closing it closes
+    handle: Cell<Option<DialogHandle>>,
+    /// Default extension of each filter, see [`compound_extensions`]
+    default_extensions: Vec<Option<Vec<u16>>>,
+}
+
+static VTBL: IFileDialogEventsVtbl = IFileDialogEventsVtbl {
+    parent: IUnknownVtbl {
+        QueryInterface: query_interface,
+        AddRef: add_ref,
+        Release: release,
+    },
+    OnFileOk: on_file_ok,
+    OnFolderChanging: on_folder_changing,
+    OnFolderChange: on_folder_change,
+    OnSelectionChange: on_selection_change,
+    OnShareViolation: on_share_violation,
+    OnTypeChange: on_type_change,
+    OnOverwrite: on_overwrite,
+};
+
+unsafe fn handler<'a, T>(this: *mut T) -> &'a EventHandler {
+    &*(this as *mut EventHandler)
+}
+
+unsafe extern "system" fn query_interface(
+    this: *mut IUnknown,
+    riid: REFIID,
+    object: *mut *mut c_void,
+) -> HRESULT {
+    if IsEqualGUID(&*riid, &IUnknown::uuidof()) || IsEqualGUID(&*riid, &IFileDialogEvents::uuidof())
+    {
+        add_ref(this);
+        *object = this as _;
+        S_OK
+    } else {
+        *object = ptr::null_mut();
+        E_NOINTERFACE
+    }
+}
+
+unsafe extern "system" fn add_ref(this: *mut IUnknown) -> ULONG {
+    let handler = &*(this as *mut EventHandler);
+    handler.refs.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn release(this: *mut IUnknown) -> ULONG {
+    let handler = &*(this as *mut EventHandler);
+    let refs = handler.refs.fetch_sub(1, Ordering::Release) - 1;
+    if refs == 0 {
+        drop(Box::from_raw(this as *mut EventHandler));
+    }
+    refs
+}
+
+/// Paths the dialog is about to return
+unsafe fn results(dialog: *mut IFileDialog) -> Vec<PathBuf> {
+    let mut open_dialog: *mut IFileOpenDialog = ptr::null_mut();
+    let is_open_dialog = (*dialog)
+        .QueryInterface(
+            &IFileOpenDialog::uuidof(),
+            &mut open_dialog as *mut *mut IFileOpenDialog as *mut *mut _,
+        )
+        .check()
+        .is_ok();
+
+    let mut paths = Vec::new();
+    if is_open_dialog {
+        let mut items: *mut IShellItemArray = ptr::null_mut();
+        let res = (*open_dialog).GetResults(&mut items).check();
+        (*open_dialog).Release();
+        if res.is_ok() {
+            paths = item_paths(items);
+        }
+    } else {
+        let mut item: *mut IShellItem = ptr::null_mut();
+        if (*dialog).GetResult(&mut item).check().is_ok() {
+            paths.extend(item_path(item).ok());
+            (*item).Release();
+        }
+    }
+    paths
+}
+
+/// Window of the dialog, null before it is shown
+unsafe fn dialog_window(dialog: *mut IFileDialog) -> HWND {
+    let mut window: *mut IOleWindow = ptr::null_mut();
+    let mut hwnd: HWND = ptr::null_mut();
+    if (*dialog)
+        .QueryInterface(
+            &IOleWindow::uuidof(),
+            &mut window as *mut *mut IOleWindow as *mut *mut _,
+        )
+        .check()
+        .is_ok()
+    {
+        (*window).GetWindow(&mut hwnd);
+        (*window).Release();
+    }
+    hwnd
+}
+
+unsafe fn show_error(dialog: *mut IFileDialog, message: &str) {
+    let hwnd = dialog_window(dialog);
+    let message: Vec<u16> = OsStr::new(message).encode_wide().chain(once(0)).collect();
+    MessageBoxW(hwnd, message.as_ptr(), ptr::null(), MB_OK | MB_ICONERROR);
+}
+
+unsafe fn store_choices(dialog: *mut IFileDialog, handler: &EventHandler) {
+    let mut customize: *mut IFileDialogCustomize = ptr::null_mut();
+    let res = (*dialog)
+        .QueryInterface(
+            &IFileDialogCustomize::uuidof(),
+            &mut customize as *mut *mut IFileDialogCustomize as *mut *mut _,
+        )
+        .check();
+    if res.is_err() {
+        return;
+    }
+
+    for id in &handler.checkboxes {
+        let mut checked: BOOL = FALSE;
+        if (*customize)
+            .GetCheckButtonState(*id, &mut checked)
+            .check()
+            .is_ok()
+        {
+            handler.choices.set_checked(*id, checked != FALSE);
+        }
+    }
+    for id in &handler.combos {
+        let mut index: DWORD = 0;
+        if (*customize)
+            .GetSelectedControlItem(*id, &mut index)
+            .check()
+            .is_ok()
+        {
+            handler.choices.set_selected(*id, index as usize);
+        }
+    }
+    for id in &handler.text_fields {
+        let mut text: LPWSTR = ptr::null_mut();
+        if (*customize).GetEditBoxText(*id, &mut text).check().is_ok() {
+            let len = (0..).take_while(|&i| *text.offset(i) != 0).count();
+            let content = String::from_utf16_lossy(std::slice::from_raw_parts(text, len));
+            CoTaskMemFree(text as _);
+            handler.choices.set_text(*id, content);
+        }
+    }
+    (*customize).Release();
+}
+
+unsafe extern "system" fn on_file_ok(
+    this: *mut IFileDialogEvents,
+    dialog: *mut IFileDialog,
+) -> HRESULT {
+    let handler = handler(this);
+
+    if let Some(validator) = &handler.validator {
+        let rejected = results(dialog)
+            .iter()
+            .map(|path| validator(path))
+            .find(Result::is_err);
+        if let Some(Err(message)) = rejected {
+            show_error(dialog, &message);
+            // Keeps the dialog open
+            return S_FALSE;
+        }
+    }
+
+    store_choices(dialog, handler);
+    S_OK
+}
+
+unsafe extern "system" fn on_folder_changing(
+    _: *mut IFileDialogEvents,
+    _: *mut IFileDialog,
+    _: *mut IShellItem,
+) -> HRESULT {
+    S_OK
+}
+
+unsafe extern "system" fn push_monitor(
+    monitor: HMONITOR,
+    _: HDC,
+    _: LPRECT,
+    monitors: LPARAM,
+) -> BOOL {
+    (*(monitors as *mut Vec<HMONITOR>)).push(monitor);
+    TRUE
+}
+
+/// Monitor `selection` points to, the one the dialog is on without a selection
+unsafe fn monitor(hwnd: HWND, selection: Option<MonitorSelection>) -> HMONITOR {
+    let nearest = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+    match selection {
+        None => nearest,
+        Some(MonitorSelection::Primary) => {
+            MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY)
+        }
+        Some(MonitorSelection::Index(index)) => {
+            let mut monitors: Vec<HMONITOR> = Vec::new();
+            EnumDisplayMonitors(
+                ptr::null_mut(),
+                ptr::null(),
+                Some(push_monitor),
+                &mut monitors as *mut Vec<HMONITOR> as LPARAM,
+            );
+            monitors.get(index as usize).copied().unwrap_or(nearest)
+        }
+        Some(MonitorSelection::ContainingCursor) => {
+            let mut cursor = POINT { x: 0, y: 0 };
+            if GetCursorPos(&mut cursor) == FALSE {
+                return nearest;
+            }
+            MonitorFromPoint(cursor, MONITOR_DEFAULTTONEAREST)
+        }
+    }
+}
+
+/// Moves the dialog window to `position`, centered ones on the monitor of `selection`
+unsafe fn place_window(hwnd: HWND, position: Position, selection: Option<MonitorSelection>) {
+    let (x, y) = match position {
+        Position::At(x, y) => (x, y),
+        Position::CenterOnParent | Position::CenterOnScreen => {
+            let owner = GetWindow(hwnd, GW_OWNER);
+            let mut area: RECT = std::mem::zeroed();
+            if position == Position::CenterOnParent && !owner.is_null() {
+                GetWindowRect(owner, &mut area);
+            } else {
+                // Without an owner the dialog is centered on its screen
+                let mut info: MONITORINFO = std::mem::zeroed();
+                info.cbSize = std::mem::size_of::<MONITORINFO>() as DWORD;
+                GetMonitorInfoW(monitor(hwnd, selection), &mut info);
+                area = info.rcWork;
+            }
+
+            let mut rect: RECT = std::mem::zeroed();
+            GetWindowRect(hwnd, &mut rect);
+            let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+            (
+                area.left + (area.right - area.left - width) / 2,
+                area.top + (area.bottom - area.top - height) / 2,
+            )
+        }
+    };
+    SetWindowPos(
+        hwnd,
+        ptr::null_mut(),
+        x,
+        y,
+        0,
+        0,
+        SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+    );
+}
+
+unsafe extern "system" fn on_folder_change(
+    this: *mut IFileDialogEvents,
+    dialog: *mut IFileDialog,
+) -> HRESULT {
+    // The first folder change happens while the window is set up, before it is shown
+    let placement = handler(this).placement.take();
+    let topmost = handler(this).topmost.take();
+    let handle = handler(this).handle.take();
+    if placement.is_some() || topmost.is_some() || handle.is_some() {
+        let hwnd = dialog_window(dialog);
+        if !hwnd.is_null() {
+            if let Some(handle) = handle {
+                // Window handles can be sent, only the window's thread may use the dialog itself
+                let window = hwnd as usize;
+                handle.on_close(move || {
+                    let hwnd = window as HWND;
+                    // Gone when the handle is closed after the dialog finished
+                    if IsWindow(hwnd) != FALSE {
+                        PostMessageW(hwnd, WM_CLOSE, 0, 0);
+                    }
+                });
+            }
+            if let Some((position, selection)) = placement {
+                place_window(hwnd, position, selection);
+            }
+            if let Some(topmost) = topmost {
+                let after = if topmost {
+                    HWND_TOPMOST
+                } else {
+                    HWND_NOTOPMOST
+                };
+                SetWindowPos(
+                    hwnd,
+                    after,
+                    0,
+                    0,
+                    0,
+                    0,
+                    SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+                );
+            }
+        }
+    }
+
+    let mut item: *mut IShellItem = ptr::null_mut();
+    if (*dialog).GetFolder(&mut item).check().is_ok() {
+        let path = item_path(item);
+        (*item).Release();
+        // Virtual folders have no path
+        if let Ok(path) = path {
+            if let Some(events) = &handler(this).events {
+                events.on_folder_change(&path);
+            }
+        }
+    }
+    S_OK
+}
+
+/// Paths of the items in `items`, releases the array
+unsafe fn item_paths(items: *mut IShellItemArray) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let mut count = 0;
+    (*items).GetCount(&mut count);
+    for id in 0..count {
+        let mut item: *mut IShellItem = ptr::null_mut();
+        if (*items).GetItemAt(id, &mut item).check().is_ok() {
+            paths.extend(item_path(item).ok());
+            (*item).Release();
+        }
+    }
+    (*items).Release();
+    paths
+}
+
+/// Highlighted items, open dialogs can highlight more than one
+unsafe fn selection(dialog: *mut IFileDialog) -> Vec<PathBuf> {
+    let mut open_dialog: *mut IFileOpenDialog = ptr::null_mut();
+    let is_open_dialog = (*dialog)
+        .QueryInterface(
+            &IFileOpenDialog::uuidof(),
+            &mut open_dialog as *mut *mut IFileOpenDialog as *mut *mut _,
+        )
+        .check()
+        .is_ok();
+
+    if is_open_dialog {
+        let mut items: *mut IShellItemArray = ptr::null_mut();
+        let res = (*open_dialog).GetSelectedItems(&mut items).check();
+        (*open_dialog).Release();
+
+        if res.is_ok() {
+            item_paths(items)
+        } else {
+            Vec::new()
+        }
+    } else {
+        let mut item: *mut IShellItem = ptr::null_mut();
+        if (*dialog).GetCurrentSelection(&mut item).check().is_ok() {
+            let path = item_path(item);
+            (*item).Release();
+            path.into_iter().collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+unsafe extern "system" fn on_selection_change(
+    this: *mut IFileDialogEvents,
+    dialog: *mut IFileDialog,
+) -> HRESULT {
+    let handler = handler(this);
+    if handler.events.is_none() && handler.preview.is_none() {
+        return S_OK;
+    }
+
+    let selection = selection(dialog);
+    if let Some(events) = &handler.events {
+        events.on_selection_change(&selection);
+    }
+    if let Some(preview) = &handler.preview {
+        let content = match selection.first() {
+            Some(path) => preview(path),
+            None => PreviewContent::None,
+        };
+        show_preview(dialog, content);
+    }
+    S_OK
+}
+
+/// Text previews go to the preview control, there is no way to show images
+unsafe fn show_preview(dialog: *mut IFileDialog, content: PreviewContent) {
+    let mut customize: *mut IFileDialogCustomize = ptr::null_mut();
+    let res = (*dialog)
+        .QueryInterface(
+            &IFileDialogCustomize::uuidof(),
+            &mut customize as *mut *mut IFileDialogCustomize as *mut *mut _,
+        )
+        .check();
+    if res.is_err() {
+        return;
+    }
+
+    match content {
+        PreviewContent::Text(text) => {
+            let text: Vec<u16> = OsStr::new(&text).encode_wide().chain(once(0)).collect();
+            (*customize).SetControlLabel(PREVIEW_ID, text.as_ptr());
+            (*customize).SetControlState(PREVIEW_ID, CDCS_VISIBLE | CDCS_ENABLED);
+        }
+        PreviewContent::Image { .. } | PreviewContent::None => {
+            (*customize).SetControlState(PREVIEW_ID, CDCS_INACTIVE);
+        }
+    }
+    (*customize).Release();
+}
+
+unsafe extern "system" fn on_share_violation(
+    _: *mut IFileDialogEvents,
+    _: *mut IFileDialog,
+    _: *mut IShellItem,
+    _: *mut FDE_SHAREVIOLATION_RESPONSE,
+) -> HRESULT {
+    // Default handling
+    E_NOTIMPL
+}
+
+unsafe extern "system" fn on_type_change(
+    this: *mut IFileDialogEvents,
+    dialog: *mut IFileDialog,
+) -> HRESULT {
+    let mut index: UINT = 0;
+    if (*dialog).GetFileTypeIndex(&mut index).check().is_ok() {
+        // `GetFileTypeIndex` is one based
+        let index = index.saturating_sub(1) as usize;
+        let handler = handler(this);
+        if let Some(Some(extension)) = handler.default_extensions.get(index) {
+            (*dialog).SetDefaultExtension(extension.as_ptr());
+        }
+        if let Some(events) = &handler.events {
+            events.on_type_change(index);
+        }
+    }
+    S_OK
+}
+
+unsafe extern "system" fn on_overwrite(
+    _: *mut IFileDialogEvents,
+    _: *mut IFileDialog,
+    _: *mut IShellItem,
+    _: *mut FDE_OVERWRITE_RESPONSE,
+) -> HRESULT {
+    // Default handling
+    E_NOTIMPL
+}
+
+/// Registers the events, validator, checkboxes, position, z-order and handle of `opt` with the dialog, the dialog keeps them alive until it is released
+pub fn advise(dialog: *mut IFileDialog, opt: &FileDialog) -> Result<(), HRESULT> {
+    let handler = Box::into_raw(Box::new(EventHandler {
+        interface: IFileDialogEvents { lpVtbl: &VTBL },
+        refs: AtomicU32::new(1),
+        events: opt.events.as_ref().map(|events| events.0.clone()),
+        validator: opt.validator.as_ref().map(|validator| validator.0.clone()),
+        checkboxes: opt.checkboxes.iter().map(|checkbox| checkbox.id).collect(),
+        combos: opt.combos.iter().map(|combo| combo.id).collect(),
+        text_fields: opt.text_fields.iter().map(|field| field.id).collect(),
+        preview: opt.preview.as_ref().map(|preview| preview.0.clone()),
+        choices: opt.choices.clone(),
+        placement: Cell::new(opt.placement()),
+        topmost: Cell::new(opt.always_on_top),
+        handle: Cell::new(Some(opt.handle.clone())),
+        default_extensions: compound_extensions(opt),
+    }));
+
+    unsafe {
+        let mut cookie = 0;
+        let res = (*dialog)
+            .Advise(handler as *mut IFileDialogEvents, &mut cookie)
+            .check();
+        // The dialog took its own reference
+        release(handler as *mut IUnknown);
+        res?;
+    }
+    Ok(())
+}
+
+/// First extension of each filter when it is a compound one like `tar.gz`, `None` for the others
+///
+/// When the user picks another filter the dialog takes the part after the last dot of its pattern
+/// as the default extension, `gz` of `*.tar.gz`, so the whole one is put back on type change.
+pub fn compound_extensions(opt: &FileDialog) -> Vec<Option<Vec<u16>>> {
+    if opt.append_extension == Some(false) {
+        return Vec::new();
+    }
+    opt.filters
+        .iter()
+        .map(|filter| {
+            let extension = filter.extensions.first().filter(|ext| ext.contains('.'))?;
+            Some(extension.encode_utf16().chain(once(0)).collect())
+        })
+        .collect()
+}
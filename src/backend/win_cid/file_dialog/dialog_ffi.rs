@@ -1,27 +1,34 @@
-use crate::FileDialog;
+use crate::windows::{long_path, strip_verbatim};
+use crate::{FileDialog, Modality};
 
 use std::{
     ffi::{c_void, OsStr, OsString},
     iter::once,
     ops::Deref,
     os::windows::{ffi::OsStrExt, prelude::OsStringExt},
-    path::PathBuf,
+    path::{Path, PathBuf},
     ptr,
 };
 
 use winapi::{
     shared::{
-        guiddef::GUID, minwindef::LPVOID, ntdef::LPWSTR, winerror::HRESULT,
+        guiddef::GUID,
+        minwindef::{LPVOID, UINT},
+        ntdef::LPWSTR,
+        winerror::HRESULT,
         wtypesbase::CLSCTX_INPROC_SERVER,
     },
     um::{
         combaseapi::{CoCreateInstance, CoTaskMemFree},
         shobjidl::{
-            IFileDialog, IFileOpenDialog, IFileSaveDialog, FOS_ALLOWMULTISELECT, FOS_PICKFOLDERS,
+            IFileDialog, IFileDialogCustomize, IFileOpenDialog, IFileSaveDialog, CDCS_INACTIVE,
+            FDAP_BOTTOM, FILEOPENDIALOGOPTIONS, FOS_ALLOWMULTISELECT, FOS_DONTADDTORECENT,
+            FOS_FORCEFILESYSTEM, FOS_FORCESHOWHIDDEN, FOS_HIDEMRUPLACES, FOS_HIDEPINNEDPLACES,
+            FOS_NOCHANGEDIR, FOS_OVERWRITEPROMPT, FOS_PICKFOLDERS, FOS_STRICTFILETYPES,
         },
         shobjidl_core::{
             CLSID_FileOpenDialog, CLSID_FileSaveDialog, IShellItem, IShellItemArray,
-            SHCreateItemFromParsingName, SIGDN_FILESYSPATH,
+            SHCreateItemFromParsingName, SIGDN_FILESYSPATH, SIGDN_URL,
         },
         shtypes::COMDLG_FILTERSPEC,
     },
@@ -31,7 +38,7 @@ use winapi::{
 #[cfg(feature = "parent")]
 use raw_window_handle::RawWindowHandle;
 
-use super::super::utils::ToResult;
+use super::super::utils::{owner, AppModal, RtlLayout, ToResult};
 
 fn to_os_string(s: &LPWSTR) -> OsString {
     let slice = unsafe {
@@ -44,7 +51,71 @@ fn to_os_string(s: &LPWSTR) -> OsString {
     OsStringExt::from_wide(slice)
 }
 
-pub struct IDialog(pub *mut IFileDialog, Option<*mut c_void>);
+/// Stable GUID for a persistence id, Windows keys the remembered dialog state by it
+fn client_guid(id: &str) -> GUID {
+    // FNV-1a, unlike `DefaultHasher` it gives the same GUID across Rust versions
+    fn fnv1a(bytes: &[u8], seed: u64) -> u64 {
+        bytes.iter().fold(seed, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    let hi = fnv1a(id.as_bytes(), 0xcbf2_9ce4_8422_2325);
+    let lo = fnv1a(id.as_bytes(), hi);
+
+    GUID {
+        Data1: (hi >> 32) as u32,
+        Data2: (hi >> 16) as u16,
+        Data3: hi as u16,
+        Data4: lo.to_be_bytes(),
+    }
+}
+
+/// Filesystem path of a shell item, fails for virtual items
+///
+/// Written as [`set_long_path_mode`](crate::windows::set_long_path_mode) asks for.
+pub unsafe fn item_path(item: *mut IShellItem) -> Result<PathBuf, HRESULT> {
+    let mut display_name: LPWSTR = ptr::null_mut();
+    (*item)
+        .GetDisplayName(SIGDN_FILESYSPATH, &mut display_name)
+        .check()?;
+
+    let filename = to_os_string(&display_name);
+    CoTaskMemFree(display_name as LPVOID);
+
+    Ok(long_path(PathBuf::from(filename)))
+}
+
+/// Wide, nul terminated path the shell can parse, `\\?\` prefixes are not understood by it
+fn parsing_name(path: &Path) -> Vec<u16> {
+    strip_verbatim(path)
+        .as_os_str()
+        .encode_wide()
+        .chain(once(0))
+        .collect()
+}
+
+/// Shell item of `path`
+unsafe fn create_item(path: &Path) -> Result<*mut IShellItem, HRESULT> {
+    let wide_path = parsing_name(path);
+    let mut item: *mut IShellItem = ptr::null_mut();
+    SHCreateItemFromParsingName(
+        wide_path.as_ptr(),
+        ptr::null_mut(),
+        &IShellItem::uuidof(),
+        &mut item as *mut *mut IShellItem as *mut *mut _,
+    )
+    .check()?;
+    Ok(item)
+}
+
+/// Set on the ids of the groups that label combo boxes and text fields, so they don't clash with the ids of the controls
+const LABEL_GROUP_ID: u32 = 1 << 31;
+
+/// Id of the text control that shows the preview, the highest one below the label groups
+pub const PREVIEW_ID: u32 = LABEL_GROUP_ID - 1;
+
+pub struct IDialog(pub *mut IFileDialog, Option<*mut c_void>, Modality);
 
 impl IDialog {
     fn new_file_dialog(class: &GUID, id: &GUID) -> Result<*mut IFileDialog, HRESULT> {
@@ -64,30 +135,37 @@ impl IDialog {
         Ok(dialog)
     }
 
+    /// Creates and releases an open dialog, which loads the libraries it needs
+    pub fn preload() -> Result<(), HRESULT> {
+        let ptr = Self::new_file_dialog(&CLSID_FileOpenDialog, &IFileOpenDialog::uuidof())?;
+        unsafe { (*ptr).Release() };
+        Ok(())
+    }
+
     fn new_open_dialog(opt: &FileDialog) -> Result<Self, HRESULT> {
         let ptr = Self::new_file_dialog(&CLSID_FileOpenDialog, &IFileOpenDialog::uuidof())?;
         #[cfg(feature = "parent")]
-        let parent = match opt.parent {
+        let parent = match opt.parent() {
             Some(RawWindowHandle::Windows(handle)) => Some(handle.hwnd),
             None => None,
             _ => unreachable!("unsupported window handle, expected: Windows"),
         };
         #[cfg(not(feature = "parent"))]
         let parent = None;
-        Ok(Self(ptr, parent))
+        Ok(Self(ptr, owner(parent, opt.modality), opt.modality))
     }
 
     fn new_save_dialog(opt: &FileDialog) -> Result<Self, HRESULT> {
         let ptr = Self::new_file_dialog(&CLSID_FileSaveDialog, &IFileSaveDialog::uuidof())?;
         #[cfg(feature = "parent")]
-        let parent = match opt.parent {
+        let parent = match opt.parent() {
             Some(RawWindowHandle::Windows(handle)) => Some(handle.hwnd),
             None => None,
             _ => unreachable!("unsupported window handle, expected: Windows"),
         };
         #[cfg(not(feature = "parent"))]
         let parent = None;
-        Ok(Self(ptr, parent))
+        Ok(Self(ptr, owner(parent, opt.modality), opt.modality))
     }
 
     fn add_filters(&self, filters: &[crate::dialog::Filter]) -> Result<(), HRESULT> {
@@ -105,12 +183,13 @@ impl IDialog {
 
             for f in filters.iter() {
                 let name: Vec<u16> = OsStr::new(&f.name).encode_wide().chain(once(0)).collect();
-                let ext_string = f
-                    .extensions
-                    .iter()
-                    .map(|item| format!("*.{}", item))
-                    .collect::<Vec<_>>()
-                    .join(";");
+                // There is no MIME type filtering, so a MIME only filter matches everything
+                let globs: Vec<_> = f.globs().collect();
+                let ext_string = if globs.is_empty() {
+                    "*.*".to_string()
+                } else {
+                    globs.join(";")
+                };
 
                 let ext: Vec<u16> = OsStr::new(&ext_string)
                     .encode_wide()
@@ -140,32 +219,123 @@ impl IDialog {
         Ok(())
     }
 
+    fn set_filter_index(
+        &self,
+        filters: &[crate::dialog::Filter],
+        index: Option<usize>,
+    ) -> Result<(), HRESULT> {
+        let filter = index.and_then(|index| Some((index, filters.get(index)?)));
+        if let Some((index, filter)) = filter {
+            if let Some(extension) = filter.extensions.first() {
+                let extension: Vec<u16> = extension.encode_utf16().chain(Some(0)).collect();
+                unsafe {
+                    (*self.0).SetDefaultExtension(extension.as_ptr()).check()?;
+                }
+            }
+
+            // `SetFileTypeIndex` is one based
+            unsafe {
+                (*self.0).SetFileTypeIndex(index as UINT + 1).check()?;
+            }
+        }
+        Ok(())
+    }
+
     fn set_path(&self, path: &Option<PathBuf>) -> Result<(), HRESULT> {
         if let Some(path) = path {
-            if let Some(path) = path.to_str() {
-                let wide_path: Vec<u16> = OsStr::new(path).encode_wide().chain(once(0)).collect();
+            unsafe {
+                let item = create_item(path)?;
 
-                unsafe {
-                    let mut item: *mut IShellItem = ptr::null_mut();
-                    SHCreateItemFromParsingName(
-                        wide_path.as_ptr(),
-                        ptr::null_mut(),
-                        &IShellItem::uuidof(),
-                        &mut item as *mut *mut IShellItem as *mut *mut _,
-                    )
-                    .check()?;
+                // For some reason SetDefaultFolder(), does not guarantees default path, so we use SetFolder
+                let res = (*self.0).SetFolder(item).check();
+                (*item).Release();
+                res?;
+            }
+        }
+        Ok(())
+    }
 
-                    // For some reason SetDefaultFolder(), does not guarantees default path, so we use SetFolder
-                    (*self.0).SetFolder(item).check()?;
+    /// Places that don't resolve, eg. a folder that was removed, are left out instead of failing the dialog
+    fn add_places(&self, places: &[crate::dialog::Place]) {
+        for place in places {
+            unsafe {
+                if let Ok(item) = create_item(&place.path) {
+                    (*self.0).AddPlace(item, FDAP_BOTTOM);
+                    (*item).Release();
                 }
             }
         }
-        Ok(())
+    }
+
+    fn add_controls(&self, opt: &FileDialog) -> Result<(), HRESULT> {
+        if opt.checkboxes.is_empty()
+            && opt.combos.is_empty()
+            && opt.text_fields.is_empty()
+            && opt.preview.is_none()
+        {
+            return Ok(());
+        }
+
+        let wide = |s: &str| -> Vec<u16> { OsStr::new(s).encode_wide().chain(once(0)).collect() };
+
+        unsafe {
+            let mut customize: *mut IFileDialogCustomize = ptr::null_mut();
+            (*self.0)
+                .QueryInterface(
+                    &IFileDialogCustomize::uuidof(),
+                    &mut customize as *mut *mut IFileDialogCustomize as *mut *mut _,
+                )
+                .check()?;
+            let c = &*customize;
+
+            let res = (|| {
+                for combo in &opt.combos {
+                    // Combo boxes and text fields have no label of their own, it is shown by a group around them
+                    c.StartVisualGroup(LABEL_GROUP_ID | combo.id, wide(&combo.label).as_ptr())
+                        .check()?;
+                    c.AddComboBox(combo.id).check()?;
+                    for (index, option) in combo.options.iter().enumerate() {
+                        c.AddControlItem(combo.id, index as _, wide(option).as_ptr())
+                            .check()?;
+                    }
+                    c.SetSelectedControlItem(combo.id, combo.default as _)
+                        .check()?;
+                    c.EndVisualGroup().check()?;
+                }
+                for text_field in &opt.text_fields {
+                    c.StartVisualGroup(
+                        LABEL_GROUP_ID | text_field.id,
+                        wide(&text_field.label).as_ptr(),
+                    )
+                    .check()?;
+                    c.AddEditBox(text_field.id, wide(&text_field.default).as_ptr())
+                        .check()?;
+                    c.EndVisualGroup().check()?;
+                }
+                for checkbox in &opt.checkboxes {
+                    c.AddCheckButton(
+                        checkbox.id,
+                        wide(&checkbox.label).as_ptr(),
+                        checkbox.default as _,
+                    )
+                    .check()?;
+                }
+                if opt.preview.is_some() {
+                    // Filled and shown once a file gets highlighted
+                    c.AddText(PREVIEW_ID, wide("").as_ptr()).check()?;
+                    c.SetControlState(PREVIEW_ID, CDCS_INACTIVE).check()?;
+                }
+                Ok(())
+            })();
+            c.Release();
+            res
+        }
     }
 
     fn set_file_name(&self, file_name: &Option<String>) -> Result<(), HRESULT> {
         if let Some(path) = file_name {
-            let wide_path: Vec<u16> = OsStr::new(path).encode_wide().chain(once(0)).collect();
+            // A whole `\\?\` path would show up as the name, the shell parses it without the prefix
+            let wide_path = parsing_name(Path::new(path));
 
             unsafe {
                 (*self.0).SetFileName(wide_path.as_ptr()).check()?;
@@ -174,6 +344,23 @@ impl IDialog {
         Ok(())
     }
 
+    /// Shows the existing file `path` as the one being saved, the dialog opens in its folder with its name filled in
+    fn set_save_as_item(&self, path: &Option<PathBuf>) -> Result<(), HRESULT> {
+        // A file that does not exist yet has no shell item, its folder and name are set already
+        if let Some(path) = path.as_ref().filter(|path| path.exists()) {
+            unsafe {
+                let item = create_item(path)?;
+
+                let res = (*(self.0 as *mut IFileSaveDialog))
+                    .SetSaveAsItem(item)
+                    .check();
+                (*item).Release();
+                res?;
+            }
+        }
+        Ok(())
+    }
+
     fn set_title(&self, title: &Option<String>) -> Result<(), HRESULT> {
         if let Some(title) = title {
             let wide_title: Vec<u16> = OsStr::new(title).encode_wide().chain(once(0)).collect();
@@ -185,6 +372,79 @@ impl IDialog {
         Ok(())
     }
 
+    fn set_persistence_id(&self, id: &Option<String>) -> Result<(), HRESULT> {
+        if let Some(id) = id {
+            unsafe {
+                (*self.0).SetClientGuid(&client_guid(id)).check()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_accept_label(&self, label: &Option<String>) -> Result<(), HRESULT> {
+        if let Some(label) = label {
+            let wide_label: Vec<u16> = OsStr::new(label).encode_wide().chain(once(0)).collect();
+
+            unsafe {
+                (*self.0).SetOkButtonLabel(wide_label.as_ptr()).check()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_file_name_label(&self, label: &Option<String>) -> Result<(), HRESULT> {
+        if let Some(label) = label {
+            let wide_label: Vec<u16> = OsStr::new(label).encode_wide().chain(once(0)).collect();
+
+            unsafe {
+                (*self.0).SetFileNameLabel(wide_label.as_ptr()).check()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_option(
+        &self,
+        option: FILEOPENDIALOGOPTIONS,
+        enabled: Option<bool>,
+    ) -> Result<(), HRESULT> {
+        if let Some(enabled) = enabled {
+            unsafe {
+                let mut options = 0;
+                (*self.0).GetOptions(&mut options).check()?;
+
+                let options = if enabled {
+                    options | option
+                } else {
+                    options & !option
+                };
+                (*self.0).SetOptions(options).check()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Optional settings, applied last so the flags are added on top of the ones a builder sets
+    fn set_options(&self, opt: &FileDialog) -> Result<(), HRESULT> {
+        self.add_places(&opt.places);
+        self.add_controls(opt)?;
+        self.set_file_name_label(&opt.file_name_label)?;
+        // Always needed, the events register how the handle closes the window
+        super::dialog_events::advise(self.0, opt)?;
+
+        self.set_option(FOS_FORCEFILESYSTEM, opt.local_only)?;
+        self.set_option(FOS_FORCESHOWHIDDEN, opt.show_hidden)?;
+        self.set_option(FOS_OVERWRITEPROMPT, opt.confirm_overwrite)?;
+        self.set_option(FOS_STRICTFILETYPES, opt.strict_file_types)?;
+        self.set_option(FOS_DONTADDTORECENT, opt.add_to_recent.map(|add| !add))?;
+        self.set_option(FOS_NOCHANGEDIR, opt.no_change_dir)?;
+        self.set_option(
+            FOS_HIDEPINNEDPLACES | FOS_HIDEMRUPLACES,
+            opt.hide_default_places,
+        )?;
+        Ok(())
+    }
+
     pub fn get_results(&self) -> Result<Vec<PathBuf>, HRESULT> {
         unsafe {
             let mut res_items: *mut IShellItemArray = ptr::null_mut();
@@ -199,14 +459,7 @@ impl IDialog {
             for id in 0..count {
                 let mut res_item: *mut IShellItem = ptr::null_mut();
                 items.GetItemAt(id, &mut res_item).check()?;
-                let mut display_name: LPWSTR = ptr::null_mut();
-                (*res_item)
-                    .GetDisplayName(SIGDN_FILESYSPATH, &mut display_name)
-                    .check()?;
-                let filename = to_os_string(&display_name);
-                CoTaskMemFree(display_name as LPVOID);
-                let path = PathBuf::from(filename);
-                paths.push(path);
+                paths.push(item_path(res_item)?);
             }
             items.Release();
 
@@ -219,20 +472,41 @@ impl IDialog {
         unsafe {
             (*self.0).GetResult(&mut res_item).check()?;
 
-            let mut display_name: LPWSTR = ptr::null_mut();
+            item_path(res_item)
+        }
+    }
 
-            (*res_item)
-                .GetDisplayName(SIGDN_FILESYSPATH, &mut display_name)
-                .check()?;
+    /// URL of the chosen item, virtual items without a filesystem path have one as well
+    pub fn get_result_url(&self) -> Result<String, HRESULT> {
+        let mut res_item: *mut IShellItem = ptr::null_mut();
+        unsafe {
+            (*self.0).GetResult(&mut res_item).check()?;
+
+            let mut display_name: LPWSTR = ptr::null_mut();
+            let res = (*res_item).GetDisplayName(SIGDN_URL, &mut display_name);
+            (*res_item).Release();
+            res.check()?;
 
-            let filename = to_os_string(&display_name);
+            let url = to_os_string(&display_name);
             CoTaskMemFree(display_name as LPVOID);
 
-            Ok(PathBuf::from(filename))
+            Ok(url.to_string_lossy().into_owned())
+        }
+    }
+
+    /// Zero based index of the selected filter
+    pub fn get_file_type_index(&self) -> Result<usize, HRESULT> {
+        let mut index: UINT = 0;
+        unsafe {
+            (*self.0).GetFileTypeIndex(&mut index).check()?;
         }
+        // `GetFileTypeIndex` is one based
+        Ok(index.saturating_sub(1) as usize)
     }
 
     pub fn show(&self) -> Result<(), HRESULT> {
+        let _app_modal = AppModal::new(self.2);
+        let _layout = RtlLayout::new();
         unsafe {
             self.Show(self.1.unwrap_or_else(|| ptr::null_mut()) as _)
                 .check()?
@@ -246,9 +520,14 @@ impl IDialog {
         let dialog = IDialog::new_open_dialog(opt)?;
 
         dialog.add_filters(&opt.filters)?;
+        dialog.set_filter_index(&opt.filters, opt.default_filter)?;
         dialog.set_path(&opt.starting_directory)?;
         dialog.set_file_name(&opt.file_name)?;
         dialog.set_title(&opt.title)?;
+        dialog.set_accept_label(&opt.accept_label)?;
+        dialog.set_persistence_id(&opt.persistence_id)?;
+
+        dialog.set_options(opt)?;
 
         Ok(dialog)
     }
@@ -257,9 +536,19 @@ impl IDialog {
         let dialog = IDialog::new_save_dialog(opt)?;
 
         dialog.add_filters(&opt.filters)?;
+        dialog.set_filter_index(&opt.filters, opt.default_filter)?;
+        if opt.append_extension == Some(false) {
+            // An empty default extension stops the dialog from appending the one of the selected filter
+            unsafe { (*dialog.0).SetDefaultExtension([0u16].as_ptr()).check()? };
+        }
         dialog.set_path(&opt.starting_directory)?;
         dialog.set_file_name(&opt.file_name)?;
+        dialog.set_save_as_item(&opt.save_as_item)?;
         dialog.set_title(&opt.title)?;
+        dialog.set_accept_label(&opt.accept_label)?;
+        dialog.set_persistence_id(&opt.persistence_id)?;
+
+        dialog.set_options(opt)?;
 
         Ok(dialog)
     }
@@ -269,11 +558,34 @@ impl IDialog {
 
         dialog.set_path(&opt.starting_directory)?;
         dialog.set_title(&opt.title)?;
+        dialog.set_accept_label(&opt.accept_label)?;
+        dialog.set_persistence_id(&opt.persistence_id)?;
 
         unsafe {
             dialog.SetOptions(FOS_PICKFOLDERS).check()?;
         }
 
+        dialog.set_options(opt)?;
+
+        Ok(dialog)
+    }
+
+    pub fn build_pick_folders(opt: &FileDialog) -> Result<Self, HRESULT> {
+        let dialog = IDialog::new_open_dialog(opt)?;
+
+        dialog.set_path(&opt.starting_directory)?;
+        dialog.set_title(&opt.title)?;
+        dialog.set_accept_label(&opt.accept_label)?;
+        dialog.set_persistence_id(&opt.persistence_id)?;
+
+        unsafe {
+            dialog
+                .SetOptions(FOS_PICKFOLDERS | FOS_ALLOWMULTISELECT)
+                .check()?;
+        }
+
+        dialog.set_options(opt)?;
+
         Ok(dialog)
     }
 
@@ -281,14 +593,19 @@ impl IDialog {
         let dialog = IDialog::new_open_dialog(opt)?;
 
         dialog.add_filters(&opt.filters)?;
+        dialog.set_filter_index(&opt.filters, opt.default_filter)?;
         dialog.set_path(&opt.starting_directory)?;
         dialog.set_file_name(&opt.file_name)?;
         dialog.set_title(&opt.title)?;
+        dialog.set_accept_label(&opt.accept_label)?;
+        dialog.set_persistence_id(&opt.persistence_id)?;
 
         unsafe {
             dialog.SetOptions(FOS_ALLOWMULTISELECT).check()?;
         }
 
+        dialog.set_options(opt)?;
+
         Ok(dialog)
     }
 }
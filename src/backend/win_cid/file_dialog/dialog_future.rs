@@ -1,54 +1,48 @@
 use super::super::thread_future::ThreadFuture;
-use super::super::utils::init_com;
+use super::super::utils::{cancelled_to_none, init_com};
 use super::dialog_ffi::IDialog;
 
-use winapi::shared::winerror::HRESULT;
+use winapi::shared::winerror::{ERROR_CANCELLED, HRESULT, HRESULT_FROM_WIN32};
 
 use crate::file_handle::FileHandle;
+use crate::{DialogHandle, Error};
+
+/// Runs `build` and shows the dialog on the current thread,
+/// closing `handle` sends `WM_CLOSE` to the dialog window which cancels the dialog, see [`advise`](super::dialog_events::advise)
+pub fn run_dialog<T>(
+    handle: &DialogHandle,
+    build: impl FnOnce() -> Result<IDialog, HRESULT>,
+    result: impl FnOnce(&IDialog) -> Result<T, HRESULT>,
+) -> Result<Option<T>, Error> {
+    let ret = init_com(|| {
+        let dialog = build()?;
+        if handle.is_closed() {
+            return Err(HRESULT_FROM_WIN32(ERROR_CANCELLED));
+        }
+        dialog.show()?;
+        result(&dialog)
+    })
+    .and_then(|res| res);
+
+    cancelled_to_none(ret)
+}
 
 pub fn single_return_future<F: FnOnce() -> Result<IDialog, HRESULT> + Send + 'static>(
+    handle: DialogHandle,
     build: F,
-) -> ThreadFuture<Option<FileHandle>> {
+) -> ThreadFuture<Result<Option<FileHandle>, Error>> {
     ThreadFuture::new(move |data| {
-        let ret: Result<(), HRESULT> = (|| {
-            init_com(|| {
-                let dialog = build()?;
-                dialog.show()?;
-
-                let path = dialog.get_result().ok().map(FileHandle::wrap);
-                *data = Some(path);
-
-                Ok(())
-            })?
-        })();
-
-        if ret.is_err() {
-            *data = Some(None);
-        }
+        let path = run_dialog(&handle, build, IDialog::get_result);
+        *data = Some(path.map(|path| path.map(FileHandle::wrap)));
     })
 }
 
 pub fn multiple_return_future<F: FnOnce() -> Result<IDialog, HRESULT> + Send + 'static>(
+    handle: DialogHandle,
     build: F,
-) -> ThreadFuture<Option<Vec<FileHandle>>> {
+) -> ThreadFuture<Result<Option<Vec<FileHandle>>, Error>> {
     ThreadFuture::new(move |data| {
-        let ret: Result<(), HRESULT> = (|| {
-            init_com(|| {
-                let dialog = build()?;
-                dialog.show()?;
-
-                let list = dialog
-                    .get_results()
-                    .ok()
-                    .map(|r| r.into_iter().map(FileHandle::wrap).collect());
-                *data = Some(list);
-
-                Ok(())
-            })?
-        })();
-
-        if ret.is_err() {
-            *data = Some(None);
-        }
+        let list = run_dialog(&handle, build, IDialog::get_results);
+        *data = Some(list.map(|list| list.map(|r| r.into_iter().map(FileHandle::wrap).collect())));
     })
 }
@@ -0,0 +1,223 @@
+use super::dialog_template::{DialogTemplate, BUTTON, EDIT, STATIC};
+use super::thread_future::ThreadFuture;
+use crate::backend::DialogFutureType;
+use crate::localizer::{localize, mnemonic};
+use crate::{BuiltinString, InputDialog};
+
+use winapi::shared::basetsd::INT_PTR;
+use winapi::shared::minwindef::{FALSE, LOWORD, LPARAM, TRUE, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::winuser::{
+    DialogBoxIndirectParamW, EndDialog, GetDlgItem, GetWindowLongPtrW, GetWindowTextLengthW,
+    GetWindowTextW, InvalidateRect, IsDlgButtonChecked, SendMessageW, SetFocus, SetWindowLongPtrW,
+    SetWindowTextW, BST_CHECKED, BS_AUTOCHECKBOX, BS_DEFPUSHBUTTON, BS_PUSHBUTTON,
+    EM_GETPASSWORDCHAR, EM_SETPASSWORDCHAR, EM_SETSEL, ES_AUTOHSCROLL, ES_AUTOVSCROLL,
+    ES_MULTILINE, ES_PASSWORD, ES_WANTRETURN, GWLP_USERDATA, IDCANCEL, IDOK, SS_LEFT, WM_COMMAND,
+    WM_INITDIALOG, WS_BORDER, WS_TABSTOP, WS_VSCROLL,
+};
+
+#[cfg(feature = "parent")]
+use raw_window_handle::RawWindowHandle;
+
+use std::{
+    ffi::{c_void, OsStr},
+    iter::once,
+    os::raw::c_int,
+    os::windows::ffi::OsStrExt,
+    ptr,
+};
+
+const ID_EDIT: u16 = 100;
+const ID_REVEAL: u16 = 101;
+
+/// Win32 has no input dialog, it is built from an in-memory dialog template
+pub struct WinInputDialog {
+    parent: Option<*mut c_void>,
+    template: Vec<u32>,
+    default: Vec<u16>,
+}
+
+// Oh god, I don't like sending RawWindowHandle between threads but here we go anyways...
+// fingers crossed
+unsafe impl Send for WinInputDialog {}
+
+/// Shared with the dialog procedure through `GWLP_USERDATA`
+struct State {
+    default: Vec<u16>,
+    /// Mask of a password field, restored when "Show password" is unchecked
+    password_char: WPARAM,
+    result: Option<String>,
+}
+
+impl WinInputDialog {
+    pub fn new(opt: InputDialog) -> Self {
+        #[cfg(feature = "parent")]
+        let parent = match opt.parent() {
+            Some(RawWindowHandle::Windows(handle)) => Some(handle.hwnd),
+            None => None,
+            _ => unreachable!("unsupported window handle, expected: Windows"),
+        };
+        #[cfg(not(feature = "parent"))]
+        let parent = None;
+
+        Self {
+            parent,
+            template: template(&opt),
+            // Multi-line edit controls break lines with CRLF
+            default: OsStr::new(&opt.default.replace("\r\n", "\n").replace('\n', "\r\n"))
+                .encode_wide()
+                .chain(once(0))
+                .collect(),
+        }
+    }
+
+    pub fn run(self) -> Option<String> {
+        let mut state = State {
+            default: self.default,
+            password_char: 0,
+            result: None,
+        };
+
+        unsafe {
+            DialogBoxIndirectParamW(
+                GetModuleHandleW(ptr::null()),
+                self.template.as_ptr() as *const _,
+                self.parent.unwrap_or_else(|| ptr::null_mut()) as _,
+                Some(dialog_proc),
+                &mut state as *mut State as LPARAM,
+            );
+        }
+
+        state.result
+    }
+
+    pub fn run_async(self) -> ThreadFuture<Option<String>> {
+        ThreadFuture::new(move |data| *data = Some(self.run()))
+    }
+}
+
+unsafe extern "system" fn dialog_proc(
+    hwnd: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> INT_PTR {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam);
+            let state = &mut *(lparam as *mut State);
+
+            let edit = GetDlgItem(hwnd, ID_EDIT as c_int);
+            state.password_char = SendMessageW(edit, EM_GETPASSWORDCHAR, 0, 0) as WPARAM;
+            SetWindowTextW(edit, state.default.as_ptr());
+            // Typing replaces the default text
+            SendMessageW(edit, EM_SETSEL, 0, -1);
+            SetFocus(edit);
+
+            // The focus is set already
+            FALSE as INT_PTR
+        }
+        WM_COMMAND => {
+            let id = LOWORD(wparam as u32) as c_int;
+            if id == IDOK {
+                let state = &mut *(GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut State);
+
+                let edit = GetDlgItem(hwnd, ID_EDIT as c_int);
+                let mut text = vec![0u16; GetWindowTextLengthW(edit) as usize + 1];
+                let len = GetWindowTextW(edit, text.as_mut_ptr(), text.len() as c_int);
+                let text = String::from_utf16_lossy(&text[..len as usize]);
+                state.result = Some(text.replace("\r\n", "\n"));
+
+                EndDialog(hwnd, IDOK as INT_PTR);
+            } else if id == IDCANCEL {
+                // Also sent by Escape and the close button
+                EndDialog(hwnd, IDCANCEL as INT_PTR);
+            } else if id == ID_REVEAL as c_int {
+                let state = &*(GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const State);
+
+                let edit = GetDlgItem(hwnd, ID_EDIT as c_int);
+                let reveal = IsDlgButtonChecked(hwnd, ID_REVEAL as c_int) as WPARAM == BST_CHECKED;
+                let mask = if reveal { 0 } else { state.password_char };
+                SendMessageW(edit, EM_SETPASSWORDCHAR, mask, 0);
+                InvalidateRect(edit, ptr::null(), TRUE);
+            }
+            TRUE as INT_PTR
+        }
+        _ => FALSE as INT_PTR,
+    }
+}
+
+/// Dialog with the prompt, the edit field, the optional "Show password" checkbox
+/// and the OK and Cancel buttons
+fn template(opt: &InputDialog) -> Vec<u32> {
+    let reveal = opt.password && opt.reveal_toggle;
+
+    // Dialog units of Segoe UI 9pt are about 7/4 by 15/8 pixels
+    let (edit_width, edit_height) = if opt.multiline {
+        let (width, height) = opt.size_hint();
+        ((width * 4 / 7) as u16, (height * 8 / 15) as u16)
+    } else {
+        (206, 14)
+    };
+    let width = edit_width.max(206) + 14;
+
+    // Rows below the edit field
+    let mut y = 38 + edit_height + 5;
+    let reveal_y = y;
+    y += if reveal { 18 } else { 2 };
+    let buttons_y = y;
+    let height = y + 21;
+
+    let mut template = DialogTemplate::new(&opt.title, 4 + reveal as u16, width, height);
+
+    template.item(SS_LEFT, [7, 7, width - 14, 28], 0xFFFF, STATIC, &opt.prompt);
+
+    let mut style = WS_BORDER | WS_TABSTOP;
+    if opt.multiline {
+        style |= ES_MULTILINE | ES_AUTOVSCROLL | ES_WANTRETURN | WS_VSCROLL;
+    } else {
+        style |= ES_AUTOHSCROLL;
+    }
+    if opt.password {
+        style |= ES_PASSWORD;
+    }
+    template.item(style, [7, 38, edit_width, edit_height], ID_EDIT, EDIT, "");
+
+    if reveal {
+        template.item(
+            BS_AUTOCHECKBOX | WS_TABSTOP,
+            [7, reveal_y, width - 14, 10],
+            ID_REVEAL,
+            BUTTON,
+            &mnemonic(&localize(BuiltinString::ShowPassword), '&'),
+        );
+    }
+
+    template.item(
+        BS_DEFPUSHBUTTON | WS_TABSTOP,
+        [width - 111, buttons_y, 50, 14],
+        IDOK as u16,
+        BUTTON,
+        &localize(BuiltinString::Ok),
+    );
+    template.item(
+        BS_PUSHBUTTON | WS_TABSTOP,
+        [width - 57, buttons_y, 50, 14],
+        IDCANCEL as u16,
+        BUTTON,
+        &localize(BuiltinString::Cancel),
+    );
+
+    template.finish()
+}
+
+pub fn show(opt: InputDialog) -> Option<String> {
+    let dialog = WinInputDialog::new(opt);
+    dialog.run()
+}
+
+pub fn show_async(opt: InputDialog) -> DialogFutureType<Option<String>> {
+    let dialog = WinInputDialog::new(opt);
+    Box::pin(dialog.run_async())
+}
@@ -1,9 +1,32 @@
 use super::thread_future::ThreadFuture;
+use super::utils::{owner, rtl, AppModal};
+use crate::backend::DialogFutureType;
 use crate::dialog::{MessageButtons, MessageDialog, MessageLevel};
+use crate::dialog_links::{plain_description, LinkHandler};
+use crate::{DialogChoices, Modality};
+
+#[cfg(feature = "task-dialog")]
+use crate::dialog_windows::TaskDialogOptions;
 
+use winapi::shared::basetsd::LONG_PTR;
+use winapi::shared::minwindef::{BOOL, FALSE, FARPROC, LPARAM, UINT, WPARAM};
+use winapi::shared::ntdef::{LPCWSTR, PCWSTR};
+use winapi::shared::windef::{HICON, HWND};
+use winapi::shared::winerror::{HRESULT, SUCCEEDED, S_OK};
+#[cfg(feature = "task-dialog")]
+use winapi::um::commctrl::TDF_USE_COMMAND_LINKS;
+use winapi::um::commctrl::{
+    TASKDIALOGCONFIG, TASKDIALOG_BUTTON, TASKDIALOG_FLAGS, TDCBF_CANCEL_BUTTON, TDCBF_NO_BUTTON,
+    TDCBF_OK_BUTTON, TDCBF_YES_BUTTON, TDF_ALLOW_DIALOG_CANCELLATION, TDF_ENABLE_HYPERLINKS,
+    TDF_RTL_LAYOUT, TDF_USE_HICON_MAIN, TDN_HYPERLINK_CLICKED,
+};
+use winapi::um::libloaderapi::{GetProcAddress, LoadLibraryW};
+use winapi::um::shellapi::ShellExecuteW;
 use winapi::um::winuser::{
-    MessageBoxW, IDOK, IDYES, MB_ICONERROR, MB_ICONINFORMATION, MB_ICONWARNING, MB_OK, MB_OKCANCEL,
-    MB_YESNO,
+    LoadIconW, MessageBoxW, IDCANCEL, IDI_QUESTION, IDNO, IDOK, IDYES, MB_DEFBUTTON1,
+    MB_DEFBUTTON2, MB_DEFBUTTON3, MB_ICONERROR, MB_ICONINFORMATION, MB_ICONQUESTION,
+    MB_ICONWARNING, MB_OK, MB_OKCANCEL, MB_RIGHT, MB_RTLREADING, MB_YESNO, MB_YESNOCANCEL,
+    SW_SHOWNORMAL,
 };
 
 #[cfg(feature = "parent")]
@@ -12,44 +35,106 @@ use raw_window_handle::RawWindowHandle;
 use std::{
     ffi::{c_void, OsStr},
     iter::once,
+    mem,
+    os::raw::c_int,
     os::windows::ffi::OsStrExt,
     ptr,
 };
 
+/// `TaskDialogIndirect` is only exported by comctl32 version 6, so it is looked up at runtime
+type TaskDialogIndirect = unsafe extern "system" fn(
+    *const TASKDIALOGCONFIG,
+    *mut c_int,
+    *mut c_int,
+    *mut BOOL,
+) -> HRESULT;
+
+// MAKEINTRESOURCEW(-1), (-2) and (-3)
+const TD_WARNING_ICON: usize = 0xFFFF;
+const TD_ERROR_ICON: usize = 0xFFFE;
+const TD_INFORMATION_ICON: usize = 0xFFFD;
+
+/// Id of the first custom button, the following ones count up from it
+const BUTTON_ID: c_int = 100;
+
 pub struct WinMessageDialog {
     parent: Option<*mut c_void>,
+    modality: Modality,
     text: Vec<u16>,
     caption: Vec<u16>,
+    description: Vec<u16>,
     flags: u32,
+    default_button: usize,
+    /// TaskDialog icon, `None` for the question icon that has to be loaded
+    icon: Option<usize>,
+    /// Labels of the buttons, see [`MessageDialog::button_labels`]
+    labels: Vec<String>,
+    /// Ids the dialog returns for the buttons, in the order of `labels`
+    ids: Vec<c_int>,
+    custom: bool,
+    /// Label of the suppression checkbox, only TaskDialog has one
+    suppression: Option<Vec<u16>>,
+    choices: DialogChoices,
+    hyperlinks: bool,
+    link_handler: Option<LinkHandler>,
+    #[cfg(feature = "task-dialog")]
+    options: TaskDialogOptions,
 }
 
 // Oh god, I don't like sending RawWindowHandle between threads but here we go anyways...
 // fingers crossed
 unsafe impl Send for WinMessageDialog {}
 
+pub(super) fn wide(s: &str) -> Vec<u16> {
+    OsStr::new(s).encode_wide().chain(once(0)).collect()
+}
+
 impl WinMessageDialog {
     pub fn new(opt: MessageDialog) -> Self {
-        let input = format!("{}\n{}", opt.title, opt.description);
-        let text: Vec<u16> = OsStr::new(&input).encode_wide().chain(once(0)).collect();
-        let caption: Vec<u16> = OsStr::new(&opt.title)
-            .encode_wide()
-            .chain(once(0))
-            .collect();
+        // The message box shows links as plain text
+        let input = format!("{}\n{}", opt.title, plain_description(&opt));
+        let text: Vec<u16> = wide(&input);
+        let caption: Vec<u16> = wide(&opt.title);
+        let description: Vec<u16> = wide(&opt.description);
+
+        let (level, icon) = match opt.level {
+            MessageLevel::Info => (MB_ICONINFORMATION, Some(TD_INFORMATION_ICON)),
+            MessageLevel::Warning => (MB_ICONWARNING, Some(TD_WARNING_ICON)),
+            MessageLevel::Error => (MB_ICONERROR, Some(TD_ERROR_ICON)),
+            MessageLevel::Question => (MB_ICONQUESTION, None),
+        };
+
+        let custom = !opt.custom_buttons.is_empty();
 
-        let level = match opt.level {
-            MessageLevel::Info => MB_ICONINFORMATION,
-            MessageLevel::Warning => MB_ICONWARNING,
-            MessageLevel::Error => MB_ICONERROR,
+        // Without TaskDialog the custom buttons fall back to `Ok`/`Cancel`
+        let (buttons, ids) = match opt.buttons {
+            _ if custom => {
+                let ids = (0..opt.custom_buttons.len() as c_int).map(|i| BUTTON_ID + i);
+                let buttons = if opt.custom_buttons.len() > 1 {
+                    MB_OKCANCEL
+                } else {
+                    MB_OK
+                };
+                (buttons, ids.collect())
+            }
+            MessageButtons::Ok => (MB_OK, vec![IDOK]),
+            MessageButtons::OkCancel => (MB_OKCANCEL, vec![IDOK, IDCANCEL]),
+            MessageButtons::YesNo => (MB_YESNO, vec![IDYES, IDNO]),
+            MessageButtons::YesNoCancel => (MB_YESNOCANCEL, vec![IDYES, IDNO, IDCANCEL]),
         };
 
-        let buttons = match opt.buttons {
-            MessageButtons::Ok => MB_OK,
-            MessageButtons::OkCancel => MB_OKCANCEL,
-            MessageButtons::YesNo => MB_YESNO,
+        let default_button = match opt.default_button {
+            0 => MB_DEFBUTTON1,
+            // Without TaskDialog the custom buttons after the first stand for `Cancel`
+            _ if custom => MB_DEFBUTTON2,
+            1 => MB_DEFBUTTON2,
+            _ => MB_DEFBUTTON3,
         };
 
+        let layout = if rtl() { MB_RTLREADING | MB_RIGHT } else { 0 };
+
         #[cfg(feature = "parent")]
-        let parent = match opt.parent {
+        let parent = match opt.parent() {
             Some(RawWindowHandle::Windows(handle)) => Some(handle.hwnd),
             None => None,
             _ => unreachable!("unsupported window handle, expected: Windows"),
@@ -58,46 +143,251 @@ impl WinMessageDialog {
         let parent = None;
 
         Self {
-            parent,
+            parent: owner(parent, opt.modality),
+            modality: opt.modality,
             text,
             caption,
-            flags: level | buttons,
+            description,
+            flags: level | buttons | default_button | layout,
+            default_button: opt.default_button,
+            icon,
+            labels: opt.button_labels(),
+            ids,
+            custom,
+            suppression: opt.suppression.as_deref().map(wide),
+            choices: opt.choices,
+            hyperlinks: opt.hyperlinks,
+            link_handler: opt.link_handler,
+            #[cfg(feature = "task-dialog")]
+            options: opt.task_dialog,
         }
     }
 
     pub fn run(self) -> bool {
-        let ret = unsafe {
+        self.run_index() == Some(0)
+    }
+
+    pub fn run_custom(self) -> Option<String> {
+        let index = self.run_index()?;
+        self.labels.get(index).cloned()
+    }
+
+    /// Index of the pressed button, `None` when the dialog was closed without pressing one
+    fn run_index(&self) -> Option<usize> {
+        // The `task-dialog` feature uses TaskDialog for all dialogs, otherwise only custom buttons,
+        // the suppression checkbox and links need it
+        let task_dialog = self.custom || self.suppression.is_some() || self.hyperlinks;
+        let _app_modal = AppModal::new(self.modality);
+        if cfg!(feature = "task-dialog") || task_dialog {
+            if let Some(pressed) = self.task_dialog() {
+                return self.ids.iter().position(|&id| id == pressed);
+            }
+        }
+
+        let ret = self.message_box();
+        if self.custom {
+            match ret {
+                IDOK => Some(0),
+                IDCANCEL if self.labels.len() > 1 => Some(self.labels.len() - 1),
+                _ => None,
+            }
+        } else {
+            self.ids.iter().position(|&id| id == ret)
+        }
+    }
+
+    fn message_box(&self) -> c_int {
+        unsafe {
             MessageBoxW(
                 self.parent.unwrap_or_else(|| ptr::null_mut()) as _,
                 self.text.as_ptr(),
                 self.caption.as_ptr(),
                 self.flags,
             )
+        }
+    }
+
+    /// Shows a TaskDialog, returns the id of the pressed button,
+    /// or `None` when TaskDialog is not available
+    fn task_dialog(&self) -> Option<c_int> {
+        let task_dialog_indirect = task_dialog_indirect()?;
+
+        let labels: Vec<Vec<u16>> = if self.custom {
+            self.labels.iter().map(|label| wide(label)).collect()
+        } else {
+            Vec::new()
         };
+        let buttons: Vec<TASKDIALOG_BUTTON> = labels
+            .iter()
+            .zip(&self.ids)
+            .map(|(label, &id)| TASKDIALOG_BUTTON {
+                nButtonID: id,
+                pszButtonText: label.as_ptr(),
+            })
+            .collect();
+
+        let common_buttons = if self.custom {
+            0
+        } else {
+            self.ids.iter().fold(0, |buttons, &id| {
+                buttons
+                    | match id {
+                        IDOK => TDCBF_OK_BUTTON,
+                        IDCANCEL => TDCBF_CANCEL_BUTTON,
+                        IDYES => TDCBF_YES_BUTTON,
+                        _ => TDCBF_NO_BUTTON,
+                    }
+            })
+        };
+
+        #[cfg(feature = "task-dialog")]
+        let expanded_text = self.options.expanded_text.as_deref().map(wide);
+        #[cfg(feature = "task-dialog")]
+        let footer = self.options.footer.as_deref().map(wide);
+
+        unsafe {
+            let mut config: TASKDIALOGCONFIG = mem::zeroed();
+            config.cbSize = mem::size_of::<TASKDIALOGCONFIG>() as u32;
+            config.hwndParent = self.parent.unwrap_or_else(|| ptr::null_mut()) as _;
+            config.dwFlags = TDF_ALLOW_DIALOG_CANCELLATION | rtl_layout();
+            config.dwCommonButtons = common_buttons;
+            config.pszWindowTitle = self.caption.as_ptr();
+            // The struct is packed, so the icon union is written without a reference
+            let main_icon = ptr::addr_of_mut!(config.u1);
+            match self.icon {
+                Some(icon) => ptr::write_unaligned(main_icon as *mut PCWSTR, icon as PCWSTR),
+                // TaskDialog has no question icon, the one of MessageBox is used instead
+                None => {
+                    config.dwFlags |= TDF_USE_HICON_MAIN;
+                    let icon = LoadIconW(ptr::null_mut(), IDI_QUESTION);
+                    ptr::write_unaligned(main_icon as *mut HICON, icon);
+                }
+            }
+            config.pszMainInstruction = self.caption.as_ptr();
+            config.pszContent = self.description.as_ptr();
+            config.cButtons = buttons.len() as u32;
+            config.pButtons = buttons.as_ptr();
+            config.nDefaultButton = self.ids.get(self.default_button).copied().unwrap_or(0);
+            if let Some(label) = &self.suppression {
+                config.pszVerificationText = label.as_ptr();
+            }
+
+            #[cfg(feature = "task-dialog")]
+            {
+                if let Some(text) = &expanded_text {
+                    config.pszExpandedInformation = text.as_ptr();
+                }
+                if let Some(text) = &footer {
+                    config.pszFooter = text.as_ptr();
+                }
+                // Only custom buttons can be command links, the common ones stay in the button row
+                if self.options.command_links && self.custom {
+                    config.dwFlags |= TDF_USE_COMMAND_LINKS;
+                }
+            }
+
+            if self.hyperlinks {
+                config.dwFlags |= TDF_ENABLE_HYPERLINKS;
+                config.pfCallback = Some(callback);
+                config.lpCallbackData = &self.link_handler as *const _ as LONG_PTR;
+            }
 
-        ret == IDOK || ret == IDYES
+            let mut pressed: c_int = 0;
+            let mut suppressed: BOOL = FALSE;
+            let res = task_dialog_indirect(&config, &mut pressed, ptr::null_mut(), &mut suppressed);
+            if !SUCCEEDED(res) {
+                return None;
+            }
+
+            if self.suppression.is_some() {
+                self.choices.set_suppressed(suppressed != FALSE);
+            }
+            Some(pressed)
+        }
     }
 
     pub fn run_async(self) -> ThreadFuture<bool> {
         ThreadFuture::new(move |data| *data = Some(self.run()))
     }
+
+    pub fn run_custom_async(self) -> ThreadFuture<Option<String>> {
+        ThreadFuture::new(move |data| *data = Some(self.run_custom()))
+    }
 }
 
-use crate::backend::MessageDialogImpl;
+pub(super) fn task_dialog_indirect() -> Option<TaskDialogIndirect> {
+    unsafe {
+        let module = LoadLibraryW(wide("comctl32.dll").as_ptr());
+        if module.is_null() {
+            return None;
+        }
 
-impl MessageDialogImpl for MessageDialog {
-    fn show(self) -> bool {
-        let dialog = WinMessageDialog::new(self);
-        dialog.run()
+        let proc = GetProcAddress(module, b"TaskDialogIndirect\0".as_ptr() as _);
+        if proc.is_null() {
+            None
+        } else {
+            Some(mem::transmute::<FARPROC, TaskDialogIndirect>(proc))
+        }
     }
 }
 
-use crate::backend::AsyncMessageDialogImpl;
-use crate::backend::DialogFutureType;
+/// `TDF_RTL_LAYOUT` when `set_text_direction` asked for mirrored dialogs
+pub(super) fn rtl_layout() -> TASKDIALOG_FLAGS {
+    if rtl() {
+        TDF_RTL_LAYOUT
+    } else {
+        0
+    }
+}
 
-impl AsyncMessageDialogImpl for MessageDialog {
-    fn show_async(self) -> DialogFutureType<bool> {
-        let dialog = WinMessageDialog::new(self);
-        Box::pin(dialog.run_async())
+/// Opens clicked `<a href="...">` links with the link handler in `data`, or with the default handler
+unsafe extern "system" fn callback(
+    hwnd: HWND,
+    msg: UINT,
+    _: WPARAM,
+    lparam: LPARAM,
+    data: LONG_PTR,
+) -> HRESULT {
+    if msg == TDN_HYPERLINK_CLICKED {
+        let href = lparam as LPCWSTR;
+        match &*(data as *const Option<LinkHandler>) {
+            Some(handler) => {
+                let len = (0..).take_while(|&i| *href.offset(i) != 0).count();
+                let href = String::from_utf16_lossy(std::slice::from_raw_parts(href, len));
+                (handler.0)(&href);
+            }
+            None => {
+                let operation = wide("open");
+                ShellExecuteW(
+                    hwnd,
+                    operation.as_ptr(),
+                    href,
+                    ptr::null(),
+                    ptr::null(),
+                    SW_SHOWNORMAL,
+                );
+            }
+        }
     }
+    S_OK
+}
+
+pub fn show(opt: MessageDialog) -> bool {
+    let dialog = WinMessageDialog::new(opt);
+    dialog.run()
+}
+
+pub fn show_async(opt: MessageDialog) -> DialogFutureType<bool> {
+    let dialog = WinMessageDialog::new(opt);
+    Box::pin(dialog.run_async())
+}
+
+pub fn show_custom(opt: MessageDialog) -> Option<String> {
+    let dialog = WinMessageDialog::new(opt);
+    dialog.run_custom()
+}
+
+pub fn show_custom_async(opt: MessageDialog) -> DialogFutureType<Option<String>> {
+    let dialog = WinMessageDialog::new(opt);
+    Box::pin(dialog.run_custom_async())
 }
@@ -0,0 +1,150 @@
+use crate::{Error, Notification};
+
+use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::shared::winerror::E_FAIL;
+use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::shellapi::{
+    Shell_NotifyIconW, NIF_ICON, NIF_INFO, NIF_MESSAGE, NIF_TIP, NIIF_INFO, NIM_ADD, NIM_DELETE,
+    NIN_BALLOONHIDE, NIN_BALLOONTIMEOUT, NIN_BALLOONUSERCLICK, NOTIFYICONDATAW,
+};
+use winapi::um::winuser::{
+    CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW, LoadIconW,
+    PostQuitMessage, RegisterClassW, SetTimer, TranslateMessage, HWND_MESSAGE, IDI_APPLICATION,
+    MSG, WM_APP, WM_DESTROY, WM_TIMER, WNDCLASSW,
+};
+
+use std::{ffi::OsStr, iter::once, mem, os::windows::ffi::OsStrExt, ptr, sync::mpsc, thread};
+
+/// Message the notification area sends about the icon
+const WM_NOTIFY_ICON: UINT = WM_APP + 1;
+
+/// Removes the icon even when the balloon never reports back, eg. in focus assist mode
+const TIMEOUT_MS: UINT = 30_000;
+
+/// Win32 has no notifications of its own, the balloon of a notification area icon is shown instead.
+/// The icon belongs to a hidden window on its own thread, it is removed once the balloon is gone.
+pub fn show(opt: Notification) -> Result<(), Error> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || unsafe {
+        let hwnd = match add_icon(&opt) {
+            Ok(hwnd) => hwnd,
+            Err(err) => {
+                let _ = sender.send(Err(err));
+                return;
+            }
+        };
+        let _ = sender.send(Ok(()));
+
+        SetTimer(hwnd, 1, TIMEOUT_MS, None);
+
+        let mut msg: MSG = mem::zeroed();
+        while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) > 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    });
+
+    receiver.recv().unwrap_or(Err(Error::Hresult(E_FAIL)))
+}
+
+unsafe fn add_icon(opt: &Notification) -> Result<HWND, Error> {
+    let instance = GetModuleHandleW(ptr::null());
+    let class_name = wide("rfd_notification");
+
+    let mut class: WNDCLASSW = mem::zeroed();
+    class.lpfnWndProc = Some(window_proc);
+    class.hInstance = instance;
+    class.lpszClassName = class_name.as_ptr();
+    // Fails for every notification but the first one, the class stays registered
+    RegisterClassW(&class);
+
+    let hwnd = CreateWindowExW(
+        0,
+        class_name.as_ptr(),
+        ptr::null(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        HWND_MESSAGE,
+        ptr::null_mut(),
+        instance,
+        ptr::null_mut(),
+    );
+    if hwnd.is_null() {
+        return Err(Error::Hresult(E_FAIL));
+    }
+
+    let mut data = icon_data(hwnd);
+    data.uFlags = NIF_MESSAGE | NIF_ICON | NIF_TIP | NIF_INFO;
+    data.uCallbackMessage = WM_NOTIFY_ICON;
+    data.hIcon = LoadIconW(ptr::null_mut(), IDI_APPLICATION);
+    data.dwInfoFlags = NIIF_INFO;
+    copy_wide(&mut data.szTip, &opt.title);
+    copy_wide(&mut data.szInfoTitle, &opt.title);
+    // Balloons without text are not shown
+    let body = if opt.body.is_empty() { " " } else { &opt.body };
+    copy_wide(&mut data.szInfo, body);
+
+    if Shell_NotifyIconW(NIM_ADD, &mut data) == 0 {
+        DestroyWindow(hwnd);
+        return Err(Error::Hresult(E_FAIL));
+    }
+
+    Ok(hwnd)
+}
+
+unsafe extern "system" fn window_proc(
+    hwnd: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_NOTIFY_ICON => {
+            let event = lparam as UINT;
+            if event == NIN_BALLOONHIDE
+                || event == NIN_BALLOONTIMEOUT
+                || event == NIN_BALLOONUSERCLICK
+            {
+                DestroyWindow(hwnd);
+            }
+            0
+        }
+        WM_TIMER => {
+            DestroyWindow(hwnd);
+            0
+        }
+        WM_DESTROY => {
+            let mut data = icon_data(hwnd);
+            Shell_NotifyIconW(NIM_DELETE, &mut data);
+            PostQuitMessage(0);
+            0
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+/// Identifies the icon of `hwnd`, every window has only one
+unsafe fn icon_data(hwnd: HWND) -> NOTIFYICONDATAW {
+    let mut data: NOTIFYICONDATAW = mem::zeroed();
+    data.cbSize = mem::size_of::<NOTIFYICONDATAW>() as u32;
+    data.hWnd = hwnd;
+    data.uID = 1;
+    data
+}
+
+/// Copies `text` into a fixed size buffer, cut off to leave room for the terminating null
+fn copy_wide(buffer: &mut [u16], text: &str) {
+    let len = buffer.len() - 1;
+    for (dst, src) in buffer.iter_mut().zip(text.encode_utf16().take(len)) {
+        *dst = src;
+    }
+}
+
+fn wide(text: &str) -> Vec<u16> {
+    OsStr::new(text).encode_wide().chain(once(0)).collect()
+}
@@ -0,0 +1,233 @@
+use super::dialog_template::{DialogTemplate, BUTTON, LISTBOX, STATIC};
+use super::message_dialog::{rtl_layout, task_dialog_indirect, wide};
+use super::thread_future::ThreadFuture;
+use crate::backend::DialogFutureType;
+use crate::localizer::localize;
+use crate::{BuiltinString, PickListDialog};
+
+use winapi::shared::basetsd::INT_PTR;
+use winapi::shared::minwindef::{FALSE, HIWORD, LOWORD, LPARAM, TRUE, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::shared::winerror::SUCCEEDED;
+use winapi::um::commctrl::{
+    TASKDIALOGCONFIG, TASKDIALOG_BUTTON, TDCBF_CANCEL_BUTTON, TDF_ALLOW_DIALOG_CANCELLATION,
+    TDF_USE_COMMAND_LINKS,
+};
+use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::winuser::{
+    DialogBoxIndirectParamW, EndDialog, GetDlgItem, GetWindowLongPtrW, SendMessageW, SetFocus,
+    SetWindowLongPtrW, BS_DEFPUSHBUTTON, BS_PUSHBUTTON, GWLP_USERDATA, IDCANCEL, IDOK, LBN_DBLCLK,
+    LBS_NOINTEGRALHEIGHT, LBS_NOTIFY, LB_ADDSTRING, LB_ERR, LB_GETCURSEL, LB_SETCURSEL, SS_LEFT,
+    WM_COMMAND, WM_INITDIALOG, WS_BORDER, WS_TABSTOP, WS_VSCROLL,
+};
+
+#[cfg(feature = "parent")]
+use raw_window_handle::RawWindowHandle;
+
+use std::{ffi::c_void, mem, os::raw::c_int, ptr};
+
+/// Id of the first command link, the following ones count up from it
+const BUTTON_ID: c_int = 100;
+const ID_LIST: u16 = 100;
+
+/// Longer lists are shown in a list box instead of command links
+const MAX_COMMAND_LINKS: usize = 8;
+
+pub struct WinPickListDialog {
+    parent: Option<*mut c_void>,
+    title: String,
+    prompt: String,
+    items: Vec<String>,
+}
+
+// Oh god, I don't like sending RawWindowHandle between threads but here we go anyways...
+// fingers crossed
+unsafe impl Send for WinPickListDialog {}
+
+/// Shared with the dialog procedure through `GWLP_USERDATA`
+struct State {
+    items: Vec<Vec<u16>>,
+    result: Option<usize>,
+}
+
+impl WinPickListDialog {
+    pub fn new(opt: PickListDialog) -> Self {
+        #[cfg(feature = "parent")]
+        let parent = match opt.parent() {
+            Some(RawWindowHandle::Windows(handle)) => Some(handle.hwnd),
+            None => None,
+            _ => unreachable!("unsupported window handle, expected: Windows"),
+        };
+        #[cfg(not(feature = "parent"))]
+        let parent = None;
+
+        Self {
+            parent,
+            title: opt.title,
+            prompt: opt.prompt,
+            items: opt.items,
+        }
+    }
+
+    pub fn run(self) -> Option<usize> {
+        if self.items.len() <= MAX_COMMAND_LINKS {
+            if let Some(pressed) = self.task_dialog() {
+                return pressed;
+            }
+        }
+
+        self.list_box()
+    }
+
+    /// One command link per item, `None` when TaskDialog is not available
+    fn task_dialog(&self) -> Option<Option<usize>> {
+        let task_dialog_indirect = task_dialog_indirect()?;
+
+        let title = wide(&self.title);
+        let prompt = wide(&self.prompt);
+        let labels: Vec<Vec<u16>> = self.items.iter().map(|item| wide(item)).collect();
+        let buttons: Vec<TASKDIALOG_BUTTON> = labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| TASKDIALOG_BUTTON {
+                nButtonID: BUTTON_ID + i as c_int,
+                pszButtonText: label.as_ptr(),
+            })
+            .collect();
+
+        unsafe {
+            let mut config: TASKDIALOGCONFIG = mem::zeroed();
+            config.cbSize = mem::size_of::<TASKDIALOGCONFIG>() as u32;
+            config.hwndParent = self.parent.unwrap_or_else(|| ptr::null_mut()) as _;
+            config.dwFlags = TDF_ALLOW_DIALOG_CANCELLATION | TDF_USE_COMMAND_LINKS | rtl_layout();
+            config.dwCommonButtons = TDCBF_CANCEL_BUTTON;
+            config.pszWindowTitle = title.as_ptr();
+            config.pszMainInstruction = prompt.as_ptr();
+            config.cButtons = buttons.len() as u32;
+            config.pButtons = buttons.as_ptr();
+
+            let mut pressed: c_int = 0;
+            let res = task_dialog_indirect(&config, &mut pressed, ptr::null_mut(), ptr::null_mut());
+            if !SUCCEEDED(res) {
+                return None;
+            }
+
+            let index = (pressed - BUTTON_ID) as usize;
+            Some(if pressed >= BUTTON_ID && index < self.items.len() {
+                Some(index)
+            } else {
+                None
+            })
+        }
+    }
+
+    /// Dialog with a list box, for long lists and systems without TaskDialog
+    fn list_box(&self) -> Option<usize> {
+        // As many rows as fit in 120 dialog units, the list box scrolls for the rest
+        let list_height = (self.items.len().clamp(1, 12) * 10 + 4) as u16;
+        let buttons_y = 38 + list_height + 7;
+
+        let mut template = DialogTemplate::new(&self.title, 4, 220, buttons_y + 21);
+        template.item(SS_LEFT, [7, 7, 206, 28], 0xFFFF, STATIC, &self.prompt);
+        template.item(
+            LBS_NOTIFY | LBS_NOINTEGRALHEIGHT | WS_VSCROLL | WS_BORDER | WS_TABSTOP,
+            [7, 38, 206, list_height],
+            ID_LIST,
+            LISTBOX,
+            "",
+        );
+        template.item(
+            BS_DEFPUSHBUTTON | WS_TABSTOP,
+            [109, buttons_y, 50, 14],
+            IDOK as u16,
+            BUTTON,
+            &localize(BuiltinString::Ok),
+        );
+        template.item(
+            BS_PUSHBUTTON | WS_TABSTOP,
+            [163, buttons_y, 50, 14],
+            IDCANCEL as u16,
+            BUTTON,
+            &localize(BuiltinString::Cancel),
+        );
+        let template = template.finish();
+
+        let mut state = State {
+            items: self.items.iter().map(|item| wide(item)).collect(),
+            result: None,
+        };
+
+        unsafe {
+            DialogBoxIndirectParamW(
+                GetModuleHandleW(ptr::null()),
+                template.as_ptr() as *const _,
+                self.parent.unwrap_or_else(|| ptr::null_mut()) as _,
+                Some(dialog_proc),
+                &mut state as *mut State as LPARAM,
+            );
+        }
+
+        state.result
+    }
+
+    pub fn run_async(self) -> ThreadFuture<Option<usize>> {
+        ThreadFuture::new(move |data| *data = Some(self.run()))
+    }
+}
+
+unsafe extern "system" fn dialog_proc(
+    hwnd: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> INT_PTR {
+    match msg {
+        WM_INITDIALOG => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, lparam);
+            let state = &*(lparam as *const State);
+
+            let list = GetDlgItem(hwnd, ID_LIST as c_int);
+            for item in &state.items {
+                SendMessageW(list, LB_ADDSTRING, 0, item.as_ptr() as LPARAM);
+            }
+            SendMessageW(list, LB_SETCURSEL, 0, 0);
+            SetFocus(list);
+
+            // The focus is set already
+            FALSE as INT_PTR
+        }
+        WM_COMMAND => {
+            let id = LOWORD(wparam as u32) as c_int;
+            // A double click on an item presses OK
+            let double_click =
+                id == ID_LIST as c_int && HIWORD(wparam as u32) as u32 == LBN_DBLCLK as u32;
+
+            if id == IDOK || double_click {
+                let state = &mut *(GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut State);
+
+                let list = GetDlgItem(hwnd, ID_LIST as c_int);
+                let selected = SendMessageW(list, LB_GETCURSEL, 0, 0);
+                if selected != LB_ERR as isize {
+                    state.result = Some(selected as usize);
+                }
+
+                EndDialog(hwnd, IDOK as INT_PTR);
+            } else if id == IDCANCEL {
+                // Also sent by Escape and the close button
+                EndDialog(hwnd, IDCANCEL as INT_PTR);
+            }
+            TRUE as INT_PTR
+        }
+        _ => FALSE as INT_PTR,
+    }
+}
+
+pub fn show(opt: PickListDialog) -> Option<usize> {
+    let dialog = WinPickListDialog::new(opt);
+    dialog.run()
+}
+
+pub fn show_async(opt: PickListDialog) -> DialogFutureType<Option<usize>> {
+    let dialog = WinPickListDialog::new(opt);
+    Box::pin(dialog.run_async())
+}
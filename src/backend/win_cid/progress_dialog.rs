@@ -0,0 +1,143 @@
+use super::message_dialog::{rtl_layout, task_dialog_indirect, wide};
+use crate::{ProgressDialog, ProgressState};
+
+use winapi::shared::basetsd::LONG_PTR;
+use winapi::shared::minwindef::{LPARAM, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::shared::winerror::{HRESULT, S_OK};
+use winapi::um::commctrl::{
+    TASKDIALOGCONFIG, TDCBF_CANCEL_BUTTON, TDE_CONTENT, TDF_ALLOW_DIALOG_CANCELLATION,
+    TDF_CALLBACK_TIMER, TDF_SHOW_MARQUEE_PROGRESS_BAR, TDF_SHOW_PROGRESS_BAR, TDM_CLICK_BUTTON,
+    TDM_SET_ELEMENT_TEXT, TDM_SET_MARQUEE_PROGRESS_BAR, TDM_SET_PROGRESS_BAR_MARQUEE,
+    TDM_SET_PROGRESS_BAR_POS, TDM_SET_PROGRESS_BAR_RANGE, TDN_BUTTON_CLICKED, TDN_CREATED,
+    TDN_TIMER,
+};
+use winapi::um::winuser::{SendMessageW, IDCANCEL};
+
+#[cfg(feature = "parent")]
+use raw_window_handle::RawWindowHandle;
+
+use std::{mem, ptr};
+
+/// The bar counts in steps of 0.1%
+const RANGE: u32 = 1000;
+
+/// Brings the TaskDialog up to date with the state, on every tick of its timer
+struct Poller {
+    state: ProgressState,
+    /// Set once the application closed the dialog, the click on Cancel is not the user's then
+    closing: bool,
+    marquee: Option<bool>,
+    position: Option<WPARAM>,
+    message: Option<String>,
+}
+
+impl Poller {
+    unsafe fn update(&mut self, hwnd: HWND) {
+        let marquee = self.state.marquee();
+        if self.marquee != Some(marquee) {
+            SendMessageW(hwnd, TDM_SET_MARQUEE_PROGRESS_BAR, marquee as WPARAM, 0);
+            SendMessageW(hwnd, TDM_SET_PROGRESS_BAR_MARQUEE, marquee as WPARAM, 0);
+            if !marquee {
+                SendMessageW(hwnd, TDM_SET_PROGRESS_BAR_RANGE, 0, (RANGE << 16) as LPARAM);
+                self.position = None;
+            }
+            self.marquee = Some(marquee);
+        }
+
+        let position = (self.state.progress() * RANGE as f32) as WPARAM;
+        if !marquee && self.position != Some(position) {
+            SendMessageW(hwnd, TDM_SET_PROGRESS_BAR_POS, position, 0);
+            self.position = Some(position);
+        }
+
+        let message = self.state.message();
+        if self.message.as_ref() != Some(&message) {
+            let text = wide(&message);
+            SendMessageW(
+                hwnd,
+                TDM_SET_ELEMENT_TEXT,
+                TDE_CONTENT as WPARAM,
+                text.as_ptr() as LPARAM,
+            );
+            self.message = Some(message);
+        }
+    }
+}
+
+unsafe extern "system" fn callback(
+    hwnd: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    _: LPARAM,
+    data: LONG_PTR,
+) -> HRESULT {
+    let poller = &mut *(data as *mut Poller);
+    match msg {
+        TDN_CREATED | TDN_TIMER => {
+            if poller.state.is_closed() {
+                poller.closing = true;
+                SendMessageW(hwnd, TDM_CLICK_BUTTON, IDCANCEL as WPARAM, 0);
+            } else {
+                poller.update(hwnd);
+            }
+        }
+        TDN_BUTTON_CLICKED if wparam == IDCANCEL as WPARAM && !poller.closing => {
+            poller.state.cancel();
+        }
+        _ => {}
+    }
+    S_OK
+}
+
+/// Shows a TaskDialog with a progress bar on its own thread, without TaskDialog nothing is shown
+pub fn show(opt: ProgressDialog, state: ProgressState) {
+    #[cfg(feature = "parent")]
+    let parent = match opt.parent() {
+        Some(RawWindowHandle::Windows(handle)) => handle.hwnd as usize,
+        None => 0,
+        _ => unreachable!("unsupported window handle, expected: Windows"),
+    };
+    #[cfg(not(feature = "parent"))]
+    let parent = 0usize;
+
+    std::thread::spawn(move || {
+        let task_dialog_indirect = match task_dialog_indirect() {
+            Some(task_dialog_indirect) => task_dialog_indirect,
+            None => return,
+        };
+
+        let title = wide(&opt.title);
+        // The content element only exists when the dialog starts with some text
+        let message = state.message();
+        let content = wide(if message.is_empty() { " " } else { &message });
+
+        let mut poller = Poller {
+            state,
+            closing: false,
+            marquee: None,
+            position: None,
+            message: Some(message),
+        };
+
+        unsafe {
+            let mut config: TASKDIALOGCONFIG = mem::zeroed();
+            config.cbSize = mem::size_of::<TASKDIALOGCONFIG>() as u32;
+            config.hwndParent = parent as _;
+            config.dwFlags = TDF_ALLOW_DIALOG_CANCELLATION | TDF_CALLBACK_TIMER | rtl_layout();
+            config.dwFlags |= if poller.state.marquee() {
+                TDF_SHOW_MARQUEE_PROGRESS_BAR
+            } else {
+                TDF_SHOW_PROGRESS_BAR
+            };
+            config.dwCommonButtons = TDCBF_CANCEL_BUTTON;
+            config.pszWindowTitle = title.as_ptr();
+            config.pszMainInstruction = title.as_ptr();
+            config.pszContent = content.as_ptr();
+            config.pfCallback = Some(callback);
+            config.lpCallbackData = &mut poller as *mut Poller as LONG_PTR;
+
+            task_dialog_indirect(&config, ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+        }
+    });
+}
@@ -1,29 +1,76 @@
 use winapi::{
-    shared::winerror::{HRESULT, SUCCEEDED},
+    shared::{
+        minwindef::{BOOL, DWORD, FALSE, LPARAM, TRUE},
+        windef::HWND,
+        winerror::{ERROR_CANCELLED, HRESULT, HRESULT_FROM_WIN32, RPC_E_CHANGED_MODE, SUCCEEDED},
+    },
     um::{
         combaseapi::{CoInitializeEx, CoUninitialize},
         objbase::{COINIT_APARTMENTTHREADED, COINIT_DISABLE_OLE1DDE},
+        processthreadsapi::GetCurrentProcessId,
+        wingdi::LAYOUT_RTL,
+        winuser::{
+            EnableWindow, EnumWindows, GetProcessDefaultLayout, GetWindowThreadProcessId,
+            IsWindowEnabled, IsWindowVisible, SetProcessDefaultLayout,
+        },
     },
 };
 
-use std::ptr;
+use std::{cell::Cell, ffi::c_void, ptr};
+
+use crate::windows::{com_model, ComModel};
+use crate::{text_direction, Error, Modality, TextDirection};
 
 pub trait ToResult {
     fn check(self) -> Result<HRESULT, HRESULT>;
 }
 
 impl ToResult for HRESULT {
+    #[track_caller]
     fn check(self) -> Result<HRESULT, HRESULT> {
         if SUCCEEDED(self) {
             Ok(self)
         } else {
+            if self == HRESULT_FROM_WIN32(ERROR_CANCELLED) {
+                trace_debug!("dialog cancelled");
+            } else {
+                trace_warn!(
+                    hresult = format_args!("0x{:08X}", self),
+                    caller = %std::panic::Location::caller(),
+                    "COM call failed"
+                );
+            }
             Err(self)
         }
     }
 }
 
+thread_local! {
+    /// Set while [`in_sta`] runs a dialog on a thread the application initialized COM on
+    static REUSE_COM: Cell<bool> = Cell::new(false);
+}
+
+/// Sets [`REUSE_COM`] while alive and puts the previous value back after, also when the dialog panics
+struct ReuseCom(bool);
+
+impl ReuseCom {
+    fn new() -> Self {
+        Self(REUSE_COM.with(|reuse| reuse.replace(true)))
+    }
+}
+
+impl Drop for ReuseCom {
+    fn drop(&mut self) {
+        REUSE_COM.with(|reuse| reuse.set(self.0));
+    }
+}
+
 /// Makes sure that COM lib is initialized long enought
 pub fn init_com<T, F: FnOnce() -> T>(f: F) -> Result<T, HRESULT> {
+    if REUSE_COM.with(Cell::get) {
+        return Ok(f());
+    }
+
     unsafe {
         CoInitializeEx(
             ptr::null_mut(),
@@ -40,3 +87,126 @@ pub fn init_com<T, F: FnOnce() -> T>(f: F) -> Result<T, HRESULT> {
 
     Ok(out)
 }
+
+/// Runs `f`, which shows a dialog from a thread of the application, where the
+/// [`ComModel`](crate::windows::ComModel) wants it
+///
+/// With the default model it runs on the current thread, unless COM is initialized as
+/// multithreaded here, the shell dialogs only work in a single-threaded apartment.
+pub fn in_sta<T, F>(f: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    match com_model() {
+        ComModel::Mta => return crate::ui_thread::spawn(f).wait(),
+        ComModel::ReuseExisting => {
+            let _reuse = ReuseCom::new();
+            return f();
+        }
+        ComModel::Sta => {}
+    }
+
+    let res = unsafe {
+        CoInitializeEx(
+            ptr::null_mut(),
+            COINIT_APARTMENTTHREADED | COINIT_DISABLE_OLE1DDE,
+        )
+    };
+    if res == RPC_E_CHANGED_MODE {
+        return crate::ui_thread::spawn(f).wait();
+    }
+    if SUCCEEDED(res) {
+        unsafe { CoUninitialize() };
+    }
+    f()
+}
+
+/// Closing the dialog fails with `ERROR_CANCELLED`, that becomes `Ok(None)`, every other `HRESULT` is an [`Error`]
+pub fn cancelled_to_none<T>(res: Result<T, HRESULT>) -> Result<Option<T>, Error> {
+    match res {
+        Ok(value) => Ok(Some(value)),
+        Err(hr) if hr == HRESULT_FROM_WIN32(ERROR_CANCELLED) => Ok(None),
+        Err(hr) => Err(Error::Hresult(hr)),
+    }
+}
+
+/// Owner window passed to the dialog, only window modal dialogs keep their parent
+///
+/// Windows dialogs always disable their owner, so dialogs that block nothing have none,
+/// application modal ones get [`AppModal`] instead.
+pub fn owner(parent: Option<*mut c_void>, modality: Modality) -> Option<*mut c_void> {
+    match modality {
+        Modality::WindowModal => parent,
+        Modality::ApplicationModal | Modality::None => None,
+    }
+}
+
+/// Disables every enabled top-level window of the process while it is alive,
+/// so an application modal dialog blocks all of them and not only its owner
+pub struct AppModal(Vec<HWND>);
+
+impl AppModal {
+    /// Starts blocking when `modality` is [`Modality::ApplicationModal`]
+    pub fn new(modality: Modality) -> Self {
+        let mut windows: Vec<HWND> = Vec::new();
+        if modality == Modality::ApplicationModal {
+            unsafe extern "system" fn collect(hwnd: HWND, windows: LPARAM) -> BOOL {
+                let mut pid: DWORD = 0;
+                GetWindowThreadProcessId(hwnd, &mut pid);
+                if pid == GetCurrentProcessId()
+                    && IsWindowVisible(hwnd) != 0
+                    && IsWindowEnabled(hwnd) != 0
+                {
+                    (*(windows as *mut Vec<HWND>)).push(hwnd);
+                }
+                TRUE
+            }
+
+            unsafe {
+                EnumWindows(Some(collect), &mut windows as *mut Vec<HWND> as LPARAM);
+                for hwnd in &windows {
+                    EnableWindow(*hwnd, FALSE);
+                }
+            }
+        }
+        Self(windows)
+    }
+}
+
+impl Drop for AppModal {
+    fn drop(&mut self) {
+        for hwnd in &self.0 {
+            unsafe { EnableWindow(*hwnd, TRUE) };
+        }
+    }
+}
+
+/// Whether `set_text_direction` asked for mirrored dialogs
+pub fn rtl() -> bool {
+    text_direction() == TextDirection::Rtl
+}
+
+/// Mirrors the windows the process creates while it is alive, for the dialogs
+/// built by the shell that take no layout flag
+pub struct RtlLayout(Option<DWORD>);
+
+impl RtlLayout {
+    /// Mirrors when [`rtl`] is set
+    pub fn new() -> Self {
+        let mut previous: DWORD = 0;
+        if !rtl() || unsafe { GetProcessDefaultLayout(&mut previous) } == FALSE {
+            return Self(None);
+        }
+        unsafe { SetProcessDefaultLayout(LAYOUT_RTL) };
+        Self(Some(previous))
+    }
+}
+
+impl Drop for RtlLayout {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0 {
+            unsafe { SetProcessDefaultLayout(previous) };
+        }
+    }
+}
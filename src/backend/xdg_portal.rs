@@ -0,0 +1,511 @@
+//! XDG Desktop Portal
+//! `org.freedesktop.portal.FileChooser` over D-Bus
+
+use std::future::{poll_fn, Future};
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Poll, Waker};
+
+use crate::backend::{DialogBackend, DialogFutureType};
+use crate::localizer::or_localized;
+use crate::{
+    BuiltinString, CredentialDialog, Credentials, DialogHandle, Error, FileDialog, FileHandle,
+    InputDialog, MessageDialog, Modality, Notification, PickListDialog,
+};
+#[cfg(feature = "gtk3")]
+use crate::{ProgressDialog, ProgressState};
+
+use ashpd::desktop::file_chooser::{Choice, FileFilter, SelectedFiles};
+use ashpd::desktop::notification::NotificationProxy;
+use ashpd::desktop::ResponseError;
+use ashpd::{zbus, WindowIdentifier};
+
+#[cfg(feature = "parent")]
+use raw_window_handle::RawWindowHandle;
+
+use pollster::block_on;
+
+/// Identifier of the parent window, a Wayland surface gets exported with `xdg_foreign`
+///
+/// The export is revoked when the identifier is dropped, so it has to live until the dialog is closed.
+#[cfg_attr(not(feature = "parent"), allow(unused_variables))]
+async fn window_identifier(opt: &FileDialog) -> WindowIdentifier {
+    #[cfg(feature = "parent")]
+    let identifier = match opt.parent() {
+        Some(RawWindowHandle::Xlib(handle)) => WindowIdentifier::from_xid(handle.window),
+        Some(RawWindowHandle::Xcb(handle)) => WindowIdentifier::from_xid(handle.window as _),
+        Some(RawWindowHandle::Wayland(handle)) if !handle.surface.is_null() => {
+            // SAFETY: the handle comes from `set_parent`, whose window handle traits guarantee
+            // valid pointers while the window is alive, and the dialog is closed before that.
+            // Compositors without `zxdg_exporter_v2` get an unparented dialog.
+            unsafe { WindowIdentifier::from_wayland_raw(handle.surface, handle.display).await }
+                .unwrap_or_default()
+        }
+        _ => WindowIdentifier::default(),
+    };
+    #[cfg(not(feature = "parent"))]
+    let identifier = WindowIdentifier::default();
+
+    identifier
+}
+
+fn filters(opt: &FileDialog) -> Vec<FileFilter> {
+    opt.filters
+        .iter()
+        .map(|f| {
+            let filter = f
+                .caseless_globs()
+                .fold(FileFilter::new(&f.name), |filter, glob| filter.glob(&glob));
+            f.mime_types
+                .iter()
+                .fold(filter, |filter, mime| filter.mimetype(mime))
+        })
+        .collect()
+}
+
+fn current_filter(opt: &FileDialog, filters: &[FileFilter]) -> Option<FileFilter> {
+    opt.default_filter
+        .and_then(|index| filters.get(index))
+        .cloned()
+}
+
+fn choices(opt: &FileDialog) -> Vec<Choice> {
+    let checkboxes = opt
+        .checkboxes
+        .iter()
+        .map(|c| Choice::boolean(&c.id.to_string(), &c.label, c.default));
+    // Options are keyed by their index
+    let combos = opt.combos.iter().map(|c| {
+        c.options.iter().enumerate().fold(
+            Choice::new(&c.id.to_string(), &c.label, &c.default.to_string()),
+            |choice, (index, option)| choice.insert(&index.to_string(), option),
+        )
+    });
+    checkboxes.chain(combos).collect()
+}
+
+/// Records the checkbox and combo box states of an accepted request
+fn store_choices(opt: &FileDialog, files: &SelectedFiles) {
+    for (id, value) in files.choices() {
+        let id = match id.parse() {
+            Ok(id) => id,
+            Err(_) => continue,
+        };
+        if opt.combos.iter().any(|c| c.id == id) {
+            if let Ok(index) = value.parse() {
+                opt.choices.set_selected(id, index);
+            }
+        } else {
+            opt.choices.set_checked(id, value == "true");
+        }
+    }
+}
+
+fn paths(files: SelectedFiles) -> Vec<PathBuf> {
+    // The portal hands out `file://` uris, anything else has no local path
+    files
+        .uris()
+        .iter()
+        .filter_map(|uri| uri.to_file_path().ok())
+        .collect()
+}
+
+async fn open(
+    opt: FileDialog,
+    title: BuiltinString,
+    multiple: bool,
+    directory: bool,
+) -> ashpd::Result<Vec<PathBuf>> {
+    let filters = if directory { Vec::new() } else { filters(&opt) };
+    let title = or_localized(opt.title.as_deref(), title);
+    let request = SelectedFiles::open_file()
+        .identifier(window_identifier(&opt).await)
+        .title(title.as_str())
+        .accept_label(opt.accept_label.as_deref())
+        .modal(opt.modality != Modality::None)
+        .multiple(multiple)
+        .directory(directory)
+        .current_filter(current_filter(&opt, &filters))
+        .filters(filters)
+        .choices(choices(&opt))
+        .current_folder::<&Path>(opt.starting_directory.as_deref())?
+        .send()
+        .await?;
+
+    let files = request.response()?;
+    store_choices(&opt, &files);
+    Ok(paths(files))
+}
+
+async fn save(opt: FileDialog) -> ashpd::Result<Option<PathBuf>> {
+    let filters = filters(&opt);
+    let title = or_localized(opt.title.as_deref(), BuiltinString::SaveFile);
+    let mut request = SelectedFiles::save_file()
+        .identifier(window_identifier(&opt).await)
+        .title(title.as_str())
+        .accept_label(opt.accept_label.as_deref())
+        .modal(opt.modality != Modality::None)
+        .current_filter(current_filter(&opt, &filters))
+        .filters(filters)
+        .choices(choices(&opt))
+        .current_folder::<&Path>(opt.starting_directory.as_deref())?;
+
+    if let (Some(mut path), Some(file_name)) =
+        (opt.starting_directory.to_owned(), opt.file_name.as_deref())
+    {
+        path.push(file_name);
+        if path.exists() {
+            // the user edited an existing document
+            request = request.current_file::<&Path>(Some(path.as_path()))?;
+        } else {
+            // the user just created a new document
+            request = request.current_name(file_name);
+        }
+    } else {
+        // the user just created a new document
+        request = request.current_name(opt.file_name.as_deref());
+    }
+
+    let request = request.send().await?;
+
+    let files = request.response()?;
+    store_choices(&opt, &files);
+    Ok(paths(files).into_iter().next())
+}
+
+async fn add_notification(opt: Notification) -> ashpd::Result<()> {
+    // Reusing an id replaces the notification, every one gets its own
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    let id = format!("rfd-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+
+    let body = Some(opt.body.as_str()).filter(|body| !body.is_empty());
+    let notification = ashpd::desktop::notification::Notification::new(&opt.title).body(body);
+
+    let proxy = NotificationProxy::new().await?;
+    proxy.add_notification(&id, notification).await
+}
+
+/// Cancelled requests become `Ok(None)`, every other failure is an [`Error::Portal`]
+fn cancelled_to_none<T>(res: ashpd::Result<T>) -> Result<Option<T>, Error> {
+    match res {
+        Ok(value) => Ok(Some(value)),
+        Err(ashpd::Error::Response(ResponseError::Cancelled)) => Ok(None),
+        Err(err) => Err(Error::Portal(err.to_string())),
+    }
+}
+
+/// Resolves to `Ok(None)` as soon as `handle` gets closed
+///
+/// ashpd only hands out the portal request once it got a response, so there is no way to call `Close` on it,
+/// the request is abandoned instead.
+async fn or_closed<T>(
+    handle: &DialogHandle,
+    future: impl Future<Output = Result<Option<T>, Error>>,
+) -> Result<Option<T>, Error> {
+    let closed: Arc<Mutex<(bool, Option<Waker>)>> = Default::default();
+    {
+        let closed = closed.clone();
+        handle.on_close(move || {
+            let mut closed = closed.lock().unwrap();
+            closed.0 = true;
+            if let Some(waker) = closed.1.take() {
+                waker.wake();
+            }
+        });
+    }
+
+    let mut future = pin!(future);
+    poll_fn(|cx| {
+        {
+            let mut closed = closed.lock().unwrap();
+            if closed.0 {
+                return Poll::Ready(Ok(None));
+            }
+            closed.1 = Some(cx.waker().clone());
+        }
+        future.as_mut().poll(cx)
+    })
+    .await
+}
+
+/// Reads the version of the FileChooser portal, this fails if there is no portal to talk to
+async fn file_chooser_version() -> zbus::Result<u32> {
+    let connection = zbus::Connection::session().await?;
+    let proxy = zbus::Proxy::new(
+        &connection,
+        "org.freedesktop.portal.Desktop",
+        "/org/freedesktop/portal/desktop",
+        "org.freedesktop.portal.FileChooser",
+    )
+    .await?;
+    proxy.get_property("version").await
+}
+
+/// XDG Desktop Portal backend, messages are delegated to GTK when the `gtk3` feature is enabled
+pub struct PortalBackend;
+
+impl DialogBackend for PortalBackend {
+    //
+    // File Picker
+    //
+
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        let handle = dialog.handle.clone();
+        let paths = block_on(or_closed(&handle, async {
+            cancelled_to_none(open(dialog, BuiltinString::OpenFile, false, false).await)
+        }))?;
+        Ok(paths.and_then(|paths| paths.into_iter().next()))
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        let handle = dialog.handle.clone();
+        block_on(or_closed(&handle, async {
+            cancelled_to_none(open(dialog, BuiltinString::OpenFile, true, false).await)
+        }))
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let handle = dialog.handle.clone();
+        Box::pin(async move {
+            let paths = or_closed(&handle, async {
+                cancelled_to_none(open(dialog, BuiltinString::OpenFile, false, false).await)
+            })
+            .await?;
+            Ok(paths
+                .and_then(|paths| paths.into_iter().next())
+                .map(FileHandle::wrap))
+        })
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        let handle = dialog.handle.clone();
+        Box::pin(async move {
+            let paths = or_closed(&handle, async {
+                cancelled_to_none(open(dialog, BuiltinString::OpenFile, true, false).await)
+            })
+            .await?;
+            Ok(paths.map(|paths| paths.into_iter().map(FileHandle::wrap).collect()))
+        })
+    }
+
+    //
+    // Folder Picker
+    //
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        let handle = dialog.handle.clone();
+        let paths = block_on(or_closed(&handle, async {
+            cancelled_to_none(open(dialog, BuiltinString::SelectFolder, false, true).await)
+        }))?;
+        Ok(paths.and_then(|paths| paths.into_iter().next()))
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let handle = dialog.handle.clone();
+        Box::pin(async move {
+            let paths = or_closed(&handle, async {
+                cancelled_to_none(open(dialog, BuiltinString::SelectFolder, false, true).await)
+            })
+            .await?;
+            Ok(paths
+                .and_then(|paths| paths.into_iter().next())
+                .map(FileHandle::wrap))
+        })
+    }
+
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        let handle = dialog.handle.clone();
+        block_on(or_closed(&handle, async {
+            cancelled_to_none(open(dialog, BuiltinString::SelectFolders, true, true).await)
+        }))
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        let handle = dialog.handle.clone();
+        Box::pin(async move {
+            let paths = or_closed(&handle, async {
+                cancelled_to_none(open(dialog, BuiltinString::SelectFolders, true, true).await)
+            })
+            .await?;
+            Ok(paths.map(|paths| paths.into_iter().map(FileHandle::wrap).collect()))
+        })
+    }
+
+    //
+    // File Save
+    //
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        let handle = dialog.handle.clone();
+        block_on(or_closed(&handle, async {
+            cancelled_to_none(save(dialog).await)
+        }))
+        .map(Option::flatten)
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let handle = dialog.handle.clone();
+        Box::pin(async move {
+            let path = or_closed(&handle, async { cancelled_to_none(save(dialog).await) }).await?;
+            Ok(path.flatten().map(FileHandle::wrap))
+        })
+    }
+
+    //
+    // Message Dialog
+    //
+
+    #[cfg(feature = "gtk3")]
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        super::gtk3::Gtk3Backend.show_message(dialog)
+    }
+
+    #[cfg(feature = "gtk3")]
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        super::gtk3::Gtk3Backend.show_message_async(dialog)
+    }
+
+    #[cfg(feature = "gtk3")]
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        super::gtk3::Gtk3Backend.show_message_custom(dialog)
+    }
+
+    #[cfg(feature = "gtk3")]
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        super::gtk3::Gtk3Backend.show_message_custom_async(dialog)
+    }
+
+    #[cfg(feature = "gtk3")]
+    fn show_input(&self, dialog: InputDialog) -> Option<String> {
+        super::gtk3::Gtk3Backend.show_input(dialog)
+    }
+
+    #[cfg(feature = "gtk3")]
+    fn show_input_async(&self, dialog: InputDialog) -> DialogFutureType<Option<String>> {
+        super::gtk3::Gtk3Backend.show_input_async(dialog)
+    }
+
+    #[cfg(feature = "gtk3")]
+    fn show_pick_list(&self, dialog: PickListDialog) -> Option<usize> {
+        super::gtk3::Gtk3Backend.show_pick_list(dialog)
+    }
+
+    #[cfg(feature = "gtk3")]
+    fn show_pick_list_async(&self, dialog: PickListDialog) -> DialogFutureType<Option<usize>> {
+        super::gtk3::Gtk3Backend.show_pick_list_async(dialog)
+    }
+
+    #[cfg(feature = "gtk3")]
+    fn show_credentials(&self, dialog: CredentialDialog) -> Option<Credentials> {
+        super::gtk3::Gtk3Backend.show_credentials(dialog)
+    }
+
+    #[cfg(feature = "gtk3")]
+    fn show_credentials_async(
+        &self,
+        dialog: CredentialDialog,
+    ) -> DialogFutureType<Option<Credentials>> {
+        super::gtk3::Gtk3Backend.show_credentials_async(dialog)
+    }
+
+    #[cfg(feature = "gtk3")]
+    fn show_progress(&self, dialog: ProgressDialog, state: ProgressState) {
+        super::gtk3::Gtk3Backend.show_progress(dialog, state)
+    }
+
+    fn show_notification(&self, notification: Notification) -> Result<(), Error> {
+        block_on(add_notification(notification)).map_err(|err| Error::Portal(err.to_string()))
+    }
+
+    // There is no message dialog portal, so without GTK there is nothing native to show
+    #[cfg(not(feature = "gtk3"))]
+    fn show_message(&self, _dialog: MessageDialog) -> bool {
+        trace_warn!(
+            title = %_dialog.title,
+            "MessageDialog requires the `gtk3` feature, it was not shown"
+        );
+        false
+    }
+
+    #[cfg(not(feature = "gtk3"))]
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        Box::pin(std::future::ready(self.show_message(dialog)))
+    }
+
+    #[cfg(not(feature = "gtk3"))]
+    fn try_show_message(&self, _dialog: MessageDialog) -> Result<Option<String>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    #[cfg(not(feature = "gtk3"))]
+    fn try_show_input(&self, _dialog: InputDialog) -> Result<Option<String>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    #[cfg(not(feature = "gtk3"))]
+    fn try_show_pick_list(&self, _dialog: PickListDialog) -> Result<Option<usize>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    #[cfg(not(feature = "gtk3"))]
+    fn try_show_credentials(
+        &self,
+        _dialog: CredentialDialog,
+    ) -> Result<Option<Credentials>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    #[cfg(not(feature = "gtk3"))]
+    fn try_show_message_async(
+        &self,
+        _dialog: MessageDialog,
+    ) -> DialogFutureType<Result<Option<String>, Error>> {
+        Box::pin(std::future::ready(Err(Error::Unsupported)))
+    }
+
+    #[cfg(not(feature = "gtk3"))]
+    fn try_show_input_async(
+        &self,
+        _dialog: InputDialog,
+    ) -> DialogFutureType<Result<Option<String>, Error>> {
+        Box::pin(std::future::ready(Err(Error::Unsupported)))
+    }
+
+    #[cfg(not(feature = "gtk3"))]
+    fn try_show_pick_list_async(
+        &self,
+        _dialog: PickListDialog,
+    ) -> DialogFutureType<Result<Option<usize>, Error>> {
+        Box::pin(std::future::ready(Err(Error::Unsupported)))
+    }
+
+    #[cfg(not(feature = "gtk3"))]
+    fn try_show_credentials_async(
+        &self,
+        _dialog: CredentialDialog,
+    ) -> DialogFutureType<Result<Option<Credentials>, Error>> {
+        Box::pin(std::future::ready(Err(Error::Unsupported)))
+    }
+
+    fn is_available(&self) -> bool {
+        static AVAILABLE: OnceLock<bool> = OnceLock::new();
+        *AVAILABLE.get_or_init(|| block_on(file_chooser_version()).is_ok())
+    }
+}
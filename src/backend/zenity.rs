@@ -0,0 +1,637 @@
+//! `zenity`, `kdialog` and `yad` subprocesses
+//!
+//! Fallback for systems without GTK or the XDG portal, it needs no library at all.
+//! Arguments are passed without a shell, text shown as markup is escaped.
+//! Each dialog waits for its process on a `std::thread`, closing its [`DialogHandle`] kills it.
+
+use std::convert::TryFrom;
+use std::future::Future;
+use std::io::Read;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+
+use crate::backend::{DialogBackend, DialogFutureType};
+use crate::dialog::{Filter, MessageLevel};
+use crate::{DialogHandle, Error, FileDialog, FileHandle, MessageDialog};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    Zenity,
+    KDialog,
+    Yad,
+}
+
+impl Tool {
+    fn program(self) -> &'static str {
+        match self {
+            Tool::Zenity => "zenity",
+            Tool::KDialog => "kdialog",
+            Tool::Yad => "yad",
+        }
+    }
+
+    /// First tool found in `PATH`, `kdialog` is preferred on KDE
+    fn find() -> Option<Tool> {
+        static TOOL: OnceLock<Option<Tool>> = OnceLock::new();
+        *TOOL.get_or_init(|| {
+            let kde = std::env::var("XDG_CURRENT_DESKTOP")
+                .is_ok_and(|desktop| desktop.to_ascii_uppercase().contains("KDE"));
+            let order: &[Tool] = if kde {
+                &[Tool::KDialog, Tool::Zenity, Tool::Yad]
+            } else {
+                &[Tool::Zenity, Tool::KDialog, Tool::Yad]
+            };
+            order.iter().copied().find(|tool| in_path(tool.program()))
+        })
+    }
+}
+
+fn in_path(program: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    let paths = match std::env::var_os("PATH") {
+        Some(paths) => paths,
+        None => return false,
+    };
+    std::env::split_paths(&paths).any(|dir| {
+        dir.join(program)
+            .metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    })
+}
+
+/// `zenity` and `yad` show `--text` as Pango markup
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Exit code and output of a finished dialog process
+struct Output {
+    code: Option<i32>,
+    stdout: String,
+    /// Killed through the `DialogHandle`
+    closed: bool,
+}
+
+struct RunningState {
+    res: Option<Result<Output, Error>>,
+    waker: Option<Waker>,
+}
+
+/// Dialog process, waited for on a `std::thread`
+struct Running {
+    state: Arc<(Mutex<RunningState>, Condvar)>,
+}
+
+impl Running {
+    fn spawn(tool: Tool, args: Vec<String>, handle: DialogHandle) -> Self {
+        let state = Arc::new((
+            Mutex::new(RunningState {
+                res: None,
+                waker: None,
+            }),
+            Condvar::new(),
+        ));
+
+        trace_debug!(program = tool.program(), ?args, "running dialog process");
+        let child = Command::new(tool.program())
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match child {
+            Ok(child) => {
+                let state = state.clone();
+                std::thread::Builder::new()
+                    .name("rfd_zenity".into())
+                    .spawn(move || {
+                        let res = wait(child, &handle);
+                        finish(&state, res);
+                    })
+                    .unwrap();
+            }
+            Err(err) => finish(&state, Err(spawn_error(tool, err))),
+        }
+
+        Self { state }
+    }
+
+    /// Blocks until the dialog is closed
+    fn wait(self) -> Result<Output, Error> {
+        let mut state = self.state.0.lock().unwrap();
+        loop {
+            if let Some(res) = state.res.take() {
+                return res;
+            }
+            state = self.state.1.wait(state).unwrap();
+        }
+    }
+}
+
+impl Future for Running {
+    type Output = Result<Output, Error>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.0.lock().unwrap();
+        match state.res.take() {
+            Some(res) => Poll::Ready(res),
+            None => {
+                state.waker.replace(ctx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+fn finish(state: &(Mutex<RunningState>, Condvar), res: Result<Output, Error>) {
+    let mut running = state.0.lock().unwrap();
+    running.res.replace(res);
+    if let Some(waker) = running.waker.take() {
+        waker.wake();
+    }
+    state.1.notify_all();
+}
+
+fn spawn_error(tool: Tool, err: std::io::Error) -> Error {
+    Error::Process(format!("could not run {}: {}", tool.program(), err))
+}
+
+fn wait(mut child: Child, handle: &DialogHandle) -> Result<Output, Error> {
+    let mut stdout = child.stdout.take().unwrap();
+    let child = Arc::new(Mutex::new(child));
+    {
+        let child = child.clone();
+        // Closes stdout as well, which ends the read below
+        handle.on_close(move || {
+            let _ = child.lock().unwrap().kill();
+        });
+    }
+
+    let mut output = String::new();
+    stdout
+        .read_to_string(&mut output)
+        .map_err(|err| Error::Process(err.to_string()))?;
+    let status = child
+        .lock()
+        .unwrap()
+        .wait()
+        .map_err(|err| Error::Process(err.to_string()))?;
+
+    Ok(Output {
+        code: status.code(),
+        stdout: output,
+        closed: handle.is_closed(),
+    })
+}
+
+//
+// File dialogs
+//
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    PickFile,
+    PickFiles,
+    PickFolder,
+    SaveFile,
+}
+
+/// Path the dialog starts at, the starting directory joined with the file name
+fn start_path(opt: &FileDialog) -> Option<PathBuf> {
+    match (&opt.starting_directory, &opt.file_name) {
+        (Some(dir), Some(name)) => Some(dir.join(name)),
+        (Some(dir), None) => Some(dir.clone()),
+        (None, Some(name)) => Some(PathBuf::from(name)),
+        (None, None) => None,
+    }
+}
+
+/// `zenity` and `yad` filters, `NAME | PATTERN1 PATTERN2`
+fn zenity_filter(filter: &Filter) -> String {
+    // GTK globs are case-sensitive, `kdialog` matches any case already
+    let globs: Vec<String> = filter.caseless_globs().collect();
+    format!("{} | {}", filter.name, globs.join(" "))
+}
+
+/// `kdialog` filters, `PATTERN1 PATTERN2|NAME` separated by new lines
+fn kdialog_filter(filters: &[Filter]) -> String {
+    filters
+        .iter()
+        .map(|filter| {
+            let globs: Vec<String> = filter.globs().collect();
+            format!("{}|{}", globs.join(" "), filter.name)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn file_args(tool: Tool, opt: &FileDialog, mode: Mode) -> Vec<String> {
+    let mut args = Vec::new();
+    let start = start_path(opt);
+
+    if tool == Tool::KDialog {
+        args.push(
+            match mode {
+                Mode::PickFile | Mode::PickFiles => "--getopenfilename",
+                Mode::PickFolder => "--getexistingdirectory",
+                Mode::SaveFile => "--getsavefilename",
+            }
+            .to_string(),
+        );
+        // The filter is positional, so it needs a start path before it
+        let start = start.unwrap_or_else(|| PathBuf::from("."));
+        args.push(start.to_string_lossy().into_owned());
+        if mode != Mode::PickFolder && !opt.filters.is_empty() {
+            args.push(kdialog_filter(&opt.filters));
+        }
+        if mode == Mode::PickFiles {
+            args.push("--multiple".into());
+            args.push("--separate-output".into());
+        }
+        if let Some(title) = &opt.title {
+            args.push("--title".into());
+            args.push(title.clone());
+        }
+        return args;
+    }
+
+    args.push("--file-selection".into());
+    match mode {
+        Mode::PickFile => {}
+        Mode::PickFiles => {
+            args.push("--multiple".into());
+            args.push("--separator=\n".into());
+        }
+        Mode::PickFolder => args.push("--directory".into()),
+        Mode::SaveFile => {
+            args.push("--save".into());
+            if opt.confirm_overwrite.unwrap_or(true) {
+                args.push("--confirm-overwrite".into());
+            }
+        }
+    }
+    if let Some(title) = &opt.title {
+        args.push(format!("--title={}", title));
+    }
+    if let Some(start) = start {
+        let mut start = start.to_string_lossy().into_owned();
+        // A trailing slash opens a directory instead of selecting it
+        if opt.file_name.is_none() && !start.ends_with('/') {
+            start.push('/');
+        }
+        args.push(format!("--filename={}", start));
+    }
+    if mode != Mode::PickFolder {
+        for filter in &opt.filters {
+            args.push(format!("--file-filter={}", zenity_filter(filter)));
+        }
+    }
+    args
+}
+
+fn file_dialog(opt: &FileDialog, mode: Mode) -> Result<Running, Error> {
+    let tool = Tool::find().ok_or(Error::Unsupported)?;
+    let args = file_args(tool, opt, mode);
+    Ok(Running::spawn(tool, args, opt.handle.clone()))
+}
+
+fn paths(output: Output) -> Result<Option<Vec<PathBuf>>, Error> {
+    match output.code {
+        Some(0) => {
+            let paths: Vec<PathBuf> = output
+                .stdout
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from)
+                .collect();
+            Ok(Some(paths).filter(|paths| !paths.is_empty()))
+        }
+        // Cancelled, or killed through the `DialogHandle`
+        Some(1) => Ok(None),
+        _ if output.closed => Ok(None),
+        code => Err(Error::Process(format!("dialog exited with {:?}", code))),
+    }
+}
+
+fn first<T>(items: Option<Vec<T>>) -> Option<T> {
+    items.and_then(|items| items.into_iter().next())
+}
+
+fn pick(opt: &FileDialog, mode: Mode) -> Result<Option<Vec<PathBuf>>, Error> {
+    paths(file_dialog(opt, mode)?.wait()?)
+}
+
+fn pick_async(
+    opt: &FileDialog,
+    mode: Mode,
+) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+    let running = file_dialog(opt, mode);
+    Box::pin(async move {
+        let paths = paths(running?.await?)?;
+        Ok(paths.map(|paths| paths.into_iter().map(FileHandle::wrap).collect()))
+    })
+}
+
+//
+// Message dialogs
+//
+
+fn message_args(tool: Tool, opt: &MessageDialog, labels: &[String]) -> Vec<String> {
+    let mut args = Vec::new();
+    match tool {
+        Tool::Zenity => {
+            let kind = match opt.level {
+                _ if labels.len() > 1 => "--question",
+                MessageLevel::Info | MessageLevel::Question => "--info",
+                MessageLevel::Warning => "--warning",
+                MessageLevel::Error => "--error",
+            };
+            args.push(kind.into());
+            args.push(format!("--title={}", opt.title));
+            args.push(format!("--text={}", escape_markup(&opt.description)));
+            if let Some(first) = labels.first() {
+                args.push(format!("--ok-label={}", first));
+            }
+            if labels.len() > 1 {
+                args.push(format!("--cancel-label={}", labels[labels.len() - 1]));
+                // Their label is printed when pressed
+                for label in &labels[1..labels.len() - 1] {
+                    args.push(format!("--extra-button={}", label));
+                }
+            }
+        }
+        Tool::Yad => {
+            let icon = match opt.level {
+                MessageLevel::Info => "dialog-information",
+                MessageLevel::Warning => "dialog-warning",
+                MessageLevel::Error => "dialog-error",
+                MessageLevel::Question => "dialog-question",
+            };
+            args.push(format!("--image={}", icon));
+            args.push(format!("--title={}", opt.title));
+            args.push(format!("--text={}", escape_markup(&opt.description)));
+            // The exit code is the index of the pressed button
+            for (index, label) in labels.iter().enumerate() {
+                args.push(format!("--button={}:{}", label.replace(':', " "), index));
+            }
+        }
+        Tool::KDialog => {
+            let kind = match (labels.len(), opt.level) {
+                (0..=1, MessageLevel::Error) => "--error",
+                (0..=1, MessageLevel::Warning) => "--sorry",
+                (0..=1, _) => "--msgbox",
+                (2, MessageLevel::Warning) => "--warningyesno",
+                (2, _) => "--yesno",
+                (_, MessageLevel::Warning) => "--warningyesnocancel",
+                _ => "--yesnocancel",
+            };
+            args.push(kind.into());
+            args.push(opt.description.clone());
+            args.push("--title".into());
+            args.push(opt.title.clone());
+            // There are at most three buttons, the middle ones share the second
+            let flags = ["--yes-label", "--no-label", "--cancel-label"];
+            for (flag, index) in flags.iter().zip(kdialog_buttons(labels.len())) {
+                args.push(flag.to_string());
+                args.push(labels[index].clone());
+            }
+        }
+    }
+    args
+}
+
+/// Indices of the labels on the `kdialog` buttons, the first, second and last one
+fn kdialog_buttons(len: usize) -> Vec<usize> {
+    match len {
+        0 | 1 => Vec::new(),
+        2 => vec![0, 1],
+        _ => vec![0, 1, len - 1],
+    }
+}
+
+/// Index of the pressed button, `None` when the dialog was closed without one
+fn pressed(tool: Tool, output: &Output, labels: &[String]) -> Option<usize> {
+    let code = output.code?;
+    match tool {
+        Tool::Zenity => {
+            let extra = output.stdout.trim_end_matches('\n');
+            let middle = labels.get(1..labels.len().saturating_sub(1)).unwrap_or(&[]);
+            match code {
+                0 => Some(0),
+                _ if !extra.is_empty() => middle.iter().position(|l| l == extra).map(|i| i + 1),
+                // Cancel and closing the window can't be told apart
+                _ if labels.len() > 1 && !output.closed => Some(labels.len() - 1),
+                _ => None,
+            }
+        }
+        // 252 when the window was closed
+        Tool::Yad => usize::try_from(code)
+            .ok()
+            .filter(|index| *index < labels.len()),
+        Tool::KDialog if labels.len() <= 1 => Some(0).filter(|_| code == 0),
+        Tool::KDialog => kdialog_buttons(labels.len()).get(code as usize).copied(),
+    }
+}
+
+fn message(opt: &MessageDialog) -> Option<(Tool, Running, Vec<String>)> {
+    let tool = Tool::find();
+    if tool.is_none() {
+        trace_warn!(
+            title = %opt.title,
+            "no zenity, kdialog or yad found, the message dialog was not shown"
+        );
+    }
+    let tool = tool?;
+    // Zenity prints the shown label, the index of the button maps it back to `button_labels`
+    let labels = opt.shown_button_labels();
+    let args = message_args(tool, opt, &labels);
+    Some((
+        tool,
+        Running::spawn(tool, args, DialogHandle::default()),
+        labels,
+    ))
+}
+
+/// A message dialog has no error to return, a failed process is reported and counts as closed
+fn pressed_index(tool: Tool, res: Result<Output, Error>, labels: &[String]) -> Option<usize> {
+    #[cfg(feature = "tracing")]
+    {
+        if let Err(err) = &res {
+            trace_warn!(error = %err, "message dialog failed");
+        }
+    }
+    pressed(tool, &res.ok()?, labels)
+}
+
+/// Subprocess backend, `zenity`, `kdialog` or `yad`
+pub struct ZenityBackend;
+
+impl DialogBackend for ZenityBackend {
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        pick(&dialog, Mode::PickFile).map(first)
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        pick(&dialog, Mode::PickFiles)
+    }
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        pick(&dialog, Mode::PickFolder).map(first)
+    }
+
+    // None of the tools can pick several folders
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        pick(&dialog, Mode::PickFolder)
+    }
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        pick(&dialog, Mode::SaveFile).map(first)
+    }
+
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        let pressed = message(&dialog)
+            .and_then(|(tool, running, labels)| pressed_index(tool, running.wait(), &labels));
+        pressed == Some(0)
+    }
+
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        let labels = dialog.button_labels();
+        let index = self.show_message_index(dialog)?;
+        labels.get(index).cloned()
+    }
+
+    fn show_message_index(&self, dialog: MessageDialog) -> Option<usize> {
+        let (tool, running, shown) = message(&dialog)?;
+        pressed_index(tool, running.wait(), &shown)
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(&dialog, Mode::PickFile);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        pick_async(&dialog, Mode::PickFiles)
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(&dialog, Mode::PickFolder);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        pick_async(&dialog, Mode::PickFolder)
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        let future = pick_async(&dialog, Mode::SaveFile);
+        Box::pin(async move { future.await.map(first) })
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        let message = message(&dialog);
+        Box::pin(async move {
+            match message {
+                Some((tool, running, labels)) => {
+                    pressed_index(tool, running.await, &labels) == Some(0)
+                }
+                None => false,
+            }
+        })
+    }
+
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        let labels = dialog.button_labels();
+        let index = self.show_message_index_async(dialog);
+        Box::pin(async move { labels.get(index.await?).cloned() })
+    }
+
+    fn show_message_index_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<usize>> {
+        let message = message(&dialog);
+        Box::pin(async move {
+            let (tool, running, shown) = message?;
+            pressed_index(tool, running.await, &shown)
+        })
+    }
+
+    // Whether kdialog asks is up to the KDE dialog it shows
+    fn confirms_overwrite(&self, dialog: &FileDialog) -> Option<bool> {
+        match Tool::find()? {
+            Tool::KDialog => None,
+            Tool::Zenity | Tool::Yad => Some(dialog.confirm_overwrite != Some(false)),
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        let display = ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()));
+        display && Tool::find().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_markup, kdialog_buttons, pressed, Output, Tool};
+
+    fn output(code: i32, stdout: &str) -> Output {
+        Output {
+            code: Some(code),
+            stdout: stdout.into(),
+            closed: false,
+        }
+    }
+
+    #[test]
+    fn markup_is_escaped() {
+        assert_eq!(
+            escape_markup("<b>a & b</b>"),
+            "&lt;b&gt;a &amp; b&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn pressed_zenity_button() {
+        let labels: Vec<String> = vec!["Save".into(), "Don't Save".into(), "Cancel".into()];
+
+        assert_eq!(pressed(Tool::Zenity, &output(0, ""), &labels), Some(0));
+        assert_eq!(
+            pressed(Tool::Zenity, &output(1, "Don't Save\n"), &labels),
+            Some(1)
+        );
+        assert_eq!(pressed(Tool::Zenity, &output(1, ""), &labels), Some(2));
+        assert_eq!(pressed(Tool::Zenity, &output(1, ""), &labels[..1]), None);
+    }
+
+    #[test]
+    fn pressed_kdialog_and_yad_button() {
+        let labels: Vec<String> = vec!["A".into(), "B".into(), "C".into(), "D".into()];
+
+        assert_eq!(kdialog_buttons(4), vec![0, 1, 3]);
+        assert_eq!(pressed(Tool::KDialog, &output(2, ""), &labels), Some(3));
+        assert_eq!(pressed(Tool::Yad, &output(2, ""), &labels), Some(2));
+        assert_eq!(pressed(Tool::Yad, &output(252, ""), &labels), None);
+    }
+}
@@ -1,15 +1,182 @@
-use crate::FileHandle;
+use crate::backend::{Backend, DialogBackend};
+use crate::dialog_events::{Events, Validator};
+use crate::dialog_links::LinkHandler;
+use crate::dialog_preview::Previewer;
+use crate::localizer::{localize, localize_with, or_localized, BuiltinString};
+use crate::{
+    Credentials, DialogChoices, DialogEvents, DialogHandle, DialogResult, FileHandle, OpenLink,
+    Password, Preview, PreviewContent, ProgressHandle, ProgressState, Validate, ValidationIssue,
+};
 
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(feature = "parent")]
-use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use crate::{ParentHandle, ParentWindow};
+#[cfg(feature = "parent")]
+use raw_window_handle::RawWindowHandle;
 
+/// File filter added with [`FileDialog::add_filter`], [`FileDialog::add_mime_filter`], [`FileDialog::add_pattern_filter`] or [`FileDialog::add_file_name_filter`]
 #[derive(Debug, Clone)]
-pub(crate) struct Filter {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Filter {
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub extensions: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mime_types: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub patterns: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub file_names: Vec<String>,
+}
+
+impl Filter {
+    /// Adds everything `other` matches, skipping duplicates
+    fn merge(&mut self, other: &Filter) {
+        fn extend(into: &mut Vec<String>, from: &[String]) {
+            for item in from {
+                if !into.contains(item) {
+                    into.push(item.clone());
+                }
+            }
+        }
+
+        extend(&mut self.extensions, &other.extensions);
+        extend(&mut self.mime_types, &other.mime_types);
+        extend(&mut self.patterns, &other.patterns);
+        extend(&mut self.file_names, &other.file_names);
+    }
+
+    /// Extensions as `*.{ext}` globs, followed by the glob patterns and the file names,
+    /// which are globs matching only themselves
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    pub(crate) fn globs(&self) -> impl Iterator<Item = String> + '_ {
+        self.extensions
+            .iter()
+            .map(|ext| format!("*.{}", ext))
+            .chain(self.patterns.iter().cloned())
+            .chain(self.file_names.iter().cloned())
+    }
+
+    /// [`globs`](Self::globs) with every letter matching both cases, eg. `*.[jJ][pP][gG]`,
+    /// for the backends whose globs are case-sensitive while extensions match in any case elsewhere
+    #[cfg(target_os = "linux")]
+    pub(crate) fn caseless_globs(&self) -> impl Iterator<Item = String> + '_ {
+        self.globs().map(|glob| caseless_glob(&glob))
+    }
+
+    /// Extensions, plus the patterns that only match an extension (`*.{ext}`)
+    ///
+    /// Used by platforms that can't filter by glob. Their file types only know the last part
+    /// of compound extensions, so `tar.gz` becomes `gz`.
+    #[cfg(any(target_os = "macos", target_os = "ios", target_arch = "wasm32"))]
+    pub(crate) fn plain_extensions(&self) -> impl Iterator<Item = String> + '_ {
+        let from_patterns = self.patterns.iter().filter_map(|pattern| {
+            let ext = pattern.strip_prefix("*.")?;
+            if ext.contains(&['*', '?', '['][..]) {
+                None
+            } else {
+                Some(ext)
+            }
+        });
+
+        self.extensions
+            .iter()
+            .map(String::as_str)
+            .chain(from_patterns)
+            .map(|ext| ext.rsplit('.').next().unwrap_or(ext).to_string())
+    }
+
+    /// Longest of the extensions that `name` ends with, compound ones like `tar.gz` included
+    pub(crate) fn matching_extension(&self, name: &str) -> Option<&str> {
+        self.extensions
+            .iter()
+            .filter(|ext| has_extension(name, ext))
+            .max_by_key(|ext| ext.len())
+            .map(String::as_str)
+    }
+}
+
+/// `name` ends with `.{ext}` in any case, after at least one other character
+fn has_extension(name: &str, ext: &str) -> bool {
+    let start = match name.len().checked_sub(ext.len() + 1) {
+        Some(start) if start > 0 && name.is_char_boundary(start) => start,
+        _ => return false,
+    };
+    let (dot, suffix) = name[start..].split_at(1);
+    dot == "." && suffix.eq_ignore_ascii_case(ext)
+}
+
+/// `glob` with each letter outside of a `[...]` class turned into a class of both its cases
+#[cfg(target_os = "linux")]
+fn caseless_glob(glob: &str) -> String {
+    let mut caseless = String::with_capacity(glob.len() * 4);
+    let mut in_class = false;
+    for c in glob.chars() {
+        let mut lower = c.to_lowercase();
+        let mut upper = c.to_uppercase();
+        match (lower.next(), lower.next(), upper.next(), upper.next()) {
+            (Some(lower), None, Some(upper), None) if !in_class && lower != upper => {
+                caseless.push('[');
+                caseless.push(lower);
+                caseless.push(upper);
+                caseless.push(']');
+            }
+            _ => {
+                match c {
+                    '[' => in_class = true,
+                    ']' => in_class = false,
+                    _ => {}
+                }
+                caseless.push(c);
+            }
+        }
+    }
+    caseless
+}
+
+/// Sidebar shortcut added with [`FileDialog::add_place`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Place {
+    pub path: PathBuf,
+    pub label: String,
+}
+
+/// Checkbox added with [`FileDialog::add_checkbox`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Checkbox {
+    pub id: u32,
+    pub label: String,
+    pub default: bool,
+}
+
+/// Combo box added with [`FileDialog::add_combo`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Combo {
+    pub id: u32,
+    pub label: String,
+    pub options: Vec<String>,
+    pub default: usize,
+}
+
+/// Text field added with [`FileDialog::add_text_field`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TextField {
+    pub id: u32,
+    pub label: String,
+    pub default: String,
 }
 
 /// ## Synchronous File Dialog
@@ -17,19 +184,125 @@ pub(crate) struct Filter {
 /// - Linux
 /// - Windows
 /// - Mac
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct FileDialog {
     pub(crate) filters: Vec<Filter>,
+    pub(crate) all_supported_filter: bool,
+    pub(crate) default_filter: Option<usize>,
     pub(crate) starting_directory: Option<PathBuf>,
     pub(crate) file_name: Option<String>,
+    pub(crate) save_as_item: Option<PathBuf>,
     pub(crate) title: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) accept_label: Option<String>,
+    pub(crate) file_name_label: Option<String>,
+    pub(crate) cancel_label: Option<String>,
+    pub(crate) persistence_id: Option<String>,
+    pub(crate) local_only: Option<bool>,
+    pub(crate) show_hidden: Option<bool>,
+    pub(crate) can_create_directories: Option<bool>,
+    pub(crate) confirm_overwrite: Option<bool>,
+    pub(crate) strict_file_types: Option<bool>,
+    pub(crate) append_extension: Option<bool>,
+    pub(crate) add_to_recent: Option<bool>,
+    pub(crate) no_change_dir: Option<bool>,
+    pub(crate) places: Vec<Place>,
+    pub(crate) hide_default_places: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) events: Option<Events>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) validator: Option<Validator>,
+    pub(crate) min_files: Option<usize>,
+    pub(crate) max_files: Option<usize>,
+    pub(crate) checkboxes: Vec<Checkbox>,
+    pub(crate) combos: Vec<Combo>,
+    pub(crate) text_fields: Vec<TextField>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) preview: Option<Previewer>,
+    #[cfg(target_os = "macos")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) accessory_view: Option<crate::dialog_macos::AccessoryView>,
+    #[cfg(target_os = "macos")]
+    pub(crate) treat_packages_as_directories: Option<bool>,
+    #[cfg(all(target_os = "linux", feature = "gtk3"))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) extra_widget: Option<crate::dialog_gtk::ExtraWidget>,
     #[cfg(feature = "parent")]
-    pub(crate) parent: Option<RawWindowHandle>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) parent: Option<ParentHandle>,
+    pub(crate) modality: Modality,
+    pub(crate) position: Option<Position>,
+    pub(crate) monitor: Option<MonitorSelection>,
+    pub(crate) always_on_top: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) icon: Option<WindowIcon>,
+    pub(crate) app_name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) backend: Backend,
+    pub(crate) timeout: Option<Duration>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) handle: DialogHandle,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) choices: DialogChoices,
 }
 
-// Oh god, I don't like sending RawWindowHandle between threads but here we go anyways...
-// fingers crossed
-unsafe impl Send for FileDialog {}
+// Every dialog gets its own handle and choices, otherwise closing one would close all dialogs made from a cloned builder
+impl Clone for FileDialog {
+    fn clone(&self) -> Self {
+        Self {
+            filters: self.filters.clone(),
+            all_supported_filter: self.all_supported_filter,
+            default_filter: self.default_filter,
+            starting_directory: self.starting_directory.clone(),
+            file_name: self.file_name.clone(),
+            save_as_item: self.save_as_item.clone(),
+            title: self.title.clone(),
+            description: self.description.clone(),
+            accept_label: self.accept_label.clone(),
+            file_name_label: self.file_name_label.clone(),
+            cancel_label: self.cancel_label.clone(),
+            persistence_id: self.persistence_id.clone(),
+            local_only: self.local_only,
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            confirm_overwrite: self.confirm_overwrite,
+            strict_file_types: self.strict_file_types,
+            append_extension: self.append_extension,
+            add_to_recent: self.add_to_recent,
+            no_change_dir: self.no_change_dir,
+            places: self.places.clone(),
+            hide_default_places: self.hide_default_places,
+            events: self.events.clone(),
+            validator: self.validator.clone(),
+            min_files: self.min_files,
+            max_files: self.max_files,
+            checkboxes: self.checkboxes.clone(),
+            combos: self.combos.clone(),
+            text_fields: self.text_fields.clone(),
+            preview: self.preview.clone(),
+            #[cfg(target_os = "macos")]
+            accessory_view: self.accessory_view.clone(),
+            #[cfg(target_os = "macos")]
+            treat_packages_as_directories: self.treat_packages_as_directories,
+            #[cfg(all(target_os = "linux", feature = "gtk3"))]
+            extra_widget: self.extra_widget.clone(),
+            #[cfg(feature = "parent")]
+            parent: self.parent,
+            modality: self.modality,
+            position: self.position,
+            monitor: self.monitor,
+            always_on_top: self.always_on_top,
+            icon: self.icon.clone(),
+            app_name: self.app_name.clone(),
+            backend: self.backend,
+            timeout: self.timeout,
+            handle: DialogHandle::default(),
+            choices: DialogChoices::default(),
+        }
+    }
+}
 
 impl FileDialog {
     /// New file dialog builder
@@ -39,127 +312,345 @@ impl FileDialog {
 
     /// Add file extension filter.
     ///
-    /// Takes in the name of the filter, and list of extensions
+    /// Takes in the name of the filter, and list of extensions.
+    /// Extensions match in any case on every platform, `"jpg"` also shows `IMG_01.JPG`.
+    /// Compound extensions like `"tar.gz"` or `"d.ts"` work as well, MacOS and WASM32 filter by their last part.
     ///
     /// #### Name of the filter will be displayed on supported platforms
     /// - Windows
     /// - Linux
     ///
     /// On platforms that don't support filter names, all filters will be merged into one filter
-    pub fn add_filter(mut self, name: &str, extensions: &[&str]) -> Self {
-        self.filters.push(Filter {
+    pub fn add_filter(self, name: &str, extensions: &[&str]) -> Self {
+        self.push_filter(Filter {
             name: name.into(),
             extensions: extensions.iter().map(|e| e.to_string()).collect(),
-        });
-        self
+            mime_types: Vec::new(),
+            patterns: Vec::new(),
+            file_names: Vec::new(),
+        })
     }
 
-    /// Set starting directory of the dialog.
+    /// Add MIME type filter, eg. `"image/png"` or `"text/*"`.
+    ///
+    /// Unlike extensions this also matches files without an extension on Linux.
+    ///
+    /// #### Supported Platforms:
+    /// - Linux
+    /// - Mac (wildcards are not supported)
+    /// - WASM32
+    ///
+    /// Windows has no MIME type filtering, a filter that only has MIME types shows all files there
+    pub fn add_mime_filter(self, name: &str, mime_types: &[&str]) -> Self {
+        self.push_filter(Filter {
+            name: name.into(),
+            extensions: Vec::new(),
+            mime_types: mime_types.iter().map(|m| m.to_string()).collect(),
+            patterns: Vec::new(),
+            file_names: Vec::new(),
+        })
+    }
+
+    /// Add glob pattern filter, eg. `"Makefile"`, `"*.tar.*"` or `"IMG_????.jpg"`.
+    ///
+    /// Patterns are matched against the file name, `*` matches any run of characters and `?` a single one.
+    ///
     /// #### Supported Platforms:
     /// - Linux
     /// - Windows
-    /// - Mac
-    pub fn set_directory<P: AsRef<Path>>(mut self, path: P) -> Self {
-        self.starting_directory = Some(path.as_ref().into());
+    ///
+    /// Mac and WASM32 can only filter by extension, there only patterns of the form `*.{ext}` are used
+    pub fn add_pattern_filter(self, name: &str, patterns: &[&str]) -> Self {
+        self.push_filter(Filter {
+            name: name.into(),
+            extensions: Vec::new(),
+            mime_types: Vec::new(),
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            file_names: Vec::new(),
+        })
+    }
+
+    /// Add filter of files without an extension, eg. `"Makefile"`, `"Dockerfile"` or `"LICENSE"`.
+    ///
+    /// The names are matched exactly, in any case, against the whole file name.
+    ///
+    /// #### Supported Platforms:
+    /// - Linux
+    /// - Windows
+    ///
+    /// Mac and WASM32 can only filter by extension, the names are left out there
+    pub fn add_file_name_filter(self, name: &str, file_names: &[&str]) -> Self {
+        self.push_filter(Filter {
+            name: name.into(),
+            extensions: Vec::new(),
+            mime_types: Vec::new(),
+            patterns: Vec::new(),
+            file_names: file_names.iter().map(|n| n.to_string()).collect(),
+        })
+    }
+
+    /// Add a filter that matches everything the other filters match, eg. "All supported formats".
+    ///
+    /// It is placed first so it is the one selected when the dialog opens,
+    /// filters added after this call are included as well.
+    pub fn add_all_supported_filter(mut self, name: &str) -> Self {
+        if self.all_supported_filter {
+            self.filters[0].name = name.into();
+            return self;
+        }
+
+        let mut all = Filter {
+            name: name.into(),
+            extensions: Vec::new(),
+            mime_types: Vec::new(),
+            patterns: Vec::new(),
+            file_names: Vec::new(),
+        };
+        for filter in self.filters.iter() {
+            all.merge(filter);
+        }
+
+        self.filters.insert(0, all);
+        self.all_supported_filter = true;
         self
     }
 
-    /// Set starting file name of the dialog.
+    /// Select the filter at `index` when the dialog opens, instead of the first one.
+    ///
+    /// The index counts filters in the order they were added, including the one from
+    /// [`add_all_supported_filter`](Self::add_all_supported_filter). An index out of range is ignored.
+    ///
     /// #### Supported Platforms:
     /// - Windows
     /// - Linux
-    /// - Mac
-    pub fn set_file_name(mut self, file_name: &str) -> Self {
-        self.file_name = Some(file_name.into());
+    pub fn set_default_filter(mut self, index: usize) -> Self {
+        self.default_filter = Some(index);
         self
     }
 
-    /// Set the title of the dialog.
+    /// Only allow items that exist on the local filesystem, so every returned path can be opened directly.
+    ///
+    /// Without this Windows lets users pick virtual items (eg. phones connected over MTP), which fail to resolve to a path.
+    /// GTK only allows local files unless this is set to `false`.
     /// #### Supported Platforms:
     /// - Windows
-    /// - Linux
-    /// - Mac (Only below version 10.11)
-    pub fn set_title(mut self, title: &str) -> Self {
-        self.title = Some(title.into());
+    /// - Linux (GTK only, the portal always returns local paths)
+    pub fn set_local_only(mut self, local_only: bool) -> Self {
+        self.local_only = Some(local_only);
         self
     }
 
-    #[cfg(feature = "parent")]
-    /// Set parent windows explicitly (optional)
-    /// Suported in: `macos` and `windows`
-    pub fn set_parent<W: HasRawWindowHandle>(mut self, parent: &W) -> Self {
-        self.parent = Some(parent.raw_window_handle());
+    /// Show hidden files and folders, eg. dotfiles.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only)
+    /// - Mac
+    pub fn set_show_hidden(mut self, show_hidden: bool) -> Self {
+        self.show_hidden = Some(show_hidden);
         self
     }
-}
 
-use crate::backend::{FilePickerDialogImpl, FileSaveDialogImpl, FolderPickerDialogImpl};
+    /// Show or hide the "New Folder" button of save and folder dialogs.
+    /// #### Supported Platforms:
+    /// - Linux (GTK only)
+    /// - Mac
+    ///
+    /// Windows dialogs always allow creating folders.
+    pub fn set_can_create_directories(mut self, can: bool) -> Self {
+        self.can_create_directories = Some(can);
+        self
+    }
 
-#[cfg(not(target_arch = "wasm32"))]
-impl FileDialog {
-    /// Pick one file
-    pub fn pick_file(self) -> Option<PathBuf> {
-        FilePickerDialogImpl::pick_file(self)
+    /// Ask before overwriting an existing file in the save dialog, enabled by default.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only)
+    ///
+    /// Mac and the XDG portal always ask.
+    pub fn set_confirm_overwrite(mut self, confirm: bool) -> Self {
+        self.confirm_overwrite = Some(confirm);
+        self
     }
 
-    /// Pick multiple files
-    pub fn pick_files(self) -> Option<Vec<PathBuf>> {
-        FilePickerDialogImpl::pick_files(self)
+    /// Make sure the path returned by the save dialog has an extension from the filters.
+    ///
+    /// When the user typed an extension none of the filters has, the first extension of the selected filter gets appended,
+    /// eg. `notes.md` becomes `notes.md.txt`.
+    ///
+    /// Windows enforces this in the dialog itself and GTK refuses such names while the dialog is open.
+    /// Other platforms fix up the returned path and ask before it replaces an existing file.
+    pub fn set_strict_file_types(mut self, strict: bool) -> Self {
+        self.strict_file_types = Some(strict);
+        self
     }
 
-    /// Pick one folder
-    pub fn pick_folder(self) -> Option<PathBuf> {
-        FolderPickerDialogImpl::pick_folder(self)
+    /// Append the first extension of the selected filter when the user typed a name without one,
+    /// eg. `report` becomes `report.png` while the "PNG" filter is selected.
+    ///
+    /// Windows and MacOS do this in the dialog itself and GTK renames the typed name when another filter
+    /// gets selected. Other platforms fix up the returned path and ask before it replaces an existing file.
+    /// Setting it to `false` stops Windows from appending, MacOS always does.
+    pub fn set_append_extension(mut self, append: bool) -> Self {
+        self.append_extension = Some(append);
+        self
     }
 
-    /// Opens save file dialog
+    /// Set to `false` to keep the chosen files out of the recently used documents list.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only, recent files are turned off for the whole process while the dialog is open)
+    pub fn set_add_to_recent(mut self, add: bool) -> Self {
+        self.add_to_recent = Some(add);
+        self
+    }
+
+    /// Keep the current directory of the process as it is while the dialog is shown.
     ///
-    /// #### Platform specific notes regarding save dialog filters:
-    /// - On MacOs
-    ///     - If filter is set, all files will be grayed out (no matter the extension sadly)
-    ///     - If user does not type an extension MacOs will append first available extension from filters list
-    ///     - If user types in filename with extension MacOs will check if it exists in filters list, if not it will display appropriate message
-    /// - On GTK
-    ///     - It only filters which already existing files get shown to the user
-    ///     - It does not append extensions automatically
-    ///     - It does not prevent users from adding any unsupported extension
-    /// - On Win:
-    ///     - If no extension was provided it will just add currently selected one
-    ///     - If selected extension was typed in by the user it will just return
-    ///     - If unselected extension was provided it will append selected one at the end, example: `test.png.txt`
-    pub fn save_file(self) -> Option<PathBuf> {
-        FileSaveDialogImpl::save_file(self)
+    /// Windows dialogs can change it to the folder the user browsed to,
+    /// dialogs on other platforms never change it.
+    pub fn set_no_change_dir(mut self, no_change_dir: bool) -> Self {
+        self.no_change_dir = Some(no_change_dir);
+        self
     }
-}
 
-/// ## Asynchronous File Dialog
-/// #### Supported Platforms:
-/// - Linux
-/// - Windows
-/// - Mac
-/// - WASM32
-#[derive(Default, Debug, Clone)]
-pub struct AsyncFileDialog {
-    file_dialog: FileDialog,
-}
+    /// Pin a folder to the sidebar of the dialog, eg. the project or plugin directory.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only)
+    ///
+    /// Both show the name of the folder, `label` is available to custom backends through [`places`](Self::places).
+    /// Folders that don't exist are left out.
+    pub fn add_place<P: AsRef<Path>>(mut self, path: P, label: &str) -> Self {
+        self.places.push(Place {
+            path: path.as_ref().into(),
+            label: label.into(),
+        });
+        self
+    }
 
-impl AsyncFileDialog {
-    /// New file dialog builder
-    pub fn new() -> Self {
-        Default::default()
+    /// Hide the places the OS pins to the sidebar and the recently used ones,
+    /// so only the places added with [`add_place`](Self::add_place) are left.
+    /// #### Supported Platforms:
+    /// - Windows
+    pub fn set_hide_default_places(mut self, hide: bool) -> Self {
+        self.hide_default_places = Some(hide);
+        self
     }
 
-    /// Add file extension filter.
+    /// Get notified about folder, selection and filter changes while the dialog is shown.
+    ///
+    /// See [`DialogEvents`] for supported platforms
+    pub fn set_events<E: DialogEvents + 'static>(mut self, events: E) -> Self {
+        self.events = Some(Events(Arc::new(events)));
+        self
+    }
+
+    /// Check the chosen paths before the dialog accepts them, eg. that they are inside the workspace.
+    /// When `validator` returns `Err` the message is shown to the user and the dialog stays open.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only)
+    pub fn set_validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&Path) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.validator = Some(Validator(Arc::new(validator)));
+        self
+    }
+
+    /// Ask [`pick_files`](Self::pick_files) for at least `count` files.
     ///
-    /// Takes in the name of the filter, and list of extensions
+    /// When fewer are picked a warning says how many are needed and the dialog is shown again,
+    /// until the user picks enough or cancels.
+    pub fn set_min_files(mut self, count: usize) -> Self {
+        self.min_files = Some(count);
+        self
+    }
+
+    /// Ask [`pick_files`](Self::pick_files) for at most `count` files, eg. "select exactly 2 files to diff"
+    /// together with [`set_min_files`](Self::set_min_files).
     ///
-    /// #### Name of the filter will be displayed on supported platforms
+    /// When more are picked a warning says how many are allowed and the dialog is shown again.
+    /// A `count` below the minimum is reported by [`validate`](Self::validate) and ignored.
+    pub fn set_max_files(mut self, count: usize) -> Self {
+        self.max_files = Some(count);
+        self
+    }
+
+    /// Add a checkbox to the dialog, eg. "Open as read-only".
+    /// Its state when the dialog gets accepted is read from [`choices`](Self::choices) under `id`.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux
+    pub fn add_checkbox(mut self, id: u32, label: &str, default: bool) -> Self {
+        self.checkboxes.push(Checkbox {
+            id,
+            label: label.into(),
+            default,
+        });
+        self
+    }
+
+    /// Add a combo box to the dialog, eg. to pick the encoding of the opened file.
+    /// The index of the option selected when the dialog gets accepted is read from [`choices`](Self::choices) under `id`,
+    /// ids have to be unique across checkboxes, combo boxes and text fields.
+    /// #### Supported Platforms:
     /// - Windows
     /// - Linux
+    pub fn add_combo(mut self, id: u32, label: &str, options: &[&str], default: usize) -> Self {
+        self.combos.push(Combo {
+            id,
+            label: label.into(),
+            options: options.iter().map(|option| option.to_string()).collect(),
+            default,
+        });
+        self
+    }
+
+    /// Add a text field to the dialog, eg. for the frame range to import.
+    /// Its content when the dialog gets accepted is read from [`choices`](Self::choices) under `id`.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only)
+    pub fn add_text_field(mut self, id: u32, label: &str, default: &str) -> Self {
+        self.text_fields.push(TextField {
+            id,
+            label: label.into(),
+            default: default.into(),
+        });
+        self
+    }
+
+    /// Show a preview of the highlighted file, `renderer` turns its path into text or an image.
     ///
-    /// On platforms that don't support filter names, all filters will be merged into one filter
-    pub fn add_filter(mut self, name: &str, extensions: &[&str]) -> Self {
-        self.file_dialog = self.file_dialog.add_filter(name, extensions);
+    /// ```no_run
+    /// use rfd::PreviewContent;
+    ///
+    /// let file = rfd::FileDialog::new()
+    ///     .set_preview(|path| match std::fs::read_to_string(path) {
+    ///         Ok(text) => PreviewContent::Text(text.lines().take(10).collect::<Vec<_>>().join("\n")),
+    ///         Err(_) => PreviewContent::None,
+    ///     })
+    ///     .pick_file();
+    /// ```
+    ///
+    /// #### Supported Platforms:
+    /// - Windows (text only)
+    /// - Linux (GTK only)
+    /// - MacOS (below the accessory view of `FileDialogMacExt::set_accessory_view`, if there is one)
+    pub fn set_preview<F>(mut self, renderer: F) -> Self
+    where
+        F: Fn(&Path) -> PreviewContent + Send + Sync + 'static,
+    {
+        self.preview = Some(Previewer(Arc::new(renderer)));
+        self
+    }
+
+    fn push_filter(mut self, filter: Filter) -> Self {
+        if self.all_supported_filter {
+            self.filters[0].merge(&filter);
+        }
+        self.filters.push(filter);
         self
     }
 
@@ -169,7 +660,7 @@ impl AsyncFileDialog {
     /// - Windows
     /// - Mac
     pub fn set_directory<P: AsRef<Path>>(mut self, path: P) -> Self {
-        self.file_dialog = self.file_dialog.set_directory(path);
+        self.starting_directory = Some(path.as_ref().into());
         self
     }
 
@@ -179,7 +670,23 @@ impl AsyncFileDialog {
     /// - Linux
     /// - Mac
     pub fn set_file_name(mut self, file_name: &str) -> Self {
-        self.file_dialog = self.file_dialog.set_file_name(file_name);
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Open the save dialog pointing at the file `path`, eg. the current document in a "Save As" flow.
+    ///
+    /// Sets the starting directory to the folder of `path` and the file name to its name,
+    /// Windows also shows the file as the item being saved.
+    pub fn set_save_as_item<P: AsRef<Path>>(mut self, path: P) -> Self {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            self.starting_directory = Some(directory.into());
+        }
+        if let Some(file_name) = path.file_name() {
+            self.file_name = Some(file_name.to_string_lossy().into_owned());
+        }
+        self.save_as_item = Some(path.into());
         self
     }
 
@@ -189,48 +696,580 @@ impl AsyncFileDialog {
     /// - Linux
     /// - Mac (Only below version 10.11)
     pub fn set_title(mut self, title: &str) -> Self {
-        self.file_dialog = self.file_dialog.set_title(title);
+        self.title = Some(title.into());
         self
     }
 
-    #[cfg(feature = "parent")]
-    /// Set parent windows explicitly (optional)
-    /// Suported in: `macos` and `windows`
-    pub fn set_parent<W: HasRawWindowHandle>(mut self, parent: &W) -> Self {
-        self.file_dialog = self.file_dialog.set_parent(parent);
+    /// Set a one-line instruction shown above the files, eg. "Choose a folder that contains mod files".
+    /// #### Supported Platforms:
+    /// - Mac
+    /// - Linux (GTK and the software backend)
+    pub fn set_description(mut self, description: &str) -> Self {
+        self.description = Some(description.into());
         self
     }
-}
 
-use crate::backend::{
-    AsyncFilePickerDialogImpl, AsyncFileSaveDialogImpl, AsyncFolderPickerDialogImpl,
-};
-use std::future::Future;
+    /// Set the label of the accept button, eg. "Import" instead of "Open".
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux
+    /// - Mac
+    pub fn set_accept_label(mut self, label: &str) -> Self {
+        self.accept_label = Some(label.into());
+        self
+    }
 
-impl AsyncFileDialog {
-    /// Pick one file
-    pub fn pick_file(self) -> impl Future<Output = Option<FileHandle>> {
-        AsyncFilePickerDialogImpl::pick_file_async(self.file_dialog)
+    /// Set the label of the file name field, eg. "Project name:" instead of "File name:".
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Mac (save dialogs only, open dialogs have no name field)
+    pub fn set_file_name_label(mut self, label: &str) -> Self {
+        self.file_name_label = Some(label.into());
+        self
     }
 
-    /// Pick multiple files
-    pub fn pick_files(self) -> impl Future<Output = Option<Vec<FileHandle>>> {
-        AsyncFilePickerDialogImpl::pick_files_async(self.file_dialog)
+    /// Set the label of the cancel button.
+    /// #### Supported Platforms:
+    /// - Linux (GTK only)
+    pub fn set_cancel_label(mut self, label: &str) -> Self {
+        self.cancel_label = Some(label.into());
+        self
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    /// Pick one folder
+    /// Name of the logical dialog, eg. `"export"` or `"open-project"`.
     ///
-    /// Does not exist in `WASM32`
-    pub fn pick_folder(self) -> impl Future<Output = Option<FileHandle>> {
-        AsyncFolderPickerDialogImpl::pick_folder_async(self.file_dialog)
+    /// Dialogs with the same id share their remembered state (last folder, size, view),
+    /// dialogs without one share the state of the whole application.
+    ///
+    /// Windows remembers the state itself, on every platform the last directory is remembered
+    /// once [`persistence`](crate::persistence) is enabled.
+    pub fn set_persistence_id(mut self, id: &str) -> Self {
+        self.persistence_id = Some(id.into());
+        self
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    /// Opens save file dialog
-    ///
-    /// Does not exist in `WASM32`
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.parent = ParentHandle::of(parent);
+        self
+    }
+
+    /// Set what the dialog blocks while it is open, see [`Modality`]
+    pub fn set_modality(mut self, modality: Modality) -> Self {
+        self.modality = modality;
+        self
+    }
+
+    /// Set where the dialog opens, see [`Position`]
     ///
+    /// Without it the platform places the dialog, usually centered on the parent window.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only)
+    /// - Mac (not for sheets, which always hang from their parent)
+    pub fn set_position(mut self, position: Position) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Set the monitor a dialog without parent opens on, see [`MonitorSelection`]
+    ///
+    /// The dialog is centered on it, unless [`set_position`](Self::set_position) places it at given coordinates.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only)
+    /// - Mac
+    pub fn set_monitor(mut self, monitor: MonitorSelection) -> Self {
+        self.monitor = Some(monitor);
+        self
+    }
+
+    /// Keep the dialog above other windows, so it is not lost behind borderless fullscreen windows
+    /// or overlays
+    ///
+    /// MacOS panels are kept above other windows unless this is set to `false`.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only, the window manager may ignore it)
+    /// - Mac
+    pub fn set_always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = Some(always_on_top);
+        self
+    }
+
+    /// Set the icon of the dialog window, instead of the generic one of apps without a window,
+    /// like daemons and command line tools
+    /// #### Supported Platforms:
+    /// - Linux (GTK and the software backend)
+    pub fn set_icon(mut self, icon: WindowIcon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Set the name of the app, shown in the title bar next to the title
+    /// #### Supported Platforms:
+    /// - Linux (GTK and the software backend)
+    pub fn set_app_name(mut self, name: &str) -> Self {
+        self.app_name = Some(name.into());
+        self
+    }
+
+    /// Choose the built-in backend that shows the dialog.
+    ///
+    /// If it is unavailable the next one from the fallback chain is used, see [`Backend`]
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Cancel the dialog when the user did not pick anything within `timeout` of it showing up
+    ///
+    /// A timed out dialog always counts as cancelled, it is closed through its [`DialogHandle`] like
+    /// [`DialogHandle::close`] does, which can't pick a default. Message and input dialogs have no handle
+    /// the backends could close them by, so only file dialogs have a timeout.
+    ///
+    /// Not supported in `WASM32`, see [`AsyncFileDialog::handle`] for XDG Portal limitations
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Check the options before showing anything, eg. in a debug build or a test of the app
+    ///
+    /// Reports a starting directory that does not exist, filters that match nothing or contain
+    /// characters the backends can't pass on, and options ignored by the backend that would show
+    /// the dialog now. Custom backends registered with [`set_backend`](crate::set_backend) are
+    /// assumed to honor every option.
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        crate::validation::validate(self)
+    }
+
+    /// Backend that shows this dialog, starts the timeout
+    fn start(&self) -> Started {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(timeout) = self.timeout {
+            self.handle.close_after(timeout);
+        }
+        let backend = backend(self.backend);
+        #[cfg(not(target_arch = "wasm32"))]
+        let backend: Arc<dyn DialogBackend> = if crate::persistence::is_enabled() {
+            Arc::new(crate::persistence::Persisted(backend))
+        } else {
+            backend
+        };
+        Started {
+            backend,
+            handle: self.handle.clone(),
+        }
+    }
+
+    /// Copy to show the dialog again with, sharing the handle and the choices
+    fn reshown(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+            choices: self.choices.clone(),
+            ..self.clone()
+        }
+    }
+
+    /// Warning when the files picked in `res` are fewer or more than
+    /// [`set_min_files`](Self::set_min_files) and [`set_max_files`](Self::set_max_files) allow
+    fn file_count_warning<T>(&self, res: &DialogResult<Vec<T>>) -> Option<MessageDialog> {
+        let count = match res {
+            DialogResult::Ok(files) => files.len(),
+            _ => return None,
+        };
+        let min = self.min_files.unwrap_or(0);
+        let max = self.max_files.unwrap_or(usize::MAX);
+        // No pick can satisfy both, `validate` reports it
+        if min > max {
+            return None;
+        }
+        let text = if min == max && count != min {
+            localize_with(BuiltinString::ExactFileCount, &min.to_string())
+        } else if count < min {
+            localize_with(BuiltinString::MinFileCount, &min.to_string())
+        } else if count > max {
+            localize_with(BuiltinString::MaxFileCount, &max.to_string())
+        } else {
+            return None;
+        };
+
+        Some(MessageDialog {
+            title: or_localized(self.title.as_deref(), BuiltinString::OpenFiles),
+            description: text,
+            level: MessageLevel::Warning,
+            buttons: MessageButtons::Ok,
+            #[cfg(feature = "parent")]
+            parent: self.parent,
+            modality: self.modality,
+            icon: self.icon.clone(),
+            app_name: self.app_name.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Shows the dialog with `backend` until the number of picked files fits the limits
+    ///
+    /// Backends that are not interactive would pick the same files again, so these cancel instead
+    fn pick_files_with(&self, backend: &dyn DialogBackend) -> DialogResult<Vec<PathBuf>> {
+        loop {
+            let res = backend.pick_files(self.reshown()).into();
+            match self.file_count_warning(&res) {
+                Some(_) if !backend.is_interactive() => return DialogResult::Cancelled,
+                Some(warning) => backend.show_message(warning),
+                None => return res,
+            };
+        }
+    }
+}
+
+/// Path chosen in a save dialog, with what the dialog and [`FileDialog::save_file_with`] know about it
+struct Saved {
+    /// Fixed up path, see [`FileDialog::file_type_check`]
+    path: PathBuf,
+    chosen: PathBuf,
+    filter_index: Option<usize>,
+    /// `path` existed when the dialog returned
+    existed: bool,
+    /// The user was asked about replacing `path` and agreed
+    overwrite_confirmed: bool,
+}
+
+impl Saved {
+    /// `confirms` tells if the dialog asked about `chosen` when it existed, see
+    /// [`DialogBackend::confirms_overwrite`], unknown counts as not asked
+    fn new(
+        path: PathBuf,
+        chosen: PathBuf,
+        filter_index: Option<usize>,
+        confirms: Option<bool>,
+    ) -> Self {
+        let existed = path.exists();
+        Self {
+            overwrite_confirmed: existed && path == chosen && confirms == Some(true),
+            path,
+            chosen,
+            filter_index,
+            existed,
+        }
+    }
+
+    /// The user agreed to replace the file in [`FileDialog::replace_question`]
+    fn confirmed(self) -> Self {
+        Self {
+            overwrite_confirmed: true,
+            ..self
+        }
+    }
+
+    fn into_result(self) -> SaveFileResult {
+        SaveFileResult {
+            path: self.path,
+            filter_index: self.filter_index.unwrap_or_default(),
+            overwrite_confirmed: self.overwrite_confirmed,
+            created: !self.existed,
+        }
+    }
+}
+
+/// Backend of a started [`FileDialog`], finishes its handle when dropped
+///
+/// Async callers move it into their future, so the handle finishes with the future.
+struct Started {
+    backend: Arc<dyn DialogBackend>,
+    handle: DialogHandle,
+}
+
+impl std::ops::Deref for Started {
+    type Target = dyn DialogBackend;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.backend
+    }
+}
+
+impl Drop for Started {
+    fn drop(&mut self) {
+        self.handle.finish();
+    }
+}
+
+/// Options read by a [`DialogBackend`](crate::DialogBackend)
+impl FileDialog {
+    /// Filters added with [`add_filter`](Self::add_filter), [`add_mime_filter`](Self::add_mime_filter), [`add_pattern_filter`](Self::add_pattern_filter) and [`add_file_name_filter`](Self::add_file_name_filter)
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    /// Index set with [`set_default_filter`](Self::set_default_filter)
+    pub fn default_filter(&self) -> Option<usize> {
+        self.default_filter
+    }
+
+    /// Directory set with [`set_directory`](Self::set_directory)
+    pub fn directory(&self) -> Option<&Path> {
+        self.starting_directory.as_deref()
+    }
+
+    /// File name set with [`set_file_name`](Self::set_file_name)
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// File set with [`set_save_as_item`](Self::set_save_as_item)
+    pub fn save_as_item(&self) -> Option<&Path> {
+        self.save_as_item.as_deref()
+    }
+
+    /// Title set with [`set_title`](Self::set_title)
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Instruction set with [`set_description`](Self::set_description)
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Label set with [`set_accept_label`](Self::set_accept_label)
+    pub fn accept_label(&self) -> Option<&str> {
+        self.accept_label.as_deref()
+    }
+
+    /// Label set with [`set_file_name_label`](Self::set_file_name_label)
+    pub fn file_name_label(&self) -> Option<&str> {
+        self.file_name_label.as_deref()
+    }
+
+    /// Label set with [`set_cancel_label`](Self::set_cancel_label)
+    pub fn cancel_label(&self) -> Option<&str> {
+        self.cancel_label.as_deref()
+    }
+
+    /// Id set with [`set_persistence_id`](Self::set_persistence_id)
+    pub fn persistence_id(&self) -> Option<&str> {
+        self.persistence_id.as_deref()
+    }
+
+    /// Value set with [`set_local_only`](Self::set_local_only), `None` keeps the platform default
+    pub fn local_only(&self) -> Option<bool> {
+        self.local_only
+    }
+
+    /// Value set with [`set_show_hidden`](Self::set_show_hidden), `None` keeps the platform default
+    pub fn show_hidden(&self) -> Option<bool> {
+        self.show_hidden
+    }
+
+    /// Value set with [`set_can_create_directories`](Self::set_can_create_directories), `None` keeps the platform default
+    pub fn can_create_directories(&self) -> Option<bool> {
+        self.can_create_directories
+    }
+
+    /// Value set with [`set_confirm_overwrite`](Self::set_confirm_overwrite), `None` means enabled
+    pub fn confirm_overwrite(&self) -> Option<bool> {
+        self.confirm_overwrite
+    }
+
+    /// Value set with [`set_strict_file_types`](Self::set_strict_file_types), `None` means disabled
+    pub fn strict_file_types(&self) -> Option<bool> {
+        self.strict_file_types
+    }
+
+    /// Value set with [`set_append_extension`](Self::set_append_extension), `None` keeps the platform default
+    pub fn append_extension(&self) -> Option<bool> {
+        self.append_extension
+    }
+
+    /// Value set with [`set_add_to_recent`](Self::set_add_to_recent), `None` keeps the platform default
+    pub fn add_to_recent(&self) -> Option<bool> {
+        self.add_to_recent
+    }
+
+    /// Value set with [`set_no_change_dir`](Self::set_no_change_dir), `None` keeps the platform default
+    pub fn no_change_dir(&self) -> Option<bool> {
+        self.no_change_dir
+    }
+
+    /// Places added with [`add_place`](Self::add_place)
+    pub fn places(&self) -> &[Place] {
+        &self.places
+    }
+
+    /// Value set with [`set_hide_default_places`](Self::set_hide_default_places), `None` keeps the platform default
+    pub fn hide_default_places(&self) -> Option<bool> {
+        self.hide_default_places
+    }
+
+    /// Events set with [`set_events`](Self::set_events)
+    pub fn events(&self) -> Option<&dyn DialogEvents> {
+        self.events.as_ref().map(|events| &*events.0)
+    }
+
+    /// Validator set with [`set_validator`](Self::set_validator)
+    pub fn validator(&self) -> Option<&Validate> {
+        self.validator.as_ref().map(|validator| &*validator.0)
+    }
+
+    /// Count set with [`set_min_files`](Self::set_min_files)
+    pub fn min_files(&self) -> Option<usize> {
+        self.min_files
+    }
+
+    /// Count set with [`set_max_files`](Self::set_max_files)
+    pub fn max_files(&self) -> Option<usize> {
+        self.max_files
+    }
+
+    #[cfg(feature = "parent")]
+    /// Parent set with [`set_parent`](Self::set_parent)
+    pub fn parent(&self) -> Option<RawWindowHandle> {
+        self.parent.map(ParentHandle::get)
+    }
+
+    /// Set with [`set_modality`](Self::set_modality)
+    pub fn modality(&self) -> Modality {
+        self.modality
+    }
+
+    /// Set with [`set_position`](Self::set_position)
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// Set with [`set_monitor`](Self::set_monitor)
+    pub fn monitor(&self) -> Option<MonitorSelection> {
+        self.monitor
+    }
+
+    /// Set with [`set_always_on_top`](Self::set_always_on_top)
+    pub fn always_on_top(&self) -> Option<bool> {
+        self.always_on_top
+    }
+
+    /// Set with [`set_icon`](Self::set_icon)
+    pub fn icon(&self) -> Option<&WindowIcon> {
+        self.icon.as_ref()
+    }
+
+    /// Set with [`set_app_name`](Self::set_app_name)
+    pub fn app_name(&self) -> Option<&str> {
+        self.app_name.as_deref()
+    }
+
+    /// Where backends move the dialog to, a monitor alone centers dialogs without parent on it
+    #[cfg_attr(
+        not(any(
+            target_os = "windows",
+            target_os = "macos",
+            all(target_os = "linux", feature = "gtk3")
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn placement(&self) -> Option<(Position, Option<MonitorSelection>)> {
+        let position = self
+            .position
+            .or_else(|| self.monitor.map(|_| Position::CenterOnParent))?;
+        Some((position, self.monitor))
+    }
+
+    #[cfg(target_os = "macos")]
+    /// `NSView` set with [`FileDialogMacExt::set_accessory_view`](crate::FileDialogMacExt::set_accessory_view)
+    pub fn accessory_view(&self) -> Option<*mut std::ffi::c_void> {
+        self.accessory_view.as_ref().map(|view| view.as_ptr() as _)
+    }
+
+    #[cfg(target_os = "macos")]
+    /// Value set with [`FileDialogMacExt::set_treat_packages_as_directories`](crate::FileDialogMacExt::set_treat_packages_as_directories),
+    /// `None` keeps the platform default
+    pub fn treat_packages_as_directories(&self) -> Option<bool> {
+        self.treat_packages_as_directories
+    }
+
+    /// Handle closed by [`AsyncFileDialog::handle`] or the [timeout](Self::set_timeout), register a closer with [`DialogHandle::on_close`]
+    pub fn handle(&self) -> &DialogHandle {
+        &self.handle
+    }
+
+    /// Checkboxes added with [`add_checkbox`](Self::add_checkbox)
+    pub fn checkboxes(&self) -> &[Checkbox] {
+        &self.checkboxes
+    }
+
+    /// Combo boxes added with [`add_combo`](Self::add_combo)
+    pub fn combos(&self) -> &[Combo] {
+        &self.combos
+    }
+
+    /// Text fields added with [`add_text_field`](Self::add_text_field)
+    pub fn text_fields(&self) -> &[TextField] {
+        &self.text_fields
+    }
+
+    /// Renderer set with [`set_preview`](Self::set_preview)
+    pub fn preview(&self) -> Option<&Preview> {
+        self.preview.as_ref().map(|preview| &*preview.0)
+    }
+
+    /// State of the [checkboxes](Self::add_checkbox), [combo boxes](Self::add_combo) and [text fields](Self::add_text_field), clone it before the dialog is shown.
+    ///
+    /// Clones of this builder get their own choices.
+    pub fn choices(&self) -> &DialogChoices {
+        &self.choices
+    }
+}
+
+use crate::backend::backend;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileDialog {
+    /// Pick one file
+    pub fn pick_file(self) -> Option<PathBuf> {
+        self.try_pick_file().ok()
+    }
+
+    /// Pick multiple files
+    pub fn pick_files(self) -> Option<Vec<PathBuf>> {
+        self.try_pick_files().ok()
+    }
+
+    /// Pick one folder
+    pub fn pick_folder(self) -> Option<PathBuf> {
+        self.try_pick_folder().ok()
+    }
+
+    /// Pick multiple folders
+    pub fn pick_folders(self) -> Option<Vec<PathBuf>> {
+        self.try_pick_folders().ok()
+    }
+
+    /// Pick one file or one folder in the same dialog
+    ///
+    /// #### Supported Platforms:
+    /// - Mac
+    ///
+    /// Other platforms can't select both in one dialog, so this shows the [`pick_file`](Self::pick_file) dialog there.
+    pub fn pick_path(self) -> Option<PathBuf> {
+        self.try_pick_path().ok()
+    }
+
+    /// Pick one file as a URL, so items without a local path can be picked as well,
+    /// eg. files on phones connected over MTP, OneDrive placeholders and network locations.
+    ///
+    /// Allows non-local items unless [`set_local_only`](Self::set_local_only) says otherwise.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only, GIO URIs)
+    ///
+    /// Other platforms return the `file://` URL of the picked path.
+    pub fn pick_file_url(self) -> Option<crate::Url> {
+        self.try_pick_file_url().ok()
+    }
+
+    /// Opens save file dialog
     ///
     /// #### Platform specific notes regarding save dialog filters:
     /// - On MacOs
@@ -245,166 +1284,2590 @@ impl AsyncFileDialog {
     ///     - If no extension was provided it will just add currently selected one
     ///     - If selected extension was typed in by the user it will just return
     ///     - If unselected extension was provided it will append selected one at the end, example: `test.png.txt`
-    pub fn save_file(self) -> impl Future<Output = Option<FileHandle>> {
-        AsyncFileSaveDialogImpl::save_file_async(self.file_dialog)
+    pub fn save_file(self) -> Option<PathBuf> {
+        self.try_save_file().ok()
+    }
+
+    /// Opens save file dialog, also returns the index of the filter that was selected when the user accepted
+    ///
+    /// The index points into the filters added with [`add_filter`](Self::add_filter), it is `0` when there are none.
+    ///
+    /// #### Platform specific notes:
+    /// - On Windows and GTK the filter selected in the dialog is returned
+    /// - On MacOs and the XDG portal the first filter that contains the extension of the chosen file is returned, or `0` if none does,
+    ///   the filter added with [`add_all_supported_filter`](Self::add_all_supported_filter) is only returned when nothing else matches
+    pub fn save_file_with_filter(self) -> Option<(PathBuf, usize)> {
+        self.try_save_file_with_filter().ok()
+    }
+
+    /// Opens save file dialog, returns the path together with the chosen filter and whether the file already exists
+    ///
+    /// See [`save_file_with_filter`](Self::save_file_with_filter) for platform specific notes
+    pub fn save_file_result(self) -> Option<SaveFileResult> {
+        self.try_save_file_result().ok()
+    }
+
+    /// Pick one file, see [`DialogResult`]
+    pub fn try_pick_file(self) -> DialogResult<PathBuf> {
+        self.start().pick_file(self).into()
+    }
+
+    /// Pick one file as a URL, see [`DialogResult`]
+    ///
+    /// See [`pick_file_url`](Self::pick_file_url) for supported platforms
+    pub fn try_pick_file_url(self) -> DialogResult<crate::Url> {
+        let dialog = self.url_dialog();
+        dialog.start().pick_file_url(dialog).into()
+    }
+
+    /// Pick multiple files, see [`DialogResult`]
+    ///
+    /// Shown again while the number of files breaks [`set_min_files`](Self::set_min_files) or
+    /// [`set_max_files`](Self::set_max_files)
+    pub fn try_pick_files(self) -> DialogResult<Vec<PathBuf>> {
+        self.pick_files_with(&*self.start())
+    }
+
+    /// Pick one folder, see [`DialogResult`]
+    pub fn try_pick_folder(self) -> DialogResult<PathBuf> {
+        self.start().pick_folder(self).into()
+    }
+
+    /// Pick multiple folders, see [`DialogResult`]
+    pub fn try_pick_folders(self) -> DialogResult<Vec<PathBuf>> {
+        self.start().pick_folders(self).into()
+    }
+
+    /// Pick one file or one folder, see [`DialogResult`]
+    ///
+    /// See [`pick_path`](Self::pick_path) for supported platforms
+    pub fn try_pick_path(self) -> DialogResult<PathBuf> {
+        self.start().pick_path(self).into()
+    }
+
+    /// Opens save file dialog, see [`DialogResult`]
+    ///
+    /// See [`save_file`](Self::save_file) for platform specific notes
+    pub fn try_save_file(self) -> DialogResult<PathBuf> {
+        // The extension to append depends on the selected filter
+        let with_filter = self.append_extension == Some(true);
+        self.save_file_with(&*self.start(), with_filter)
+            .map(|saved| saved.path)
+    }
+
+    /// Opens save file dialog and returns the selected filter index, see [`DialogResult`]
+    ///
+    /// See [`save_file_with_filter`](Self::save_file_with_filter) for platform specific notes
+    pub fn try_save_file_with_filter(self) -> DialogResult<(PathBuf, usize)> {
+        self.save_file_with(&*self.start(), true)
+            .map(|saved| (saved.path, saved.filter_index.unwrap_or_default()))
+    }
+
+    /// Opens save file dialog and returns a [`SaveFileResult`], see [`DialogResult`]
+    ///
+    /// See [`save_file_with_filter`](Self::save_file_with_filter) for platform specific notes
+    pub fn try_save_file_result(self) -> DialogResult<SaveFileResult> {
+        self.save_file_with(&*self.start(), true)
+            .map(Saved::into_result)
+    }
+
+    /// Shows the dialog of [`pick_file`](Self::pick_file) and returns right away,
+    /// [`PendingDialog::poll`](crate::PendingDialog::poll) tells when the user picked something.
+    ///
+    /// Made for loops that can't block or await, eg. game loops. On MacOS the loop has to keep
+    /// running the `NSApplication` event loop, as every windowed app does.
+    pub fn show_non_blocking(self) -> crate::PendingDialog {
+        let handle = self.handle.clone();
+        let started = self.start();
+        let future = started.pick_file_async(self);
+        let future = Box::pin(async move {
+            let _started = started;
+            future.await
+        });
+        crate::PendingDialog::new(future, handle)
+    }
+
+    /// Shows the dialog of [`pick_file`](Self::pick_file) and returns right away,
+    /// `callback` gets the picked file, `None` when the user cancelled.
+    ///
+    /// The callback runs on a thread of its own, for C FFI consumers and GUI toolkits that are
+    /// neither blocking nor async. See [`show_non_blocking`](Self::show_non_blocking) for MacOS notes.
+    pub fn show_with_callback<F>(self, callback: F)
+    where
+        F: FnOnce(Option<PathBuf>) + Send + 'static,
+    {
+        let pending = self.show_non_blocking();
+        std::thread::spawn(move || callback(pending.wait()));
+    }
+
+    /// Lets the dialog of [`pick_file_url`](Self::pick_file_url) show non-local items unless told otherwise
+    fn url_dialog(mut self) -> Self {
+        self.local_only = self.local_only.or(Some(false));
+        self
+    }
+
+    /// Shows the save dialog with `backend` and fixes up the extension of the chosen path,
+    /// see [`file_type_check`](Self::file_type_check)
+    ///
+    /// The filter index is only asked for when `with_filter`.
+    /// The dialog is shown again when the user does not want to replace the file the fixed up path names.
+    fn save_file_with(
+        &self,
+        backend: &dyn DialogBackend,
+        with_filter: bool,
+    ) -> DialogResult<Saved> {
+        let file_type = self.file_type_check();
+        let confirms = backend.confirms_overwrite(self);
+        loop {
+            let res = if with_filter {
+                DialogResult::from(backend.save_file_with_filter(self.reshown()))
+                    .map(|(path, index)| (path, Some(index)))
+            } else {
+                DialogResult::from(backend.save_file(self.reshown())).map(|path| (path, None))
+            };
+            let saved = res.map(|(chosen, index)| {
+                Saved::new(file_type(chosen.clone(), index), chosen, index, confirms)
+            });
+            let question = match self.replace_question(&saved) {
+                Some(question) => question,
+                None => return saved,
+            };
+            if backend.show_message(question) {
+                return saved.map(Saved::confirmed);
+            }
+            // Without a user the same path would come back every time
+            if !backend.is_interactive() {
+                return DialogResult::Cancelled;
+            }
+        }
+    }
+
+    /// Question before the fixed up path of `saved` replaces an existing file, the dialog only
+    /// asked about the path the user chose
+    fn replace_question(&self, saved: &DialogResult<Saved>) -> Option<MessageDialog> {
+        let saved = match saved {
+            DialogResult::Ok(saved) if saved.path != saved.chosen && saved.existed => saved,
+            _ => return None,
+        };
+        if self.confirm_overwrite == Some(false) {
+            return None;
+        }
+
+        let name = saved.path.file_name().unwrap_or_default().to_string_lossy();
+        Some(MessageDialog {
+            title: or_localized(self.title.as_deref(), BuiltinString::SaveFile),
+            description: localize_with(BuiltinString::ReplaceRenamed, &name),
+            level: MessageLevel::Warning,
+            buttons: MessageButtons::YesNo,
+            #[cfg(feature = "parent")]
+            parent: self.parent,
+            modality: self.modality,
+            icon: self.icon.clone(),
+            app_name: self.app_name.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Fixes up save dialog results, see [`set_strict_file_types`](Self::set_strict_file_types)
+    /// and [`set_append_extension`](Self::set_append_extension)
+    ///
+    /// Takes the path and the index of the selected filter, if it is known
+    fn file_type_check(&self) -> impl Fn(PathBuf, Option<usize>) -> PathBuf + Send + 'static {
+        let strict = self.strict_file_types == Some(true);
+        let filters = if strict || self.append_extension == Some(true) {
+            self.filters.clone()
+        } else {
+            Vec::new()
+        };
+        let default = self.default_filter.unwrap_or(0);
+
+        move |mut path, selected| {
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("");
+            let covered = match path.extension() {
+                Some(_) => {
+                    !strict
+                        || filters
+                            .iter()
+                            .any(|filter| filter.matching_extension(name).is_some())
+                }
+                None => false,
+            };
+            if covered {
+                return path;
+            }
+
+            let filter = filters.get(selected.unwrap_or(default)).or(filters.first());
+            if let (Some(ext), Some(name)) = (
+                filter.and_then(|filter| filter.extensions.first()),
+                path.file_name(),
+            ) {
+                let mut name = name.to_owned();
+                name.push(".");
+                name.push(ext);
+                path.set_file_name(name);
+            }
+            path
+        }
+    }
+}
+
+/// ## Asynchronous File Dialog
+/// #### Supported Platforms:
+/// - Linux
+/// - Windows
+/// - Mac
+/// - WASM32
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct AsyncFileDialog {
+    pub(crate) file_dialog: FileDialog,
+}
+
+impl AsyncFileDialog {
+    /// New file dialog builder
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add file extension filter.
+    ///
+    /// Takes in the name of the filter, and list of extensions.
+    /// Extensions match in any case on every platform, `"jpg"` also shows `IMG_01.JPG`.
+    /// Compound extensions like `"tar.gz"` or `"d.ts"` work as well, MacOS and WASM32 filter by their last part.
+    ///
+    /// #### Name of the filter will be displayed on supported platforms
+    /// - Windows
+    /// - Linux
+    ///
+    /// On platforms that don't support filter names, all filters will be merged into one filter
+    pub fn add_filter(mut self, name: &str, extensions: &[&str]) -> Self {
+        self.file_dialog = self.file_dialog.add_filter(name, extensions);
+        self
+    }
+
+    /// Add MIME type filter, eg. `"image/png"` or `"text/*"`.
+    ///
+    /// See [`FileDialog::add_mime_filter`] for supported platforms
+    pub fn add_mime_filter(mut self, name: &str, mime_types: &[&str]) -> Self {
+        self.file_dialog = self.file_dialog.add_mime_filter(name, mime_types);
+        self
+    }
+
+    /// Add glob pattern filter, eg. `"Makefile"`, `"*.tar.*"` or `"IMG_????.jpg"`.
+    ///
+    /// See [`FileDialog::add_pattern_filter`] for supported platforms
+    pub fn add_pattern_filter(mut self, name: &str, patterns: &[&str]) -> Self {
+        self.file_dialog = self.file_dialog.add_pattern_filter(name, patterns);
+        self
+    }
+
+    /// Add filter of files without an extension, eg. `"Makefile"`, `"Dockerfile"` or `"LICENSE"`.
+    ///
+    /// See [`FileDialog::add_file_name_filter`] for supported platforms
+    pub fn add_file_name_filter(mut self, name: &str, file_names: &[&str]) -> Self {
+        self.file_dialog = self.file_dialog.add_file_name_filter(name, file_names);
+        self
+    }
+
+    /// Add a filter that matches everything the other filters match, eg. "All supported formats".
+    ///
+    /// See [`FileDialog::add_all_supported_filter`]
+    pub fn add_all_supported_filter(mut self, name: &str) -> Self {
+        self.file_dialog = self.file_dialog.add_all_supported_filter(name);
+        self
+    }
+
+    /// Select the filter at `index` when the dialog opens, instead of the first one.
+    ///
+    /// See [`FileDialog::set_default_filter`]
+    pub fn set_default_filter(mut self, index: usize) -> Self {
+        self.file_dialog = self.file_dialog.set_default_filter(index);
+        self
+    }
+
+    /// Only allow items that exist on the local filesystem.
+    ///
+    /// See [`FileDialog::set_local_only`]
+    pub fn set_local_only(mut self, local_only: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_local_only(local_only);
+        self
+    }
+
+    /// Show hidden files and folders, eg. dotfiles.
+    ///
+    /// See [`FileDialog::set_show_hidden`] for supported platforms
+    pub fn set_show_hidden(mut self, show_hidden: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_show_hidden(show_hidden);
+        self
+    }
+
+    /// Show or hide the "New Folder" button of save and folder dialogs.
+    ///
+    /// See [`FileDialog::set_can_create_directories`] for supported platforms
+    pub fn set_can_create_directories(mut self, can: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_can_create_directories(can);
+        self
+    }
+
+    /// Ask before overwriting an existing file in the save dialog, enabled by default.
+    ///
+    /// See [`FileDialog::set_confirm_overwrite`] for supported platforms
+    pub fn set_confirm_overwrite(mut self, confirm: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_confirm_overwrite(confirm);
+        self
+    }
+
+    /// Make sure the path returned by the save dialog has an extension from the filters.
+    ///
+    /// See [`FileDialog::set_strict_file_types`]
+    pub fn set_strict_file_types(mut self, strict: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_strict_file_types(strict);
+        self
+    }
+
+    /// Append the extension of the selected filter to names typed without one.
+    ///
+    /// See [`FileDialog::set_append_extension`]
+    pub fn set_append_extension(mut self, append: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_append_extension(append);
+        self
+    }
+
+    /// Set to `false` to keep the chosen files out of the recently used documents list.
+    ///
+    /// See [`FileDialog::set_add_to_recent`] for supported platforms
+    pub fn set_add_to_recent(mut self, add: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_add_to_recent(add);
+        self
+    }
+
+    /// Keep the current directory of the process as it is while the dialog is shown.
+    ///
+    /// See [`FileDialog::set_no_change_dir`]
+    pub fn set_no_change_dir(mut self, no_change_dir: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_no_change_dir(no_change_dir);
+        self
+    }
+
+    /// Pin a folder to the sidebar of the dialog, eg. the project or plugin directory.
+    ///
+    /// See [`FileDialog::add_place`] for supported platforms
+    pub fn add_place<P: AsRef<Path>>(mut self, path: P, label: &str) -> Self {
+        self.file_dialog = self.file_dialog.add_place(path, label);
+        self
+    }
+
+    /// Hide the places the OS pins to the sidebar and the recently used ones.
+    ///
+    /// See [`FileDialog::set_hide_default_places`] for supported platforms
+    pub fn set_hide_default_places(mut self, hide: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_hide_default_places(hide);
+        self
+    }
+
+    /// Get notified about folder, selection and filter changes while the dialog is shown.
+    ///
+    /// See [`DialogEvents`] for supported platforms
+    pub fn set_events<E: DialogEvents + 'static>(mut self, events: E) -> Self {
+        self.file_dialog = self.file_dialog.set_events(events);
+        self
+    }
+
+    /// Check the chosen paths before the dialog accepts them.
+    ///
+    /// See [`FileDialog::set_validator`] for supported platforms
+    pub fn set_validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&Path) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.file_dialog = self.file_dialog.set_validator(validator);
+        self
+    }
+
+    /// Ask [`pick_files`](Self::pick_files) for at least `count` files.
+    ///
+    /// See [`FileDialog::set_min_files`]
+    pub fn set_min_files(mut self, count: usize) -> Self {
+        self.file_dialog = self.file_dialog.set_min_files(count);
+        self
+    }
+
+    /// Ask [`pick_files`](Self::pick_files) for at most `count` files.
+    ///
+    /// See [`FileDialog::set_max_files`]
+    pub fn set_max_files(mut self, count: usize) -> Self {
+        self.file_dialog = self.file_dialog.set_max_files(count);
+        self
+    }
+
+    /// Add a checkbox to the dialog, its state is read from [`choices`](Self::choices).
+    ///
+    /// See [`FileDialog::add_checkbox`] for supported platforms
+    pub fn add_checkbox(mut self, id: u32, label: &str, default: bool) -> Self {
+        self.file_dialog = self.file_dialog.add_checkbox(id, label, default);
+        self
+    }
+
+    /// Add a combo box to the dialog, the selected option is read from [`choices`](Self::choices).
+    ///
+    /// See [`FileDialog::add_combo`] for supported platforms
+    pub fn add_combo(mut self, id: u32, label: &str, options: &[&str], default: usize) -> Self {
+        self.file_dialog = self.file_dialog.add_combo(id, label, options, default);
+        self
+    }
+
+    /// Add a text field to the dialog, its content is read from [`choices`](Self::choices).
+    ///
+    /// See [`FileDialog::add_text_field`] for supported platforms
+    pub fn add_text_field(mut self, id: u32, label: &str, default: &str) -> Self {
+        self.file_dialog = self.file_dialog.add_text_field(id, label, default);
+        self
+    }
+
+    /// Show a preview of the highlighted file, `renderer` turns its path into text or an image.
+    ///
+    /// See [`FileDialog::set_preview`] for supported platforms
+    pub fn set_preview<F>(mut self, renderer: F) -> Self
+    where
+        F: Fn(&Path) -> PreviewContent + Send + Sync + 'static,
+    {
+        self.file_dialog = self.file_dialog.set_preview(renderer);
+        self
+    }
+
+    /// Set starting directory of the dialog.
+    /// #### Supported Platforms:
+    /// - Linux
+    /// - Windows
+    /// - Mac
+    pub fn set_directory<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.file_dialog = self.file_dialog.set_directory(path);
+        self
+    }
+
+    /// Set starting file name of the dialog.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux
+    /// - Mac
+    pub fn set_file_name(mut self, file_name: &str) -> Self {
+        self.file_dialog = self.file_dialog.set_file_name(file_name);
+        self
+    }
+
+    /// Open the save dialog pointing at the file `path`.
+    ///
+    /// See [`FileDialog::set_save_as_item`]
+    pub fn set_save_as_item<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.file_dialog = self.file_dialog.set_save_as_item(path);
+        self
+    }
+
+    /// Set the title of the dialog.
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux
+    /// - Mac (Only below version 10.11)
+    pub fn set_title(mut self, title: &str) -> Self {
+        self.file_dialog = self.file_dialog.set_title(title);
+        self
+    }
+
+    /// Set a one-line instruction shown above the files.
+    ///
+    /// See [`FileDialog::set_description`]
+    pub fn set_description(mut self, description: &str) -> Self {
+        self.file_dialog = self.file_dialog.set_description(description);
+        self
+    }
+
+    /// Set the label of the accept button, eg. "Import" instead of "Open".
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux
+    /// - Mac
+    pub fn set_accept_label(mut self, label: &str) -> Self {
+        self.file_dialog = self.file_dialog.set_accept_label(label);
+        self
+    }
+
+    /// Set the label of the file name field, eg. "Project name:" instead of "File name:".
+    ///
+    /// See [`FileDialog::set_file_name_label`]
+    pub fn set_file_name_label(mut self, label: &str) -> Self {
+        self.file_dialog = self.file_dialog.set_file_name_label(label);
+        self
+    }
+
+    /// Set the label of the cancel button.
+    /// #### Supported Platforms:
+    /// - Linux (GTK only)
+    pub fn set_cancel_label(mut self, label: &str) -> Self {
+        self.file_dialog = self.file_dialog.set_cancel_label(label);
+        self
+    }
+
+    /// Name of the logical dialog, eg. `"export"` or `"open-project"`.
+    ///
+    /// See [`FileDialog::set_persistence_id`]
+    pub fn set_persistence_id(mut self, id: &str) -> Self {
+        self.file_dialog = self.file_dialog.set_persistence_id(id);
+        self
+    }
+
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.file_dialog = self.file_dialog.set_parent(parent);
+        self
+    }
+
+    /// Set what the dialog blocks while it is open, see [`Modality`]
+    pub fn set_modality(mut self, modality: Modality) -> Self {
+        self.file_dialog = self.file_dialog.set_modality(modality);
+        self
+    }
+
+    /// Set where the dialog opens, see [`Position`]
+    ///
+    /// See [`FileDialog::set_position`] for supported platforms
+    pub fn set_position(mut self, position: Position) -> Self {
+        self.file_dialog = self.file_dialog.set_position(position);
+        self
+    }
+
+    /// Set the monitor a dialog without parent opens on, see [`MonitorSelection`]
+    ///
+    /// See [`FileDialog::set_monitor`] for supported platforms
+    pub fn set_monitor(mut self, monitor: MonitorSelection) -> Self {
+        self.file_dialog = self.file_dialog.set_monitor(monitor);
+        self
+    }
+
+    /// Keep the dialog above other windows
+    ///
+    /// See [`FileDialog::set_always_on_top`] for supported platforms
+    pub fn set_always_on_top(mut self, always_on_top: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_always_on_top(always_on_top);
+        self
+    }
+
+    /// Set the icon of the dialog window
+    ///
+    /// See [`FileDialog::set_icon`] for supported platforms
+    pub fn set_icon(mut self, icon: WindowIcon) -> Self {
+        self.file_dialog = self.file_dialog.set_icon(icon);
+        self
+    }
+
+    /// Set the name of the app, shown in the title bar next to the title
+    ///
+    /// See [`FileDialog::set_app_name`] for supported platforms
+    pub fn set_app_name(mut self, name: &str) -> Self {
+        self.file_dialog = self.file_dialog.set_app_name(name);
+        self
+    }
+
+    /// Choose the built-in backend that shows the dialog.
+    ///
+    /// If it is unavailable the next one from the fallback chain is used, see [`Backend`]
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.file_dialog = self.file_dialog.with_backend(backend);
+        self
+    }
+
+    /// Cancel the dialog when the user did not pick anything within `timeout` of it showing up
+    ///
+    /// See [`FileDialog::set_timeout`], not supported in `WASM32`, see [`handle`](Self::handle) for XDG Portal limitations
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.file_dialog = self.file_dialog.set_timeout(timeout);
+        self
+    }
+
+    /// Check the options before showing anything
+    ///
+    /// See [`FileDialog::validate`]
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        self.file_dialog.validate()
+    }
+
+    /// Handle that can dismiss the dialog once it is shown
+    ///
+    /// ```no_run
+    /// # async fn f() {
+    /// let dialog = rfd::AsyncFileDialog::new();
+    /// let handle = dialog.handle();
+    ///
+    /// let file = dialog.pick_file();
+    /// // Later, eg. on shutdown
+    /// handle.close();
+    /// assert!(file.await.is_none());
+    /// # }
+    /// ```
+    ///
+    /// Clones of this builder get their own handle.
+    /// Not supported in `WASM32`, on the XDG Portal the future resolves but the portal may keep its dialog open.
+    pub fn handle(&self) -> DialogHandle {
+        self.file_dialog.handle.clone()
+    }
+
+    /// State of the [checkboxes](Self::add_checkbox), [combo boxes](Self::add_combo) and [text fields](Self::add_text_field), filled in when the dialog gets accepted.
+    ///
+    /// Clones of this builder get their own choices.
+    pub fn choices(&self) -> DialogChoices {
+        self.file_dialog.choices.clone()
+    }
+}
+
+use std::future::Future;
+
+impl AsyncFileDialog {
+    /// Pick one file
+    pub fn pick_file(self) -> impl Future<Output = Option<FileHandle>> {
+        let future = self.try_pick_file();
+        async move { future.await.ok() }
+    }
+
+    /// Pick multiple files
+    pub fn pick_files(self) -> impl Future<Output = Option<Vec<FileHandle>>> {
+        let future = self.try_pick_files();
+        async move { future.await.ok() }
+    }
+
+    /// Pick one folder
+    ///
+    /// In `WASM32` it uses `showDirectoryPicker`, or an `<input webkitdirectory>` in browsers without it.
+    /// The files in the folder are listed with [`FileHandle::entries`]
+    pub fn pick_folder(self) -> impl Future<Output = Option<FileHandle>> {
+        let future = self.try_pick_folder();
+        async move { future.await.ok() }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Pick multiple folders
+    ///
+    /// Does not exist in `WASM32`
+    pub fn pick_folders(self) -> impl Future<Output = Option<Vec<FileHandle>>> {
+        let future = self.try_pick_folders();
+        async move { future.await.ok() }
+    }
+
+    /// Pick one file or one folder in the same dialog
+    ///
+    /// #### Supported Platforms:
+    /// - Mac
+    ///
+    /// Other platforms can't select both in one dialog, so this shows the [`pick_file`](Self::pick_file) dialog there.
+    pub fn pick_path(self) -> impl Future<Output = Option<FileHandle>> {
+        let future = self.try_pick_path();
+        async move { future.await.ok() }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Pick one file as a URL, items that have no local path included
+    ///
+    /// Does not exist in `WASM32`, see [`FileDialog::pick_file_url`] for supported platforms
+    pub fn pick_file_url(self) -> impl Future<Output = Option<crate::Url>> {
+        let future = self.try_pick_file_url();
+        async move { future.await.ok() }
+    }
+
+    /// Opens save file dialog
+    ///
+    /// In `WASM32` it is async only and uses the File System Access API (`showSaveFilePicker`).
+    /// Browsers without it show no dialog, writing the returned [`FileHandle`] with
+    /// [`FileHandle::write`] downloads the data under the name set with [`set_file_name`](Self::set_file_name).
+    ///
+    ///
+    /// #### Platform specific notes regarding save dialog filters:
+    /// - On MacOs
+    ///     - If filter is set, all files will be grayed out (no matter the extension sadly)
+    ///     - If user does not type an extension MacOs will append first available extension from filters list
+    ///     - If user types in filename with extension MacOs will check if it exists in filters list, if not it will display appropriate message
+    /// - On GTK
+    ///     - It only filters which already existing files get shown to the user
+    ///     - It does not append extensions automatically
+    ///     - It does not prevent users from adding any unsupported extension
+    /// - On Win:
+    ///     - If no extension was provided it will just add currently selected one
+    ///     - If selected extension was typed in by the user it will just return
+    ///     - If unselected extension was provided it will append selected one at the end, example: `test.png.txt`
+    pub fn save_file(self) -> impl Future<Output = Option<FileHandle>> {
+        let future = self.try_save_file();
+        async move { future.await.ok() }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Opens save file dialog, also returns the index of the filter that was selected when the user accepted
+    ///
+    /// Does not exist in `WASM32`, see [`FileDialog::save_file_with_filter`] for platform specific notes
+    pub fn save_file_with_filter(self) -> impl Future<Output = Option<(FileHandle, usize)>> {
+        let future = self.try_save_file_with_filter();
+        async move { future.await.ok() }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Opens save file dialog, returns the path together with the chosen filter and whether the file already exists
+    ///
+    /// Does not exist in `WASM32`, see [`FileDialog::save_file_with_filter`] for platform specific notes
+    pub fn save_file_result(self) -> impl Future<Output = Option<SaveFileResult>> {
+        let future = self.try_save_file_result();
+        async move { future.await.ok() }
+    }
+
+    /// Pick one file, see [`DialogResult`]
+    pub fn try_pick_file(self) -> impl Future<Output = DialogResult<FileHandle>> {
+        let started = self.file_dialog.start();
+        let future = started.pick_file_async(self.file_dialog);
+        async move {
+            let _started = started;
+            future.await.into()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Pick one file as a URL, see [`DialogResult`]
+    ///
+    /// Does not exist in `WASM32`
+    pub fn try_pick_file_url(self) -> impl Future<Output = DialogResult<crate::Url>> {
+        let dialog = self.file_dialog.url_dialog();
+        let started = dialog.start();
+        let future = started.pick_file_url_async(dialog);
+        async move {
+            let _started = started;
+            future.await.into()
+        }
+    }
+
+    /// Pick multiple files, see [`DialogResult`]
+    ///
+    /// See [`FileDialog::try_pick_files`] for the number of files
+    pub fn try_pick_files(self) -> impl Future<Output = DialogResult<Vec<FileHandle>>> {
+        let dialog = self.file_dialog;
+        let backend = dialog.start();
+        let future = backend.pick_files_async(dialog.reshown());
+        async move {
+            let mut res = future.await.into();
+            while let Some(warning) = dialog.file_count_warning(&res) {
+                if !backend.is_interactive() {
+                    return DialogResult::Cancelled;
+                }
+                backend.show_message_async(warning).await;
+                res = backend.pick_files_async(dialog.reshown()).await.into();
+            }
+            res
+        }
+    }
+
+    /// Pick one folder, see [`DialogResult`]
+    ///
+    /// See [`pick_folder`](Self::pick_folder) for `WASM32`
+    pub fn try_pick_folder(self) -> impl Future<Output = DialogResult<FileHandle>> {
+        let started = self.file_dialog.start();
+        let future = started.pick_folder_async(self.file_dialog);
+        async move {
+            let _started = started;
+            future.await.into()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Pick multiple folders, see [`DialogResult`]
+    ///
+    /// Does not exist in `WASM32`
+    pub fn try_pick_folders(self) -> impl Future<Output = DialogResult<Vec<FileHandle>>> {
+        let started = self.file_dialog.start();
+        let future = started.pick_folders_async(self.file_dialog);
+        async move {
+            let _started = started;
+            future.await.into()
+        }
+    }
+
+    /// Pick one file or one folder, see [`DialogResult`]
+    ///
+    /// See [`pick_path`](Self::pick_path) for supported platforms
+    pub fn try_pick_path(self) -> impl Future<Output = DialogResult<FileHandle>> {
+        let started = self.file_dialog.start();
+        let future = started.pick_path_async(self.file_dialog);
+        async move {
+            let _started = started;
+            future.await.into()
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    /// Opens save file dialog, see [`DialogResult`]
+    ///
+    /// See [`save_file`](Self::save_file) for browsers without the File System Access API
+    pub fn try_save_file(self) -> impl Future<Output = DialogResult<FileHandle>> {
+        let started = self.file_dialog.start();
+        let future = started.save_file_async(self.file_dialog);
+        async move {
+            let _started = started;
+            future.await.into()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Opens save file dialog, see [`DialogResult`]
+    ///
+    /// See [`save_file`](Self::save_file) for platform specific notes
+    pub fn try_save_file(self) -> impl Future<Output = DialogResult<FileHandle>> {
+        // The extension to append depends on the selected filter
+        let with_filter = self.file_dialog.append_extension == Some(true);
+        let future = self.save_file_with(with_filter);
+        async move { future.await.map(|saved| FileHandle::wrap(saved.path)) }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Opens save file dialog and returns the selected filter index, see [`DialogResult`]
+    ///
+    /// Does not exist in `WASM32`, see [`FileDialog::save_file_with_filter`] for platform specific notes
+    pub fn try_save_file_with_filter(
+        self,
+    ) -> impl Future<Output = DialogResult<(FileHandle, usize)>> {
+        let future = self.save_file_with(true);
+        async move {
+            future.await.map(|saved| {
+                (
+                    FileHandle::wrap(saved.path),
+                    saved.filter_index.unwrap_or_default(),
+                )
+            })
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Opens save file dialog and returns a [`SaveFileResult`], see [`DialogResult`]
+    ///
+    /// Does not exist in `WASM32`, see [`FileDialog::save_file_with_filter`] for platform specific notes
+    pub fn try_save_file_result(self) -> impl Future<Output = DialogResult<SaveFileResult>> {
+        let future = self.save_file_with(true);
+        async move { future.await.map(Saved::into_result) }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// See [`FileDialog::save_file_with`], the first dialog is shown right away
+    fn save_file_with(self, with_filter: bool) -> impl Future<Output = DialogResult<Saved>> {
+        type SaveFuture = crate::backend::DialogFutureType<
+            Result<Option<(FileHandle, Option<usize>)>, crate::Error>,
+        >;
+        let show = move |started: &Started, dialog: FileDialog| -> SaveFuture {
+            if with_filter {
+                let future = started.save_file_with_filter_async(dialog);
+                Box::pin(async move {
+                    let res = future.await?;
+                    Ok(res.map(|(file, index)| (file, Some(index))))
+                })
+            } else {
+                let future = started.save_file_async(dialog);
+                Box::pin(async move { Ok(future.await?.map(|file| (file, None))) })
+            }
+        };
+
+        let dialog = self.file_dialog;
+        let file_type = dialog.file_type_check();
+        let started = dialog.start();
+        let confirms = started.confirms_overwrite(&dialog);
+        let mut future = show(&started, dialog.reshown());
+        async move {
+            loop {
+                let saved = DialogResult::from(future.await).map(|(file, index)| {
+                    let chosen = file.path().to_owned();
+                    Saved::new(file_type(chosen.clone(), index), chosen, index, confirms)
+                });
+                let question = match dialog.replace_question(&saved) {
+                    Some(question) => question,
+                    None => return saved,
+                };
+                if started.show_message_async(question).await {
+                    return saved.map(Saved::confirmed);
+                }
+                if !started.is_interactive() {
+                    return DialogResult::Cancelled;
+                }
+                future = show(&started, dialog.reshown());
+            }
+        }
+    }
+}
+
+/// ## Synchronous Message Dialog
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MessageDialog {
+    pub(crate) title: String,
+    pub(crate) description: String,
+    pub(crate) level: MessageLevel,
+    pub(crate) buttons: MessageButtons,
+    pub(crate) custom_buttons: Vec<String>,
+    pub(crate) default_button: usize,
+    #[cfg(all(target_os = "windows", feature = "task-dialog"))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) task_dialog: crate::dialog_windows::TaskDialogOptions,
+    pub(crate) suppression: Option<String>,
+    pub(crate) hyperlinks: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) link_handler: Option<LinkHandler>,
+    #[cfg(feature = "parent")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) parent: Option<ParentHandle>,
+    pub(crate) modality: Modality,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) icon: Option<WindowIcon>,
+    pub(crate) app_name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) choices: DialogChoices,
+}
+
+// Like file dialogs, every message dialog gets its own choices
+impl Clone for MessageDialog {
+    fn clone(&self) -> Self {
+        Self {
+            title: self.title.clone(),
+            description: self.description.clone(),
+            level: self.level,
+            buttons: self.buttons,
+            custom_buttons: self.custom_buttons.clone(),
+            default_button: self.default_button,
+            #[cfg(all(target_os = "windows", feature = "task-dialog"))]
+            task_dialog: self.task_dialog.clone(),
+            suppression: self.suppression.clone(),
+            hyperlinks: self.hyperlinks,
+            link_handler: self.link_handler.clone(),
+            #[cfg(feature = "parent")]
+            parent: self.parent,
+            modality: self.modality,
+            icon: self.icon.clone(),
+            app_name: self.app_name.clone(),
+            choices: DialogChoices::default(),
+        }
+    }
+}
+
+impl MessageDialog {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set level of a dialog
+    ///
+    /// Depending on the system it can result in level specific icon to show up,
+    /// the will inform user it message is a error, warning, question or just information.
+    pub fn set_level(mut self, level: MessageLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set title of a dialog
+    pub fn set_title(mut self, text: &str) -> Self {
+        self.title = text.into();
+        self
+    }
+
+    /// Set description of a dialog
+    ///
+    /// Description is a content of a dialog
+    pub fn set_description(mut self, text: &str) -> Self {
+        self.description = text.into();
+        self
+    }
+
+    /// Set the set of button that will be displayed on the dialog
+    ///
+    /// - `Ok` dialog is a single `Ok` button
+    /// - `OkCancel` dialog, will display 2 buttons ok and cancel.
+    /// - `YesNo` dialog, will display 2 buttons yes and no.
+    pub fn set_buttons(mut self, btn: MessageButtons) -> Self {
+        self.buttons = btn;
+        self
+    }
+
+    /// Show buttons with the given labels instead of the [`MessageButtons`], eg. "Save", "Don't Save" and "Cancel".
+    ///
+    /// [`show_custom`](Self::show_custom) returns the label of the pressed one.
+    /// [`show`](Self::show) returns `true` when the first one was pressed.
+    pub fn set_custom_buttons(mut self, labels: &[&str]) -> Self {
+        self.custom_buttons = labels.iter().map(|label| label.to_string()).collect();
+        self
+    }
+
+    /// Set which button is pressed by the Enter key, `index` points into [`button_labels`](Self::button_labels).
+    ///
+    /// The first button is the default, eg. `1` makes `Cancel` the default of an `OkCancel` dialog
+    /// to guard a destructive action.
+    pub fn set_default_button(mut self, index: usize) -> Self {
+        self.default_button = index;
+        self
+    }
+
+    /// Add a "Don't ask me again" checkbox with the given label,
+    /// whether it was checked is read from [`choices`](Self::choices).
+    ///
+    /// ```no_run
+    /// let dialog = rfd::MessageDialog::new()
+    ///     .set_title("Delete the file?")
+    ///     .set_buttons(rfd::MessageButtons::OkCancel)
+    ///     .set_suppression_checkbox("Don't ask me again");
+    /// let choices = dialog.choices().clone();
+    ///
+    /// if dialog.show() && choices.suppressed() == Some(true) {
+    ///     // Delete without asking next time
+    /// }
+    /// ```
+    ///
+    /// #### Supported Platforms:
+    /// - Windows (needs Common Controls 6, the message box it falls back to has no checkbox)
+    /// - Linux (GTK only)
+    /// - MacOS
+    pub fn set_suppression_checkbox(mut self, label: &str) -> Self {
+        self.suppression = Some(label.into());
+        self
+    }
+
+    /// Turn `<a href="...">text</a>` tags in the description into clickable links.
+    /// Clicked links are opened with the default handler of the system, unless a
+    /// [link handler](Self::set_link_handler) is set.
+    ///
+    /// ```no_run
+    /// rfd::MessageDialog::new()
+    ///     .set_title("Could not open the project")
+    ///     .set_description(r#"See <a href="https://example.com/docs">the documentation</a> for help."#)
+    ///     .set_hyperlinks(true)
+    ///     .show();
+    /// ```
+    ///
+    /// #### Supported Platforms:
+    /// - Windows (needs Common Controls 6, the message box it falls back to shows the text without links)
+    /// - Linux (GTK only)
+    /// - MacOS
+    ///
+    /// Other platforms show the text without the tags.
+    pub fn set_hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.hyperlinks = hyperlinks;
+        self
+    }
+
+    /// Call `handler` with the `href` of clicked links instead of opening them,
+    /// it runs on the thread of the dialog while it is still shown.
+    ///
+    /// #### Supported Platforms:
+    /// - Windows
+    /// - Linux (GTK only)
+    pub fn set_link_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.link_handler = Some(LinkHandler(Arc::new(handler)));
+        self
+    }
+
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.parent = ParentHandle::of(parent);
+        self
+    }
+
+    /// Set what the dialog blocks while it is open, see [`Modality`]
+    pub fn set_modality(mut self, modality: Modality) -> Self {
+        self.modality = modality;
+        self
+    }
+
+    /// Set the icon of the dialog window
+    ///
+    /// See [`FileDialog::set_icon`] for supported platforms
+    pub fn set_icon(mut self, icon: WindowIcon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Set the name of the app, shown in the title bar
+    ///
+    /// See [`FileDialog::set_app_name`] for supported platforms
+    pub fn set_app_name(mut self, name: &str) -> Self {
+        self.app_name = Some(name.into());
+        self
+    }
+
+    /// Shows a message dialog:
+    ///
+    /// - In `Ok` dialog, it will return `true` when `OK` was pressed
+    /// - In `OkCancel` dialog, it will return `true` when `OK` was pressed
+    /// - In `YesNo` and `YesNoCancel` dialog, it will return `true` when `Yes` was pressed
+    pub fn show(self) -> bool {
+        backend(Backend::Default).show_message(self)
+    }
+
+    /// Shows a message dialog, returns which of the [`MessageButtons`] was pressed
+    ///
+    /// Closing the dialog counts as `Cancel`, or as `No` and `Ok` in dialogs without a `Cancel` button.
+    /// Buttons set with [`set_custom_buttons`](Self::set_custom_buttons) are not shown, the result can't name them,
+    /// use [`show_custom`](Self::show_custom) for those.
+    ///
+    /// ```no_run
+    /// use rfd::{MessageButtons, MessageDialogResult};
+    ///
+    /// let result = rfd::MessageDialog::new()
+    ///     .set_title("Save changes before closing?")
+    ///     .set_buttons(MessageButtons::YesNoCancel)
+    ///     .show_result();
+    ///
+    /// if result == MessageDialogResult::Cancel {
+    ///     // Keep the document open
+    /// }
+    /// ```
+    pub fn show_result(mut self) -> MessageDialogResult {
+        self.custom_buttons.clear();
+        let buttons = self.buttons;
+        let index = backend(Backend::Default).show_message_index(self);
+        MessageDialogResult::new(buttons, index)
+    }
+
+    /// Shows a message dialog, returns the label of the pressed button
+    /// or `None` when the dialog was closed without pressing one
+    ///
+    /// ```no_run
+    /// let pressed = rfd::MessageDialog::new()
+    ///     .set_title("Unsaved changes")
+    ///     .set_custom_buttons(&["Save", "Don't Save", "Cancel"])
+    ///     .show_custom();
+    /// ```
+    pub fn show_custom(self) -> Option<String> {
+        backend(Backend::Default).show_message_custom(self)
+    }
+
+    /// Shows a message dialog, returns the label of the pressed button, see [`DialogResult`]
+    ///
+    /// Closing the dialog without pressing a button counts as cancelled
+    pub fn try_show(self) -> DialogResult<String> {
+        backend(Backend::Default).try_show_message(self).into()
+    }
+}
+
+/// Options read by a [`DialogBackend`](crate::DialogBackend)
+impl MessageDialog {
+    /// Title set with [`set_title`](Self::set_title)
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Description set with [`set_description`](Self::set_description)
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Level set with [`set_level`](Self::set_level)
+    pub fn level(&self) -> MessageLevel {
+        self.level
+    }
+
+    /// Buttons set with [`set_buttons`](Self::set_buttons)
+    pub fn buttons(&self) -> MessageButtons {
+        self.buttons
+    }
+
+    /// Labels set with [`set_custom_buttons`](Self::set_custom_buttons)
+    pub fn custom_buttons(&self) -> &[String] {
+        &self.custom_buttons
+    }
+
+    /// Labels of the buttons, the custom ones or the English labels of the [`MessageButtons`]
+    ///
+    /// They name the pressed button in the result of [`show_custom`](Self::show_custom),
+    /// even when a [`Localizer`](crate::Localizer) translates the shown ones
+    pub fn button_labels(&self) -> Vec<String> {
+        if !self.custom_buttons.is_empty() {
+            return self.custom_buttons.clone();
+        }
+        let labels: &[&str] = match self.buttons {
+            MessageButtons::Ok => &["OK"],
+            MessageButtons::OkCancel => &["OK", "Cancel"],
+            MessageButtons::YesNo => &["Yes", "No"],
+            MessageButtons::YesNoCancel => &["Yes", "No", "Cancel"],
+        };
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    /// [`button_labels`](Self::button_labels) as the buttons show them, standard ones are translated
+    /// by the [`Localizer`](crate::Localizer)
+    #[cfg_attr(any(target_arch = "wasm32", target_os = "windows"), allow(dead_code))]
+    pub(crate) fn shown_button_labels(&self) -> Vec<String> {
+        if !self.custom_buttons.is_empty() {
+            return self.custom_buttons.clone();
+        }
+        let strings: &[BuiltinString] = match self.buttons {
+            MessageButtons::Ok => &[BuiltinString::Ok],
+            MessageButtons::OkCancel => &[BuiltinString::Ok, BuiltinString::Cancel],
+            MessageButtons::YesNo => &[BuiltinString::Yes, BuiltinString::No],
+            MessageButtons::YesNoCancel => {
+                &[BuiltinString::Yes, BuiltinString::No, BuiltinString::Cancel]
+            }
+        };
+        strings.iter().map(|string| localize(*string)).collect()
+    }
+
+    /// Index set with [`set_default_button`](Self::set_default_button)
+    pub fn default_button(&self) -> usize {
+        self.default_button
+    }
+
+    /// Label set with [`set_suppression_checkbox`](Self::set_suppression_checkbox)
+    pub fn suppression_checkbox(&self) -> Option<&str> {
+        self.suppression.as_deref()
+    }
+
+    /// Set with [`set_hyperlinks`](Self::set_hyperlinks)
+    pub fn hyperlinks(&self) -> bool {
+        self.hyperlinks
+    }
+
+    /// Handler set with [`set_link_handler`](Self::set_link_handler)
+    pub fn link_handler(&self) -> Option<&OpenLink> {
+        self.link_handler.as_ref().map(|handler| &*handler.0)
+    }
+
+    /// State of the suppression checkbox, filled in by the backend when the dialog is closed.
+    ///
+    /// Clones of this builder get their own choices.
+    pub fn choices(&self) -> &DialogChoices {
+        &self.choices
+    }
+
+    #[cfg(feature = "parent")]
+    /// Parent set with [`set_parent`](Self::set_parent)
+    pub fn parent(&self) -> Option<RawWindowHandle> {
+        self.parent.map(ParentHandle::get)
+    }
+
+    /// Set with [`set_modality`](Self::set_modality)
+    pub fn modality(&self) -> Modality {
+        self.modality
+    }
+}
+
+/// ## Asynchronous Message Dialog
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct AsyncMessageDialog(pub(crate) MessageDialog);
+
+impl AsyncMessageDialog {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set level of a dialog
+    ///
+    /// Depending on the system it can result in level specific icon to show up,
+    /// the will inform user it message is a error, warning, question or just information.
+    pub fn set_level(mut self, level: MessageLevel) -> Self {
+        self.0 = self.0.set_level(level);
+        self
+    }
+
+    /// Set title of a dialog
+    pub fn set_title(mut self, text: &str) -> Self {
+        self.0 = self.0.set_title(text);
+        self
+    }
+
+    /// Set description of a dialog
+    ///
+    /// Description is a content of a dialog
+    pub fn set_description(mut self, text: &str) -> Self {
+        self.0 = self.0.set_description(text);
+        self
+    }
+
+    /// Set the set of button that will be displayed on the dialog
+    ///
+    /// - `Ok` dialog is a single `Ok` button
+    /// - `OkCancel` dialog, will display 2 buttons ok and cancel.
+    /// - `YesNo` dialog, will display 2 buttons yes and no.
+    pub fn set_buttons(mut self, btn: MessageButtons) -> Self {
+        self.0 = self.0.set_buttons(btn);
+        self
+    }
+
+    /// Show buttons with the given labels instead of the [`MessageButtons`].
+    ///
+    /// See [`MessageDialog::set_custom_buttons`]
+    pub fn set_custom_buttons(mut self, labels: &[&str]) -> Self {
+        self.0 = self.0.set_custom_buttons(labels);
+        self
+    }
+
+    /// Set which button is pressed by the Enter key, `index` points into [`MessageDialog::button_labels`].
+    pub fn set_default_button(mut self, index: usize) -> Self {
+        self.0 = self.0.set_default_button(index);
+        self
+    }
+
+    /// Add a "Don't ask me again" checkbox with the given label,
+    /// whether it was checked is read from [`choices`](Self::choices).
+    ///
+    /// See [`MessageDialog::set_suppression_checkbox`]
+    pub fn set_suppression_checkbox(mut self, label: &str) -> Self {
+        self.0 = self.0.set_suppression_checkbox(label);
+        self
+    }
+
+    /// Turn `<a href="...">text</a>` tags in the description into clickable links.
+    ///
+    /// See [`MessageDialog::set_hyperlinks`]
+    pub fn set_hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.0 = self.0.set_hyperlinks(hyperlinks);
+        self
+    }
+
+    /// Call `handler` with the `href` of clicked links instead of opening them.
+    ///
+    /// See [`MessageDialog::set_link_handler`]
+    pub fn set_link_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.0 = self.0.set_link_handler(handler);
+        self
+    }
+
+    /// State of the suppression checkbox, take it before showing the dialog
+    ///
+    /// Clones of this builder get their own choices.
+    pub fn choices(&self) -> DialogChoices {
+        self.0.choices.clone()
+    }
+
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.0 = self.0.set_parent(parent);
+        self
+    }
+
+    /// Set what the dialog blocks while it is open, see [`Modality`]
+    pub fn set_modality(mut self, modality: Modality) -> Self {
+        self.0 = self.0.set_modality(modality);
+        self
+    }
+
+    /// Set the icon of the dialog window
+    ///
+    /// See [`FileDialog::set_icon`] for supported platforms
+    pub fn set_icon(mut self, icon: WindowIcon) -> Self {
+        self.0 = self.0.set_icon(icon);
+        self
+    }
+
+    /// Set the name of the app, shown in the title bar
+    ///
+    /// See [`FileDialog::set_app_name`] for supported platforms
+    pub fn set_app_name(mut self, name: &str) -> Self {
+        self.0 = self.0.set_app_name(name);
+        self
+    }
+
+    /// Shows a message dialog:
+    /// - In `Ok` dialog, it will return `true` when `OK` was pressed
+    /// - In `OkCancel` dialog, it will return `true` when `OK` was pressed
+    /// - In `YesNo` and `YesNoCancel` dialog, it will return `true` when `Yes` was pressed
+    pub fn show(self) -> impl Future<Output = bool> {
+        backend(Backend::Default).show_message_async(self.0)
+    }
+
+    /// Shows a message dialog, resolves to which of the [`MessageButtons`] was pressed
+    ///
+    /// See [`MessageDialog::show_result`]
+    pub fn show_result(mut self) -> impl Future<Output = MessageDialogResult> {
+        self.0.custom_buttons.clear();
+        let buttons = self.0.buttons;
+        let index = backend(Backend::Default).show_message_index_async(self.0);
+        async move { MessageDialogResult::new(buttons, index.await) }
+    }
+
+    /// Shows a message dialog, resolves to the label of the pressed button
+    /// or `None` when the dialog was closed without pressing one
+    pub fn show_custom(self) -> impl Future<Output = Option<String>> {
+        backend(Backend::Default).show_message_custom_async(self.0)
+    }
+
+    /// Shows a message dialog, resolves to the label of the pressed button, see [`DialogResult`]
+    ///
+    /// See [`MessageDialog::try_show`]
+    pub fn try_show(self) -> impl Future<Output = DialogResult<String>> {
+        let future = backend(Backend::Default).try_show_message_async(self.0);
+        async move { future.await.into() }
+    }
+}
+
+/// ## Synchronous Input Dialog
+///
+/// Asks the user for a line of text, eg. the new name of a layer.
+#[derive(Default, Debug, Clone)]
+pub struct InputDialog {
+    pub(crate) title: String,
+    pub(crate) prompt: String,
+    pub(crate) default: String,
+    pub(crate) password: bool,
+    pub(crate) reveal_toggle: bool,
+    pub(crate) multiline: bool,
+    pub(crate) size_hint: Option<(u32, u32)>,
+    #[cfg(feature = "parent")]
+    pub(crate) parent: Option<ParentHandle>,
+}
+
+impl InputDialog {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set title of a dialog
+    pub fn set_title(mut self, text: &str) -> Self {
+        self.title = text.into();
+        self
+    }
+
+    /// Set the text shown above the input field
+    pub fn set_prompt(mut self, text: &str) -> Self {
+        self.prompt = text.into();
+        self
+    }
+
+    /// Set the text the input field starts with, it is selected so typing replaces it
+    pub fn set_default(mut self, text: &str) -> Self {
+        self.default = text.into();
+        self
+    }
+
+    /// Use a scrolling text area instead of a single line field, eg. for a commit message.
+    /// Enter inserts a line break, the text is accepted with the OK button.
+    ///
+    /// The browser prompt has a single line only, WASM falls back to it.
+    pub fn set_multiline(mut self, multiline: bool) -> Self {
+        self.multiline = multiline;
+        self
+    }
+
+    /// Preferred size of the multi-line text area in logical pixels, the default is 360x120.
+    /// Platforms treat it as a hint, eg. Windows rounds it to dialog units.
+    ///
+    /// #### Supported Platforms:
+    /// - Linux
+    /// - Windows
+    /// - Mac
+    pub fn set_size_hint(mut self, width: u32, height: u32) -> Self {
+        self.size_hint = Some((width, height));
+        self
+    }
+
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.parent = ParentHandle::of(parent);
+        self
+    }
+
+    /// Shows the dialog, returns the entered text or `None` when it was cancelled
+    ///
+    /// ```no_run
+    /// let name = rfd::InputDialog::new()
+    ///     .set_title("Rename layer")
+    ///     .set_prompt("New name:")
+    ///     .set_default("Layer 1")
+    ///     .show();
+    /// ```
+    pub fn show(self) -> Option<String> {
+        backend(Backend::Default).show_input(self)
+    }
+
+    /// Shows the dialog, returns the entered text, see [`DialogResult`]
+    pub fn try_show(self) -> DialogResult<String> {
+        backend(Backend::Default).try_show_input(self).into()
+    }
+}
+
+/// Options read by a [`DialogBackend`](crate::DialogBackend)
+impl InputDialog {
+    /// Title set with [`set_title`](Self::set_title)
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Prompt set with [`set_prompt`](Self::set_prompt)
+    pub fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    /// Text set with [`set_default`](Self::set_default)
+    pub fn default_text(&self) -> &str {
+        &self.default
+    }
+
+    /// `true` for a [`PasswordDialog`], the entered characters are masked
+    pub fn password(&self) -> bool {
+        self.password
+    }
+
+    /// Set with [`PasswordDialog::set_show_password_toggle`]
+    pub fn show_password_toggle(&self) -> bool {
+        self.reveal_toggle
+    }
+
+    /// Set with [`set_multiline`](Self::set_multiline)
+    pub fn multiline(&self) -> bool {
+        self.multiline
+    }
+
+    /// Size set with [`set_size_hint`](Self::set_size_hint), or the default one
+    pub fn size_hint(&self) -> (u32, u32) {
+        self.size_hint.unwrap_or((360, 120))
+    }
+
+    #[cfg(feature = "parent")]
+    /// Parent set with [`set_parent`](Self::set_parent)
+    pub fn parent(&self) -> Option<RawWindowHandle> {
+        self.parent.map(ParentHandle::get)
+    }
+}
+
+/// ## Asynchronous Input Dialog
+#[derive(Default, Debug, Clone)]
+pub struct AsyncInputDialog(pub(crate) InputDialog);
+
+impl AsyncInputDialog {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set title of a dialog
+    pub fn set_title(mut self, text: &str) -> Self {
+        self.0 = self.0.set_title(text);
+        self
+    }
+
+    /// Set the text shown above the input field
+    pub fn set_prompt(mut self, text: &str) -> Self {
+        self.0 = self.0.set_prompt(text);
+        self
+    }
+
+    /// Set the text the input field starts with, it is selected so typing replaces it
+    pub fn set_default(mut self, text: &str) -> Self {
+        self.0 = self.0.set_default(text);
+        self
+    }
+
+    /// Use a scrolling text area instead of a single line field, eg. for a commit message.
+    /// Enter inserts a line break, the text is accepted with the OK button.
+    ///
+    /// The browser prompt has a single line only, WASM falls back to it.
+    pub fn set_multiline(mut self, multiline: bool) -> Self {
+        self.0 = self.0.set_multiline(multiline);
+        self
+    }
+
+    /// Preferred size of the multi-line text area in logical pixels, the default is 360x120.
+    /// Platforms treat it as a hint, eg. Windows rounds it to dialog units.
+    ///
+    /// #### Supported Platforms:
+    /// - Linux
+    /// - Windows
+    /// - Mac
+    pub fn set_size_hint(mut self, width: u32, height: u32) -> Self {
+        self.0 = self.0.set_size_hint(width, height);
+        self
+    }
+
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.0 = self.0.set_parent(parent);
+        self
+    }
+
+    /// Shows the dialog, resolves to the entered text or `None` when it was cancelled
+    pub fn show(self) -> impl Future<Output = Option<String>> {
+        backend(Backend::Default).show_input_async(self.0)
+    }
+
+    /// Shows the dialog, resolves to the entered text, see [`DialogResult`]
+    pub fn try_show(self) -> impl Future<Output = DialogResult<String>> {
+        let future = backend(Backend::Default).try_show_input_async(self.0);
+        async move { future.await.into() }
+    }
+}
+
+/// ## Synchronous Password Dialog
+///
+/// An [`InputDialog`] that masks the entered characters, eg. to unlock an encrypted file.
+/// Backends receive it as an `InputDialog` with [`InputDialog::password`] set.
+#[derive(Debug, Clone)]
+pub struct PasswordDialog(InputDialog);
+
+impl Default for PasswordDialog {
+    fn default() -> Self {
+        Self(InputDialog {
+            password: true,
+            ..Default::default()
+        })
+    }
+}
+
+impl PasswordDialog {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set title of a dialog
+    pub fn set_title(mut self, text: &str) -> Self {
+        self.0 = self.0.set_title(text);
+        self
+    }
+
+    /// Set the text shown above the password field
+    pub fn set_prompt(mut self, text: &str) -> Self {
+        self.0 = self.0.set_prompt(text);
+        self
+    }
+
+    /// Adds a "Show password" checkbox that unmasks the entered characters
+    ///
+    /// #### Supported Platforms:
+    /// - Linux
+    /// - Windows
+    pub fn set_show_password_toggle(mut self, show: bool) -> Self {
+        self.0.reveal_toggle = show;
+        self
+    }
+
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.0 = self.0.set_parent(parent);
+        self
+    }
+
+    /// Shows the dialog, returns the entered password or `None` when it was cancelled
+    ///
+    /// The browser can't mask a prompt, on WASM this always returns `None`.
+    ///
+    /// ```no_run
+    /// let password = rfd::PasswordDialog::new()
+    ///     .set_title("Unlock")
+    ///     .set_prompt("Password for secrets.kdbx:")
+    ///     .show();
+    /// ```
+    pub fn show(self) -> Option<Password> {
+        backend(Backend::Default)
+            .show_input(self.0)
+            .map(Password::new)
+    }
+
+    /// Shows the dialog, returns the entered password, see [`DialogResult`]
+    pub fn try_show(self) -> DialogResult<Password> {
+        DialogResult::from(backend(Backend::Default).try_show_input(self.0)).map(Password::new)
+    }
+}
+
+/// ## Asynchronous Password Dialog
+#[derive(Default, Debug, Clone)]
+pub struct AsyncPasswordDialog(PasswordDialog);
+
+impl AsyncPasswordDialog {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set title of a dialog
+    pub fn set_title(mut self, text: &str) -> Self {
+        self.0 = self.0.set_title(text);
+        self
+    }
+
+    /// Set the text shown above the password field
+    pub fn set_prompt(mut self, text: &str) -> Self {
+        self.0 = self.0.set_prompt(text);
+        self
+    }
+
+    /// Adds a "Show password" checkbox that unmasks the entered characters
+    ///
+    /// #### Supported Platforms:
+    /// - Linux
+    /// - Windows
+    pub fn set_show_password_toggle(mut self, show: bool) -> Self {
+        self.0 = self.0.set_show_password_toggle(show);
+        self
+    }
+
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.0 = self.0.set_parent(parent);
+        self
+    }
+
+    /// Shows the dialog, resolves to the entered password or `None` when it was cancelled
+    pub fn show(self) -> impl Future<Output = Option<Password>> {
+        let future = backend(Backend::Default).show_input_async((self.0).0);
+        async move { future.await.map(Password::new) }
+    }
+
+    /// Shows the dialog, resolves to the entered password, see [`DialogResult`]
+    pub fn try_show(self) -> impl Future<Output = DialogResult<Password>> {
+        let future = backend(Backend::Default).try_show_input_async((self.0).0);
+        async move { DialogResult::from(future.await).map(Password::new) }
+    }
+}
+
+/// ## Synchronous Credential Dialog
+///
+/// Asks for a user name and a password, eg. to open a network share picked in a folder dialog.
+/// Windows shows its own credential prompt, the other platforms a dialog with two fields.
+#[derive(Default, Debug, Clone)]
+pub struct CredentialDialog {
+    pub(crate) title: String,
+    pub(crate) message: String,
+    pub(crate) user: String,
+    #[cfg(feature = "parent")]
+    pub(crate) parent: Option<ParentHandle>,
+}
+
+impl CredentialDialog {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set title of a dialog
+    pub fn set_title(mut self, text: &str) -> Self {
+        self.title = text.into();
+        self
+    }
+
+    /// Set the text shown above the fields, eg. what the credentials are for
+    pub fn set_message(mut self, text: &str) -> Self {
+        self.message = text.into();
+        self
+    }
+
+    /// Set the user name the dialog starts with
+    pub fn set_user(mut self, user: &str) -> Self {
+        self.user = user.into();
+        self
+    }
+
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.parent = ParentHandle::of(parent);
+        self
+    }
+
+    /// Shows the dialog, returns the entered credentials or `None` when it was cancelled
+    ///
+    /// The browser can't mask a prompt, on WASM this always returns `None`.
+    ///
+    /// ```no_run
+    /// let credentials = rfd::CredentialDialog::new()
+    ///     .set_title("Connect to server")
+    ///     .set_message("Credentials for \\\\nas\\photos")
+    ///     .show();
+    /// ```
+    pub fn show(self) -> Option<Credentials> {
+        backend(Backend::Default).show_credentials(self)
+    }
+
+    /// Shows the dialog, returns the entered credentials, see [`DialogResult`]
+    pub fn try_show(self) -> DialogResult<Credentials> {
+        backend(Backend::Default).try_show_credentials(self).into()
     }
 }
 
-use crate::backend::AsyncMessageDialogImpl;
-use crate::backend::MessageDialogImpl;
+/// Options read by a [`DialogBackend`](crate::DialogBackend)
+impl CredentialDialog {
+    /// Title set with [`set_title`](Self::set_title)
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Message set with [`set_message`](Self::set_message)
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// User name set with [`set_user`](Self::set_user)
+    pub fn user(&self) -> &str {
+        &self.user
+    }
 
-/// ## Synchronous Message Dialog
-#[derive(Default, Debug, Clone)]
-pub struct MessageDialog {
-    pub(crate) title: String,
-    pub(crate) description: String,
-    pub(crate) level: MessageLevel,
-    pub(crate) buttons: MessageButtons,
     #[cfg(feature = "parent")]
-    pub(crate) parent: Option<RawWindowHandle>,
+    /// Parent set with [`set_parent`](Self::set_parent)
+    pub fn parent(&self) -> Option<RawWindowHandle> {
+        self.parent.map(ParentHandle::get)
+    }
 }
 
-// Oh god, I don't like sending RawWindowHandle between threads but here we go anyways...
-// fingers crossed
-unsafe impl Send for MessageDialog {}
+/// ## Asynchronous Credential Dialog
+#[derive(Default, Debug, Clone)]
+pub struct AsyncCredentialDialog(pub(crate) CredentialDialog);
 
-impl MessageDialog {
+impl AsyncCredentialDialog {
     pub fn new() -> Self {
         Default::default()
     }
 
-    /// Set level of a dialog
-    ///
-    /// Depending on the system it can result in level specific icon to show up,
-    /// the will inform user it message is a error, warning or just information.
-    pub fn set_level(mut self, level: MessageLevel) -> Self {
-        self.level = level;
+    /// Set title of a dialog
+    pub fn set_title(mut self, text: &str) -> Self {
+        self.0 = self.0.set_title(text);
+        self
+    }
+
+    /// Set the text shown above the fields, eg. what the credentials are for
+    pub fn set_message(mut self, text: &str) -> Self {
+        self.0 = self.0.set_message(text);
+        self
+    }
+
+    /// Set the user name the dialog starts with
+    pub fn set_user(mut self, user: &str) -> Self {
+        self.0 = self.0.set_user(user);
+        self
+    }
+
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.0 = self.0.set_parent(parent);
         self
     }
 
+    /// Shows the dialog, resolves to the entered credentials or `None` when it was cancelled
+    pub fn show(self) -> impl Future<Output = Option<Credentials>> {
+        backend(Backend::Default).show_credentials_async(self.0)
+    }
+
+    /// Shows the dialog, resolves to the entered credentials, see [`DialogResult`]
+    pub fn try_show(self) -> impl Future<Output = DialogResult<Credentials>> {
+        let future = backend(Backend::Default).try_show_credentials_async(self.0);
+        async move { future.await.into() }
+    }
+}
+
+/// ## Synchronous Pick List Dialog
+///
+/// Asks the user to choose one item of a list, eg. "Which device?".
+#[derive(Debug, Clone)]
+pub struct PickListDialog {
+    pub(crate) title: String,
+    pub(crate) prompt: String,
+    pub(crate) items: Vec<String>,
+    #[cfg(feature = "parent")]
+    pub(crate) parent: Option<ParentHandle>,
+}
+
+impl PickListDialog {
+    pub fn new(items: &[&str]) -> Self {
+        Self {
+            title: String::new(),
+            prompt: String::new(),
+            items: items.iter().map(|item| item.to_string()).collect(),
+            #[cfg(feature = "parent")]
+            parent: None,
+        }
+    }
+
     /// Set title of a dialog
     pub fn set_title(mut self, text: &str) -> Self {
         self.title = text.into();
         self
     }
 
-    /// Set description of a dialog
-    ///
-    /// Description is a content of a dialog
-    pub fn set_description(mut self, text: &str) -> Self {
-        self.description = text.into();
+    /// Set the text shown above the list
+    pub fn set_prompt(mut self, text: &str) -> Self {
+        self.prompt = text.into();
         self
     }
 
-    /// Set the set of button that will be displayed on the dialog
+    #[cfg(feature = "parent")]
+    /// Set parent windows explicitly (optional)
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.parent = ParentHandle::of(parent);
+        self
+    }
+
+    /// Shows the dialog, returns the index of the chosen item or `None` when it was cancelled
     ///
-    /// - `Ok` dialog is a single `Ok` button
-    /// - `OkCancel` dialog, will display 2 buttons ok and cancel.
-    /// - `YesNo` dialog, will display 2 buttons yes and no.
-    pub fn set_buttons(mut self, btn: MessageButtons) -> Self {
-        self.buttons = btn;
+    /// ```no_run
+    /// let device = rfd::PickListDialog::new(&["/dev/sda", "/dev/sdb"])
+    ///     .set_title("Flash image")
+    ///     .set_prompt("Which device?")
+    ///     .show();
+    /// ```
+    pub fn show(self) -> Option<usize> {
+        backend(Backend::Default).show_pick_list(self)
+    }
+
+    /// Shows the dialog, returns the index of the chosen item, see [`DialogResult`]
+    pub fn try_show(self) -> DialogResult<usize> {
+        backend(Backend::Default).try_show_pick_list(self).into()
+    }
+}
+
+/// Options read by a [`DialogBackend`](crate::DialogBackend)
+impl PickListDialog {
+    /// Title set with [`set_title`](Self::set_title)
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Prompt set with [`set_prompt`](Self::set_prompt)
+    pub fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    /// Items passed to [`new`](Self::new)
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    #[cfg(feature = "parent")]
+    /// Parent set with [`set_parent`](Self::set_parent)
+    pub fn parent(&self) -> Option<RawWindowHandle> {
+        self.parent.map(ParentHandle::get)
+    }
+}
+
+/// ## Asynchronous Pick List Dialog
+#[derive(Debug, Clone)]
+pub struct AsyncPickListDialog(pub(crate) PickListDialog);
+
+impl AsyncPickListDialog {
+    pub fn new(items: &[&str]) -> Self {
+        Self(PickListDialog::new(items))
+    }
+
+    /// Set title of a dialog
+    pub fn set_title(mut self, text: &str) -> Self {
+        self.0 = self.0.set_title(text);
+        self
+    }
+
+    /// Set the text shown above the list
+    pub fn set_prompt(mut self, text: &str) -> Self {
+        self.0 = self.0.set_prompt(text);
         self
     }
 
     #[cfg(feature = "parent")]
     /// Set parent windows explicitly (optional)
-    /// Suported in: `macos` and `windows`
-    pub fn set_parent<W: HasRawWindowHandle>(mut self, parent: &W) -> Self {
-        self.parent = Some(parent.raw_window_handle());
+    /// Suported in: `macos`, `windows` and Linux (X11 windows, Wayland ones with the portal)
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.0 = self.0.set_parent(parent);
         self
     }
 
-    /// Shows a message dialog:
-    ///
-    /// - In `Ok` dialog, it will return `true` when `OK` was pressed
-    /// - In `OkCancel` dialog, it will return `true` when `OK` was pressed
-    /// - In `YesNo` dialog, it will return `true` when `Yes` was pressed
-    pub fn show(self) -> bool {
-        MessageDialogImpl::show(self)
+    /// Shows the dialog, resolves to the index of the chosen item or `None` when it was cancelled
+    pub fn show(self) -> impl Future<Output = Option<usize>> {
+        backend(Backend::Default).show_pick_list_async(self.0)
+    }
+
+    /// Shows the dialog, resolves to the index of the chosen item, see [`DialogResult`]
+    pub fn try_show(self) -> impl Future<Output = DialogResult<usize>> {
+        let future = backend(Backend::Default).try_show_pick_list_async(self.0);
+        async move { future.await.into() }
     }
 }
 
-/// ## Asynchronous Message Dialog
+/// ## Progress Dialog
+///
+/// Shows the progress of a long operation, eg. copying the files picked in a file dialog.
+/// Unlike the other dialogs it does not block, [`show`](Self::show) returns a [`ProgressHandle`]
+/// that updates the dialog and tells if the user pressed Cancel.
 #[derive(Default, Debug, Clone)]
-pub struct AsyncMessageDialog(MessageDialog);
+pub struct ProgressDialog {
+    pub(crate) title: String,
+    pub(crate) message: String,
+    pub(crate) marquee: bool,
+    #[cfg(feature = "parent")]
+    pub(crate) parent: Option<ParentHandle>,
+}
 
-impl AsyncMessageDialog {
+impl ProgressDialog {
     pub fn new() -> Self {
         Default::default()
     }
 
-    /// Set level of a dialog
-    ///
-    /// Depending on the system it can result in level specific icon to show up,
-    /// the will inform user it message is a error, warning or just information.
-    pub fn set_level(mut self, level: MessageLevel) -> Self {
-        self.0 = self.0.set_level(level);
-        self
-    }
-
     /// Set title of a dialog
     pub fn set_title(mut self, text: &str) -> Self {
-        self.0 = self.0.set_title(text);
+        self.title = text.into();
         self
     }
 
-    /// Set description of a dialog
-    ///
-    /// Description is a content of a dialog
-    pub fn set_description(mut self, text: &str) -> Self {
-        self.0 = self.0.set_description(text);
+    /// Set the text shown below the title, it can be changed later with [`ProgressHandle::set_message`]
+    pub fn set_message(mut self, text: &str) -> Self {
+        self.message = text.into();
         self
     }
 
-    /// Set the set of button that will be displayed on the dialog
-    ///
-    /// - `Ok` dialog is a single `Ok` button
-    /// - `OkCancel` dialog, will display 2 buttons ok and cancel.
-    /// - `YesNo` dialog, will display 2 buttons yes and no.
-    pub fn set_buttons(mut self, btn: MessageButtons) -> Self {
-        self.0 = self.0.set_buttons(btn);
+    /// Start with a bar that only shows activity, for work of unknown length
+    pub fn set_marquee(mut self, marquee: bool) -> Self {
+        self.marquee = marquee;
         self
     }
 
     #[cfg(feature = "parent")]
     /// Set parent windows explicitly (optional)
-    /// Suported in: `macos` and `windows`
-    pub fn set_parent<W: HasRawWindowHandle>(mut self, parent: &W) -> Self {
-        self.0 = self.0.set_parent(parent);
+    /// Suported in: `windows`
+    pub fn set_parent<W: ParentWindow + ?Sized>(mut self, parent: &W) -> Self {
+        self.parent = ParentHandle::of(parent);
         self
     }
 
-    /// Shows a message dialog:
-    /// - In `Ok` dialog, it will return `true` when `OK` was pressed
-    /// - In `OkCancel` dialog, it will return `true` when `OK` was pressed
-    /// - In `YesNo` dialog, it will return `true` when `Yes` was pressed
-    pub fn show(self) -> impl Future<Output = bool> {
-        AsyncMessageDialogImpl::show_async(self.0)
+    /// Shows the dialog and returns right away, the dialog stays open until the handle is dropped
+    /// or the user presses Cancel
+    ///
+    /// #### Supported Platforms:
+    /// - Linux
+    /// - Windows (needs version 6 of the common controls, which comes with a manifest)
+    /// - Mac (needs a running event loop)
+    ///
+    /// ```no_run
+    /// # let files: Vec<std::path::PathBuf> = Vec::new();
+    /// let progress = rfd::ProgressDialog::new()
+    ///     .set_title("Copying files")
+    ///     .show();
+    ///
+    /// for (i, file) in files.iter().enumerate() {
+    ///     if progress.is_cancelled() {
+    ///         break;
+    ///     }
+    ///     progress.set_message(&file.display().to_string());
+    ///     // copy the file
+    ///     progress.set_progress((i + 1) as f32 / files.len() as f32);
+    /// }
+    /// ```
+    pub fn show(self) -> ProgressHandle {
+        let state = ProgressState::new(&self.message, self.marquee);
+        backend(Backend::Default).show_progress(self, state.clone());
+        ProgressHandle::new(state)
+    }
+}
+
+/// Options read by a [`DialogBackend`](crate::DialogBackend)
+impl ProgressDialog {
+    /// Title set with [`set_title`](Self::set_title)
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    #[cfg(feature = "parent")]
+    /// Parent set with [`set_parent`](Self::set_parent)
+    pub fn parent(&self) -> Option<RawWindowHandle> {
+        self.parent.map(ParentHandle::get)
+    }
+}
+
+/// What a dialog blocks while it is open
+///
+/// Synchronous dialogs always block the calling thread, this only changes which windows
+/// the user can still interact with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Modality {
+    /// Blocks the parent window set with `set_parent`, other windows of the application keep
+    /// working. Without a parent this is the same as `ApplicationModal`.
+    ///
+    /// Shown as a sheet on macOS
+    #[default]
+    WindowModal,
+    /// Blocks every window of the application
+    ApplicationModal,
+    /// Blocks no window
+    ///
+    /// Not supported by macOS alerts and synchronous macOS dialogs, they are application modal
+    None,
+}
+
+/// Where a file dialog opens, see [`FileDialog::set_position`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Position {
+    /// Centered on the parent window set with `set_parent`, or on the screen without one
+    CenterOnParent,
+    /// Centered on the screen the dialog opens on, or the one set with [`FileDialog::set_monitor`]
+    CenterOnScreen,
+    /// Top left corner at these screen coordinates, in pixels from the top left of the primary screen
+    At(i32, i32),
+}
+
+/// Monitor a file dialog opens on, see [`FileDialog::set_monitor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MonitorSelection {
+    /// The primary monitor
+    Primary,
+    /// Monitor at this position in the list of the platform, `0` is not necessarily the primary one.
+    /// Falls back to the monitor the platform would choose when there are fewer.
+    Index(u32),
+    /// The monitor the mouse cursor is on
+    ContainingCursor,
+}
+
+/// Icon of a dialog window, see [`FileDialog::set_icon`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowIcon {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) rgba: Vec<u8>,
+}
+
+impl WindowIcon {
+    /// Icon of `width * height` pixels, `rgba` holds them row by row, 4 bytes each
+    ///
+    /// `None` when the size is zero or `rgba` does not hold that many pixels
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Option<Self> {
+        if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+}
+
+/// Title bar text of a dialog titled `title`, with the app name unless the title already shows it
+#[cfg_attr(
+    not(all(target_os = "linux", any(feature = "gtk3", feature = "software"))),
+    allow(dead_code)
+)]
+pub(crate) fn branded_title(title: &str, app_name: Option<&str>) -> String {
+    match app_name {
+        Some(name) if title.is_empty() => name.to_string(),
+        Some(name) if !name.is_empty() && !title.contains(name) => format!("{} - {}", title, name),
+        _ => title.to_string(),
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MessageLevel {
+    #[default]
     Info,
     Warning,
     Error,
+    /// A question for the user, shown with a question mark icon on Windows and Linux
+    Question,
 }
 
-impl Default for MessageLevel {
-    fn default() -> Self {
-        Self::Info
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MessageButtons {
+    #[default]
     Ok,
     OkCancel,
     YesNo,
+    YesNoCancel,
 }
 
-impl Default for MessageButtons {
-    fn default() -> Self {
-        Self::Ok
+/// File chosen in a save dialog, see [`FileDialog::save_file_result`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveFileResult {
+    /// Path to save to, fixed up like the one of [`FileDialog::save_file`]
+    pub path: PathBuf,
+    /// Index of the chosen filter, like the one of [`FileDialog::save_file_with_filter`]
+    pub filter_index: usize,
+    /// The file already exists and the user agreed to replace it, when the dialog asked or when rfd asked
+    /// after fixing up the extension, see [`set_confirm_overwrite`](FileDialog::set_confirm_overwrite).
+    ///
+    /// Always `false` when the backend can't tell if its dialog asks, see
+    /// [`DialogBackend::confirms_overwrite`](crate::DialogBackend::confirms_overwrite),
+    /// which is the case for the XDG portal, `kdialog` and the mobile and web backends.
+    pub overwrite_confirmed: bool,
+    /// The file does not exist yet, saving creates it.
+    ///
+    /// No dialog reports this, it is checked right as the dialog returns.
+    pub created: bool,
+}
+
+/// Button pressed in a message dialog, see [`MessageDialog::show_result`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDialogResult {
+    Ok,
+    Cancel,
+    Yes,
+    No,
+}
+
+impl MessageDialogResult {
+    /// Result for the index of the pressed one of `buttons`, `None` when the dialog was closed
+    fn new(buttons: MessageButtons, index: Option<usize>) -> Self {
+        match (buttons, index) {
+            (MessageButtons::Ok, _) => Self::Ok,
+            (MessageButtons::OkCancel, Some(0)) => Self::Ok,
+            (MessageButtons::YesNo, Some(0)) | (MessageButtons::YesNoCancel, Some(0)) => Self::Yes,
+            (MessageButtons::YesNo, _) | (MessageButtons::YesNoCancel, Some(1)) => Self::No,
+            (MessageButtons::OkCancel, _) | (MessageButtons::YesNoCancel, _) => Self::Cancel,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn builders_are_send_and_sync() {
+        assert_send_sync::<FileDialog>();
+        assert_send_sync::<AsyncFileDialog>();
+        assert_send_sync::<MessageDialog>();
+        assert_send_sync::<AsyncMessageDialog>();
+        assert_send_sync::<InputDialog>();
+        assert_send_sync::<PasswordDialog>();
+        assert_send_sync::<CredentialDialog>();
+        assert_send_sync::<PickListDialog>();
+        assert_send_sync::<ProgressDialog>();
+    }
+
+    #[test]
+    fn save_as_item_sets_directory_and_name() {
+        let dialog = FileDialog::new().set_save_as_item("/home/user/report.odt");
+        assert_eq!(dialog.directory(), Some(Path::new("/home/user")));
+        assert_eq!(dialog.file_name(), Some("report.odt"));
+        assert_eq!(
+            dialog.save_as_item(),
+            Some(Path::new("/home/user/report.odt"))
+        );
+
+        let dialog = FileDialog::new().set_save_as_item("report.odt");
+        assert_eq!(dialog.directory(), None);
+        assert_eq!(dialog.file_name(), Some("report.odt"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn appends_extension_of_selected_filter() {
+        let dialog = FileDialog::new()
+            .add_filter("Text", &["txt"])
+            .add_filter("PNG", &["png"])
+            .set_append_extension(true);
+        let file_type = dialog.file_type_check();
+
+        assert_eq!(
+            file_type("report".into(), Some(1)),
+            PathBuf::from("report.png")
+        );
+        assert_eq!(
+            file_type("report".into(), None),
+            PathBuf::from("report.txt")
+        );
+        // Unlike strict file types, a typed extension is kept
+        assert_eq!(
+            file_type("report.md".into(), Some(1)),
+            PathBuf::from("report.md")
+        );
+    }
+
+    #[test]
+    fn strict_file_types_know_compound_extensions() {
+        let dialog = FileDialog::new()
+            .add_filter("Tarball", &["tar.gz"])
+            .set_strict_file_types(true);
+        let file_type = dialog.file_type_check();
+
+        assert_eq!(
+            file_type("backup.TAR.GZ".into(), None),
+            PathBuf::from("backup.TAR.GZ")
+        );
+        assert_eq!(
+            file_type("backup.gz".into(), None),
+            PathBuf::from("backup.gz.tar.gz")
+        );
+        assert_eq!(
+            file_type("backup".into(), None),
+            PathBuf::from("backup.tar.gz")
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn url_dialog_allows_non_local_items() {
+        assert_eq!(FileDialog::new().url_dialog().local_only(), Some(false));
+        let dialog = FileDialog::new().set_local_only(true).url_dialog();
+        assert_eq!(dialog.local_only(), Some(true));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn monitor_centers_dialogs_without_position() {
+        assert_eq!(FileDialog::new().placement(), None);
+        assert_eq!(
+            FileDialog::new()
+                .set_monitor(MonitorSelection::Primary)
+                .placement(),
+            Some((Position::CenterOnParent, Some(MonitorSelection::Primary)))
+        );
+        assert_eq!(
+            FileDialog::new()
+                .set_position(Position::At(10, 20))
+                .placement(),
+            Some((Position::At(10, 20), None))
+        );
+    }
+
+    #[test]
+    fn window_icon_needs_four_bytes_per_pixel() {
+        assert!(WindowIcon::from_rgba(vec![0; 16], 2, 2).is_some());
+        assert!(WindowIcon::from_rgba(vec![0; 12], 2, 2).is_none());
+        assert!(WindowIcon::from_rgba(Vec::new(), 0, 0).is_none());
+    }
+
+    #[test]
+    fn app_name_is_added_to_titles_once() {
+        assert_eq!(branded_title("Open File", None), "Open File");
+        assert_eq!(
+            branded_title("Open File", Some("Notes")),
+            "Open File - Notes"
+        );
+        assert_eq!(
+            branded_title("Notes: Export", Some("Notes")),
+            "Notes: Export"
+        );
+        assert_eq!(branded_title("", Some("Notes")), "Notes");
+    }
+
+    #[test]
+    fn save_result_tells_what_was_asked() {
+        let dir = std::env::temp_dir().join(format!("rfd-save-result-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let existing = dir.join("notes.txt");
+        std::fs::write(&existing, "").unwrap();
+        let dialog = FileDialog::new()
+            .add_filter("Text", &["txt"])
+            .set_strict_file_types(true);
+
+        // The mock never asks, neither does rfd about a path it did not change
+        let mock = crate::testing::MockBackend::new();
+        mock.expect_save_file().returns(&existing);
+        let res = dialog.save_file_with(&mock, true).map(Saved::into_result);
+        let res = res.ok().unwrap();
+        assert_eq!(res.path, existing);
+        assert!(!res.overwrite_confirmed && !res.created);
+
+        mock.expect_save_file().returns(dir.join("new.txt"));
+        let res = dialog.save_file_with(&mock, true).map(Saved::into_result);
+        let res = res.ok().unwrap();
+        assert!(!res.overwrite_confirmed && res.created);
+
+        // rfd asks about `notes.txt` after appending the extension
+        mock.expect_save_file().returns(dir.join("notes"));
+        mock.expect_message().returns(true);
+        let res = dialog.save_file_with(&mock, true).map(Saved::into_result);
+        let res = res.ok().unwrap();
+        assert_eq!(res.path, existing);
+        assert!(res.overwrite_confirmed && !res.created);
+        mock.assert_done();
+
+        // A dialog that asks about existing files did so before returning
+        let saved = Saved::new(existing.clone(), existing.clone(), None, Some(true));
+        assert!(saved.overwrite_confirmed);
+        let saved = Saved::new(existing.clone(), existing, None, None);
+        assert!(!saved.overwrite_confirmed);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn replacing_a_file_by_appending_its_extension_is_confirmed() {
+        let dir = std::env::temp_dir().join(format!("rfd-replace-renamed-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+        // The dialog asked about `notes`, not about `notes.txt`
+        let mock = crate::testing::MockBackend::new();
+        mock.expect_save_file().returns(dir.join("notes"));
+        mock.expect_message().returns(false);
+        mock.expect_save_file().returns(dir.join("draft"));
+
+        let dialog = FileDialog::new()
+            .add_filter("Text", &["txt"])
+            .set_strict_file_types(true);
+        let saved = dialog.save_file_with(&mock, false).ok().unwrap();
+        assert_eq!(
+            (saved.path, saved.chosen),
+            (dir.join("draft.txt"), dir.join("draft"))
+        );
+        mock.assert_done();
+
+        let saved = DialogResult::Ok(Saved::new(
+            dir.join("notes.txt"),
+            dir.join("notes"),
+            None,
+            None,
+        ));
+        assert!(dialog.replace_question(&saved).is_some());
+        let dialog = dialog.set_confirm_overwrite(false);
+        assert!(dialog.replace_question(&saved).is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn file_count_outside_the_limits_is_warned_about() {
+        let picked = |count| DialogResult::Ok(vec![PathBuf::new(); count]);
+        let warning = |dialog: &FileDialog, count| {
+            let warning = dialog.file_count_warning(&picked(count));
+            warning.map(|message| message.description)
+        };
+
+        let dialog = FileDialog::new().set_min_files(2).set_max_files(2);
+        let exactly = Some("Select exactly 2 files".to_string());
+        assert_eq!(warning(&dialog, 1), exactly);
+        assert_eq!(warning(&dialog, 3), exactly);
+        assert_eq!(warning(&dialog, 2), None);
+
+        let dialog = FileDialog::new().set_min_files(2).set_max_files(3);
+        let at_least = Some("Select at least 2 files".to_string());
+        assert_eq!(warning(&dialog, 1), at_least);
+        let at_most = Some("Select at most 3 files".to_string());
+        assert_eq!(warning(&dialog, 4), at_most);
+        assert_eq!(warning(&FileDialog::new(), 0), None);
+        assert!(dialog
+            .file_count_warning::<PathBuf>(&DialogResult::Cancelled)
+            .is_none());
+        let dialog = FileDialog::new().set_min_files(3).set_max_files(2);
+        assert_eq!(warning(&dialog, 1), None);
+    }
+
+    #[test]
+    fn message_result_is_the_pressed_button() {
+        let mock = crate::testing::MockBackend::new();
+        let dialog = || MessageDialog::new().set_buttons(MessageButtons::YesNoCancel);
+        mock.expect_message().presses("No");
+        assert_eq!(mock.show_message_index(dialog()), Some(1));
+        mock.expect_message().cancels();
+        assert_eq!(mock.show_message_index(dialog()), None);
+        mock.assert_done();
+
+        let result = |buttons, index| MessageDialogResult::new(buttons, index);
+        assert_eq!(
+            result(MessageButtons::YesNoCancel, Some(1)),
+            MessageDialogResult::No
+        );
+        assert_eq!(
+            result(MessageButtons::YesNoCancel, None),
+            MessageDialogResult::Cancel
+        );
+        assert_eq!(result(MessageButtons::YesNo, None), MessageDialogResult::No);
+        assert_eq!(
+            result(MessageButtons::OkCancel, Some(0)),
+            MessageDialogResult::Ok
+        );
+        assert_eq!(result(MessageButtons::Ok, None), MessageDialogResult::Ok);
+    }
+
+    #[test]
+    fn pick_files_is_shown_again_until_the_count_fits() {
+        let mock = crate::testing::MockBackend::new();
+        mock.expect_pick_files().returns(["a.txt"]);
+        mock.expect_message().presses("OK");
+        mock.expect_pick_files().returns(["a.txt", "b.txt"]);
+
+        let dialog = FileDialog::new().set_min_files(2);
+        let res = dialog.pick_files_with(&mock);
+        assert_eq!(
+            res,
+            DialogResult::Ok(vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")])
+        );
+        mock.assert_done();
+
+        // A script answers the same way every time, showing the dialog again would never end
+        let scripted = crate::testing::MockBackend::new().set_interactive(false);
+        scripted.expect_pick_files().returns(["a.txt"]);
+        let res = dialog.pick_files_with(&scripted);
+        scripted.assert_done();
+        assert_eq!(res, DialogResult::Cancelled);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn caseless_globs_match_either_case() {
+        assert_eq!(caseless_glob("*.jpg"), "*.[jJ][pP][gG]");
+        assert_eq!(caseless_glob("IMG_??.7z"), "[iI][mM][gG]_??.7[zZ]");
+        assert_eq!(caseless_glob("*.[ch]pp"), "*.[ch][pP][pP]");
+        // Letters without a single other case are kept
+        assert_eq!(caseless_glob("straße"), "[sS][tT][rR][aA]ß[eE]");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn file_names_are_globs_of_themselves() {
+        let dialog = FileDialog::new()
+            .add_file_name_filter("Build files", &["Makefile", "Dockerfile"])
+            .add_filter("Rust", &["rs"])
+            .add_all_supported_filter("All supported");
+
+        let globs: Vec<String> = dialog.filters()[0].globs().collect();
+        assert_eq!(globs, ["*.rs", "Makefile", "Dockerfile"]);
+        let globs: Vec<String> = dialog.filters()[1].caseless_globs().collect();
+        assert_eq!(globs[0], "[mM][aA][kK][eE][fF][iI][lL][eE]");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn presets_round_trip_through_serde() {
+        let dialog = FileDialog::new()
+            .set_title("Export")
+            .set_directory("/home/user/exports")
+            .add_filter("Image", &["png", "jpg"]);
+        let json = serde_json::to_string(&dialog).unwrap();
+        let preset: FileDialog = serde_json::from_str(&json).unwrap();
+        assert_eq!(preset.title(), Some("Export"));
+        assert_eq!(preset.directory(), Some(Path::new("/home/user/exports")));
+        assert_eq!(preset.filters()[0].extensions, ["png", "jpg"]);
+
+        // Missing options keep their defaults
+        let preset: FileDialog =
+            serde_json::from_str(r#"{"filters": [{"name": "Text", "extensions": ["txt"]}]}"#)
+                .unwrap();
+        assert_eq!(preset.filters()[0].name, "Text");
+        assert_eq!(preset.title(), None);
+
+        let message: MessageDialog =
+            serde_json::from_str(r#"{"title": "Done", "buttons": "OkCancel"}"#).unwrap();
+        assert_eq!(message.button_labels(), ["OK", "Cancel"]);
     }
 }
@@ -0,0 +1,91 @@
+//! DialogChoices holds the state of the checkboxes, combo boxes and text fields of a file dialog,
+//! and of the suppression checkbox of a message dialog
+//!
+//! Like the [`DialogHandle`](crate::DialogHandle) it is taken from a dialog builder before the dialog is shown,
+//! the backend fills it in when the user accepts the dialog.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct State {
+    checked: HashMap<u32, bool>,
+    selected: HashMap<u32, usize>,
+    text: HashMap<u32, String>,
+    suppressed: Option<bool>,
+}
+
+/// State of the checkboxes added with [`FileDialog::add_checkbox`](crate::FileDialog::add_checkbox)
+/// the combo boxes added with [`FileDialog::add_combo`](crate::FileDialog::add_combo)
+/// and the text fields added with [`FileDialog::add_text_field`](crate::FileDialog::add_text_field),
+/// or of the checkbox added with [`MessageDialog::set_suppression_checkbox`](crate::MessageDialog::set_suppression_checkbox)
+///
+/// ```no_run
+/// let dialog = rfd::FileDialog::new().add_checkbox(1, "Open as read-only", false);
+/// let choices = dialog.choices().clone();
+///
+/// if let Some(path) = dialog.pick_file() {
+///     let read_only = choices.checked(1).unwrap_or(false);
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct DialogChoices(Arc<Mutex<State>>);
+
+impl DialogChoices {
+    /// State of checkbox `id` when the dialog was accepted,
+    /// `None` before that, for unknown ids and on platforms without checkboxes
+    pub fn checked(&self, id: u32) -> Option<bool> {
+        self.0.lock().unwrap().checked.get(&id).copied()
+    }
+
+    /// Index of the option selected in combo box `id` when the dialog was accepted,
+    /// `None` before that, for unknown ids and on platforms without combo boxes
+    pub fn selected(&self, id: u32) -> Option<usize> {
+        self.0.lock().unwrap().selected.get(&id).copied()
+    }
+
+    /// Content of text field `id` when the dialog was accepted,
+    /// `None` before that, for unknown ids and on platforms without text fields
+    pub fn text(&self, id: u32) -> Option<String> {
+        self.0.lock().unwrap().text.get(&id).cloned()
+    }
+
+    /// State of the suppression checkbox of a message dialog when it was closed,
+    /// `None` before that and on platforms without it
+    pub fn suppressed(&self) -> Option<bool> {
+        self.0.lock().unwrap().suppressed
+    }
+
+    /// Used by a [`DialogBackend`](crate::DialogBackend) to record the state of checkbox `id`
+    pub fn set_checked(&self, id: u32, checked: bool) {
+        self.0.lock().unwrap().checked.insert(id, checked);
+    }
+
+    /// Used by a [`DialogBackend`](crate::DialogBackend) to record the option selected in combo box `id`
+    pub fn set_selected(&self, id: u32, index: usize) {
+        self.0.lock().unwrap().selected.insert(id, index);
+    }
+
+    /// Used by a [`DialogBackend`](crate::DialogBackend) to record the content of text field `id`
+    pub fn set_text(&self, id: u32, text: String) {
+        self.0.lock().unwrap().text.insert(id, text);
+    }
+
+    /// Used by a [`DialogBackend`](crate::DialogBackend) to record the state of the suppression checkbox
+    pub fn set_suppressed(&self, suppressed: bool) {
+        self.0.lock().unwrap().suppressed = Some(suppressed);
+    }
+}
+
+impl fmt::Debug for DialogChoices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.0.lock().unwrap();
+        f.debug_struct("DialogChoices")
+            .field("checked", &state.checked)
+            .field("selected", &state.selected)
+            .field("text", &state.text)
+            .field("suppressed", &state.suppressed)
+            .finish()
+    }
+}
@@ -0,0 +1,46 @@
+//! DialogEvents lets an application follow what the user does in a file dialog
+//!
+//! The hooks are called on the thread that runs the dialog, while it is still shown.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Hooks called while a file dialog is shown, see [`FileDialog::set_events`](crate::FileDialog::set_events)
+///
+/// #### Supported Platforms:
+/// - Windows
+/// - Linux (GTK only)
+pub trait DialogEvents: Send + Sync {
+    /// The user navigated to `folder`
+    fn on_folder_change(&self, _folder: &Path) {}
+
+    /// The highlighted items changed, `selection` is empty when nothing is highlighted
+    fn on_selection_change(&self, _selection: &[PathBuf]) {}
+
+    /// The user selected another filter, `filter_index` points into [`FileDialog::filters`](crate::FileDialog::filters)
+    fn on_type_change(&self, _filter_index: usize) {}
+}
+
+/// Events registered on a dialog builder
+#[derive(Clone)]
+pub(crate) struct Events(pub(crate) Arc<dyn DialogEvents>);
+
+impl fmt::Debug for Events {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DialogEvents")
+    }
+}
+
+/// Check run on every chosen path before the dialog accepts, `Err` holds the message shown to the user
+pub type Validate = dyn Fn(&Path) -> Result<(), String> + Send + Sync;
+
+/// Validator registered on a dialog builder
+#[derive(Clone)]
+pub(crate) struct Validator(pub(crate) Arc<Validate>);
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Validator")
+    }
+}
@@ -0,0 +1,50 @@
+//! GTK only additions to the file dialog builders
+
+use crate::{AsyncFileDialog, FileDialog};
+
+use std::ffi::c_void;
+use std::fmt;
+use std::sync::Arc;
+
+/// Called with the `GtkFileChooserDialog` before it is shown
+#[derive(Clone)]
+pub(crate) struct ExtraWidget(pub(crate) Arc<dyn Fn(*mut c_void) + Send + Sync>);
+
+impl fmt::Debug for ExtraWidget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ExtraWidget")
+    }
+}
+
+/// File dialog settings that only exist on the GTK backend
+pub trait FileDialogGtkExt: Sized {
+    /// Call `build` with the `GtkFileChooserDialog` before it is shown,
+    /// eg. to attach widgets with `gtk_file_chooser_set_extra_widget` that the
+    /// [portable controls](FileDialog::add_checkbox) can't express.
+    ///
+    /// `build` runs on the GTK thread. The portable controls are already in the extra widget at that point,
+    /// replacing it removes them. Dialogs shown by other backends ignore `build`.
+    fn set_extra_widget<F>(self, build: F) -> Self
+    where
+        F: Fn(*mut c_void) + Send + Sync + 'static;
+}
+
+impl FileDialogGtkExt for FileDialog {
+    fn set_extra_widget<F>(mut self, build: F) -> Self
+    where
+        F: Fn(*mut c_void) + Send + Sync + 'static,
+    {
+        self.extra_widget = Some(ExtraWidget(Arc::new(build)));
+        self
+    }
+}
+
+impl FileDialogGtkExt for AsyncFileDialog {
+    fn set_extra_widget<F>(mut self, build: F) -> Self
+    where
+        F: Fn(*mut c_void) + Send + Sync + 'static,
+    {
+        self.file_dialog = self.file_dialog.set_extra_widget(build);
+        self
+    }
+}
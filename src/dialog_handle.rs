@@ -0,0 +1,163 @@
+//! DialogHandle lets an application dismiss a dialog it opened
+//!
+//! The handle is taken from a dialog builder before the dialog is shown,
+//! the backend registers how to close the native dialog once it is on screen.
+
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+
+type Closer = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct State {
+    closed: bool,
+    /// A closer was registered, the dialog is on screen
+    shown: bool,
+    /// The backend returned, nothing is left to close
+    finished: bool,
+    closer: Option<Closer>,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    /// Notified when `shown` or `finished` change
+    changed: Condvar,
+}
+
+/// Handle to a dialog, see [`AsyncFileDialog::handle`](crate::AsyncFileDialog::handle)
+#[derive(Clone, Default)]
+pub struct DialogHandle(Arc<Shared>);
+
+impl DialogHandle {
+    /// Dismiss the dialog, it finishes the same way as if the user cancelled it.
+    ///
+    /// A dialog that is not shown yet gets dismissed as soon as it shows up,
+    /// closing a finished dialog does nothing.
+    pub fn close(&self) {
+        let closer = {
+            let mut state = self.0.state.lock().unwrap();
+            state.closed = true;
+            state.closer.take()
+        };
+
+        if let Some(closer) = closer {
+            closer();
+        }
+    }
+
+    /// `true` once [`close`](Self::close) was called
+    pub fn is_closed(&self) -> bool {
+        self.0.state.lock().unwrap().closed
+    }
+
+    /// Used by a [`DialogBackend`](crate::DialogBackend) to register how the shown dialog gets dismissed.
+    ///
+    /// `closer` runs on the thread that calls [`close`](Self::close),
+    /// or right away if the handle is already closed.
+    pub fn on_close<F: FnOnce() + Send + 'static>(&self, closer: F) {
+        let mut state = self.0.state.lock().unwrap();
+        state.shown = true;
+        self.0.changed.notify_all();
+        if state.closed {
+            drop(state);
+            closer();
+        } else {
+            state.closer = Some(Box::new(closer));
+        }
+    }
+
+    /// Called once the backend returned, drops the closer and stops the timeout
+    pub(crate) fn finish(&self) {
+        let mut state = self.0.state.lock().unwrap();
+        state.finished = true;
+        state.closer = None;
+        self.0.changed.notify_all();
+    }
+
+    /// Closes the dialog when it is still shown `timeout` after it showed up
+    ///
+    /// The waiting thread ends as soon as the dialog finishes, or right after closing it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn close_after(&self, timeout: std::time::Duration) -> std::thread::JoinHandle<()> {
+        let handle = self.clone();
+        std::thread::spawn(move || {
+            let shared = &handle.0;
+            let state = shared.state.lock().unwrap();
+            let state = shared
+                .changed
+                .wait_while(state, |state| !state.shown && !state.finished)
+                .unwrap();
+            let (state, wait) = shared
+                .changed
+                .wait_timeout_while(state, timeout, |state| !state.finished)
+                .unwrap();
+            if wait.timed_out() {
+                drop(state);
+                handle.close();
+            }
+        })
+    }
+}
+
+impl fmt::Debug for DialogHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DialogHandle")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DialogHandle;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn closer_runs_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let handle = DialogHandle::default();
+        let c = calls.clone();
+        handle.on_close(move || {
+            c.fetch_add(1, Ordering::Relaxed);
+        });
+        handle.close();
+        handle.close();
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // Registered after close
+        let c = calls.clone();
+        handle.on_close(move || {
+            c.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn timeout_waits_for_the_dialog() {
+        let handle = DialogHandle::default();
+        let timer = handle.close_after(Duration::ZERO);
+        handle.on_close(|| {});
+        timer.join().unwrap();
+        assert!(handle.is_closed());
+
+        // Never shown, the timeout did not start
+        let handle = DialogHandle::default();
+        let timer = handle.close_after(Duration::ZERO);
+        handle.finish();
+        timer.join().unwrap();
+        assert!(!handle.is_closed());
+
+        // Finished before the timeout
+        let handle = DialogHandle::default();
+        let timer = handle.close_after(Duration::from_secs(3600));
+        handle.on_close(|| {});
+        handle.finish();
+        timer.join().unwrap();
+        assert!(!handle.is_closed());
+    }
+}
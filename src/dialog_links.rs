@@ -0,0 +1,105 @@
+//! Links in the description of a message dialog, written as `<a href="...">text</a>`
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Called with the `href` of a clicked link, see [`MessageDialog::set_link_handler`](crate::MessageDialog::set_link_handler)
+pub type OpenLink = dyn Fn(&str) + Send + Sync;
+
+/// Link handler registered on a dialog builder
+#[derive(Clone)]
+pub(crate) struct LinkHandler(pub(crate) Arc<OpenLink>);
+
+impl fmt::Debug for LinkHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LinkHandler")
+    }
+}
+
+/// Piece of a description, `href` is set for the text of a link
+#[derive(Debug, PartialEq)]
+pub(crate) struct TextPart<'a> {
+    pub(crate) text: &'a str,
+    pub(crate) href: Option<&'a str>,
+}
+
+/// Splits `text` at its links, tags that are not closed are left as they are
+pub(crate) fn text_parts(mut text: &str) -> Vec<TextPart<'_>> {
+    const OPEN: &str = "<a href=\"";
+
+    let mut parts = Vec::new();
+    while let Some(start) = text.find(OPEN) {
+        let link = &text[start + OPEN.len()..];
+        let (href, link) = match link.split_once("\">") {
+            Some(split) => split,
+            None => break,
+        };
+        let (label, rest) = match link.split_once("</a>") {
+            Some(split) => split,
+            None => break,
+        };
+
+        if start > 0 {
+            parts.push(TextPart {
+                text: &text[..start],
+                href: None,
+            });
+        }
+        parts.push(TextPart {
+            text: label,
+            href: Some(href),
+        });
+        text = rest;
+    }
+
+    if !text.is_empty() {
+        parts.push(TextPart { text, href: None });
+    }
+    parts
+}
+
+/// Description of `opt` with the link tags removed, for dialogs that can't show links
+#[cfg(any(target_os = "windows", target_arch = "wasm32"))]
+pub(crate) fn plain_description(opt: &crate::MessageDialog) -> String {
+    if opt.hyperlinks {
+        text_parts(&opt.description)
+            .iter()
+            .map(|part| part.text)
+            .collect()
+    } else {
+        opt.description.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_links() {
+        let parts = text_parts(r#"See <a href="https://example.com">the docs</a> or ask."#);
+        assert_eq!(
+            parts,
+            vec![
+                TextPart {
+                    text: "See ",
+                    href: None
+                },
+                TextPart {
+                    text: "the docs",
+                    href: Some("https://example.com")
+                },
+                TextPart {
+                    text: " or ask.",
+                    href: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_unclosed_tags() {
+        let text = r#"a <a href="x">b"#;
+        assert_eq!(text_parts(text), vec![TextPart { text, href: None }]);
+    }
+}
@@ -0,0 +1,86 @@
+//! macOS only additions to the file dialog builders
+
+use crate::{AsyncFileDialog, FileDialog};
+
+use std::ffi::c_void;
+use std::fmt;
+
+use objc::runtime::Object;
+use objc::{msg_send, sel, sel_impl};
+
+/// `NSView` shown below the panel, retained as long as the builder holds it
+pub(crate) struct AccessoryView(*mut Object);
+
+impl AccessoryView {
+    pub(crate) fn as_ptr(&self) -> *mut Object {
+        self.0
+    }
+}
+
+impl Clone for AccessoryView {
+    fn clone(&self) -> Self {
+        let _: *mut Object = unsafe { msg_send![self.0, retain] };
+        Self(self.0)
+    }
+}
+
+impl Drop for AccessoryView {
+    fn drop(&mut self) {
+        let _: () = unsafe { msg_send![self.0, release] };
+    }
+}
+
+// Retain and release are thread safe, the view itself is only touched by the panel on the main thread
+unsafe impl Send for AccessoryView {}
+unsafe impl Sync for AccessoryView {}
+
+impl fmt::Debug for AccessoryView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AccessoryView").field(&self.0).finish()
+    }
+}
+
+/// File dialog settings that only exist on macOS
+pub trait FileDialogMacExt: Sized {
+    /// Show `view` as accessory view of the `NSOpenPanel`/`NSSavePanel`,
+    /// for controls the [portable ones](FileDialog::add_checkbox) can't express.
+    ///
+    /// The view is retained until the builder is dropped. Its state is read by the application itself,
+    /// eg. after the dialog finished.
+    ///
+    /// # Safety
+    ///
+    /// `view` has to point to a valid `NSView`, which is only used on the main thread.
+    unsafe fn set_accessory_view(self, view: *mut c_void) -> Self;
+
+    /// Let the user browse into packages like `.app` and `.bundle`, instead of treating them as files.
+    ///
+    /// Made for developer tools that pick files from within packages.
+    fn set_treat_packages_as_directories(self, treat: bool) -> Self;
+}
+
+impl FileDialogMacExt for FileDialog {
+    unsafe fn set_accessory_view(mut self, view: *mut c_void) -> Self {
+        let view = view as *mut Object;
+        let _: *mut Object = msg_send![view, retain];
+        self.accessory_view = Some(AccessoryView(view));
+        self
+    }
+
+    fn set_treat_packages_as_directories(mut self, treat: bool) -> Self {
+        self.treat_packages_as_directories = Some(treat);
+        self
+    }
+}
+
+impl FileDialogMacExt for AsyncFileDialog {
+    unsafe fn set_accessory_view(mut self, view: *mut c_void) -> Self {
+        self.file_dialog = self.file_dialog.set_accessory_view(view);
+        self
+    }
+
+    fn set_treat_packages_as_directories(mut self, treat: bool) -> Self {
+        self.file_dialog = self.file_dialog.set_treat_packages_as_directories(treat);
+        self
+    }
+}
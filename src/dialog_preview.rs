@@ -0,0 +1,33 @@
+//! Preview pane, filled by a renderer of the application
+
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// What the preview pane shows for the highlighted file, returned by the renderer given to [`FileDialog::set_preview`](crate::FileDialog::set_preview)
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum PreviewContent {
+    /// Nothing to show, the pane is hidden
+    None,
+    Text(String),
+    /// `rgba` holds `width * height` pixels row by row, 4 bytes each
+    Image {
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+    },
+}
+
+/// Renderer called with the highlighted file
+pub type Preview = dyn Fn(&Path) -> PreviewContent + Send + Sync;
+
+/// Renderer registered on a dialog builder
+#[derive(Clone)]
+pub(crate) struct Previewer(pub(crate) Arc<Preview>);
+
+impl fmt::Debug for Previewer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Previewer")
+    }
+}
@@ -0,0 +1,168 @@
+//! Progress of a long operation, reported to a shown [`ProgressDialog`](crate::ProgressDialog)
+//!
+//! The application updates the dialog through a [`ProgressHandle`],
+//! the backend reads the same values from a [`ProgressState`] and marks it cancelled.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+type Updater = Arc<dyn Fn(&ProgressState) + Send + Sync>;
+
+#[derive(Default)]
+struct State {
+    progress: f32,
+    message: String,
+    marquee: bool,
+    cancelled: bool,
+    closed: bool,
+    updater: Option<Updater>,
+}
+
+/// Values of a progress dialog shared with its [`DialogBackend`](crate::DialogBackend)
+#[derive(Clone, Default)]
+pub struct ProgressState(Arc<Mutex<State>>);
+
+impl ProgressState {
+    pub(crate) fn new(message: &str, marquee: bool) -> Self {
+        let state = Self::default();
+        {
+            let mut inner = state.0.lock().unwrap();
+            inner.message = message.into();
+            inner.marquee = marquee;
+        }
+        state
+    }
+
+    /// Fraction of the work that is done, from `0.0` to `1.0`
+    pub fn progress(&self) -> f32 {
+        self.0.lock().unwrap().progress
+    }
+
+    /// Text shown below the title
+    pub fn message(&self) -> String {
+        self.0.lock().unwrap().message.clone()
+    }
+
+    /// `true` when the progress is unknown and the bar only shows activity
+    pub fn marquee(&self) -> bool {
+        self.0.lock().unwrap().marquee
+    }
+
+    /// `true` once the user pressed Cancel
+    pub fn is_cancelled(&self) -> bool {
+        self.0.lock().unwrap().cancelled
+    }
+
+    /// `true` once the application closed the dialog, the backend hides it
+    pub fn is_closed(&self) -> bool {
+        self.0.lock().unwrap().closed
+    }
+
+    /// Called by the backend when the user pressed Cancel
+    pub fn cancel(&self) {
+        self.0.lock().unwrap().cancelled = true;
+    }
+
+    /// Used by a [`DialogBackend`](crate::DialogBackend) to push every change into the shown dialog.
+    ///
+    /// `updater` runs on the thread that made the change, also when the dialog gets closed.
+    /// Backends that poll the state instead don't need one.
+    pub fn on_update<F: Fn(&ProgressState) + Send + Sync + 'static>(&self, updater: F) {
+        self.0.lock().unwrap().updater = Some(Arc::new(updater));
+    }
+
+    fn update<F: FnOnce(&mut State)>(&self, change: F) {
+        let updater = {
+            let mut state = self.0.lock().unwrap();
+            if state.closed {
+                return;
+            }
+            change(&mut state);
+            state.updater.clone()
+        };
+
+        if let Some(updater) = updater {
+            updater(self);
+        }
+    }
+}
+
+impl fmt::Debug for ProgressState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.0.lock().unwrap();
+        f.debug_struct("ProgressState")
+            .field("progress", &state.progress)
+            .field("message", &state.message)
+            .field("marquee", &state.marquee)
+            .field("cancelled", &state.cancelled)
+            .field("closed", &state.closed)
+            .finish()
+    }
+}
+
+/// Updates a shown [`ProgressDialog`](crate::ProgressDialog), dropping it closes the dialog
+#[derive(Debug)]
+pub struct ProgressHandle(ProgressState);
+
+impl ProgressHandle {
+    pub(crate) fn new(state: ProgressState) -> Self {
+        Self(state)
+    }
+
+    /// Set the fraction of the work that is done, from `0.0` to `1.0`
+    pub fn set_progress(&self, progress: f32) {
+        self.0
+            .update(|state| state.progress = progress.clamp(0.0, 1.0));
+    }
+
+    /// Set the text shown below the title, eg. the name of the file that is copied
+    pub fn set_message(&self, text: &str) {
+        self.0.update(|state| state.message = text.into());
+    }
+
+    /// Switch between a bar that only shows activity and one that shows the progress
+    pub fn set_marquee(&self, marquee: bool) {
+        self.0.update(|state| state.marquee = marquee);
+    }
+
+    /// `true` once the user pressed Cancel, the operation should stop then
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_cancelled()
+    }
+
+    /// Close the dialog, the same as dropping the handle
+    pub fn close(self) {}
+}
+
+impl Drop for ProgressHandle {
+    fn drop(&mut self) {
+        self.0.update(|state| state.closed = true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProgressHandle, ProgressState};
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn updates_until_closed() {
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let state = ProgressState::new("Copying", false);
+        let c = calls.clone();
+        state.on_update(move |_| {
+            c.fetch_add(1, Ordering::Relaxed);
+        });
+
+        let handle = ProgressHandle::new(state.clone());
+        handle.set_progress(1.5);
+        assert_eq!(state.progress(), 1.0);
+
+        drop(handle);
+        assert!(state.is_closed());
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,108 @@
+//! DialogResult tells a dialog the user cancelled apart from one that failed
+//!
+//! An application can retry or fall back to another backend on `Failed`,
+//! while `Cancelled` is an explicit answer of the user that should be respected.
+
+use crate::Error;
+
+/// Outcome of a dialog, returned by the `try_*` methods, eg. [`FileDialog::try_pick_file`](crate::FileDialog::try_pick_file)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum DialogResult<T> {
+    /// The user accepted the dialog
+    Ok(T),
+    /// The user cancelled the dialog, or it was dismissed with a [`DialogHandle`](crate::DialogHandle)
+    Cancelled,
+    /// The dialog could not be shown
+    Failed(Error),
+}
+
+impl<T> DialogResult<T> {
+    /// `true` when the user accepted the dialog
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Self::Ok(_))
+    }
+
+    /// `true` when the user cancelled the dialog
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled)
+    }
+
+    /// `true` when the dialog could not be shown
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+
+    /// The accepted value, `None` when cancelled or failed
+    pub fn ok(self) -> Option<T> {
+        match self {
+            Self::Ok(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The error, `None` when accepted or cancelled
+    pub fn err(self) -> Option<Error> {
+        match self {
+            Self::Failed(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Maps the accepted value, leaves the other outcomes as they are
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> DialogResult<U> {
+        match self {
+            Self::Ok(value) => DialogResult::Ok(f(value)),
+            Self::Cancelled => DialogResult::Cancelled,
+            Self::Failed(err) => DialogResult::Failed(err),
+        }
+    }
+
+    /// Same outcome as a `Result`, where `Ok(None)` means cancelled, for use with `?`
+    pub fn into_result(self) -> Result<Option<T>, Error> {
+        self.into()
+    }
+}
+
+impl<T> From<Result<Option<T>, Error>> for DialogResult<T> {
+    fn from(res: Result<Option<T>, Error>) -> Self {
+        match res {
+            Ok(Some(value)) => Self::Ok(value),
+            Ok(None) => Self::Cancelled,
+            Err(err) => Self::Failed(err),
+        }
+    }
+}
+
+impl<T> From<DialogResult<T>> for Result<Option<T>, Error> {
+    fn from(res: DialogResult<T>) -> Self {
+        match res {
+            DialogResult::Ok(value) => Ok(Some(value)),
+            DialogResult::Cancelled => Ok(None),
+            DialogResult::Failed(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DialogResult;
+    use crate::Error;
+
+    #[test]
+    fn converts_from_and_into_result() {
+        assert_eq!(DialogResult::from(Ok(Some(1))), DialogResult::Ok(1));
+        assert_eq!(DialogResult::<i32>::from(Ok(None)), DialogResult::Cancelled);
+        assert_eq!(
+            DialogResult::<i32>::from(Err(Error::Unsupported)),
+            DialogResult::Failed(Error::Unsupported)
+        );
+
+        assert_eq!(
+            DialogResult::Ok(1).map(|v| v + 1).into_result(),
+            Ok(Some(2))
+        );
+        assert_eq!(DialogResult::<i32>::Cancelled.into_result(), Ok(None));
+        assert!(DialogResult::<i32>::Failed(Error::Unsupported).is_failed());
+    }
+}
@@ -0,0 +1,63 @@
+//! Windows only additions to the message dialog builders, enabled with the `task-dialog` feature
+
+use crate::{AsyncMessageDialog, MessageDialog};
+
+/// TaskDialog parts the classic message box has no room for
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TaskDialogOptions {
+    pub(crate) command_links: bool,
+    pub(crate) expanded_text: Option<String>,
+    pub(crate) footer: Option<String>,
+}
+
+/// Message dialog settings that only exist on Windows
+///
+/// They need `TaskDialogIndirect` from comctl32 version 6, which is only loaded when the application
+/// has a manifest that asks for it. Without it the dialog falls back to a message box that drops them.
+pub trait MessageDialogWindowsExt: Sized {
+    /// Show the [custom buttons](MessageDialog::set_custom_buttons) as command links,
+    /// the text after the first `\n` of a label is shown as a note below it
+    fn set_command_links(self, command_links: bool) -> Self;
+
+    /// Details shown below the description when the user expands them
+    fn set_expanded_text(self, text: &str) -> Self;
+
+    /// Text shown at the bottom of the dialog
+    ///
+    /// Like the details it can hold links when [`set_hyperlinks`](MessageDialog::set_hyperlinks) is on.
+    fn set_footer(self, text: &str) -> Self;
+}
+
+impl MessageDialogWindowsExt for MessageDialog {
+    fn set_command_links(mut self, command_links: bool) -> Self {
+        self.task_dialog.command_links = command_links;
+        self
+    }
+
+    fn set_expanded_text(mut self, text: &str) -> Self {
+        self.task_dialog.expanded_text = Some(text.into());
+        self
+    }
+
+    fn set_footer(mut self, text: &str) -> Self {
+        self.task_dialog.footer = Some(text.into());
+        self
+    }
+}
+
+impl MessageDialogWindowsExt for AsyncMessageDialog {
+    fn set_command_links(mut self, command_links: bool) -> Self {
+        self.0 = self.0.set_command_links(command_links);
+        self
+    }
+
+    fn set_expanded_text(mut self, text: &str) -> Self {
+        self.0 = self.0.set_expanded_text(text);
+        self
+    }
+
+    fn set_footer(mut self, text: &str) -> Self {
+        self.0 = self.0.set_footer(text);
+        self
+    }
+}
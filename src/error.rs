@@ -0,0 +1,115 @@
+use std::fmt;
+
+/// Failure of a dialog, a user closing the dialog is not an error
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Windows COM call failed with this `HRESULT`
+    Hresult(i32),
+    /// GTK could not be initialized
+    Gtk(String),
+    /// GLib call failed with this `GError`, `domain` is the name of its quark
+    Glib {
+        domain: String,
+        code: i32,
+        message: String,
+    },
+    /// XDG Desktop Portal request failed
+    Portal(String),
+    /// `zenity`, `kdialog` or `yad` could not be run or failed
+    Process(String),
+    /// The built-in software dialog could not open a window
+    Software(String),
+    /// A MacOS dialog was shown off the main thread while the main thread does not run the
+    /// event loop of the app, AppKit can only be used from the main thread
+    NotMainThread,
+    /// Android JNI call failed, eg. `RfdFileDialogActivity` is missing from the app
+    Jni(String),
+    /// The backend can't show this kind of UI, eg. notifications without a notification service
+    Unsupported,
+    /// The response file of the headless mode (`RFD_RESPONSE_FILE`) could not be read
+    Headless(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Hresult(hr) => write!(f, "COM call failed with HRESULT 0x{:08X}", hr),
+            Error::Gtk(msg) => write!(f, "GTK error: {}", msg),
+            Error::Glib {
+                domain,
+                code,
+                message,
+            } => write!(f, "GLib error {} {}: {}", domain, code, message),
+            Error::Portal(msg) => write!(f, "XDG Desktop Portal error: {}", msg),
+            Error::Process(msg) => write!(f, "dialog process error: {}", msg),
+            Error::Software(msg) => write!(f, "software dialog error: {}", msg),
+            Error::NotMainThread => write!(
+                f,
+                "MacOS dialogs have to be shown on the main thread, or while it runs the app event loop"
+            ),
+            Error::Jni(msg) => write!(f, "JNI error: {}", msg),
+            Error::Unsupported => write!(f, "not supported by this backend"),
+            Error::Headless(msg) => write!(f, "headless response error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Error code of the platform API that failed, see [`Error::native_code`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NativeCode<'a> {
+    /// Windows `HRESULT`
+    Hresult(i32),
+    /// GLib `GError` domain and code
+    Glib { domain: &'a str, code: i32 },
+    /// Error reported by the XDG Desktop Portal, or by the D-Bus connection to it
+    Portal(&'a str),
+}
+
+impl Error {
+    /// Raw error of the platform API behind this error, worth including in issue reports
+    ///
+    /// `None` for errors that did not come from a platform call
+    pub fn native_code(&self) -> Option<NativeCode<'_>> {
+        match self {
+            Error::Hresult(hr) => Some(NativeCode::Hresult(*hr)),
+            Error::Glib { domain, code, .. } => Some(NativeCode::Glib {
+                domain,
+                code: *code,
+            }),
+            Error::Portal(msg) => Some(NativeCode::Portal(msg)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, NativeCode};
+
+    #[test]
+    fn native_code_of_platform_errors() {
+        assert_eq!(
+            Error::Hresult(0x8007_04C7_u32 as i32).native_code(),
+            Some(NativeCode::Hresult(0x8007_04C7_u32 as i32))
+        );
+
+        let err = Error::Glib {
+            domain: "g-io-error-quark".into(),
+            code: 1,
+            message: "No such file or directory".into(),
+        };
+        assert_eq!(
+            err.native_code(),
+            Some(NativeCode::Glib {
+                domain: "g-io-error-quark",
+                code: 1
+            })
+        );
+
+        assert_eq!(Error::Unsupported.native_code(), None);
+    }
+}
@@ -0,0 +1,151 @@
+//! Security-scoped bookmarks of sandboxed macOS apps
+//!
+//! A sandboxed app may only access the files the user picked, and only until it quits.
+//! A bookmark taken right after picking, stored by the app, grants access again after a relaunch.
+
+use super::native::FileHandle;
+
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::ptr;
+
+use objc::runtime::{Object, BOOL, NO, YES};
+use objc::{class, msg_send, sel, sel_impl};
+use objc_foundation::{INSString, NSString};
+
+const NSURL_BOOKMARK_CREATION_WITH_SECURITY_SCOPE: usize = 1 << 11;
+const NSURL_BOOKMARK_RESOLUTION_WITH_SECURITY_SCOPE: usize = 1 << 10;
+
+/// `io::Error` with the description of an `NSError`
+unsafe fn ns_error(error: *mut Object) -> io::Error {
+    if error.is_null() {
+        return io::Error::new(io::ErrorKind::Other, "unknown bookmark error");
+    }
+    let description: *const NSString = msg_send![error, localizedDescription];
+    io::Error::new(io::ErrorKind::Other, (*description).as_str().to_owned())
+}
+
+unsafe fn file_url(path: &std::path::Path) -> io::Result<*mut Object> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path is not UTF-8"))?;
+    let path = NSString::from_str(path);
+    Ok(msg_send![class!(NSURL), fileURLWithPath: &*path])
+}
+
+impl FileHandle {
+    /// Security-scoped bookmark of the file, to store and reopen it with [`from_bookmark`](Self::from_bookmark)
+    /// after the app was relaunched.
+    ///
+    /// Take it right after the dialog returned, sandboxed apps only have access to the file until they quit.
+    /// The app needs the `com.apple.security.files.bookmarks.app-scope` entitlement.
+    pub fn security_scoped_bookmark(&self) -> io::Result<Vec<u8>> {
+        unsafe {
+            let url = file_url(self.path())?;
+            let mut error: *mut Object = ptr::null_mut();
+            let data: *mut Object = msg_send![url,
+                bookmarkDataWithOptions: NSURL_BOOKMARK_CREATION_WITH_SECURITY_SCOPE
+                includingResourceValuesForKeys: ptr::null_mut::<Object>()
+                relativeToURL: ptr::null_mut::<Object>()
+                error: &mut error];
+            if data.is_null() {
+                return Err(ns_error(error));
+            }
+
+            let length: usize = msg_send![data, length];
+            let bytes: *const u8 = msg_send![data, bytes];
+            if length == 0 {
+                return Ok(Vec::new());
+            }
+            Ok(std::slice::from_raw_parts(bytes, length).to_vec())
+        }
+    }
+
+    /// Resolves a bookmark of [`security_scoped_bookmark`](Self::security_scoped_bookmark) and starts
+    /// accessing the file, until the returned [`SecurityScope`] is dropped
+    pub fn from_bookmark(bookmark: &[u8]) -> io::Result<SecurityScope> {
+        unsafe {
+            let data: *mut Object = msg_send![class!(NSData),
+                dataWithBytes: bookmark.as_ptr()
+                length: bookmark.len()];
+
+            let mut stale: BOOL = NO;
+            let mut error: *mut Object = ptr::null_mut();
+            let url: *mut Object = msg_send![class!(NSURL),
+                URLByResolvingBookmarkData: data
+                options: NSURL_BOOKMARK_RESOLUTION_WITH_SECURITY_SCOPE
+                relativeToURL: ptr::null_mut::<Object>()
+                bookmarkDataIsStale: &mut stale
+                error: &mut error];
+            if url.is_null() {
+                return Err(ns_error(error));
+            }
+
+            let started: BOOL = msg_send![url, startAccessingSecurityScopedResource];
+            if started == NO {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "access to the bookmarked file was not granted",
+                ));
+            }
+            let _: *mut Object = msg_send![url, retain];
+
+            let path: *const NSString = msg_send![url, path];
+            let file = FileHandle::wrap(PathBuf::from((*path).as_str()));
+            Ok(SecurityScope {
+                url,
+                file,
+                stale: stale == YES,
+            })
+        }
+    }
+}
+
+/// Access to a file reopened with [`FileHandle::from_bookmark`], it ends when this is dropped
+///
+/// Dereferences to the [`FileHandle`] of the file.
+pub struct SecurityScope {
+    url: *mut Object,
+    file: FileHandle,
+    stale: bool,
+}
+
+// `NSURL` is immutable, starting and stopping access is thread safe
+unsafe impl Send for SecurityScope {}
+unsafe impl Sync for SecurityScope {}
+
+impl SecurityScope {
+    /// `true` when the file moved or the bookmark is old,
+    /// store a new one from [`FileHandle::security_scoped_bookmark`] while the access lasts
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+}
+
+impl Deref for SecurityScope {
+    type Target = FileHandle;
+
+    fn deref(&self) -> &FileHandle {
+        &self.file
+    }
+}
+
+impl Drop for SecurityScope {
+    fn drop(&mut self) {
+        unsafe {
+            let _: () = msg_send![self.url, stopAccessingSecurityScopedResource];
+            let _: () = msg_send![self.url, release];
+        }
+    }
+}
+
+impl fmt::Debug for SecurityScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecurityScope")
+            .field("file", &self.file)
+            .field("stale", &self.stale)
+            .finish()
+    }
+}
@@ -0,0 +1,61 @@
+//! Store of `FileSystemHandle` objects in IndexedDB, they can be kept there across sessions
+
+use super::web::invoke;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+const DATABASE: &str = "rfd";
+const STORE: &str = "file-handles";
+
+/// Stores `handle` under `key`, replacing what was stored there before
+pub(super) async fn put(key: &str, handle: &JsValue) -> Result<(), JsValue> {
+    let store = store("readwrite").await?;
+    let request = invoke(&store, "put", &[handle.clone(), key.into()])?;
+    finished(&request).await.map(|_| ())
+}
+
+/// Handle stored under `key`, `undefined` when there is none
+pub(super) async fn get(key: &str) -> Result<JsValue, JsValue> {
+    let store = store("readonly").await?;
+    let request = invoke(&store, "get", &[key.into()])?;
+    finished(&request).await
+}
+
+/// The object store in a new transaction, the database is created on first use
+async fn store(mode: &str) -> Result<JsValue, JsValue> {
+    let window = web_sys::window().expect("Window not found");
+    let factory = js_sys::Reflect::get(&window, &"indexedDB".into())?;
+    let request = invoke(&factory, "open", &[DATABASE.into(), 1.into()])?;
+
+    let upgraded = request.clone();
+    let create_store = Closure::once_into_js(move || {
+        if let Ok(database) = js_sys::Reflect::get(&upgraded, &"result".into()) {
+            let _ = invoke(&database, "createObjectStore", &[STORE.into()]);
+        }
+    });
+    js_sys::Reflect::set(&request, &"onupgradeneeded".into(), &create_store)?;
+
+    let database = finished(&request).await?;
+    let transaction = invoke(&database, "transaction", &[STORE.into(), mode.into()])?;
+    invoke(&transaction, "objectStore", &[STORE.into()])
+}
+
+/// Result of an `IDBRequest`, once its `success` or `error` event fired
+async fn finished(request: &JsValue) -> Result<JsValue, JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let succeeded = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let result = js_sys::Reflect::get(&succeeded, &"result".into());
+            let _ = resolve.call1(&JsValue::undefined(), &result.unwrap_or(JsValue::UNDEFINED));
+        });
+        let failed = request.clone();
+        let on_error = Closure::once_into_js(move || {
+            let error = js_sys::Reflect::get(&failed, &"error".into());
+            let _ = reject.call1(&JsValue::undefined(), &error.unwrap_or(JsValue::UNDEFINED));
+        });
+        let _ = js_sys::Reflect::set(request, &"onsuccess".into(), &on_success);
+        let _ = js_sys::Reflect::set(request, &"onerror".into(), &on_error);
+    });
+    JsFuture::from(promise).await
+}
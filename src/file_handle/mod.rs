@@ -3,17 +3,46 @@
 //! On native targets it just wraps a path of a file.
 //! In web browsers it wraps `File` js object
 //!
-//! It should allow a user to treat web browser files same way as native files
+//! It should allow a user to treat web browser files same way as native files.
+//! Every [`AsyncFileDialog`](crate::AsyncFileDialog) returns it, on native targets as well,
+//! so code reading the chosen files with [`file_name`](FileHandle::file_name) and
+//! [`read`](FileHandle::read) is written once for both.
+//! On native targets it converts into a `PathBuf` and can be used where a `&Path` is expected.
+//! In web browsers files of the File System Access API can be stored in IndexedDB with
+//! `persist`, and reopened in a later session with `restore`.
+//! Sandboxed MacOS apps do the same with `security_scoped_bookmark` and `from_bookmark`.
+
+use std::time::SystemTime;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod native;
 #[cfg(not(target_arch = "wasm32"))]
-pub use native::FileHandle;
+pub use native::{FileHandle, FileStream};
+
+#[cfg(target_os = "macos")]
+mod bookmark;
+#[cfg(target_os = "macos")]
+pub use bookmark::SecurityScope;
 
+#[cfg(target_arch = "wasm32")]
+mod indexed_db;
 #[cfg(target_arch = "wasm32")]
 mod web;
 #[cfg(target_arch = "wasm32")]
-pub use web::FileHandle;
+pub use web::{FileHandle, FileStream};
+
+/// Size, modification time and type of a file, see [`FileHandle::metadata`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// Size in bytes
+    pub size: u64,
+    /// Last modification, `None` when the platform does not record it
+    pub modified: Option<SystemTime>,
+    /// MIME type, eg. `"image/png"`, `None` when it is not known
+    ///
+    /// Browsers report it for the `File` object, on native targets it is guessed from the extension
+    pub mime_type: Option<String>,
+}
 
 #[cfg(test)]
 mod tests {
@@ -27,5 +56,55 @@ mod tests {
         let _ = FileHandle::inner;
         #[cfg(not(target_arch = "wasm32"))]
         let _ = FileHandle::path;
+        let _ = FileHandle::metadata;
+        let _ = FileHandle::read_stream;
+        let _ = FileHandle::entries;
+        let _ = FileHandle::is_dir;
+        #[cfg(target_os = "macos")]
+        let _ = FileHandle::security_scoped_bookmark;
+        #[cfg(target_os = "macos")]
+        let _ = FileHandle::from_bookmark;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn path_conversions() {
+        use std::path::{Path, PathBuf};
+
+        let file = FileHandle::wrap(PathBuf::from("/tmp/notes.txt"));
+        assert_eq!(file.file_name(), "notes.txt");
+        assert_eq!(AsRef::<Path>::as_ref(&file), Path::new("/tmp/notes.txt"));
+        assert_eq!(PathBuf::from(file), PathBuf::from("/tmp/notes.txt"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn metadata() {
+        let path = std::env::temp_dir().join("rfd_metadata_test.PNG");
+        std::fs::write(&path, [0u8; 42]).unwrap();
+
+        let metadata = FileHandle::wrap(path.clone()).metadata().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(metadata.size, 42);
+        assert!(metadata.modified.is_some());
+        assert_eq!(metadata.mime_type.as_deref(), Some("image/png"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn read_stream() {
+        let path = std::env::temp_dir().join("rfd_read_stream_test.bin");
+        let data: Vec<u8> = (0..200_000).map(|i| i as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let mut stream = FileHandle::wrap(path.clone()).read_stream().unwrap();
+        let mut read = Vec::new();
+        while let Some(chunk) = futures::executor::block_on(stream.next_chunk()) {
+            read.extend(chunk.unwrap());
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read, data);
     }
 }
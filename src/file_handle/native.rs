@@ -1,32 +1,46 @@
+use super::FileMetadata;
+
+use futures_core::Stream;
+
 use std::{
+    fs::File,
     future::Future,
+    io::{self, Read, Write},
     path::{Path, PathBuf},
     pin::Pin,
-    sync::{Arc, Mutex},
+    sync::{
+        mpsc::{self, TryRecvError},
+        Arc, Mutex,
+    },
     task::{Context, Poll, Waker},
 };
 
-#[derive(Default)]
-struct ReaderState {
-    res: Option<std::io::Result<Vec<u8>>>,
+/// Size of the chunks of a [`FileStream`]
+const CHUNK_SIZE: usize = 64 * 1024;
+
+struct BackgroundState<T> {
+    res: Option<T>,
     waker: Option<Waker>,
 }
 
-struct Reader {
-    state: Arc<Mutex<ReaderState>>,
+/// Runs a blocking file operation on a `std::thread`
+struct Background<T> {
+    state: Arc<Mutex<BackgroundState<T>>>,
 }
 
-impl Reader {
-    fn new(path: &Path) -> Self {
-        let state: Arc<Mutex<ReaderState>> = Arc::new(Mutex::new(Default::default()));
+impl<T: Send + 'static> Background<T> {
+    fn new<F: FnOnce() -> T + Send + 'static>(name: &str, run: F) -> Self {
+        let state = Arc::new(Mutex::new(BackgroundState {
+            res: None,
+            waker: None,
+        }));
 
         {
-            let path = path.to_owned();
             let state = state.clone();
             std::thread::Builder::new()
-                .name("rfd_file_read".into())
+                .name(name.into())
                 .spawn(move || {
-                    let res = std::fs::read(path);
+                    let res = run();
 
                     let mut state = state.lock().unwrap();
                     state.res.replace(res);
@@ -42,14 +56,13 @@ impl Reader {
     }
 }
 
-impl Future for Reader {
-    type Output = Vec<u8>;
+impl<T> Future for Background<T> {
+    type Output = T;
 
     fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
         let mut state = self.state.lock().unwrap();
-        if state.res.is_some() {
-            let res = state.res.take().unwrap();
-            Poll::Ready(res.unwrap())
+        if let Some(res) = state.res.take() {
+            Poll::Ready(res)
         } else {
             state.waker.replace(ctx.waker().clone());
             Poll::Pending
@@ -58,6 +71,7 @@ impl Future for Reader {
 }
 
 /// FileHandle is a way of abstracting over a file returned by a dialog
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct FileHandle(PathBuf);
 
 impl FileHandle {
@@ -70,6 +84,11 @@ impl FileHandle {
 
     /// Get name of a file
     pub fn file_name(&self) -> String {
+        #[cfg(target_os = "android")]
+        if let Some(uri) = content_uri(&self.0) {
+            return crate::backend::android::content_file_name(uri);
+        }
+
         self.0
             .file_name()
             .and_then(|f| f.to_str())
@@ -77,6 +96,49 @@ impl FileHandle {
             .unwrap_or_default()
     }
 
+    /// Whether this is a folder, returned by [`pick_folder`](crate::AsyncFileDialog::pick_folder)
+    pub fn is_dir(&self) -> bool {
+        scoped(&self.0, || self.0.is_dir())
+    }
+
+    /// Lists the files and folders in a folder, files have no entries.
+    ///
+    /// On native platforms it spawns a `std::thread` in the background, entries are sorted by name.
+    pub async fn entries(&self) -> io::Result<Vec<FileHandle>> {
+        if !self.is_dir() {
+            return Ok(Vec::new());
+        }
+        let path = self.0.clone();
+        Background::new("rfd_file_entries", move || {
+            let mut entries = scoped(&path, || std::fs::read_dir(&path))?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()?;
+            entries.sort();
+            Ok(entries.into_iter().map(FileHandle).collect())
+        })
+        .await
+    }
+
+    /// Reads the size, modification time and MIME type of a file, without reading its content.
+    ///
+    /// On native platforms it calls `std::fs::metadata` and guesses the MIME type from the extension.
+    pub fn metadata(&self) -> std::io::Result<FileMetadata> {
+        let metadata = match content_file(&self.0, "r") {
+            Some(file) => file?.metadata()?,
+            None => scoped(&self.0, || std::fs::metadata(&self.0))?,
+        };
+        Ok(FileMetadata {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            mime_type: self
+                .0
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(mime_type)
+                .map(|mime| mime.to_string()),
+        })
+    }
+
     /// Gets path to a file.
     ///
     /// Does not exist in `WASM32`
@@ -90,7 +152,32 @@ impl FileHandle {
     ///
     /// `This fn exists souly to keep native api in pair with async only web api.`
     pub async fn read(&self) -> Vec<u8> {
-        Reader::new(&self.0).await
+        let path = self.0.clone();
+        Background::new("rfd_file_read", move || {
+            let mut data = Vec::new();
+            open(&path)?.read_to_end(&mut data).map(|_| data)
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Reads a file in chunks, without holding all of it in memory.
+    ///
+    /// On native platforms a `std::thread` reads ahead in the background.
+    ///
+    /// On `WASM32` the chunks come from the `ReadableStream` of the JS `File` object.
+    pub fn read_stream(&self) -> io::Result<FileStream> {
+        open(&self.0).map(FileStream::new)
+    }
+
+    /// Writes `data` to the file asynchronously, replacing its content.
+    ///
+    /// On native platforms it spawns a `std::thread` in the background.
+    ///
+    /// On `WASM32` it needs the File System Access API, other browsers download the data.
+    pub async fn write(&self, data: &[u8]) -> std::io::Result<()> {
+        let (path, data) = (self.0.clone(), data.to_vec());
+        Background::new("rfd_file_write", move || create(&path)?.write_all(&data)).await
     }
 
     /// Unwraps a `FileHandle` and returns innet type.
@@ -108,6 +195,172 @@ impl FileHandle {
     }
 }
 
+/// `content://` URI of a document of the Android Storage Access Framework
+#[cfg(target_os = "android")]
+fn content_uri(path: &Path) -> Option<&str> {
+    path.to_str().filter(|path| path.starts_with("content://"))
+}
+
+/// Documents of the Storage Access Framework are opened through the `ContentResolver`
+#[cfg(target_os = "android")]
+fn content_file(path: &Path, mode: &str) -> Option<io::Result<File>> {
+    content_uri(path).map(|uri| crate::backend::android::open_content(uri, mode))
+}
+
+#[cfg(not(target_os = "android"))]
+fn content_file(_path: &Path, _mode: &str) -> Option<io::Result<File>> {
+    None
+}
+
+/// Files picked on iOS are only accessible while their security-scoped URL is
+#[cfg(target_os = "ios")]
+fn scoped<T>(path: &Path, run: impl FnOnce() -> T) -> T {
+    crate::backend::ios::with_security_scope(path, run)
+}
+
+#[cfg(not(target_os = "ios"))]
+fn scoped<T>(_path: &Path, run: impl FnOnce() -> T) -> T {
+    run()
+}
+
+fn open(path: &Path) -> io::Result<File> {
+    content_file(path, "r").unwrap_or_else(|| scoped(path, || File::open(path)))
+}
+
+fn create(path: &Path) -> io::Result<File> {
+    // Truncates the document like `File::create`
+    content_file(path, "wt").unwrap_or_else(|| scoped(path, || File::create(path)))
+}
+
+/// MIME type of common file extensions
+fn mime_type(extension: &str) -> Option<&'static str> {
+    let mime = match extension.to_ascii_lowercase().as_str() {
+        "txt" => "text/plain",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "csv" => "text/csv",
+        "md" => "text/markdown",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "wasm" => "application/wasm",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/vnd.microsoft.icon",
+        "tif" | "tiff" => "image/tiff",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "flac" => "audio/flac",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Chunks of a file, returned by [`FileHandle::read_stream`]
+///
+/// It is a [`Stream`](futures_core::Stream), [`next_chunk`](Self::next_chunk) reads it without one.
+pub struct FileStream {
+    chunks: mpsc::Receiver<io::Result<Vec<u8>>>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl FileStream {
+    fn new(mut file: File) -> Self {
+        // Two chunks are read ahead while the last one is processed
+        let (sender, chunks) = mpsc::sync_channel(2);
+        let waker: Arc<Mutex<Option<Waker>>> = Default::default();
+
+        {
+            let waker = waker.clone();
+            std::thread::Builder::new()
+                .name("rfd_file_stream".into())
+                .spawn(move || {
+                    while let Some(chunk) = read_chunk(&mut file) {
+                        let failed = chunk.is_err();
+                        // Fails once the stream is dropped
+                        if sender.send(chunk).is_err() || failed {
+                            break;
+                        }
+                        wake(&waker);
+                    }
+
+                    // Disconnecting the channel ends the stream
+                    drop(sender);
+                    wake(&waker);
+                })
+                .unwrap();
+        }
+
+        Self { chunks, waker }
+    }
+
+    /// Next chunk of the file, `None` after the last one
+    pub async fn next_chunk(&mut self) -> Option<io::Result<Vec<u8>>> {
+        std::future::poll_fn(|ctx| Pin::new(&mut *self).poll_next(ctx)).await
+    }
+
+    fn try_next(&self) -> Poll<Option<io::Result<Vec<u8>>>> {
+        match self.chunks.try_recv() {
+            Ok(chunk) => Poll::Ready(Some(chunk)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        }
+    }
+}
+
+impl Stream for FileStream {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Poll::Ready(chunk) = self.try_next() {
+            return Poll::Ready(chunk);
+        }
+
+        self.waker.lock().unwrap().replace(ctx.waker().clone());
+        // A chunk could have been sent before the waker was stored
+        self.try_next()
+    }
+}
+
+impl std::fmt::Debug for FileStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileStream").finish_non_exhaustive()
+    }
+}
+
+/// Up to [`CHUNK_SIZE`] bytes of `file`, `None` at its end
+fn read_chunk(file: &mut File) -> Option<io::Result<Vec<u8>>> {
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        match file.read(&mut chunk) {
+            Ok(0) => return None,
+            Ok(len) => {
+                chunk.truncate(len);
+                return Some(Ok(chunk));
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Some(Err(err)),
+        }
+    }
+}
+
+fn wake(waker: &Mutex<Option<Waker>>) {
+    if let Some(waker) = waker.lock().unwrap().take() {
+        waker.wake();
+    }
+}
+
 impl std::fmt::Debug for FileHandle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self.path())
@@ -119,3 +372,15 @@ impl From<PathBuf> for FileHandle {
         Self(path)
     }
 }
+
+impl From<FileHandle> for PathBuf {
+    fn from(file_handle: FileHandle) -> Self {
+        file_handle.0
+    }
+}
+
+impl AsRef<Path> for FileHandle {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
@@ -1,15 +1,123 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use futures_core::Stream;
+use wasm_bindgen_futures::JsFuture;
+
+use super::{indexed_db, FileMetadata};
+
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 
-pub struct FileHandle(web_sys::File);
+/// FileHandle is a way of abstracting over a file returned by a dialog
+pub struct FileHandle {
+    file: web_sys::File,
+    origin: Origin,
+}
+
+/// Where a file came from, it decides what [`FileHandle::write`] does
+enum Origin {
+    /// Picked with an `<input>` element, it can't be written
+    Input,
+    /// `FileSystemFileHandle` of the File System Access API
+    FileSystem(JsValue),
+    /// Save dialog of browsers without the File System Access API, writing downloads the data
+    Download,
+    /// `FileSystemDirectoryHandle` of the File System Access API
+    Directory(JsValue),
+    /// Folder picked with a `webkitdirectory` input, the files below it at any depth
+    Folder(Vec<web_sys::File>),
+}
 
 impl FileHandle {
+    /// On native platforms it wraps path.
+    ///
+    /// On `WASM32` it wraps JS `File` object.
     pub fn wrap(file: web_sys::File) -> Self {
-        Self(file)
+        Self {
+            file,
+            origin: Origin::Input,
+        }
+    }
+
+    /// Wraps a `FileSystemFileHandle`, the file is read and written through it
+    pub(crate) async fn from_file_system_handle(handle: JsValue) -> Result<Self, JsValue> {
+        let file = call(&handle, "getFile", &[]).await?.dyn_into()?;
+        Ok(Self {
+            file,
+            origin: Origin::FileSystem(handle),
+        })
     }
 
+    /// Wraps a `FileSystemDirectoryHandle`
+    pub(crate) fn from_directory_handle(handle: JsValue) -> Result<Self, JsValue> {
+        let name = js_sys::Reflect::get(&handle, &"name".into())?
+            .as_string()
+            .unwrap_or_default();
+        Ok(Self {
+            file: empty_file(&name)?,
+            origin: Origin::Directory(handle),
+        })
+    }
+
+    /// Folder of the files of a `webkitdirectory` input, named after the first part of their relative path
+    pub(crate) fn folder(files: Vec<web_sys::File>) -> Result<Self, JsValue> {
+        let name = files
+            .first()
+            .and_then(|file| js_sys::Reflect::get(file, &"webkitRelativePath".into()).ok())
+            .and_then(|path| path.as_string())
+            .and_then(|path| path.split('/').next().map(str::to_string))
+            .unwrap_or_default();
+        Ok(Self {
+            file: empty_file(&name)?,
+            origin: Origin::Folder(files),
+        })
+    }
+
+    /// Empty file named `file_name`, writing it downloads the data under that name
+    pub(crate) fn download(file_name: &str) -> Result<Self, JsValue> {
+        Ok(Self {
+            file: empty_file(file_name)?,
+            origin: Origin::Download,
+        })
+    }
+
+    /// Get name of a file
     pub fn file_name(&self) -> String {
-        self.0.name()
+        self.file.name()
+    }
+
+    /// Reads the size, modification time and MIME type of a file, without reading its content.
+    ///
+    /// On `WASM32` they come from the JS `File` object.
+    pub fn metadata(&self) -> io::Result<FileMetadata> {
+        let modified = Duration::from_millis(self.file.last_modified() as u64);
+        let mime_type = self.file.type_();
+        Ok(FileMetadata {
+            size: self.file.size() as u64,
+            modified: Some(SystemTime::UNIX_EPOCH + modified),
+            mime_type: Some(mime_type).filter(|mime| !mime.is_empty()),
+        })
+    }
+
+    /// Whether this is a folder, returned by [`pick_folder`](crate::AsyncFileDialog::pick_folder)
+    pub fn is_dir(&self) -> bool {
+        matches!(self.origin, Origin::Directory(_) | Origin::Folder(_))
+    }
+
+    /// Lists the files and folders in a folder, files have no entries.
+    ///
+    /// On `WASM32` browsers without `showDirectoryPicker` list every file below the folder,
+    /// its subfolders are not listed on their own.
+    pub async fn entries(&self) -> io::Result<Vec<FileHandle>> {
+        match &self.origin {
+            Origin::Directory(handle) => directory_entries(handle).await.map_err(js_error),
+            Origin::Folder(files) => Ok(files.iter().cloned().map(FileHandle::wrap).collect()),
+            _ => Ok(Vec::new()),
+        }
     }
 
     // Path is not supported in browsers.
@@ -19,7 +127,16 @@ impl FileHandle {
     //     unimplemented!("Path is not supported in browsers");
     // }
 
+    /// Reads a file asynchronously, with a JS `FileReader`.
     pub async fn read(&self) -> Vec<u8> {
+        // The `File` of a handle is a snapshot, it misses what was written since
+        let file = match &self.origin {
+            Origin::FileSystem(handle) => {
+                call(handle, "getFile", &[]).await.unwrap().unchecked_into()
+            }
+            _ => self.file.clone(),
+        };
+
         let promise = js_sys::Promise::new(&mut move |res, _rej| {
             let file_reader = web_sys::FileReader::new().unwrap();
 
@@ -33,7 +150,7 @@ impl FileHandle {
 
             closure.forget();
 
-            file_reader.read_as_array_buffer(&self.0).unwrap();
+            file_reader.read_as_array_buffer(&file).unwrap();
         });
 
         let future = wasm_bindgen_futures::JsFuture::from(promise);
@@ -47,12 +164,286 @@ impl FileHandle {
         vec
     }
 
+    /// Reads a file in chunks, without holding all of it in memory.
+    ///
+    /// On `WASM32` the chunks come from the `ReadableStream` of the JS `File` object.
+    pub fn read_stream(&self) -> io::Result<FileStream> {
+        let state = match &self.origin {
+            // The `File` of a handle is a snapshot, it misses what was written since
+            Origin::FileSystem(handle) => invoke(handle, "getFile", &[])
+                .and_then(|promise| promise.dyn_into::<js_sys::Promise>())
+                .map(|promise| State::Opening(JsFuture::from(promise))),
+            _ => reader(&self.file).map(|reader| State::Reading {
+                reader,
+                chunk: None,
+            }),
+        };
+        state.map(|state| FileStream { state }).map_err(js_error)
+    }
+
+    /// Writes `data` to the file asynchronously, replacing its content.
+    ///
+    /// On `WASM32` it goes through a `FileSystemWritableFileStream`. Browsers without the
+    /// File System Access API download the data instead, under the name given to the save dialog.
+    /// Files picked with the `<input>` element of those browsers can't be written and return an error.
+    pub async fn write(&self, data: &[u8]) -> io::Result<()> {
+        let handle = match &self.origin {
+            Origin::FileSystem(handle) => handle,
+            Origin::Download => return download(&self.file.name(), data).map_err(js_error),
+            Origin::Input => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "file was not picked with the File System Access API",
+                ))
+            }
+        };
+
+        let write = async {
+            let stream = call(handle, "createWritable", &[]).await?;
+            let data = js_sys::Uint8Array::from(data);
+            call(&stream, "write", &[data.into()]).await?;
+            call(&stream, "close", &[]).await
+        };
+        write.await.map(|_| ()).map_err(js_error)
+    }
+
+    /// Stores the file under `key` in IndexedDB, so [`restore`](Self::restore) can reopen it
+    /// in a later session.
+    ///
+    /// Only files and folders of the File System Access API can be stored,
+    /// others return an error.
+    pub async fn persist(&self, key: &str) -> io::Result<()> {
+        let handle = match &self.origin {
+            Origin::FileSystem(handle) | Origin::Directory(handle) => handle,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "file was not picked with the File System Access API",
+                ))
+            }
+        };
+        indexed_db::put(key, handle).await.map_err(js_error)
+    }
+
+    /// Reopens a file stored with [`persist`](Self::persist), `None` when nothing was stored under `key`.
+    ///
+    /// Permissions don't outlive a session, the browser asks the user again. That needs
+    /// a user gesture, so it should be called from eg. a click handler.
+    pub async fn restore(key: &str) -> io::Result<Option<FileHandle>> {
+        let handle = indexed_db::get(key).await.map_err(js_error)?;
+        if handle.is_undefined() {
+            return Ok(None);
+        }
+
+        let directory = js_sys::Reflect::get(&handle, &"kind".into())
+            .map(|kind| kind.as_string().as_deref() == Some("directory"))
+            .map_err(js_error)?;
+        // Files can come from a save dialog, folders are only read
+        let options = js_sys::Object::new();
+        let mode = if directory { "read" } else { "readwrite" };
+        js_sys::Reflect::set(&options, &"mode".into(), &mode.into()).map_err(js_error)?;
+
+        let mut permission = call(&handle, "queryPermission", &[options.clone().into()])
+            .await
+            .map_err(js_error)?;
+        if permission.as_string().as_deref() != Some("granted") {
+            permission = call(&handle, "requestPermission", &[options.into()])
+                .await
+                .map_err(js_error)?;
+        }
+        if permission.as_string().as_deref() != Some("granted") {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "access to the file was not granted",
+            ));
+        }
+
+        let file = if directory {
+            FileHandle::from_directory_handle(handle)
+        } else {
+            FileHandle::from_file_system_handle(handle).await
+        };
+        file.map(Some).map_err(js_error)
+    }
+
+    /// Unwraps a `FileHandle` and returns the JS `File` object.
+    ///
+    /// #### Behind a `file-handle-inner` feature flag
     #[cfg(feature = "file-handle-inner")]
     pub fn inner(&self) -> &web_sys::File {
-        &self.0
+        &self.file
     }
 }
 
+/// Chunks of a file, returned by [`FileHandle::read_stream`]
+///
+/// It is a [`Stream`](futures_core::Stream), [`next_chunk`](Self::next_chunk) reads it without one.
+pub struct FileStream {
+    state: State,
+}
+
+enum State {
+    /// Waiting for the current `File` of a `FileSystemFileHandle`
+    Opening(JsFuture),
+    /// Reading with a `ReadableStreamDefaultReader`, `chunk` is its pending `read()`
+    Reading {
+        reader: JsValue,
+        chunk: Option<JsFuture>,
+    },
+    Done,
+}
+
+impl FileStream {
+    /// Next chunk of the file, `None` after the last one
+    pub async fn next_chunk(&mut self) -> Option<io::Result<Vec<u8>>> {
+        std::future::poll_fn(|ctx| Pin::new(&mut *self).poll_next(ctx)).await
+    }
+}
+
+impl Stream for FileStream {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            // Every path that does not end the stream puts a state back
+            match std::mem::replace(&mut self.state, State::Done) {
+                State::Done => return Poll::Ready(None),
+                State::Opening(mut file) => match Pin::new(&mut file).poll(ctx) {
+                    Poll::Pending => {
+                        self.state = State::Opening(file);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready(file) => match file.and_then(|file| reader(&file.unchecked_into()))
+                    {
+                        Ok(reader) => {
+                            self.state = State::Reading {
+                                reader,
+                                chunk: None,
+                            }
+                        }
+                        Err(err) => return Poll::Ready(Some(Err(js_error(err)))),
+                    },
+                },
+                State::Reading { reader, chunk } => {
+                    let chunk = match chunk {
+                        Some(chunk) => Ok(chunk),
+                        None => invoke(&reader, "read", &[])
+                            .and_then(|promise| promise.dyn_into::<js_sys::Promise>())
+                            .map(JsFuture::from),
+                    };
+                    let mut chunk = match chunk {
+                        Ok(chunk) => chunk,
+                        Err(err) => return Poll::Ready(Some(Err(js_error(err)))),
+                    };
+
+                    match Pin::new(&mut chunk).poll(ctx) {
+                        Poll::Pending => {
+                            self.state = State::Reading {
+                                reader,
+                                chunk: Some(chunk),
+                            };
+                            return Poll::Pending;
+                        }
+                        // `{ done, value }`, `value` is a `Uint8Array` until it is done
+                        Poll::Ready(Ok(result)) => {
+                            let done = js_sys::Reflect::get(&result, &"done".into())
+                                .map(|done| done.is_truthy())
+                                .unwrap_or(true);
+                            if done {
+                                return Poll::Ready(None);
+                            }
+                            let value = js_sys::Reflect::get(&result, &"value".into())
+                                .map(|value| value.unchecked_into::<js_sys::Uint8Array>().to_vec());
+                            self.state = State::Reading {
+                                reader,
+                                chunk: None,
+                            };
+                            return Poll::Ready(Some(value.map_err(js_error)));
+                        }
+                        Poll::Ready(Err(err)) => return Poll::Ready(Some(Err(js_error(err)))),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for FileStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileStream").finish_non_exhaustive()
+    }
+}
+
+/// Files and folders of a `FileSystemDirectoryHandle`, from its `values()` async iterator
+async fn directory_entries(handle: &JsValue) -> Result<Vec<FileHandle>, JsValue> {
+    let values = invoke(handle, "values", &[])?;
+    let mut entries = Vec::new();
+    loop {
+        // `{ done, value }`, `value` is a `FileSystemHandle` until it is done
+        let next = call(&values, "next", &[]).await?;
+        if js_sys::Reflect::get(&next, &"done".into())?.is_truthy() {
+            break;
+        }
+        let entry = js_sys::Reflect::get(&next, &"value".into())?;
+        let kind = js_sys::Reflect::get(&entry, &"kind".into())?.as_string();
+        if kind.as_deref() == Some("directory") {
+            entries.push(FileHandle::from_directory_handle(entry)?);
+        } else {
+            entries.push(FileHandle::from_file_system_handle(entry).await?);
+        }
+    }
+    Ok(entries)
+}
+
+fn empty_file(name: &str) -> Result<web_sys::File, JsValue> {
+    web_sys::File::new_with_str_sequence(&js_sys::Array::new(), name)
+}
+
+/// Starts a download of `data` with a temporary `<a download>` link
+fn download(file_name: &str, data: &[u8]) -> Result<(), JsValue> {
+    let window = web_sys::window().expect("Window not found");
+    let document = window.document().expect("Document not found");
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(data));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let anchor: web_sys::HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    // Revoking right away could cancel the download before it started
+    let revoke = Closure::once_into_js(move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    });
+    window.set_timeout_with_callback_and_timeout_and_arguments_0(revoke.unchecked_ref(), 60_000)?;
+    Ok(())
+}
+
+/// `ReadableStreamDefaultReader` of the content of `file`
+fn reader(file: &web_sys::File) -> Result<JsValue, JsValue> {
+    let stream = invoke(file, "stream", &[])?;
+    invoke(&stream, "getReader", &[])
+}
+
+/// Calls a method of `target`
+pub(super) fn invoke(target: &JsValue, method: &str, args: &[JsValue]) -> Result<JsValue, JsValue> {
+    let function: js_sys::Function = js_sys::Reflect::get(target, &method.into())?.dyn_into()?;
+    let args: js_sys::Array = args.iter().collect();
+    function.apply(target, &args)
+}
+
+/// Calls a method of `target` that returns a promise and awaits it
+async fn call(target: &JsValue, method: &str, args: &[JsValue]) -> Result<JsValue, JsValue> {
+    let promise: js_sys::Promise = invoke(target, method, args)?.dyn_into()?;
+    JsFuture::from(promise).await
+}
+
+fn js_error(err: JsValue) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("{:?}", err))
+}
+
 impl std::fmt::Debug for FileHandle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.file_name())
@@ -1,13 +1,108 @@
+#[macro_use]
+mod trace;
+
 mod backend;
+pub use backend::{init, set_backend, Backend, DialogBackend, DialogFutureType};
 
-mod file_handle;
-pub use file_handle::FileHandle;
+mod error;
+pub use error::{Error, NativeCode};
 
-mod dialog;
+mod dialog_handle;
+pub use dialog_handle::DialogHandle;
+
+mod dialog_result;
+pub use dialog_result::DialogResult;
+
+mod dialog_choices;
+pub use dialog_choices::DialogChoices;
+
+mod dialog_events;
+pub use dialog_events::{DialogEvents, Validate};
+
+#[cfg_attr(all(target_os = "linux", not(feature = "gtk3")), allow(dead_code))]
+mod dialog_links;
+pub use dialog_links::OpenLink;
+
+mod dialog_preview;
+pub use dialog_preview::{Preview, PreviewContent};
+
+mod dialog_progress;
+pub use dialog_progress::{ProgressHandle, ProgressState};
+
+mod notification;
+pub use notification::Notification;
+
+mod password;
+pub use password::{Credentials, Password};
+
+mod validation;
+pub use validation::ValidationIssue;
+
+mod appearance;
+pub use appearance::{color_scheme, set_color_scheme, ColorScheme};
+
+mod localizer;
+pub use localizer::{
+    set_localizer, set_text_direction, text_direction, BuiltinString, Localizer, TextDirection,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod persistence;
 
 #[cfg(not(target_arch = "wasm32"))]
-pub use dialog::FileDialog;
+pub mod ui_thread;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod pending_dialog;
+#[cfg(not(target_arch = "wasm32"))]
+pub use pending_dialog::PendingDialog;
+
+#[cfg(all(target_os = "linux", feature = "gtk3"))]
+mod dialog_gtk;
+#[cfg(all(target_os = "linux", feature = "gtk3"))]
+pub use dialog_gtk::FileDialogGtkExt;
+
+#[cfg(all(target_os = "windows", feature = "task-dialog"))]
+mod dialog_windows;
+#[cfg(all(target_os = "windows", feature = "task-dialog"))]
+pub use dialog_windows::MessageDialogWindowsExt;
+
+#[cfg(target_os = "windows")]
+pub mod windows;
+
+#[cfg(target_os = "macos")]
+mod dialog_macos;
+#[cfg(target_os = "macos")]
+pub use dialog_macos::FileDialogMacExt;
+
+#[cfg(feature = "parent")]
+mod parent;
+#[cfg(feature = "parent")]
+pub(crate) use parent::ParentHandle;
+#[cfg(feature = "parent")]
+pub use parent::ParentWindow;
+
+mod file_handle;
+#[cfg(target_os = "macos")]
+pub use file_handle::SecurityScope;
+pub use file_handle::{FileHandle, FileMetadata, FileStream};
+
+/// URL type returned by [`FileDialog::pick_file_url`], re-exported from the `url` crate
+#[cfg(not(target_arch = "wasm32"))]
+pub use url::Url;
+
+mod dialog;
 
-pub use dialog::AsyncFileDialog;
+pub use dialog::{
+    AsyncFileDialog, Checkbox, Combo, FileDialog, Filter, Place, SaveFileResult, TextField,
+};
 
-pub use dialog::{AsyncMessageDialog, MessageButtons, MessageDialog, MessageLevel};
+pub use dialog::{
+    AsyncCredentialDialog, AsyncInputDialog, AsyncMessageDialog, AsyncPasswordDialog,
+    AsyncPickListDialog, CredentialDialog, InputDialog, MessageButtons, MessageDialog,
+    MessageDialogResult, MessageLevel, Modality, MonitorSelection, PasswordDialog, PickListDialog,
+    Position, ProgressDialog, WindowIcon,
+};
@@ -0,0 +1,208 @@
+//! Translations of the strings rfd shows on its own, see [`set_localizer`], and the direction
+//! dialogs are laid out in, see [`set_text_direction`]
+
+use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    Arc, RwLock,
+};
+
+/// String rfd shows when the app did not set one
+///
+/// In strings with a `{}` it is replaced by a file name, a filter name or a count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BuiltinString {
+    /// Title of dialogs picking files
+    OpenFile,
+    /// Title of the software dialog picking several files
+    OpenFiles,
+    /// Title of save dialogs
+    SaveFile,
+    /// Title of dialogs picking a folder
+    SelectFolder,
+    /// Title of dialogs picking several folders
+    SelectFolders,
+    Ok,
+    Cancel,
+    Yes,
+    No,
+    /// Accept button of dialogs picking files
+    Open,
+    /// Accept button of save dialogs
+    Save,
+    /// Accept button of dialogs picking folders
+    Select,
+    /// Label of the user name field of credential dialogs
+    UserName,
+    /// Label of the password field of credential dialogs
+    Password,
+    /// Checkbox revealing the text of password fields
+    ShowPassword,
+    /// Name of the new file in the software save dialog, `{}` is the typed name
+    NameField,
+    /// `{}` is the number of files marked in the software dialog
+    SelectedCount,
+    /// Hint of the software dialog on how to pick several files
+    MultiSelectHint,
+    /// Filter of the software dialog, `{}` is its name
+    FilterField,
+    /// Question of the software dialog before it overwrites the file `{}`
+    ReplaceQuestion,
+    /// Warning when fewer files were picked than the `{}` asked for with `set_min_files`
+    MinFileCount,
+    /// Warning when more files were picked than the `{}` allowed with `set_max_files`
+    MaxFileCount,
+    /// Warning when `set_min_files` and `set_max_files` ask for exactly `{}` files
+    ExactFileCount,
+    /// Question before a save dialog returns the existing file `{}`, named with an appended extension
+    ReplaceRenamed,
+    /// Error of save dialogs with `set_strict_file_types` when the name `{}` has none of the extensions
+    UnknownFileType,
+}
+
+impl BuiltinString {
+    /// English string, shown when no localizer translates it
+    pub fn english(self) -> &'static str {
+        match self {
+            BuiltinString::OpenFile => "Open File",
+            BuiltinString::OpenFiles => "Open Files",
+            BuiltinString::SaveFile => "Save File",
+            BuiltinString::SelectFolder => "Select Folder",
+            BuiltinString::SelectFolders => "Select Folders",
+            BuiltinString::Ok => "OK",
+            BuiltinString::Cancel => "Cancel",
+            BuiltinString::Yes => "Yes",
+            BuiltinString::No => "No",
+            BuiltinString::Open => "Open",
+            BuiltinString::Save => "Save",
+            BuiltinString::Select => "Select",
+            BuiltinString::UserName => "User name",
+            BuiltinString::Password => "Password",
+            BuiltinString::ShowPassword => "Show password",
+            BuiltinString::NameField => "Name: {}",
+            BuiltinString::SelectedCount => "{} selected",
+            BuiltinString::MultiSelectHint => "Space selects several files",
+            BuiltinString::FilterField => "Filter: {} (Tab)",
+            BuiltinString::ReplaceQuestion => "Replace \"{}\"? Enter: Yes, Esc: No",
+            BuiltinString::MinFileCount => "Select at least {} files",
+            BuiltinString::MaxFileCount => "Select at most {} files",
+            BuiltinString::ExactFileCount => "Select exactly {} files",
+            BuiltinString::ReplaceRenamed => "\"{}\" already exists. Do you want to replace it?",
+            BuiltinString::UnknownFileType => "\"{}\" has none of the extensions of the file types",
+        }
+    }
+}
+
+/// Translates the strings rfd shows on its own, see [`set_localizer`]
+///
+/// Closures taking a [`BuiltinString`] are localizers as well.
+pub trait Localizer: Send + Sync {
+    /// Translation of `string`, `None` keeps the English one
+    fn localize(&self, string: BuiltinString) -> Option<String>;
+}
+
+impl<F> Localizer for F
+where
+    F: Fn(BuiltinString) -> Option<String> + Send + Sync,
+{
+    fn localize(&self, string: BuiltinString) -> Option<String> {
+        self(string)
+    }
+}
+
+static LOCALIZER: RwLock<Option<Arc<dyn Localizer>>> = RwLock::new(None);
+
+/// Translate the titles, button and field labels rfd puts on dialogs, and every string
+/// of the software backend, with the translation catalog of the app instead of the OS language
+///
+/// Labels set on a dialog builder are shown as they are. Strings the platform provides itself,
+/// like the standard buttons of GTK and Windows message boxes, follow the language of the platform.
+pub fn set_localizer(localizer: impl Localizer + 'static) {
+    *LOCALIZER.write().unwrap() = Some(Arc::new(localizer));
+}
+
+/// Direction dialogs lay out their text and controls in, see [`set_text_direction`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextDirection {
+    /// Direction of the OS or toolkit language
+    #[default]
+    Platform,
+    /// Left to right
+    Ltr,
+    /// Right to left, with the layout mirrored
+    Rtl,
+}
+
+static TEXT_DIRECTION: AtomicU8 = AtomicU8::new(0);
+
+/// Lay dialogs out right to left for Arabic or Hebrew translations, whatever the OS language is
+///
+/// Applies to widgets GTK creates after the call, to the message, input and pick list dialogs and
+/// the file dialogs of Windows, and to the software backend. The native macOS panels and the
+/// portal follow the language of the platform.
+pub fn set_text_direction(direction: TextDirection) {
+    let value = match direction {
+        TextDirection::Platform => 0,
+        TextDirection::Ltr => 1,
+        TextDirection::Rtl => 2,
+    };
+    TEXT_DIRECTION.store(value, Ordering::Relaxed);
+}
+
+/// Direction set by [`set_text_direction`]
+pub fn text_direction() -> TextDirection {
+    match TEXT_DIRECTION.load(Ordering::Relaxed) {
+        1 => TextDirection::Ltr,
+        2 => TextDirection::Rtl,
+        _ => TextDirection::Platform,
+    }
+}
+
+/// `string` translated by the localizer, English without one
+pub(crate) fn localize(string: BuiltinString) -> String {
+    let localizer = LOCALIZER.read().unwrap().clone();
+    localizer
+        .and_then(|localizer| localizer.localize(string))
+        .unwrap_or_else(|| string.english().to_string())
+}
+
+/// [`localize`] with the `{}` replaced by `value`
+pub(crate) fn localize_with(string: BuiltinString, value: &str) -> String {
+    localize(string).replacen("{}", value, 1)
+}
+
+/// `value` set by the app, or the translated `default`
+pub(crate) fn or_localized(value: Option<&str>, default: BuiltinString) -> String {
+    value.map_or_else(|| localize(default), str::to_string)
+}
+
+/// `label` with its first character as the access key, `marker` is `_` for GTK and `&` for Windows
+#[cfg_attr(
+    not(any(target_os = "windows", all(target_os = "linux", feature = "gtk3"))),
+    allow(dead_code)
+)]
+pub(crate) fn mnemonic(label: &str, marker: char) -> String {
+    let escaped = label.replace(marker, &format!("{}{}", marker, marker));
+    format!("{}{}", marker, escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_replaced() {
+        assert_eq!(
+            localize_with(BuiltinString::SelectedCount, "3"),
+            "3 selected"
+        );
+        assert_eq!(or_localized(Some("Import"), BuiltinString::Open), "Import");
+        assert_eq!(or_localized(None, BuiltinString::Open), "Open");
+    }
+
+    #[test]
+    fn mnemonic_markers_in_labels_are_escaped() {
+        assert_eq!(mnemonic("Show password", '&'), "&Show password");
+        assert_eq!(mnemonic("Save & quit", '&'), "&Save && quit");
+    }
+}
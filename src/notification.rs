@@ -0,0 +1,63 @@
+//! Desktop notifications, shown by the system outside of the application windows
+
+use crate::backend::{backend, Backend};
+use crate::Error;
+
+/// ## Notification
+///
+/// A short message shown by the desktop, eg. once a long export finished.
+/// Notifications don't block and don't report whether the user saw them.
+#[derive(Default, Debug, Clone)]
+pub struct Notification {
+    pub(crate) title: String,
+    pub(crate) body: String,
+}
+
+impl Notification {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set title of a notification
+    pub fn set_title(mut self, text: &str) -> Self {
+        self.title = text.into();
+        self
+    }
+
+    /// Set the text shown below the title
+    pub fn set_body(mut self, text: &str) -> Self {
+        self.body = text.into();
+        self
+    }
+
+    /// Shows the notification and returns right away
+    ///
+    /// #### Supported Platforms:
+    /// - Linux (needs the `portal` feature)
+    /// - Windows (a balloon of an icon in the notification area, Windows 10 shows it as a toast)
+    /// - Mac (needs a bundle identifier, apps run outside of a bundle get [`Error::Unsupported`])
+    ///
+    /// ```no_run
+    /// rfd::Notification::new()
+    ///     .set_title("Export finished")
+    ///     .set_body("42 files were written")
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    pub fn show(self) -> Result<(), Error> {
+        backend(Backend::Default).show_notification(self)
+    }
+}
+
+/// Options read by a [`DialogBackend`](crate::DialogBackend)
+impl Notification {
+    /// Title set with [`set_title`](Self::set_title)
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Text set with [`set_body`](Self::set_body)
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+}
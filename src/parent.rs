@@ -0,0 +1,272 @@
+//! Parent windows passed to `set_parent`
+//!
+//! Backends work with `raw-window-handle` 0.3 handles, windows implementing the traits of
+//! 0.5 (`rwh-05` feature) or 0.6 (`rwh-06` feature) are converted to them here.
+//! When both features are enabled 0.6 wins.
+
+use raw_window_handle::RawWindowHandle;
+
+#[cfg(all(feature = "rwh-05", not(feature = "rwh-06")))]
+use raw_window_handle_05 as rwh;
+#[cfg(feature = "rwh-06")]
+use raw_window_handle_06 as rwh;
+
+/// Window that can parent a dialog, see [`FileDialog::set_parent`](crate::FileDialog::set_parent)
+///
+/// Implemented for everything that implements
+/// - `HasWindowHandle + HasDisplayHandle` of `raw-window-handle` 0.6 with the `rwh-06` feature, eg. `winit` 0.29 and 0.30 windows
+/// - `HasRawWindowHandle + HasRawDisplayHandle` of `raw-window-handle` 0.5 with the `rwh-05` feature, eg. `winit` 0.28 windows
+/// - `HasRawWindowHandle` of `raw-window-handle` 0.3 otherwise
+pub trait ParentWindow {
+    /// Handle of the window, `None` when it has none right now
+    #[doc(hidden)]
+    fn parent_handle(&self) -> Option<RawWindowHandle>;
+}
+
+/// Parent kept by a dialog builder until the dialog is shown
+///
+/// rfd never dereferences the pointers of the handle, only the native dialog does once it
+/// is shown, so builders holding one can be shared and sent between threads.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ParentHandle(RawWindowHandle);
+
+unsafe impl Send for ParentHandle {}
+unsafe impl Sync for ParentHandle {}
+
+impl ParentHandle {
+    pub(crate) fn of<W: ParentWindow + ?Sized>(window: &W) -> Option<Self> {
+        window.parent_handle().map(Self)
+    }
+
+    pub(crate) fn get(self) -> RawWindowHandle {
+        self.0
+    }
+}
+
+#[cfg(not(any(feature = "rwh-05", feature = "rwh-06")))]
+impl<W: raw_window_handle::HasRawWindowHandle + ?Sized> ParentWindow for W {
+    fn parent_handle(&self) -> Option<RawWindowHandle> {
+        Some(self.raw_window_handle())
+    }
+}
+
+#[cfg(feature = "rwh-06")]
+impl<W: rwh::HasWindowHandle + rwh::HasDisplayHandle + ?Sized> ParentWindow for W {
+    fn parent_handle(&self) -> Option<RawWindowHandle> {
+        let window = self.window_handle().ok()?.as_raw();
+        let display = self.display_handle().ok().map(|display| display.as_raw());
+        convert(window, display)
+    }
+}
+
+#[cfg(all(feature = "rwh-05", not(feature = "rwh-06")))]
+impl<W: rwh::HasRawWindowHandle + rwh::HasRawDisplayHandle + ?Sized> ParentWindow for W {
+    fn parent_handle(&self) -> Option<RawWindowHandle> {
+        convert(self.raw_window_handle(), Some(self.raw_display_handle()))
+    }
+}
+
+/// Pointer of an optional `NonNull`, 0.6 handles use them where 0.5 ones are nullable
+#[cfg(feature = "rwh-06")]
+macro_rules! ptr {
+    ($ptr:expr) => {
+        $ptr.map_or(std::ptr::null_mut(), |ptr| ptr.as_ptr())
+    };
+}
+
+/// Same handle in `raw-window-handle` 0.3
+#[cfg(any(feature = "rwh-05", feature = "rwh-06"))]
+#[allow(unused_variables, unreachable_patterns)]
+fn convert(
+    window: rwh::RawWindowHandle,
+    display: Option<rwh::RawDisplayHandle>,
+) -> Option<RawWindowHandle> {
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    use raw_window_handle::unix::{WaylandHandle, XcbHandle, XlibHandle};
+
+    let handle = match (window, display) {
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        (rwh::RawWindowHandle::Xlib(window), display) => {
+            #[cfg(feature = "rwh-06")]
+            let display = match display {
+                Some(rwh::RawDisplayHandle::Xlib(display)) => ptr!(display.display),
+                _ => std::ptr::null_mut(),
+            };
+            #[cfg(not(feature = "rwh-06"))]
+            let display = match display {
+                Some(rwh::RawDisplayHandle::Xlib(display)) => display.display,
+                _ => std::ptr::null_mut(),
+            };
+            RawWindowHandle::Xlib(XlibHandle {
+                window: window.window,
+                display,
+                ..XlibHandle::empty()
+            })
+        }
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        (rwh::RawWindowHandle::Xcb(window), display) => {
+            #[cfg(feature = "rwh-06")]
+            let (window, connection) = match display {
+                Some(rwh::RawDisplayHandle::Xcb(display)) => {
+                    (window.window.get(), ptr!(display.connection))
+                }
+                _ => (window.window.get(), std::ptr::null_mut()),
+            };
+            #[cfg(not(feature = "rwh-06"))]
+            let (window, connection) = match display {
+                Some(rwh::RawDisplayHandle::Xcb(display)) => (window.window, display.connection),
+                _ => (window.window, std::ptr::null_mut()),
+            };
+            RawWindowHandle::Xcb(XcbHandle {
+                window,
+                connection,
+                ..XcbHandle::empty()
+            })
+        }
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        (rwh::RawWindowHandle::Wayland(window), Some(rwh::RawDisplayHandle::Wayland(display))) => {
+            #[cfg(feature = "rwh-06")]
+            let (surface, display) = (window.surface.as_ptr(), display.display.as_ptr());
+            #[cfg(not(feature = "rwh-06"))]
+            let (surface, display) = (window.surface, display.display);
+            RawWindowHandle::Wayland(WaylandHandle {
+                surface,
+                display,
+                ..WaylandHandle::empty()
+            })
+        }
+        #[cfg(target_os = "windows")]
+        (rwh::RawWindowHandle::Win32(window), _) => {
+            use raw_window_handle::windows::WindowsHandle;
+
+            #[cfg(feature = "rwh-06")]
+            let (hwnd, hinstance) = (
+                window.hwnd.get() as *mut _,
+                window
+                    .hinstance
+                    .map_or(std::ptr::null_mut(), |hinstance| hinstance.get() as *mut _),
+            );
+            #[cfg(not(feature = "rwh-06"))]
+            let (hwnd, hinstance) = (window.hwnd, window.hinstance);
+            RawWindowHandle::Windows(WindowsHandle {
+                hwnd,
+                hinstance,
+                ..WindowsHandle::empty()
+            })
+        }
+        #[cfg(target_os = "macos")]
+        (rwh::RawWindowHandle::AppKit(window), _) => {
+            use objc::runtime::Object;
+            use objc::{msg_send, sel, sel_impl};
+            use raw_window_handle::macos::MacOSHandle;
+
+            #[cfg(feature = "rwh-06")]
+            let ns_view = window.ns_view.as_ptr();
+            #[cfg(not(feature = "rwh-06"))]
+            let ns_view = window.ns_view;
+            // 0.6 handles only have the view, backends need its window
+            let ns_window: *mut Object = unsafe { msg_send![ns_view as *mut Object, window] };
+            if ns_window.is_null() {
+                return None;
+            }
+            RawWindowHandle::MacOS(MacOSHandle {
+                ns_window: ns_window as *mut _,
+                ns_view,
+                ..MacOSHandle::empty()
+            })
+        }
+        #[cfg(target_os = "ios")]
+        (rwh::RawWindowHandle::UiKit(window), _) => {
+            use raw_window_handle::ios::IOSHandle;
+
+            #[cfg(feature = "rwh-06")]
+            let (ui_view, ui_view_controller) =
+                (window.ui_view.as_ptr(), ptr!(window.ui_view_controller));
+            #[cfg(not(feature = "rwh-06"))]
+            let (ui_view, ui_view_controller) = (window.ui_view, window.ui_view_controller);
+            RawWindowHandle::IOS(IOSHandle {
+                ui_view,
+                ui_view_controller,
+                ..IOSHandle::empty()
+            })
+        }
+        #[cfg(target_os = "android")]
+        (rwh::RawWindowHandle::AndroidNdk(window), _) => {
+            use raw_window_handle::android::AndroidHandle;
+
+            #[cfg(feature = "rwh-06")]
+            let a_native_window = window.a_native_window.as_ptr();
+            #[cfg(not(feature = "rwh-06"))]
+            let a_native_window = window.a_native_window;
+            RawWindowHandle::Android(AndroidHandle {
+                a_native_window,
+                ..AndroidHandle::empty()
+            })
+        }
+        #[cfg(target_arch = "wasm32")]
+        (rwh::RawWindowHandle::Web(window), _) => {
+            use raw_window_handle::web::WebHandle;
+
+            RawWindowHandle::Web(WebHandle {
+                id: window.id,
+                ..WebHandle::empty()
+            })
+        }
+        _ => return None,
+    };
+    Some(handle)
+}
+
+#[cfg(all(test, feature = "rwh-06", target_os = "linux"))]
+mod tests {
+    use super::{convert, rwh};
+    use raw_window_handle::RawWindowHandle;
+    use std::num::NonZeroU32;
+    use std::ptr::NonNull;
+
+    #[test]
+    fn converts_x11_and_wayland_handles() {
+        let window = rwh::XcbWindowHandle::new(NonZeroU32::new(42).unwrap());
+        let handle = convert(rwh::RawWindowHandle::Xcb(window), None);
+        assert!(matches!(handle, Some(RawWindowHandle::Xcb(h)) if h.window == 42));
+
+        let mut surface = 0u8;
+        let mut display = 0u8;
+        let window = rwh::WaylandWindowHandle::new(NonNull::from(&mut surface).cast());
+        let display = rwh::WaylandDisplayHandle::new(NonNull::from(&mut display).cast());
+        let handle = convert(
+            rwh::RawWindowHandle::Wayland(window),
+            Some(rwh::RawDisplayHandle::Wayland(display)),
+        );
+        assert!(
+            matches!(handle, Some(RawWindowHandle::Wayland(h)) if h.surface == window.surface.as_ptr())
+        );
+
+        // A Wayland surface can't be exported without its display
+        assert!(convert(rwh::RawWindowHandle::Wayland(window), None).is_none());
+    }
+}
@@ -0,0 +1,71 @@
+//! Password entered in a [`PasswordDialog`](crate::PasswordDialog) or a [`CredentialDialog`](crate::CredentialDialog)
+//!
+//! The text is kept out of `Debug` output and overwritten with zeros when it is dropped.
+//! It converts into a `String` for crates like `secrecy`, eg. `SecretString::new(password.into_string())`.
+
+use std::fmt;
+use std::sync::atomic::{compiler_fence, Ordering};
+
+/// Text entered in a [`PasswordDialog`](crate::PasswordDialog)
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Password(String);
+
+impl Password {
+    pub(crate) fn new(text: String) -> Self {
+        Self(text)
+    }
+
+    /// The entered text
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    /// Moves the text out, from then on the caller is responsible for clearing it
+    pub fn into_string(mut self) -> String {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl Drop for Password {
+    fn drop(&mut self) {
+        // Volatile writes are not optimized away like a plain fill of memory that is freed next
+        for byte in unsafe { self.0.as_mut_vec() } {
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+// Backends hand over the entered text
+impl From<String> for Password {
+    fn from(text: String) -> Self {
+        Self(text)
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(***)")
+    }
+}
+
+/// User name and password entered in a [`CredentialDialog`](crate::CredentialDialog)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// User name, with the domain in front (`DOMAIN\user`) when one was entered on Windows
+    pub user: String,
+    pub password: Password,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Password;
+
+    #[test]
+    fn debug_hides_text() {
+        let password = Password::new("hunter2".into());
+        assert_eq!(format!("{:?}", password), "Password(***)");
+        assert_eq!(password.expose_secret(), "hunter2");
+        assert_eq!(password.into_string(), "hunter2");
+    }
+}
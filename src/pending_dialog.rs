@@ -0,0 +1,182 @@
+//! PendingDialog lets loops that never block, like game loops, show a dialog,
+//! or callers that neither block nor await get a callback
+//!
+//! The dialog is shown with the async implementation of the backend, which runs the native
+//! dialog on its own thread or event loop, so polling never has to pump any events itself.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Wake, Waker};
+use std::thread::{self, Thread};
+
+use crate::backend::DialogFutureType;
+use crate::{DialogHandle, DialogResult, Error, FileHandle};
+
+type PickFuture = DialogFutureType<Result<Option<FileHandle>, Error>>;
+
+/// Nobody waits to be woken up, the caller polls again on its next iteration
+fn noop_waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        |_| RawWaker::new(std::ptr::null(), &VTABLE),
+        |_| {},
+        |_| {},
+        |_| {},
+    );
+    unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+}
+
+/// Wakes up the thread blocked in [`PendingDialog::wait`]
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Dialog shown with [`FileDialog::show_non_blocking`](crate::FileDialog::show_non_blocking)
+pub struct PendingDialog {
+    future: Option<PickFuture>,
+    result: Option<DialogResult<PathBuf>>,
+    handle: DialogHandle,
+}
+
+/// Keeps the error of a failed dialog apart from a cancelled one, and reports it
+fn finished(res: Result<Option<FileHandle>, Error>) -> DialogResult<PathBuf> {
+    let res = DialogResult::from(res).map(PathBuf::from);
+    #[cfg(feature = "tracing")]
+    {
+        if let DialogResult::Failed(err) = &res {
+            trace_warn!(error = %err, "non-blocking dialog failed");
+        }
+    }
+    res
+}
+
+impl PendingDialog {
+    pub(crate) fn new(future: PickFuture, handle: DialogHandle) -> Self {
+        Self {
+            future: Some(future),
+            result: None,
+            handle,
+        }
+    }
+
+    /// `Poll::Pending` while the dialog is open, then the picked file, `None` when the user cancelled
+    /// or the dialog failed, see [`poll_result`](Self::poll_result) to tell them apart.
+    ///
+    /// Once finished every call returns the same result.
+    pub fn poll(&mut self) -> Poll<Option<PathBuf>> {
+        self.poll_result().map(DialogResult::ok)
+    }
+
+    /// Same as [`poll`](Self::poll), but tells a cancelled dialog apart from one that failed
+    pub fn poll_result(&mut self) -> Poll<DialogResult<PathBuf>> {
+        if let Some(future) = &mut self.future {
+            let waker = noop_waker();
+            let mut cx = Context::from_waker(&waker);
+            let res = match future.as_mut().poll(&mut cx) {
+                Poll::Ready(res) => res,
+                Poll::Pending => return Poll::Pending,
+            };
+            self.future = None;
+            self.result = Some(finished(res));
+        }
+        Poll::Ready(self.result.clone().unwrap_or(DialogResult::Cancelled))
+    }
+
+    /// Blocks the calling thread until the dialog is closed, returns the same as [`poll`](Self::poll)
+    pub fn wait(self) -> Option<PathBuf> {
+        self.result().ok()
+    }
+
+    /// Blocks the calling thread until the dialog is closed, returns the same as
+    /// [`poll_result`](Self::poll_result)
+    pub fn result(mut self) -> DialogResult<PathBuf> {
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Some(future) = &mut self.future {
+                if let Poll::Ready(res) = future.as_mut().poll(&mut cx) {
+                    return finished(res);
+                }
+                thread::park();
+            } else {
+                return self.result.unwrap_or(DialogResult::Cancelled);
+            }
+        }
+    }
+
+    /// `true` once [`poll`](Self::poll) returned the result
+    pub fn is_finished(&self) -> bool {
+        self.future.is_none()
+    }
+
+    /// Handle that dismisses the dialog, see [`DialogHandle`]
+    pub fn handle(&self) -> DialogHandle {
+        self.handle.clone()
+    }
+}
+
+impl fmt::Debug for PendingDialog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingDialog")
+            .field("finished", &self.is_finished())
+            .field("result", &self.result)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PendingDialog;
+    use crate::{DialogHandle, DialogResult, Error, FileHandle};
+    use std::path::PathBuf;
+    use std::task::Poll;
+
+    #[test]
+    fn keeps_the_result_once_finished() {
+        let path = PathBuf::from("/tmp/picked.txt");
+        let file = FileHandle::wrap(path.clone());
+        let mut dialog = PendingDialog::new(
+            Box::pin(async move { Ok(Some(file)) }),
+            DialogHandle::default(),
+        );
+
+        assert_eq!(dialog.poll(), Poll::Ready(Some(path.clone())));
+        assert!(dialog.is_finished());
+        assert_eq!(dialog.poll(), Poll::Ready(Some(path)));
+    }
+
+    #[test]
+    fn wait_blocks_until_the_dialog_closes() {
+        let (send, recv) = futures::channel::oneshot::channel::<()>();
+        let mut dialog = PendingDialog::new(
+            Box::pin(async move {
+                recv.await.ok();
+                Ok(None)
+            }),
+            DialogHandle::default(),
+        );
+        assert_eq!(dialog.poll(), Poll::Pending);
+
+        std::thread::spawn(move || send.send(()));
+        assert_eq!(dialog.wait(), None);
+    }
+
+    #[test]
+    fn keeps_the_error_of_a_failed_dialog() {
+        let mut dialog = PendingDialog::new(
+            Box::pin(async { Err(Error::Unsupported) }),
+            DialogHandle::default(),
+        );
+
+        assert_eq!(
+            dialog.poll_result(),
+            Poll::Ready(DialogResult::Failed(Error::Unsupported))
+        );
+        assert_eq!(dialog.poll(), Poll::Ready(None));
+        assert_eq!(dialog.result(), DialogResult::Failed(Error::Unsupported));
+    }
+}
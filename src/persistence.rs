@@ -0,0 +1,412 @@
+//! Remembers the last directory of file dialogs across runs
+//!
+//! Persistence is opt-in, it is turned on with [`enable`].
+//! When a dialog gets accepted, the directory of the chosen item is recorded under the dialog's
+//! [persistence id](crate::FileDialog::set_persistence_id), the next dialog with the same id opens there.
+//! Dialogs with an explicit [starting directory](crate::FileDialog::set_directory) keep it.
+//!
+//! ```no_run
+//! rfd::persistence::enable("/home/user/.config/my-app/dialogs").unwrap();
+//!
+//! let file = rfd::FileDialog::new()
+//!     .set_persistence_id("export")
+//!     .save_file();
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::backend::{DialogBackend, DialogFutureType};
+use crate::{
+    CredentialDialog, Credentials, Error, FileDialog, FileHandle, InputDialog, MessageDialog,
+    Notification, PickListDialog, ProgressDialog, ProgressState, Url,
+};
+
+struct Store {
+    path: PathBuf,
+    directories: HashMap<String, PathBuf>,
+}
+
+static STORE: RwLock<Option<Store>> = RwLock::new(None);
+
+/// Start remembering directories, they are stored in the file at `path`.
+///
+/// Directories recorded by earlier runs are loaded from `path`, a missing file is not an error.
+pub fn enable<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref().to_owned();
+
+    let directories = match fs::read_to_string(&path) {
+        Ok(content) => content
+            .lines()
+            .filter_map(|line| {
+                let (id, dir) = line.split_once('\t')?;
+                Some((id.to_owned(), PathBuf::from(dir)))
+            })
+            .collect(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => HashMap::new(),
+        Err(err) => return Err(err),
+    };
+
+    *STORE.write().unwrap() = Some(Store { path, directories });
+    Ok(())
+}
+
+/// Stop remembering directories, the file given to [`enable`] is left as is
+pub fn disable() {
+    *STORE.write().unwrap() = None;
+}
+
+/// `true` between [`enable`] and [`disable`]
+pub fn is_enabled() -> bool {
+    STORE.read().unwrap().is_some()
+}
+
+fn id(dialog: &FileDialog) -> String {
+    dialog.persistence_id.clone().unwrap_or_default()
+}
+
+fn restore(mut dialog: FileDialog) -> FileDialog {
+    if dialog.starting_directory.is_none() {
+        if let Some(store) = STORE.read().unwrap().as_ref() {
+            dialog.starting_directory = store
+                .directories
+                .get(&id(&dialog))
+                .filter(|dir| dir.is_dir())
+                .cloned();
+        }
+    }
+    dialog
+}
+
+fn record(id: &str, path: &Path) {
+    let dir = match path.parent().and_then(Path::to_str) {
+        // Entries are stored one per line, so ids and directories can't span lines
+        Some(dir) if !dir.contains('\n') && !id.contains(&['\t', '\n'][..]) => dir,
+        _ => return,
+    };
+
+    if let Some(store) = STORE.write().unwrap().as_mut() {
+        store.directories.insert(id.to_owned(), dir.into());
+
+        let content: String = store
+            .directories
+            .iter()
+            .filter_map(|(id, dir)| Some(format!("{}\t{}\n", id, dir.to_str()?)))
+            .collect();
+
+        // Best effort, failing to remember a directory should not fail the dialog
+        if let Some(parent) = store.path.parent() {
+            fs::create_dir_all(parent).ok();
+        }
+        fs::write(&store.path, content).ok();
+    }
+}
+
+fn run<T>(
+    dialog: FileDialog,
+    show: impl FnOnce(FileDialog) -> Result<Option<T>, Error>,
+    path: fn(&T) -> Option<&Path>,
+) -> Result<Option<T>, Error> {
+    let id = id(&dialog);
+    let res = show(restore(dialog))?;
+    if let Some(path) = res.as_ref().and_then(path) {
+        record(&id, path);
+    }
+    Ok(res)
+}
+
+fn run_async<T: Send + 'static>(
+    dialog: FileDialog,
+    show: impl FnOnce(FileDialog) -> DialogFutureType<Result<Option<T>, Error>>,
+    path: fn(&T) -> Option<&Path>,
+) -> DialogFutureType<Result<Option<T>, Error>> {
+    let id = id(&dialog);
+    let future = show(restore(dialog));
+    Box::pin(async move {
+        let res = future.await?;
+        if let Some(path) = res.as_ref().and_then(path) {
+            record(&id, path);
+        }
+        Ok(res)
+    })
+}
+
+// Path of a dialog result, these have to take exactly the result type
+#[allow(clippy::ptr_arg)]
+fn path(path: &PathBuf) -> Option<&Path> {
+    Some(path)
+}
+
+#[allow(clippy::ptr_arg)]
+fn first_path(paths: &Vec<PathBuf>) -> Option<&Path> {
+    paths.first().map(PathBuf::as_path)
+}
+
+fn file(file: &FileHandle) -> Option<&Path> {
+    Some(file.path())
+}
+
+#[allow(clippy::ptr_arg)]
+fn first_file(files: &Vec<FileHandle>) -> Option<&Path> {
+    files.first().map(FileHandle::path)
+}
+
+/// Wraps the backend of every dialog while persistence is enabled
+pub(crate) struct Persisted(pub(crate) Arc<dyn DialogBackend>);
+
+impl DialogBackend for Persisted {
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        run(dialog, |d| self.0.pick_file(d), path)
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        run(dialog, |d| self.0.pick_files(d), first_path)
+    }
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        run(dialog, |d| self.0.pick_folder(d), path)
+    }
+
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        run(dialog, |d| self.0.pick_folders(d), first_path)
+    }
+
+    fn pick_path(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        run(dialog, |d| self.0.pick_path(d), path)
+    }
+
+    fn pick_file_url(&self, dialog: FileDialog) -> Result<Option<Url>, Error> {
+        let id = id(&dialog);
+        let url = self.0.pick_file_url(restore(dialog))?;
+        if let Some(path) = url.as_ref().and_then(|url| url.to_file_path().ok()) {
+            record(&id, &path);
+        }
+        Ok(url)
+    }
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        run(dialog, |d| self.0.save_file(d), path)
+    }
+
+    fn save_file_with_filter(&self, dialog: FileDialog) -> Result<Option<(PathBuf, usize)>, Error> {
+        run(
+            dialog,
+            |d| self.0.save_file_with_filter(d),
+            |(p, _)| Some(p),
+        )
+    }
+
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        self.0.show_message(dialog)
+    }
+
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        self.0.show_message_custom(dialog)
+    }
+
+    fn show_message_index(&self, dialog: MessageDialog) -> Option<usize> {
+        self.0.show_message_index(dialog)
+    }
+
+    fn show_input(&self, dialog: InputDialog) -> Option<String> {
+        self.0.show_input(dialog)
+    }
+
+    fn show_pick_list(&self, dialog: PickListDialog) -> Option<usize> {
+        self.0.show_pick_list(dialog)
+    }
+
+    fn show_credentials(&self, dialog: CredentialDialog) -> Option<Credentials> {
+        self.0.show_credentials(dialog)
+    }
+
+    fn try_show_message(&self, dialog: MessageDialog) -> Result<Option<String>, Error> {
+        self.0.try_show_message(dialog)
+    }
+
+    fn try_show_input(&self, dialog: InputDialog) -> Result<Option<String>, Error> {
+        self.0.try_show_input(dialog)
+    }
+
+    fn try_show_pick_list(&self, dialog: PickListDialog) -> Result<Option<usize>, Error> {
+        self.0.try_show_pick_list(dialog)
+    }
+
+    fn try_show_credentials(&self, dialog: CredentialDialog) -> Result<Option<Credentials>, Error> {
+        self.0.try_show_credentials(dialog)
+    }
+
+    fn show_progress(&self, dialog: ProgressDialog, state: ProgressState) {
+        self.0.show_progress(dialog, state)
+    }
+
+    fn show_notification(&self, notification: Notification) -> Result<(), Error> {
+        self.0.show_notification(notification)
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        run_async(dialog, |d| self.0.pick_file_async(d), file)
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        run_async(dialog, |d| self.0.pick_files_async(d), first_file)
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        run_async(dialog, |d| self.0.pick_folder_async(d), file)
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        run_async(dialog, |d| self.0.pick_folders_async(d), first_file)
+    }
+
+    fn pick_path_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        run_async(dialog, |d| self.0.pick_path_async(d), file)
+    }
+
+    fn pick_file_url_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Url>, Error>> {
+        let id = id(&dialog);
+        let future = self.0.pick_file_url_async(restore(dialog));
+        Box::pin(async move {
+            let url = future.await?;
+            if let Some(path) = url.as_ref().and_then(|url| url.to_file_path().ok()) {
+                record(&id, &path);
+            }
+            Ok(url)
+        })
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        run_async(dialog, |d| self.0.save_file_async(d), file)
+    }
+
+    fn save_file_with_filter_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<(FileHandle, usize)>, Error>> {
+        run_async(
+            dialog,
+            |d| self.0.save_file_with_filter_async(d),
+            |(f, _)| Some(f.path()),
+        )
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        self.0.show_message_async(dialog)
+    }
+
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        self.0.show_message_custom_async(dialog)
+    }
+
+    fn show_message_index_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<usize>> {
+        self.0.show_message_index_async(dialog)
+    }
+
+    fn show_input_async(&self, dialog: InputDialog) -> DialogFutureType<Option<String>> {
+        self.0.show_input_async(dialog)
+    }
+
+    fn show_pick_list_async(&self, dialog: PickListDialog) -> DialogFutureType<Option<usize>> {
+        self.0.show_pick_list_async(dialog)
+    }
+
+    fn show_credentials_async(
+        &self,
+        dialog: CredentialDialog,
+    ) -> DialogFutureType<Option<Credentials>> {
+        self.0.show_credentials_async(dialog)
+    }
+
+    fn try_show_message_async(
+        &self,
+        dialog: MessageDialog,
+    ) -> DialogFutureType<Result<Option<String>, Error>> {
+        self.0.try_show_message_async(dialog)
+    }
+
+    fn try_show_input_async(
+        &self,
+        dialog: InputDialog,
+    ) -> DialogFutureType<Result<Option<String>, Error>> {
+        self.0.try_show_input_async(dialog)
+    }
+
+    fn try_show_pick_list_async(
+        &self,
+        dialog: PickListDialog,
+    ) -> DialogFutureType<Result<Option<usize>, Error>> {
+        self.0.try_show_pick_list_async(dialog)
+    }
+
+    fn try_show_credentials_async(
+        &self,
+        dialog: CredentialDialog,
+    ) -> DialogFutureType<Result<Option<Credentials>, Error>> {
+        self.0.try_show_credentials_async(dialog)
+    }
+
+    fn is_available(&self) -> bool {
+        self.0.is_available()
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.0.is_interactive()
+    }
+
+    fn confirms_overwrite(&self, dialog: &FileDialog) -> Option<bool> {
+        self.0.confirms_overwrite(dialog)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directory_survives_reload() {
+        let dir = std::env::temp_dir().join(format!("rfd-persistence-{}", std::process::id()));
+        let store = dir.join("dialogs");
+
+        enable(&store).unwrap();
+        record("export", &dir.join("out.txt"));
+
+        // Read back from disk, like the next run would
+        enable(&store).unwrap();
+        let dialog = FileDialog::new().set_persistence_id("export");
+        assert_eq!(
+            restore(dialog).starting_directory.as_deref(),
+            Some(dir.as_path())
+        );
+
+        let dialog = FileDialog::new().set_persistence_id("open");
+        assert_eq!(restore(dialog).starting_directory, None);
+
+        disable();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
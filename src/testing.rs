@@ -0,0 +1,607 @@
+//! Scripted backend for testing code that opens dialogs, see [`MockBackend`]
+//!
+//! Responses are queued before the code under test runs, each dialog it shows takes the next one,
+//! and the dialogs it requested can be checked afterwards.
+//!
+//! ```
+//! use rfd::testing::MockBackend;
+//!
+//! let mock = MockBackend::new();
+//! mock.expect_pick_file().returns("/home/user/report.odt");
+//! mock.install();
+//!
+//! let path = rfd::FileDialog::new()
+//!     .set_title("Import report")
+//!     .add_filter("Document", &["odt"])
+//!     .pick_file();
+//!
+//! assert_eq!(path, Some("/home/user/report.odt".into()));
+//! let requests = mock.requests();
+//! assert_eq!(requests[0].title(), Some("Import report"));
+//! assert_eq!(requests[0].file_dialog().unwrap().filters()[0].name, "Document");
+//! mock.assert_done();
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::backend::{set_backend, DialogBackend, DialogFutureType};
+use crate::{
+    CredentialDialog, Credentials, Error, FileDialog, FileHandle, InputDialog, MessageDialog,
+    PickListDialog,
+};
+
+/// Kind of dialog a response is queued for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DialogKind {
+    PickFile,
+    PickFiles,
+    PickFolder,
+    PickFolders,
+    SaveFile,
+    Message,
+    Input,
+    PickList,
+    Credentials,
+}
+
+impl fmt::Display for DialogKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DialogKind::PickFile => "pick_file",
+            DialogKind::PickFiles => "pick_files",
+            DialogKind::PickFolder => "pick_folder",
+            DialogKind::PickFolders => "pick_folders",
+            DialogKind::SaveFile => "save_file",
+            DialogKind::Message => "message",
+            DialogKind::Input => "input",
+            DialogKind::PickList => "pick_list",
+            DialogKind::Credentials => "credentials",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Dialog the code under test asked [`MockBackend`] for, with all its options
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Request {
+    PickFile(FileDialog),
+    PickFiles(FileDialog),
+    PickFolder(FileDialog),
+    PickFolders(FileDialog),
+    SaveFile(FileDialog),
+    Message(MessageDialog),
+    Input(InputDialog),
+    PickList(PickListDialog),
+    Credentials(CredentialDialog),
+}
+
+impl Request {
+    pub fn kind(&self) -> DialogKind {
+        match self {
+            Request::PickFile(_) => DialogKind::PickFile,
+            Request::PickFiles(_) => DialogKind::PickFiles,
+            Request::PickFolder(_) => DialogKind::PickFolder,
+            Request::PickFolders(_) => DialogKind::PickFolders,
+            Request::SaveFile(_) => DialogKind::SaveFile,
+            Request::Message(_) => DialogKind::Message,
+            Request::Input(_) => DialogKind::Input,
+            Request::PickList(_) => DialogKind::PickList,
+            Request::Credentials(_) => DialogKind::Credentials,
+        }
+    }
+
+    /// Title of the dialog, `None` for file dialogs without one
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            Request::PickFile(dialog)
+            | Request::PickFiles(dialog)
+            | Request::PickFolder(dialog)
+            | Request::PickFolders(dialog)
+            | Request::SaveFile(dialog) => dialog.title(),
+            Request::Message(dialog) => Some(dialog.title()),
+            Request::Input(dialog) => Some(dialog.title()),
+            Request::PickList(dialog) => Some(dialog.title()),
+            Request::Credentials(dialog) => Some(dialog.title()),
+        }
+    }
+
+    /// Options of file dialogs, like the filters and the starting directory
+    pub fn file_dialog(&self) -> Option<&FileDialog> {
+        match self {
+            Request::PickFile(dialog)
+            | Request::PickFiles(dialog)
+            | Request::PickFolder(dialog)
+            | Request::PickFolders(dialog)
+            | Request::SaveFile(dialog) => Some(dialog),
+            _ => None,
+        }
+    }
+
+    pub fn message_dialog(&self) -> Option<&MessageDialog> {
+        match self {
+            Request::Message(dialog) => Some(dialog),
+            _ => None,
+        }
+    }
+
+    pub fn input_dialog(&self) -> Option<&InputDialog> {
+        match self {
+            Request::Input(dialog) => Some(dialog),
+            _ => None,
+        }
+    }
+
+    pub fn pick_list_dialog(&self) -> Option<&PickListDialog> {
+        match self {
+            Request::PickList(dialog) => Some(dialog),
+            _ => None,
+        }
+    }
+
+    pub fn credential_dialog(&self) -> Option<&CredentialDialog> {
+        match self {
+            Request::Credentials(dialog) => Some(dialog),
+            _ => None,
+        }
+    }
+}
+
+/// Queued response, the variant matches what the dialog returns
+enum Response {
+    Cancel,
+    Fail(Error),
+    Paths(Vec<PathBuf>),
+    Pressed(bool),
+    Button(String),
+    Text(String),
+    Index(usize),
+    Credentials(Credentials),
+}
+
+#[derive(Default)]
+struct State {
+    responses: VecDeque<(DialogKind, Response)>,
+    requests: Vec<Request>,
+    /// Reported by `is_interactive` inverted, so the default mock acts like a user
+    scripted: bool,
+}
+
+/// Backend that answers dialogs with queued responses instead of showing them
+///
+/// Clones share their queue and requests, so one clone can be installed with [`install`](Self::install)
+/// while the test keeps another. A dialog without a queued response, or with one queued for another
+/// kind of dialog, panics.
+#[derive(Clone, Default)]
+pub struct MockBackend(Arc<Mutex<State>>);
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reports the mock as answering from a script, like the headless backend, instead of a user.
+    /// Dialogs that would be shown again for another answer give up instead.
+    pub fn set_interactive(self, interactive: bool) -> Self {
+        self.state().scripted = !interactive;
+        self
+    }
+
+    /// Makes this mock the backend of every dialog, like [`set_backend`]
+    pub fn install(&self) {
+        set_backend(Box::new(self.clone()));
+    }
+
+    pub fn expect_pick_file(&self) -> Expectation<PathBuf> {
+        self.expect(DialogKind::PickFile)
+    }
+
+    pub fn expect_pick_files(&self) -> Expectation<Vec<PathBuf>> {
+        self.expect(DialogKind::PickFiles)
+    }
+
+    pub fn expect_pick_folder(&self) -> Expectation<PathBuf> {
+        self.expect(DialogKind::PickFolder)
+    }
+
+    pub fn expect_pick_folders(&self) -> Expectation<Vec<PathBuf>> {
+        self.expect(DialogKind::PickFolders)
+    }
+
+    pub fn expect_save_file(&self) -> Expectation<PathBuf> {
+        self.expect(DialogKind::SaveFile)
+    }
+
+    pub fn expect_message(&self) -> Expectation<MessageDialog> {
+        self.expect(DialogKind::Message)
+    }
+
+    pub fn expect_input(&self) -> Expectation<InputDialog> {
+        self.expect(DialogKind::Input)
+    }
+
+    pub fn expect_pick_list(&self) -> Expectation<PickListDialog> {
+        self.expect(DialogKind::PickList)
+    }
+
+    pub fn expect_credentials(&self) -> Expectation<CredentialDialog> {
+        self.expect(DialogKind::Credentials)
+    }
+
+    /// Dialogs requested so far, oldest first
+    pub fn requests(&self) -> Vec<Request> {
+        self.state().requests.clone()
+    }
+
+    /// Forgets the requested dialogs and the responses that are still queued
+    pub fn reset(&self) {
+        let mut state = self.state();
+        *state = State {
+            scripted: state.scripted,
+            ..State::default()
+        };
+    }
+
+    /// Panics when queued responses were not used by a dialog
+    pub fn assert_done(&self) {
+        let kinds: Vec<String> = self
+            .state()
+            .responses
+            .iter()
+            .map(|(kind, _)| kind.to_string())
+            .collect();
+        assert!(
+            kinds.is_empty(),
+            "MockBackend: no dialog took the responses queued for {}",
+            kinds.join(", ")
+        );
+    }
+
+    fn expect<T>(&self, kind: DialogKind) -> Expectation<T> {
+        Expectation {
+            mock: self.clone(),
+            kind,
+            _dialog: PhantomData,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panicking dialog must not break the assertions that follow it
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Records `request` and takes the response queued for it
+    fn respond(&self, request: Request) -> Response {
+        let kind = request.kind();
+        let next = {
+            let mut state = self.state();
+            state.requests.push(request);
+            state.responses.pop_front()
+        };
+        match next {
+            Some((expected, response)) if expected == kind => response,
+            Some((expected, _)) => panic!(
+                "MockBackend: {} dialog shown while the next response is queued for {}",
+                kind, expected
+            ),
+            None => panic!(
+                "MockBackend: {} dialog shown without a queued response, add one with expect_{}",
+                kind, kind
+            ),
+        }
+    }
+
+    fn paths(&self, request: Request) -> Result<Option<Vec<PathBuf>>, Error> {
+        match self.respond(request) {
+            Response::Paths(paths) => Ok(Some(paths)),
+            Response::Fail(error) => Err(error),
+            _ => Ok(None),
+        }
+    }
+
+    fn path(&self, request: Request) -> Result<Option<PathBuf>, Error> {
+        let paths = self.paths(request)?;
+        Ok(paths.and_then(|paths| paths.into_iter().next()))
+    }
+}
+
+/// Response of the next dialog of one kind, queued by the `expect_*` methods of [`MockBackend`]
+///
+/// Nothing is queued until one of its methods is called.
+#[must_use = "nothing is queued until `returns` or `cancels` is called"]
+pub struct Expectation<T> {
+    mock: MockBackend,
+    kind: DialogKind,
+    _dialog: PhantomData<T>,
+}
+
+impl<T> Expectation<T> {
+    /// The user closes the dialog
+    pub fn cancels(self) {
+        self.push(Response::Cancel);
+    }
+
+    /// The dialog can't be shown
+    pub fn fails(self, error: Error) {
+        self.push(Response::Fail(error));
+    }
+
+    fn push(self, response: Response) {
+        let mut state = self.mock.state();
+        state.responses.push_back((self.kind, response));
+    }
+}
+
+impl Expectation<PathBuf> {
+    pub fn returns(self, path: impl Into<PathBuf>) {
+        self.push(Response::Paths(vec![path.into()]));
+    }
+}
+
+impl Expectation<Vec<PathBuf>> {
+    pub fn returns<P: Into<PathBuf>>(self, paths: impl IntoIterator<Item = P>) {
+        self.push(Response::Paths(paths.into_iter().map(Into::into).collect()));
+    }
+}
+
+impl Expectation<MessageDialog> {
+    /// `true` presses `Ok`/`Yes`, or the first of the custom buttons
+    pub fn returns(self, pressed: bool) {
+        self.push(Response::Pressed(pressed));
+    }
+
+    /// Presses the button with this label, see [`MessageDialog::button_labels`]
+    pub fn presses(self, label: impl Into<String>) {
+        self.push(Response::Button(label.into()));
+    }
+}
+
+impl Expectation<InputDialog> {
+    pub fn returns(self, text: impl Into<String>) {
+        self.push(Response::Text(text.into()));
+    }
+}
+
+impl Expectation<PickListDialog> {
+    /// Chooses the item at `index`
+    pub fn returns(self, index: usize) {
+        self.push(Response::Index(index));
+    }
+}
+
+impl Expectation<CredentialDialog> {
+    pub fn returns(self, credentials: Credentials) {
+        self.push(Response::Credentials(credentials));
+    }
+}
+
+fn file(path: Result<Option<PathBuf>, Error>) -> Result<Option<FileHandle>, Error> {
+    path.map(|path| path.map(FileHandle::wrap))
+}
+
+fn files(paths: Result<Option<Vec<PathBuf>>, Error>) -> Result<Option<Vec<FileHandle>>, Error> {
+    paths.map(|paths| paths.map(|paths| paths.into_iter().map(FileHandle::wrap).collect()))
+}
+
+impl DialogBackend for MockBackend {
+    fn is_interactive(&self) -> bool {
+        !self.state().scripted
+    }
+
+    fn confirms_overwrite(&self, _dialog: &FileDialog) -> Option<bool> {
+        Some(false)
+    }
+
+    fn pick_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        self.path(Request::PickFile(dialog))
+    }
+
+    fn pick_files(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        self.paths(Request::PickFiles(dialog))
+    }
+
+    fn pick_folder(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        self.path(Request::PickFolder(dialog))
+    }
+
+    fn pick_folders(&self, dialog: FileDialog) -> Result<Option<Vec<PathBuf>>, Error> {
+        self.paths(Request::PickFolders(dialog))
+    }
+
+    fn save_file(&self, dialog: FileDialog) -> Result<Option<PathBuf>, Error> {
+        self.path(Request::SaveFile(dialog))
+    }
+
+    fn show_message(&self, dialog: MessageDialog) -> bool {
+        let first = dialog.button_labels().into_iter().next();
+        match self.respond(Request::Message(dialog)) {
+            Response::Pressed(pressed) => pressed,
+            Response::Button(label) => Some(label) == first,
+            _ => false,
+        }
+    }
+
+    fn show_message_custom(&self, dialog: MessageDialog) -> Option<String> {
+        self.try_show_message(dialog).unwrap_or_default()
+    }
+
+    fn show_input(&self, dialog: InputDialog) -> Option<String> {
+        self.try_show_input(dialog).unwrap_or_default()
+    }
+
+    fn show_pick_list(&self, dialog: PickListDialog) -> Option<usize> {
+        self.try_show_pick_list(dialog).unwrap_or_default()
+    }
+
+    fn show_credentials(&self, dialog: CredentialDialog) -> Option<Credentials> {
+        self.try_show_credentials(dialog).unwrap_or_default()
+    }
+
+    fn try_show_message(&self, dialog: MessageDialog) -> Result<Option<String>, Error> {
+        let labels = dialog.button_labels();
+        Ok(match self.respond(Request::Message(dialog)) {
+            Response::Button(label) => Some(label),
+            Response::Pressed(true) => labels.first().cloned(),
+            Response::Pressed(false) if labels.len() > 1 => labels.last().cloned(),
+            Response::Fail(error) => return Err(error),
+            _ => None,
+        })
+    }
+
+    fn try_show_input(&self, dialog: InputDialog) -> Result<Option<String>, Error> {
+        match self.respond(Request::Input(dialog)) {
+            Response::Text(text) => Ok(Some(text)),
+            Response::Fail(error) => Err(error),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_show_pick_list(&self, dialog: PickListDialog) -> Result<Option<usize>, Error> {
+        match self.respond(Request::PickList(dialog)) {
+            Response::Index(index) => Ok(Some(index)),
+            Response::Fail(error) => Err(error),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_show_credentials(&self, dialog: CredentialDialog) -> Result<Option<Credentials>, Error> {
+        match self.respond(Request::Credentials(dialog)) {
+            Response::Credentials(credentials) => Ok(Some(credentials)),
+            Response::Fail(error) => Err(error),
+            _ => Ok(None),
+        }
+    }
+
+    fn pick_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        Box::pin(std::future::ready(file(self.pick_file(dialog))))
+    }
+
+    fn pick_files_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        Box::pin(std::future::ready(files(self.pick_files(dialog))))
+    }
+
+    fn pick_folder_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        Box::pin(std::future::ready(file(self.pick_folder(dialog))))
+    }
+
+    fn pick_folders_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<Vec<FileHandle>>, Error>> {
+        Box::pin(std::future::ready(files(self.pick_folders(dialog))))
+    }
+
+    fn save_file_async(
+        &self,
+        dialog: FileDialog,
+    ) -> DialogFutureType<Result<Option<FileHandle>, Error>> {
+        Box::pin(std::future::ready(file(self.save_file(dialog))))
+    }
+
+    fn show_message_async(&self, dialog: MessageDialog) -> DialogFutureType<bool> {
+        Box::pin(std::future::ready(self.show_message(dialog)))
+    }
+
+    fn show_message_custom_async(&self, dialog: MessageDialog) -> DialogFutureType<Option<String>> {
+        Box::pin(std::future::ready(self.show_message_custom(dialog)))
+    }
+
+    fn try_show_message_async(
+        &self,
+        dialog: MessageDialog,
+    ) -> DialogFutureType<Result<Option<String>, Error>> {
+        Box::pin(std::future::ready(self.try_show_message(dialog)))
+    }
+
+    fn try_show_input_async(
+        &self,
+        dialog: InputDialog,
+    ) -> DialogFutureType<Result<Option<String>, Error>> {
+        Box::pin(std::future::ready(self.try_show_input(dialog)))
+    }
+
+    fn try_show_pick_list_async(
+        &self,
+        dialog: PickListDialog,
+    ) -> DialogFutureType<Result<Option<usize>, Error>> {
+        Box::pin(std::future::ready(self.try_show_pick_list(dialog)))
+    }
+
+    fn try_show_credentials_async(
+        &self,
+        dialog: CredentialDialog,
+    ) -> DialogFutureType<Result<Option<Credentials>, Error>> {
+        Box::pin(std::future::ready(self.try_show_credentials(dialog)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MessageButtons;
+    use std::path::Path;
+
+    #[test]
+    fn responses_are_taken_in_order() {
+        let mock = MockBackend::new();
+        mock.expect_pick_folder().returns("/data");
+        mock.expect_save_file().cancels();
+        mock.expect_message().presses("No");
+
+        let dialog = FileDialog::new().set_directory("/home");
+        assert_eq!(mock.pick_folder(dialog), Ok(Some("/data".into())));
+        assert_eq!(mock.save_file(FileDialog::new()), Ok(None));
+        let message = MessageDialog::new().set_buttons(MessageButtons::YesNo);
+        assert_eq!(mock.show_message_custom(message), Some("No".into()));
+        mock.assert_done();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].kind(), DialogKind::PickFolder);
+        let directory = requests[0].file_dialog().and_then(FileDialog::directory);
+        assert_eq!(directory, Some(Path::new("/home")));
+        assert!(requests[2].message_dialog().is_some());
+    }
+
+    #[test]
+    fn failing_dialogs_report_the_error() {
+        let mock = MockBackend::new();
+        mock.expect_input().fails(Error::Unsupported);
+        mock.expect_input().fails(Error::Unsupported);
+        mock.expect_pick_list().cancels();
+
+        let res = mock.try_show_input(InputDialog::new());
+        assert_eq!(
+            crate::DialogResult::from(res),
+            crate::DialogResult::Failed(Error::Unsupported)
+        );
+        assert_eq!(mock.show_input(InputDialog::new()), None);
+        let res = mock.try_show_pick_list(PickListDialog::new(&["a"]));
+        assert_eq!(
+            crate::DialogResult::from(res),
+            crate::DialogResult::<usize>::Cancelled
+        );
+        mock.assert_done();
+    }
+
+    #[test]
+    #[should_panic(expected = "pick_file dialog shown while the next response is queued for input")]
+    fn other_kind_of_dialog_panics() {
+        let mock = MockBackend::new();
+        mock.expect_input().returns("name");
+        let _ = mock.pick_file(FileDialog::new());
+    }
+}
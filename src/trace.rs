@@ -0,0 +1,24 @@
+//! Diagnostics for "the dialog never appears" reports, emitted through `tracing` with the
+//! `tracing` feature
+//!
+//! Without the feature the macros expand to nothing, so their arguments are not evaluated.
+
+macro_rules! trace_debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::debug!($($arg)*);
+        }
+    };
+}
+
+// Unused when every call site is behind the `tracing` feature or another platform's backend
+#[allow(unused_macros)]
+macro_rules! trace_warn {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::warn!($($arg)*);
+        }
+    };
+}
@@ -0,0 +1,188 @@
+//! Runs closures on the thread that is allowed to show dialogs
+//!
+//! On MacOS that is the main thread, closures are sent to its dispatch queue, so the
+//! application has to run its event loop there, as every windowed app does.
+//! Everywhere else it is a thread of rfd, started on first use, that runs one closure after
+//! the other. On Windows it is initialized as a COM single-threaded apartment, which the
+//! shell dialogs need.
+//!
+//! ```no_run
+//! let path = std::thread::spawn(|| {
+//!     rfd::ui_thread::spawn(|| rfd::FileDialog::new().pick_file()).wait()
+//! });
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[cfg(not(target_os = "macos"))]
+use std::sync::mpsc::{channel, Sender};
+#[cfg(not(target_os = "macos"))]
+use std::sync::OnceLock;
+#[cfg(not(target_os = "macos"))]
+use std::thread::{self, ThreadId};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct State<T> {
+    res: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// Result of a closure passed to [`spawn`], either [`wait`](Self::wait) for it or `.await` it
+pub struct UiTask<T>(Arc<(Mutex<State<T>>, Condvar)>);
+
+impl<T> UiTask<T> {
+    /// Blocks until the closure returned
+    ///
+    /// Waiting on the UI thread itself for a closure that did not run yet never finishes,
+    /// [`spawn`] runs closures right away on it for that reason.
+    pub fn wait(self) -> T {
+        let (state, cvar) = &*self.0;
+        let mut state = state.lock().unwrap();
+        loop {
+            if let Some(res) = state.res.take() {
+                return res;
+            }
+            state = cvar.wait(state).unwrap();
+        }
+    }
+}
+
+impl<T> Future for UiTask<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0 .0.lock().unwrap();
+        match state.res.take() {
+            Some(res) => Poll::Ready(res),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for UiTask<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let done = self.0 .0.lock().unwrap().res.is_some();
+        f.debug_struct("UiTask").field("done", &done).finish()
+    }
+}
+
+/// Runs `run` on the UI thread, right away when called from it
+pub fn spawn<T, F>(run: F) -> UiTask<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let task = Arc::new((
+        Mutex::new(State {
+            res: None,
+            waker: None,
+        }),
+        Condvar::new(),
+    ));
+
+    let job = {
+        let task = task.clone();
+        move || {
+            let res = run();
+            let (state, cvar) = &*task;
+            let mut state = state.lock().unwrap();
+            state.res = Some(res);
+            cvar.notify_all();
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+    };
+
+    if is_ui_thread() {
+        job();
+    } else {
+        send(Box::new(job));
+    }
+
+    UiTask(task)
+}
+
+/// `true` on the thread that [`spawn`] runs closures on
+pub fn is_ui_thread() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        use objc::{class, msg_send, sel, sel_impl};
+        unsafe { msg_send![class!(NSThread), isMainThread] }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        UI_THREAD.get().map(|ui| ui.id) == Some(thread::current().id())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn send(job: Job) {
+    dispatch::Queue::main().exec_async(job);
+}
+
+#[cfg(not(target_os = "macos"))]
+struct UiThread {
+    id: ThreadId,
+    jobs: Mutex<Sender<Job>>,
+}
+
+#[cfg(not(target_os = "macos"))]
+static UI_THREAD: OnceLock<UiThread> = OnceLock::new();
+
+#[cfg(not(target_os = "macos"))]
+fn send(job: Job) {
+    let ui = UI_THREAD.get_or_init(|| {
+        let (jobs, recv) = channel::<Job>();
+        let handle = thread::Builder::new()
+            .name("rfd_ui".into())
+            .spawn(move || {
+                // Never uninitialized, the thread lives as long as the process
+                #[cfg(target_os = "windows")]
+                unsafe {
+                    use winapi::um::combaseapi::CoInitializeEx;
+                    use winapi::um::objbase::{COINIT_APARTMENTTHREADED, COINIT_DISABLE_OLE1DDE};
+                    CoInitializeEx(
+                        std::ptr::null_mut(),
+                        COINIT_APARTMENTTHREADED | COINIT_DISABLE_OLE1DDE,
+                    );
+                }
+
+                for job in recv {
+                    job();
+                }
+            })
+            .expect("failed to spawn the rfd UI thread");
+        UiThread {
+            id: handle.thread().id(),
+            jobs: Mutex::new(jobs),
+        }
+    });
+    // The thread lives as long as the process, so it is always there to receive
+    let _ = ui.jobs.lock().unwrap().send(job);
+}
+
+#[cfg(all(test, not(target_os = "macos")))]
+mod tests {
+    use super::{is_ui_thread, spawn};
+
+    #[test]
+    fn runs_on_one_thread() {
+        assert!(!is_ui_thread());
+        let first = spawn(|| std::thread::current().id()).wait();
+        let second = spawn(|| (std::thread::current().id(), is_ui_thread())).wait();
+        assert_eq!(second, (first, true));
+
+        // Spawning from the UI thread runs right away instead of waiting on itself
+        let nested = spawn(|| spawn(|| 1).wait()).wait();
+        assert_eq!(nested, 1);
+    }
+}
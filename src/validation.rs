@@ -0,0 +1,376 @@
+//! Checks done by [`FileDialog::validate`] before a dialog is shown
+
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::backend::backend_name;
+use crate::FileDialog;
+
+/// Problem with the options of a [`FileDialog`], found by [`FileDialog::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationIssue {
+    /// The starting directory set with `set_directory` does not exist, the dialog opens elsewhere
+    MissingDirectory(PathBuf),
+    /// Filter `index` has no extensions, MIME types, patterns or file names, so it matches nothing
+    EmptyFilter { index: usize, name: String },
+    /// `value` of filter `index` contains `character`, which can't be passed on to the backends
+    InvalidFilterCharacter {
+        index: usize,
+        value: String,
+        character: char,
+    },
+    /// `set_min_files` is above `set_max_files`, so no pick can satisfy both
+    FileCountRange { min: usize, max: usize },
+    /// `backend` ignores the option set with the builder method `option`
+    Unsupported {
+        option: &'static str,
+        backend: &'static str,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::MissingDirectory(path) => {
+                write!(f, "starting directory {} does not exist", path.display())
+            }
+            ValidationIssue::EmptyFilter { index, name } => {
+                write!(f, "filter {} ({:?}) matches nothing", index, name)
+            }
+            ValidationIssue::InvalidFilterCharacter {
+                index,
+                value,
+                character,
+            } => write!(
+                f,
+                "{:?} of filter {} contains the invalid character {:?}",
+                value, index, character
+            ),
+            ValidationIssue::FileCountRange { min, max } => write!(
+                f,
+                "at least {} files are required but at most {} are allowed",
+                min, max
+            ),
+            ValidationIssue::Unsupported { option, backend } => {
+                write!(
+                    f,
+                    "`{}` is not supported by the {} backend",
+                    option, backend
+                )
+            }
+        }
+    }
+}
+
+/// Backends that honor each option, the ones missing here work everywhere
+const HONORED_BY: &[(&str, &[&str])] = &[
+    (
+        "add_mime_filter",
+        &[
+            "portal", "qt", "gtk3", "zenity", "software", "macos", "wasm",
+        ],
+    ),
+    (
+        "add_pattern_filter",
+        &["portal", "qt", "gtk3", "zenity", "software", "windows"],
+    ),
+    (
+        "add_file_name_filter",
+        &["portal", "qt", "gtk3", "zenity", "software", "windows"],
+    ),
+    (
+        "set_default_filter",
+        &["portal", "qt", "gtk3", "zenity", "software", "windows"],
+    ),
+    ("set_local_only", &["gtk3", "windows"]),
+    (
+        "set_show_hidden",
+        &["qt", "gtk3", "software", "windows", "macos"],
+    ),
+    ("set_can_create_directories", &["gtk3", "macos"]),
+    ("set_confirm_overwrite", &["gtk3", "windows"]),
+    ("set_add_to_recent", &["gtk3", "windows"]),
+    ("add_place", &["gtk3", "windows"]),
+    ("set_hide_default_places", &["windows"]),
+    ("set_validator", &["gtk3", "windows"]),
+    (
+        "add_checkbox",
+        &["portal", "qt", "gtk3", "zenity", "software", "windows"],
+    ),
+    (
+        "add_combo",
+        &["portal", "qt", "gtk3", "zenity", "software", "windows"],
+    ),
+    ("add_text_field", &["gtk3", "windows"]),
+    ("set_preview", &["gtk3", "windows", "macos"]),
+    ("set_description", &["gtk3", "software", "macos"]),
+    ("set_file_name_label", &["windows", "macos"]),
+    ("set_cancel_label", &["gtk3"]),
+    ("set_position", &["gtk3", "windows", "macos"]),
+    ("set_monitor", &["gtk3", "windows", "macos"]),
+    ("set_always_on_top", &["gtk3", "windows", "macos"]),
+    ("set_icon", &["gtk3", "software"]),
+    ("set_app_name", &["gtk3", "software"]),
+    (
+        "set_timeout",
+        &[
+            "portal", "qt", "gtk3", "zenity", "software", "windows", "macos",
+        ],
+    ),
+];
+
+/// Backends the table above knows, custom backends may honor anything
+const KNOWN_BACKENDS: &[&str] = &[
+    "portal", "qt", "gtk3", "zenity", "software", "windows", "macos", "wasm",
+];
+
+/// Characters no extension may contain, on top of whitespace and control characters
+const EXTENSION_INVALID: &[char] = &['*', '?', ';', ',', '|', '/', '\\'];
+
+/// Characters that separate patterns or paths in the backends, so no pattern may contain them
+const PATTERN_INVALID: &[char] = &[';', '|', '/', '\\'];
+
+/// Characters no file name may contain, the pattern ones plus the wildcards
+const NAME_INVALID: &[char] = &['*', '?', '[', ';', '|', '/', '\\'];
+
+pub(crate) fn validate(dialog: &FileDialog) -> Result<(), Vec<ValidationIssue>> {
+    let mut issues = Vec::new();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(dir) = dialog.directory() {
+        if !dir.is_dir() {
+            issues.push(ValidationIssue::MissingDirectory(dir.to_path_buf()));
+        }
+    }
+
+    check_filters(dialog, &mut issues);
+
+    if let (Some(min), Some(max)) = (dialog.min_files, dialog.max_files) {
+        if min > max {
+            issues.push(ValidationIssue::FileCountRange { min, max });
+        }
+    }
+
+    let backend = chosen_backend(dialog);
+    issues.extend(
+        unsupported_options(dialog, backend)
+            .into_iter()
+            .map(|option| ValidationIssue::Unsupported { option, backend }),
+    );
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(issues)
+    }
+}
+
+fn check_filters(dialog: &FileDialog, issues: &mut Vec<ValidationIssue>) {
+    for (index, filter) in dialog.filters().iter().enumerate() {
+        let mut values = filter
+            .extensions
+            .iter()
+            .chain(&filter.mime_types)
+            .chain(&filter.patterns)
+            .chain(&filter.file_names);
+        if values.all(|value| value.is_empty()) {
+            issues.push(ValidationIssue::EmptyFilter {
+                index,
+                name: filter.name.clone(),
+            });
+        }
+
+        let extensions = filter.extensions.iter().map(|ext| {
+            // A leading dot is a common slip, the dialogs add the dot themselves
+            let invalid = ext.chars().enumerate().find(|&(i, c)| {
+                (i == 0 && c == '.')
+                    || c.is_whitespace()
+                    || c.is_control()
+                    || EXTENSION_INVALID.contains(&c)
+            });
+            (ext, invalid.map(|(_, c)| c))
+        });
+        let mime_types = filter.mime_types.iter().map(|mime| {
+            let invalid = mime
+                .chars()
+                .find(|c| c.is_whitespace() || c.is_control() || [';', ','].contains(c));
+            (mime, invalid)
+        });
+        let patterns = filter.patterns.iter().map(|pattern| {
+            let invalid = pattern
+                .chars()
+                .find(|c| c.is_control() || PATTERN_INVALID.contains(c));
+            (pattern, invalid)
+        });
+        // Names are passed on as patterns, wildcards would make them match other files
+        let file_names = filter.file_names.iter().map(|name| {
+            let invalid = name
+                .chars()
+                .find(|c| c.is_control() || NAME_INVALID.contains(c));
+            (name, invalid)
+        });
+
+        let values = extensions.chain(mime_types).chain(patterns);
+        for (value, invalid) in values.chain(file_names) {
+            if let Some(character) = invalid {
+                issues.push(ValidationIssue::InvalidFilterCharacter {
+                    index,
+                    value: value.clone(),
+                    character,
+                });
+            }
+        }
+    }
+}
+
+/// Name of the backend that would show `dialog` now
+fn chosen_backend(dialog: &FileDialog) -> &'static str {
+    match backend_name(dialog.backend) {
+        "platform" if cfg!(target_os = "windows") => "windows",
+        "platform" if cfg!(target_os = "macos") => "macos",
+        "platform" if cfg!(target_arch = "wasm32") => "wasm",
+        name => name,
+    }
+}
+
+/// Builder methods of the options set on `dialog`
+fn set_options(dialog: &FileDialog) -> Vec<&'static str> {
+    let filters = dialog.filters();
+    // Patterns of the form `*.{ext}` work everywhere, they are used as extensions
+    let plain_pattern = |pattern: &String| {
+        pattern
+            .strip_prefix("*.")
+            .is_some_and(|ext| !ext.contains(['*', '?', '[']))
+    };
+
+    let options = [
+        (
+            "add_mime_filter",
+            filters.iter().any(|f| !f.mime_types.is_empty()),
+        ),
+        (
+            "add_pattern_filter",
+            filters
+                .iter()
+                .flat_map(|f| &f.patterns)
+                .any(|p| !plain_pattern(p)),
+        ),
+        (
+            "add_file_name_filter",
+            filters.iter().any(|f| !f.file_names.is_empty()),
+        ),
+        ("set_default_filter", dialog.default_filter.is_some()),
+        ("set_local_only", dialog.local_only.is_some()),
+        ("set_show_hidden", dialog.show_hidden.is_some()),
+        (
+            "set_can_create_directories",
+            dialog.can_create_directories.is_some(),
+        ),
+        ("set_confirm_overwrite", dialog.confirm_overwrite.is_some()),
+        ("set_add_to_recent", dialog.add_to_recent.is_some()),
+        ("add_place", !dialog.places.is_empty()),
+        (
+            "set_hide_default_places",
+            dialog.hide_default_places.is_some(),
+        ),
+        ("set_validator", dialog.validator.is_some()),
+        ("add_checkbox", !dialog.checkboxes.is_empty()),
+        ("add_combo", !dialog.combos.is_empty()),
+        ("add_text_field", !dialog.text_fields.is_empty()),
+        ("set_preview", dialog.preview.is_some()),
+        ("set_description", dialog.description.is_some()),
+        ("set_file_name_label", dialog.file_name_label.is_some()),
+        ("set_cancel_label", dialog.cancel_label.is_some()),
+        ("set_position", dialog.position.is_some()),
+        ("set_monitor", dialog.monitor.is_some()),
+        ("set_always_on_top", dialog.always_on_top.is_some()),
+        ("set_icon", dialog.icon.is_some()),
+        ("set_app_name", dialog.app_name.is_some()),
+        ("set_timeout", dialog.timeout.is_some()),
+    ];
+    options
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(option, _)| *option)
+        .collect()
+}
+
+/// Options set on `dialog` that `backend` ignores
+fn unsupported_options(dialog: &FileDialog, backend: &str) -> Vec<&'static str> {
+    if !KNOWN_BACKENDS.contains(&backend) {
+        return Vec::new();
+    }
+    set_options(dialog)
+        .into_iter()
+        .filter(|option| {
+            HONORED_BY
+                .iter()
+                .find(|(name, _)| name == option)
+                .is_some_and(|(_, backends)| !backends.contains(&backend))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_are_checked() {
+        let dialog = FileDialog::new()
+            .add_filter("Text", &["txt"])
+            .add_filter("Nothing", &[])
+            .add_filter("Images", &[".png", "jp g"])
+            .add_pattern_filter("Archives", &["*.tar.*", "a;b"])
+            .add_file_name_filter("Build", &["Makefile", "Build*"]);
+
+        let mut issues = Vec::new();
+        check_filters(&dialog, &mut issues);
+        let invalid = |index, value: &str, character| ValidationIssue::InvalidFilterCharacter {
+            index,
+            value: value.into(),
+            character,
+        };
+        assert_eq!(
+            issues,
+            [
+                ValidationIssue::EmptyFilter {
+                    index: 1,
+                    name: "Nothing".into(),
+                },
+                invalid(2, ".png", '.'),
+                invalid(2, "jp g", ' '),
+                invalid(3, "a;b", ';'),
+                invalid(4, "Build*", '*'),
+            ]
+        );
+    }
+
+    #[test]
+    fn file_count_range_is_checked() {
+        let dialog = FileDialog::new().set_min_files(3).set_max_files(2);
+        let issues = validate(&dialog).unwrap_err();
+        assert!(issues.contains(&ValidationIssue::FileCountRange { min: 3, max: 2 }));
+    }
+
+    #[test]
+    fn options_the_backend_ignores() {
+        let dialog = FileDialog::new()
+            .add_mime_filter("Images", &["image/*"])
+            .add_pattern_filter("Text", &["*.txt"])
+            .set_show_hidden(true)
+            .set_cancel_label("Back");
+
+        assert_eq!(
+            unsupported_options(&dialog, "windows"),
+            ["add_mime_filter", "set_cancel_label"]
+        );
+        assert_eq!(unsupported_options(&dialog, "gtk3"), Vec::<&str>::new());
+        assert_eq!(
+            unsupported_options(&dialog, "zenity"),
+            ["set_show_hidden", "set_cancel_label"]
+        );
+        assert_eq!(unsupported_options(&dialog, "custom"), Vec::<&str>::new());
+    }
+}
@@ -0,0 +1,157 @@
+//! Windows only settings
+//!
+//! The shell file dialogs are COM objects that need a single-threaded apartment.
+//! By default rfd initializes COM as such on the thread that shows a dialog, which fails with
+//! `RPC_E_CHANGED_MODE` on threads the application already initialized differently.
+//! [`init_com`] tells rfd how the application uses COM instead.
+//!
+//! ```no_run
+//! // The application calls `CoInitializeEx` on its threads itself
+//! rfd::windows::init_com(rfd::windows::ComModel::ReuseExisting);
+//! ```
+//!
+//! Paths longer than `MAX_PATH` are returned as the dialog gives them, [`set_long_path_mode`]
+//! asks for `\\?\` prefixed ones that Win32 APIs without long path support can still open.
+
+use std::ffi::OsString;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How the threads of the application use COM, see [`init_com`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ComModel {
+    /// rfd initializes the thread that shows a dialog as a single-threaded apartment and
+    /// uninitializes it afterwards. Threads already in the multithreaded apartment get their
+    /// dialogs shown on the [`ui_thread`](crate::ui_thread).
+    #[default]
+    Sta,
+    /// The application initializes its threads as multithreaded apartments, rfd never calls
+    /// `CoInitializeEx` on them and shows every dialog on the [`ui_thread`](crate::ui_thread)
+    Mta,
+    /// The application initializes COM on its threads itself, rfd never calls
+    /// `CoInitializeEx` on them and shows dialogs in whatever apartment the thread is in
+    ReuseExisting,
+}
+
+static MODEL: AtomicU8 = AtomicU8::new(ComModel::Sta as u8);
+
+/// Sets how rfd treats COM on the threads of the application, call it before the first dialog.
+///
+/// Threads rfd starts itself, for async dialogs and the [`ui_thread`](crate::ui_thread),
+/// are always single-threaded apartments.
+pub fn init_com(model: ComModel) {
+    MODEL.store(model as u8, Ordering::Relaxed);
+}
+
+/// The model set with [`init_com`]
+pub fn com_model() -> ComModel {
+    match MODEL.load(Ordering::Relaxed) {
+        1 => ComModel::Mta,
+        2 => ComModel::ReuseExisting,
+        _ => ComModel::Sta,
+    }
+}
+
+/// How file dialogs return paths longer than `MAX_PATH`, see [`set_long_path_mode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LongPathMode {
+    /// Paths are returned as the dialog gives them
+    #[default]
+    Keep,
+    /// Paths of `MAX_PATH` characters or more get the `\\?\` prefix
+    WhenNeeded,
+    /// Every path gets the `\\?\` prefix
+    Always,
+}
+
+static LONG_PATH_MODE: AtomicU8 = AtomicU8::new(LongPathMode::Keep as u8);
+
+/// Sets how file dialogs return paths longer than `MAX_PATH`
+///
+/// Paths passed to the dialogs, eg. with [`set_directory`](crate::FileDialog::set_directory),
+/// may have the `\\?\` prefix either way.
+pub fn set_long_path_mode(mode: LongPathMode) {
+    LONG_PATH_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// The mode set with [`set_long_path_mode`]
+pub fn long_path_mode() -> LongPathMode {
+    match LONG_PATH_MODE.load(Ordering::Relaxed) {
+        1 => LongPathMode::WhenNeeded,
+        2 => LongPathMode::Always,
+        _ => LongPathMode::Keep,
+    }
+}
+
+/// `MAX_PATH` includes the terminating nul
+const MAX_PATH: usize = 260;
+
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().collect()
+}
+
+/// `path` with the `\\?\` prefix when [`long_path_mode`] asks for it
+pub(crate) fn long_path(path: PathBuf) -> PathBuf {
+    let path_wide: Vec<u16> = path.as_os_str().encode_wide().collect();
+    let prefix = match long_path_mode() {
+        LongPathMode::Keep => false,
+        LongPathMode::WhenNeeded => path_wide.len() >= MAX_PATH,
+        LongPathMode::Always => true,
+    };
+    if !prefix || path_wide.starts_with(&wide(r"\\?\")) {
+        return path;
+    }
+
+    // `\\server\share` becomes `\\?\UNC\server\share`
+    let verbatim = match path_wide.strip_prefix(&wide(r"\\")[..]) {
+        Some(rest) => [wide(r"\\?\UNC\"), rest.to_vec()].concat(),
+        None => [wide(r"\\?\"), path_wide].concat(),
+    };
+    PathBuf::from(OsString::from_wide(&verbatim))
+}
+
+/// `path` without the `\\?\` prefix, which the shell can't parse
+pub(crate) fn strip_verbatim(path: &Path) -> PathBuf {
+    let path_wide: Vec<u16> = path.as_os_str().encode_wide().collect();
+    let plain = if let Some(rest) = path_wide.strip_prefix(&wide(r"\\?\UNC\")[..]) {
+        [wide(r"\\"), rest.to_vec()].concat()
+    } else if let Some(rest) = path_wide.strip_prefix(&wide(r"\\?\")[..]) {
+        rest.to_vec()
+    } else {
+        return path.to_owned();
+    };
+    PathBuf::from(OsString::from_wide(&plain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbatim_prefixes() {
+        assert_eq!(
+            strip_verbatim(Path::new(r"\\?\C:\Users\notes.txt")),
+            Path::new(r"C:\Users\notes.txt")
+        );
+        assert_eq!(
+            strip_verbatim(Path::new(r"\\?\UNC\server\share\notes.txt")),
+            Path::new(r"\\server\share\notes.txt")
+        );
+        assert_eq!(
+            strip_verbatim(Path::new(r"C:\notes.txt")),
+            Path::new(r"C:\notes.txt")
+        );
+
+        set_long_path_mode(LongPathMode::Always);
+        assert_eq!(
+            long_path(PathBuf::from(r"\\server\share\notes.txt")),
+            Path::new(r"\\?\UNC\server\share\notes.txt")
+        );
+        assert_eq!(
+            long_path(PathBuf::from(r"\\?\C:\notes.txt")),
+            Path::new(r"\\?\C:\notes.txt")
+        );
+        set_long_path_mode(LongPathMode::Keep);
+    }
+}